@@ -11,6 +11,9 @@ pub fn criterion_benchmark(c: &mut Criterion) {
     g.bench_function("remove", remove);
     g.bench_function("values", values);
     g.bench_function("optimize", optimize);
+    g.bench_function("clone_copy", clone_copy);
+    g.bench_function("extend_copy", extend_copy);
+    g.bench_function("drain_copy_into", drain_copy_into);
 }
 
 const COUNT: usize = 10000;
@@ -61,6 +64,39 @@ fn optimize(b: &mut Bencher) {
         s.len()
     });
 }
+fn clone_copy(b: &mut Bencher) {
+    let mut s = SlabMap::new();
+    for i in 0..COUNT {
+        s.insert(i);
+    }
+    for i in (0..COUNT).step_by(3) {
+        s.remove(i);
+    }
+    b.iter(|| s.clone_copy());
+}
+fn extend_copy(b: &mut Bencher) {
+    let values: Vec<_> = (0..COUNT).collect();
+    b.iter(|| {
+        let mut s = SlabMap::new();
+        s.extend_copy(&values);
+        s
+    });
+}
+fn drain_copy_into(b: &mut Bencher) {
+    let mut s = SlabMap::new();
+    let mut out = Vec::new();
+    b.iter(|| {
+        for i in 0..COUNT {
+            s.insert(i);
+        }
+        for i in (0..COUNT).step_by(3) {
+            s.remove(i);
+        }
+        out.clear();
+        s.drain_copy_into(&mut out);
+        out.len()
+    });
+}
 
 fn make_rng() -> StdRng {
     let seed: [u8; 32] = [17; 32];
@@ -0,0 +1,213 @@
+//! Derive macro for [slabmap](https://docs.rs/slabmap/).
+
+use proc_macro2::{Span, TokenStream};
+use quote::{format_ident, quote};
+use syn::{parse_macro_input, DeriveInput, Ident, LitStr, Result};
+
+/// Generates a typed key and a registry over `SlabMap<Self>`.
+///
+/// For a struct `Foo`, this generates:
+///
+/// - `FooKey`: a newtype over `usize` used as the key.
+/// - `FooRegistry`: a wrapper over `SlabMap<Foo>` that takes and returns `FooKey`.
+///
+/// The names can be changed with `#[slab_stored(key = "...", registry = "...")]`.
+/// The generated types have the same visibility as the struct.
+///
+/// # Examples
+/// ```
+/// use slabmap::SlabStored;
+///
+/// #[derive(SlabStored)]
+/// #[slab_stored(key = "UserId")]
+/// struct User {
+///     name: String,
+/// }
+///
+/// let mut users = UserRegistry::new();
+/// let id: UserId = users.insert(User { name: "a".into() });
+/// assert_eq!(users[id].name, "a");
+/// assert_eq!(users.remove(id).map(|u| u.name), Some("a".into()));
+/// assert!(!users.contains_key(id));
+/// ```
+#[proc_macro_derive(SlabStored, attributes(slab_stored))]
+pub fn derive_slab_stored(input: proc_macro::TokenStream) -> proc_macro::TokenStream {
+    let input = parse_macro_input!(input as DeriveInput);
+    match build(&input) {
+        Ok(s) => s.into(),
+        Err(e) => e.to_compile_error().into(),
+    }
+}
+
+fn build(input: &DeriveInput) -> Result<TokenStream> {
+    if !input.generics.params.is_empty() {
+        return Err(syn::Error::new_spanned(
+            &input.generics,
+            "`SlabStored` does not support generic types",
+        ));
+    }
+    let vis = &input.vis;
+    let ty = &input.ident;
+    let mut key = format_ident!("{}Key", ty);
+    let mut registry = format_ident!("{}Registry", ty);
+    for attr in &input.attrs {
+        if !attr.path().is_ident("slab_stored") {
+            continue;
+        }
+        attr.parse_nested_meta(|meta| {
+            let name = if meta.path.is_ident("key") {
+                &mut key
+            } else if meta.path.is_ident("registry") {
+                &mut registry
+            } else {
+                return Err(meta.error("expected `key` or `registry`"));
+            };
+            let s: LitStr = meta.value()?.parse()?;
+            *name = Ident::new(&s.value(), s.span());
+            Ok(())
+        })?;
+    }
+    let key_doc = format!("A key of [`{registry}`].");
+    let registry_doc = format!("A [`SlabMap`](::slabmap::SlabMap) of [`{ty}`] keyed by [`{key}`].");
+    let iter = Ident::new(&format!("{registry}Iter"), Span::call_site());
+    let iter_doc = format!("An iterator over the entries of a [`{registry}`].");
+    Ok(quote! {
+        #[doc = #key_doc]
+        #[derive(Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord, Hash)]
+        #vis struct #key(pub usize);
+
+        impl ::core::convert::From<usize> for #key {
+            fn from(value: usize) -> Self {
+                Self(value)
+            }
+        }
+        impl ::core::convert::From<#key> for usize {
+            fn from(value: #key) -> Self {
+                value.0
+            }
+        }
+
+        #[doc = #registry_doc]
+        #[derive(Default)]
+        #vis struct #registry {
+            map: ::slabmap::SlabMap<#ty>,
+        }
+
+        #[allow(dead_code)]
+        impl #registry {
+            /// Constructs a new, empty registry.
+            #[inline]
+            pub const fn new() -> Self {
+                Self {
+                    map: ::slabmap::SlabMap::new(),
+                }
+            }
+
+            /// Returns the number of elements in the registry.
+            #[inline]
+            pub fn len(&self) -> usize {
+                self.map.len()
+            }
+
+            /// Returns true if the registry contains no elements.
+            #[inline]
+            pub fn is_empty(&self) -> bool {
+                self.map.is_empty()
+            }
+
+            /// Returns a reference to the value corresponding to the key.
+            #[inline]
+            pub fn get(&self, key: #key) -> ::core::option::Option<&#ty> {
+                self.map.get(key.0)
+            }
+
+            /// Returns a mutable reference to the value corresponding to the key.
+            #[inline]
+            pub fn get_mut(&mut self, key: #key) -> ::core::option::Option<&mut #ty> {
+                self.map.get_mut(key.0)
+            }
+
+            /// Returns true if the registry contains a value for the specified key.
+            #[inline]
+            pub fn contains_key(&self, key: #key) -> bool {
+                self.map.contains_key(key.0)
+            }
+
+            /// Inserts a value into the registry.
+            ///
+            /// Returns the key associated with the value.
+            #[inline]
+            pub fn insert(&mut self, value: #ty) -> #key {
+                #key(self.map.insert(value))
+            }
+
+            /// Removes a key from the registry, returning the value at the key if the key was previously in the registry.
+            #[inline]
+            pub fn remove(&mut self, key: #key) -> ::core::option::Option<#ty> {
+                self.map.remove(key.0)
+            }
+
+            /// Clears the registry, removing all values.
+            #[inline]
+            pub fn clear(&mut self) {
+                self.map.clear()
+            }
+
+            /// Gets an iterator over the entries of the registry, sorted by key.
+            #[inline]
+            pub fn iter(&self) -> #iter<'_> {
+                #iter(self.map.iter())
+            }
+
+            /// Returns a reference to the underlying [`SlabMap`](::slabmap::SlabMap).
+            #[inline]
+            pub fn as_slab_map(&self) -> &::slabmap::SlabMap<#ty> {
+                &self.map
+            }
+        }
+
+        impl ::core::ops::Index<#key> for #registry {
+            type Output = #ty;
+
+            #[inline]
+            fn index(&self, index: #key) -> &Self::Output {
+                self.get(index).expect("out of index.")
+            }
+        }
+        impl ::core::ops::IndexMut<#key> for #registry {
+            #[inline]
+            fn index_mut(&mut self, index: #key) -> &mut Self::Output {
+                self.get_mut(index).expect("out of index.")
+            }
+        }
+
+        impl<'a> ::core::iter::IntoIterator for &'a #registry {
+            type Item = (#key, &'a #ty);
+            type IntoIter = #iter<'a>;
+
+            #[inline]
+            fn into_iter(self) -> Self::IntoIter {
+                self.iter()
+            }
+        }
+
+        #[doc = #iter_doc]
+        #vis struct #iter<'a>(::slabmap::slab_map::Iter<'a, #ty>);
+
+        impl<'a> ::core::iter::Iterator for #iter<'a> {
+            type Item = (#key, &'a #ty);
+
+            #[inline]
+            fn next(&mut self) -> ::core::option::Option<Self::Item> {
+                let (key, value) = self.0.next()?;
+                ::core::option::Option::Some((#key(key), value))
+            }
+            #[inline]
+            fn size_hint(&self) -> (usize, ::core::option::Option<usize>) {
+                self.0.size_hint()
+            }
+        }
+        impl ::core::iter::FusedIterator for #iter<'_> {}
+        impl ::core::iter::ExactSizeIterator for #iter<'_> {}
+    })
+}
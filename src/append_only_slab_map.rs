@@ -0,0 +1,335 @@
+//! A slab that can be appended to from multiple threads without locking.
+
+use alloc::boxed::Box;
+use core::{
+    cell::UnsafeCell,
+    fmt::{self, Debug},
+    iter::FusedIterator,
+    mem::MaybeUninit,
+    ptr::{self, null_mut},
+    slice,
+    sync::atomic::{AtomicPtr, AtomicU8, AtomicUsize, Ordering},
+};
+
+use crate::pinned_slab_map::{chunk_index, FIRST_CHUNK_LEN};
+
+#[cfg(test)]
+mod tests;
+
+const CHUNK_COUNT: usize = (usize::BITS - FIRST_CHUNK_LEN.trailing_zeros()) as usize;
+
+const EMPTY: u8 = 0;
+const OCCUPIED: u8 = 1;
+const REMOVED: u8 = 2;
+const DROPPED: u8 = 3;
+
+struct Slot<T> {
+    state: AtomicU8,
+    value: UnsafeCell<MaybeUninit<T>>,
+}
+
+/// A slab that can be shared between threads, where [`insert`](Self::insert) and [`get`](Self::get) take `&self` and never lock.
+///
+/// Values are stored in chunks with doubling sizes that are never reallocated, so a value never moves,
+/// and the reference returned by [`get`](Self::get) stays valid while other threads insert values.
+/// Keys are never reused.
+///
+/// [`remove`](Self::remove) also takes `&self`, but only hides the value.
+/// The value is dropped later by [`purge`](Self::purge), or when the AppendOnlySlabMap is dropped,
+/// since references to it may still be in use.
+///
+/// # Examples
+/// ```
+/// use slabmap::append_only_slab_map::AppendOnlySlabMap;
+///
+/// let s = AppendOnlySlabMap::new();
+/// let a = s.insert("a".to_string());
+/// let a_ref = s.get(a).unwrap();
+///
+/// std::thread::scope(|scope| {
+///     scope.spawn(|| s.insert("b".to_string()));
+///     scope.spawn(|| s.insert("c".to_string()));
+/// });
+///
+/// assert_eq!(a_ref, "a");
+/// assert_eq!(s.len(), 3);
+/// assert!(s.remove(a));
+/// assert_eq!(s.get(a), None);
+/// ```
+pub struct AppendOnlySlabMap<T> {
+    chunks: [AtomicPtr<Slot<T>>; CHUNK_COUNT],
+    next_key: AtomicUsize,
+    len: AtomicUsize,
+}
+
+// SAFETY: Values are moved into the AppendOnlySlabMap by `insert` from any thread, and shared by `get`.
+unsafe impl<T: Send> Send for AppendOnlySlabMap<T> {}
+unsafe impl<T: Send + Sync> Sync for AppendOnlySlabMap<T> {}
+
+impl<T> AppendOnlySlabMap<T> {
+    /// Constructs a new, empty `AppendOnlySlabMap<T>`.
+    #[inline]
+    pub const fn new() -> Self {
+        Self {
+            chunks: [const { AtomicPtr::new(null_mut()) }; CHUNK_COUNT],
+            next_key: AtomicUsize::new(0),
+            len: AtomicUsize::new(0),
+        }
+    }
+
+    /// Returns the number of elements in the AppendOnlySlabMap.
+    #[inline]
+    pub fn len(&self) -> usize {
+        self.len.load(Ordering::Relaxed)
+    }
+
+    /// Returns true if the AppendOnlySlabMap contains no elements.
+    #[inline]
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    fn chunk(&self, chunk: usize) -> Option<&[Slot<T>]> {
+        let p = self.chunks[chunk].load(Ordering::Acquire);
+        if p.is_null() {
+            None
+        } else {
+            // SAFETY: A non-null chunk pointer always points to a chunk of `FIRST_CHUNK_LEN << chunk` slots.
+            Some(unsafe { slice::from_raw_parts(p, FIRST_CHUNK_LEN << chunk) })
+        }
+    }
+    fn chunk_or_alloc(&self, chunk: usize) -> &[Slot<T>] {
+        if let Some(slots) = self.chunk(chunk) {
+            return slots;
+        }
+        let slots: Box<[Slot<T>]> = (0..FIRST_CHUNK_LEN << chunk)
+            .map(|_| Slot {
+                state: AtomicU8::new(EMPTY),
+                value: UnsafeCell::new(MaybeUninit::uninit()),
+            })
+            .collect();
+        let p = Box::into_raw(slots) as *mut Slot<T>;
+        if let Err(other) =
+            self.chunks[chunk].compare_exchange(null_mut(), p, Ordering::AcqRel, Ordering::Acquire)
+        {
+            // SAFETY: `p` was created from a chunk of the same length above and was never published.
+            drop(unsafe {
+                Box::from_raw(ptr::slice_from_raw_parts_mut(p, FIRST_CHUNK_LEN << chunk))
+            });
+            debug_assert!(!other.is_null());
+        }
+        self.chunk(chunk).unwrap()
+    }
+    fn slot(&self, key: usize) -> Option<&Slot<T>> {
+        if key > usize::MAX - FIRST_CHUNK_LEN {
+            return None;
+        }
+        let (chunk, offset) = chunk_index(key);
+        Some(&self.chunk(chunk)?[offset])
+    }
+    fn slot_mut(&mut self, key: usize) -> Option<&mut Slot<T>> {
+        if key > usize::MAX - FIRST_CHUNK_LEN {
+            return None;
+        }
+        let (chunk, offset) = chunk_index(key);
+        let p = *self.chunks[chunk].get_mut();
+        if p.is_null() {
+            None
+        } else {
+            // SAFETY: The chunk is valid as in `chunk`, and `&mut self` guarantees exclusive access.
+            Some(unsafe { &mut *p.add(offset) })
+        }
+    }
+
+    /// Returns a reference to the value corresponding to the key.
+    ///
+    /// The reference stays valid while other threads insert or remove values.
+    #[inline]
+    pub fn get(&self, key: usize) -> Option<&T> {
+        let slot = self.slot(key)?;
+        if slot.state.load(Ordering::Acquire) == OCCUPIED {
+            // SAFETY: The value was initialized before the state became `OCCUPIED`,
+            // and it is dropped only through `&mut self`.
+            Some(unsafe { (*slot.value.get()).assume_init_ref() })
+        } else {
+            None
+        }
+    }
+
+    /// Returns a mutable reference to the value corresponding to the key.
+    #[inline]
+    pub fn get_mut(&mut self, key: usize) -> Option<&mut T> {
+        let slot = self.slot_mut(key)?;
+        if *slot.state.get_mut() == OCCUPIED {
+            // SAFETY: The value is initialized while the state is `OCCUPIED`.
+            Some(unsafe { slot.value.get_mut().assume_init_mut() })
+        } else {
+            None
+        }
+    }
+
+    /// Returns true if the AppendOnlySlabMap contains a value for the specified key.
+    #[inline]
+    pub fn contains_key(&self, key: usize) -> bool {
+        self.get(key).is_some()
+    }
+
+    /// Inserts a value into the AppendOnlySlabMap without locking.
+    ///
+    /// Returns the key associated with the value.
+    ///
+    /// # Panics
+    /// Panics if the number of keys overflows.
+    pub fn insert(&self, value: T) -> usize {
+        let key = self.next_key.fetch_add(1, Ordering::Relaxed);
+        assert!(key <= usize::MAX - FIRST_CHUNK_LEN, "capacity overflow");
+        let (chunk, offset) = chunk_index(key);
+        let slot = &self.chunk_or_alloc(chunk)[offset];
+        // SAFETY: Each key is handed out once, so no other thread accesses the slot until the state becomes `OCCUPIED`.
+        unsafe { (*slot.value.get()).write(value) };
+        slot.state.store(OCCUPIED, Ordering::Release);
+        self.len.fetch_add(1, Ordering::Relaxed);
+        key
+    }
+
+    /// Hides the value corresponding to the key, returning true if the key was previously in the AppendOnlySlabMap.
+    ///
+    /// The value is not dropped until [`purge`](Self::purge) is called or the AppendOnlySlabMap is dropped,
+    /// so references obtained by [`get`](Self::get) remain valid.
+    pub fn remove(&self, key: usize) -> bool {
+        let Some(slot) = self.slot(key) else {
+            return false;
+        };
+        let removed = slot
+            .state
+            .compare_exchange(OCCUPIED, REMOVED, Ordering::AcqRel, Ordering::Relaxed)
+            .is_ok();
+        if removed {
+            self.len.fetch_sub(1, Ordering::Relaxed);
+        }
+        removed
+    }
+
+    /// Removes a key from the AppendOnlySlabMap, returning the value at the key if the key was previously in the AppendOnlySlabMap.
+    pub fn take(&mut self, key: usize) -> Option<T> {
+        let slot = self.slot_mut(key)?;
+        if *slot.state.get_mut() != OCCUPIED {
+            return None;
+        }
+        *slot.state.get_mut() = DROPPED;
+        // SAFETY: The value was initialized, and the state no longer refers to it.
+        let value = unsafe { slot.value.get_mut().assume_init_read() };
+        *self.len.get_mut() -= 1;
+        Some(value)
+    }
+
+    /// Drops the values hidden by [`remove`](Self::remove).
+    pub fn purge(&mut self) {
+        for key in 0..*self.next_key.get_mut() {
+            if let Some(slot) = self.slot_mut(key) {
+                if *slot.state.get_mut() == REMOVED {
+                    *slot.state.get_mut() = DROPPED;
+                    // SAFETY: The value was initialized, and the state no longer refers to it.
+                    unsafe { slot.value.get_mut().assume_init_drop() };
+                }
+            }
+        }
+    }
+
+    /// Gets an iterator over the entries of the AppendOnlySlabMap, sorted by key.
+    ///
+    /// Values inserted by other threads during the iteration may or may not be visited.
+    #[inline]
+    pub fn iter(&self) -> Iter<'_, T> {
+        Iter {
+            map: self,
+            key: 0,
+            end: self.next_key.load(Ordering::Relaxed),
+        }
+    }
+}
+impl<T> Default for AppendOnlySlabMap<T> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+impl<T> Drop for AppendOnlySlabMap<T> {
+    fn drop(&mut self) {
+        for key in 0..*self.next_key.get_mut() {
+            if let Some(slot) = self.slot_mut(key) {
+                if matches!(*slot.state.get_mut(), OCCUPIED | REMOVED) {
+                    *slot.state.get_mut() = DROPPED;
+                    // SAFETY: The value was initialized, and the state no longer refers to it.
+                    unsafe { slot.value.get_mut().assume_init_drop() };
+                }
+            }
+        }
+        for (chunk, p) in self.chunks.iter_mut().enumerate() {
+            let p = *p.get_mut();
+            if !p.is_null() {
+                // SAFETY: `p` was created by `Box::into_raw` from a chunk of this length in `chunk_or_alloc`.
+                drop(unsafe {
+                    Box::from_raw(ptr::slice_from_raw_parts_mut(p, FIRST_CHUNK_LEN << chunk))
+                });
+            }
+        }
+    }
+}
+impl<T: Debug> Debug for AppendOnlySlabMap<T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_map().entries(self.iter()).finish()
+    }
+}
+
+impl<T> core::ops::Index<usize> for AppendOnlySlabMap<T> {
+    type Output = T;
+
+    #[inline]
+    fn index(&self, index: usize) -> &Self::Output {
+        self.get(index).expect("out of index.")
+    }
+}
+impl<T> core::ops::IndexMut<usize> for AppendOnlySlabMap<T> {
+    #[inline]
+    fn index_mut(&mut self, index: usize) -> &mut Self::Output {
+        self.get_mut(index).expect("out of index.")
+    }
+}
+
+impl<'a, T> IntoIterator for &'a AppendOnlySlabMap<T> {
+    type Item = (usize, &'a T);
+    type IntoIter = Iter<'a, T>;
+
+    #[inline]
+    fn into_iter(self) -> Self::IntoIter {
+        self.iter()
+    }
+}
+
+/// An iterator over the entries of an [`AppendOnlySlabMap`].
+///
+/// This struct is created by the [`iter`](AppendOnlySlabMap::iter).
+pub struct Iter<'a, T> {
+    map: &'a AppendOnlySlabMap<T>,
+    key: usize,
+    end: usize,
+}
+impl<'a, T> Iterator for Iter<'a, T> {
+    type Item = (usize, &'a T);
+    #[inline]
+    fn next(&mut self) -> Option<Self::Item> {
+        while self.key < self.end {
+            let key = self.key;
+            self.key += 1;
+            if let Some(value) = self.map.get(key) {
+                return Some((key, value));
+            }
+        }
+        None
+    }
+    #[inline]
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        (0, Some(self.end - self.key))
+    }
+}
+impl<T> FusedIterator for Iter<'_, T> {}
@@ -0,0 +1,79 @@
+use std::{sync::Arc, thread};
+
+use crate::append_only_slab_map::AppendOnlySlabMap;
+
+#[test]
+fn concurrent_insert_and_get() {
+    let s = AppendOnlySlabMap::new();
+    let first = s.insert(usize::MAX);
+    let first_ref = s.get(first).unwrap();
+    let keys: Vec<Vec<usize>> = thread::scope(|scope| {
+        let s = &s;
+        let handles: Vec<_> = (0..8)
+            .map(|t| {
+                scope.spawn(move || {
+                    (0..1000)
+                        .map(|i| {
+                            let key = s.insert(t * 1000 + i);
+                            assert_eq!(s.get(key), Some(&(t * 1000 + i)));
+                            key
+                        })
+                        .collect()
+                })
+            })
+            .collect();
+        handles.into_iter().map(|h| h.join().unwrap()).collect()
+    });
+    assert_eq!(*first_ref, usize::MAX);
+    assert_eq!(s.len(), 8001);
+    for (t, keys) in keys.iter().enumerate() {
+        for (i, &key) in keys.iter().enumerate() {
+            assert_eq!(s[key], t * 1000 + i);
+        }
+    }
+    let mut all: Vec<_> = s.iter().map(|(k, _)| k).collect();
+    all.dedup();
+    assert_eq!(all, (0..8001).collect::<Vec<_>>());
+}
+
+#[test]
+fn remove_is_deferred() {
+    let value = Arc::new(());
+    let mut s = AppendOnlySlabMap::new();
+    let a = s.insert(value.clone());
+    let b = s.insert(value.clone());
+    let c = s.insert(value.clone());
+    assert_eq!(Arc::strong_count(&value), 4);
+
+    assert!(s.remove(a));
+    assert!(!s.remove(a));
+    assert!(!s.contains_key(a));
+    assert_eq!(s.len(), 2);
+    assert_eq!(Arc::strong_count(&value), 4);
+
+    s.purge();
+    assert_eq!(Arc::strong_count(&value), 3);
+
+    assert!(s.take(b).is_some());
+    assert_eq!(s.take(b), None);
+    assert_eq!(Arc::strong_count(&value), 2);
+    assert_eq!(s.iter().map(|(k, _)| k).collect::<Vec<_>>(), [c]);
+
+    let d = s.insert(value.clone());
+    assert!(d > c);
+    assert!(s.remove(d));
+    drop(s);
+    assert_eq!(Arc::strong_count(&value), 1);
+}
+
+#[test]
+fn get_out_of_range() {
+    let mut s = AppendOnlySlabMap::new();
+    assert_eq!(s.get(0), None);
+    assert_eq!(s.get(usize::MAX), None);
+    assert!(!s.remove(usize::MAX));
+    assert_eq!(s.get_mut(1000), None);
+    let key = s.insert(1);
+    s[key] += 1;
+    assert_eq!(format!("{s:?}"), "{0: 2}");
+}
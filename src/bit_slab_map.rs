@@ -0,0 +1,304 @@
+//! A slab of `bool` values stored as bits.
+
+use alloc::vec::Vec;
+use core::{fmt::Debug, iter::FusedIterator};
+
+#[cfg(test)]
+mod tests;
+
+const BITS: usize = u64::BITS as usize;
+
+/// A [`SlabMap`](crate::SlabMap) specialized for `bool` values.
+///
+/// Both the occupancy and the values are stored as bitmaps, so each entry uses 2 bits instead of the 16 bytes used by an entry of `SlabMap<bool>` on 64-bit targets.
+/// This is suited for large flag tables keyed by the keys of another slab.
+///
+/// Since values are bits, mutable references are not available. Use [`set`](Self::set) instead of `get_mut`.
+///
+/// # Examples
+/// ```
+/// use slabmap::bit_slab_map::BitSlabMap;
+///
+/// let mut s = BitSlabMap::new();
+/// let a = s.insert(true);
+/// let b = s.insert(false);
+///
+/// assert_eq!(s.get(a), Some(true));
+/// assert_eq!(s.set(b, true), Some(false));
+/// assert!(s[b]);
+/// assert_eq!(s.remove(a), Some(true));
+/// assert_eq!(s.get(a), None);
+/// ```
+#[derive(Clone, Default)]
+pub struct BitSlabMap {
+    occupied: Vec<u64>,
+    values: Vec<u64>,
+    len: usize,
+    /// All words before this index are fully occupied.
+    vacant_hint: usize,
+}
+
+fn split(key: usize) -> (usize, u64) {
+    (key / BITS, 1 << (key % BITS))
+}
+
+impl BitSlabMap {
+    /// Constructs a new, empty `BitSlabMap`.
+    #[inline]
+    pub const fn new() -> Self {
+        Self {
+            occupied: Vec::new(),
+            values: Vec::new(),
+            len: 0,
+            vacant_hint: 0,
+        }
+    }
+
+    /// Constructs a new, empty `BitSlabMap` with at least the specified capacity.
+    pub fn with_capacity(capacity: usize) -> Self {
+        let words = capacity.div_ceil(BITS);
+        Self {
+            occupied: Vec::with_capacity(words),
+            values: Vec::with_capacity(words),
+            len: 0,
+            vacant_hint: 0,
+        }
+    }
+
+    /// Returns the number of elements the BitSlabMap can hold without reallocating.
+    #[inline]
+    pub fn capacity(&self) -> usize {
+        self.occupied.capacity().min(self.values.capacity()) * BITS
+    }
+
+    /// Returns the number of elements in the BitSlabMap.
+    #[inline]
+    pub fn len(&self) -> usize {
+        self.len
+    }
+
+    /// Returns true if the BitSlabMap contains no elements.
+    #[inline]
+    pub fn is_empty(&self) -> bool {
+        self.len == 0
+    }
+
+    /// Returns the value corresponding to the key.
+    #[inline]
+    pub fn get(&self, key: usize) -> Option<bool> {
+        let (word, mask) = split(key);
+        if self.occupied.get(word)? & mask != 0 {
+            Some(self.values[word] & mask != 0)
+        } else {
+            None
+        }
+    }
+
+    /// Returns true if the BitSlabMap contains a value for the specified key.
+    #[inline]
+    pub fn contains_key(&self, key: usize) -> bool {
+        self.get(key).is_some()
+    }
+
+    /// Sets the value corresponding to the key, returning the old value.
+    ///
+    /// Returns `None` and does nothing if the key does not exist.
+    pub fn set(&mut self, key: usize, value: bool) -> Option<bool> {
+        let old = self.get(key)?;
+        let (word, mask) = split(key);
+        if value {
+            self.values[word] |= mask;
+        } else {
+            self.values[word] &= !mask;
+        }
+        Some(old)
+    }
+
+    /// Inserts a value into the BitSlabMap.
+    ///
+    /// Returns the key associated with the value.
+    /// The lowest free key is reused first.
+    pub fn insert(&mut self, value: bool) -> usize {
+        while self.vacant_hint < self.occupied.len() && self.occupied[self.vacant_hint] == !0 {
+            self.vacant_hint += 1;
+        }
+        if self.vacant_hint == self.occupied.len() {
+            self.occupied.push(0);
+            self.values.push(0);
+        }
+        let word = self.vacant_hint;
+        let key = word * BITS + self.occupied[word].trailing_ones() as usize;
+        let mask = split(key).1;
+        self.occupied[word] |= mask;
+        if value {
+            self.values[word] |= mask;
+        } else {
+            self.values[word] &= !mask;
+        }
+        self.len += 1;
+        key
+    }
+
+    /// Removes a key from the BitSlabMap, returning the value at the key if the key was previously in the BitSlabMap.
+    pub fn remove(&mut self, key: usize) -> Option<bool> {
+        let value = self.get(key)?;
+        let (word, mask) = split(key);
+        self.occupied[word] &= !mask;
+        self.vacant_hint = self.vacant_hint.min(word);
+        self.len -= 1;
+        if self.len == 0 {
+            self.clear();
+        }
+        Some(value)
+    }
+
+    /// Clears the BitSlabMap, removing all values.
+    pub fn clear(&mut self) {
+        self.occupied.clear();
+        self.values.clear();
+        self.len = 0;
+        self.vacant_hint = 0;
+    }
+
+    /// Retains only the elements specified by the predicate.
+    pub fn retain(&mut self, mut f: impl FnMut(usize, bool) -> bool) {
+        for key in self.keys().collect::<Vec<_>>() {
+            if !f(key, self.get(key).unwrap()) {
+                self.remove(key);
+            }
+        }
+    }
+
+    /// Gets an iterator over the entries of the BitSlabMap, sorted by key.
+    #[inline]
+    pub fn iter(&self) -> Iter<'_> {
+        Iter {
+            occupied: &self.occupied,
+            values: &self.values,
+            word: 0,
+            bits: self.occupied.first().copied().unwrap_or(0),
+            len: self.len,
+        }
+    }
+
+    /// Gets an iterator over the keys of the BitSlabMap, in sorted order.
+    #[inline]
+    pub fn keys(&self) -> Keys<'_> {
+        Keys(self.iter())
+    }
+
+    /// Gets an iterator over the values of the BitSlabMap.
+    #[inline]
+    pub fn values(&self) -> Values<'_> {
+        Values(self.iter())
+    }
+}
+impl Debug for BitSlabMap {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        f.debug_map().entries(self.iter()).finish()
+    }
+}
+
+impl core::ops::Index<usize> for BitSlabMap {
+    type Output = bool;
+
+    #[inline]
+    fn index(&self, index: usize) -> &Self::Output {
+        if self.get(index).expect("out of index.") {
+            &true
+        } else {
+            &false
+        }
+    }
+}
+
+impl FromIterator<bool> for BitSlabMap {
+    fn from_iter<I: IntoIterator<Item = bool>>(iter: I) -> Self {
+        let mut this = Self::new();
+        for value in iter {
+            this.insert(value);
+        }
+        this
+    }
+}
+
+impl<'a> IntoIterator for &'a BitSlabMap {
+    type Item = (usize, bool);
+    type IntoIter = Iter<'a>;
+
+    #[inline]
+    fn into_iter(self) -> Self::IntoIter {
+        self.iter()
+    }
+}
+
+/// An iterator over the entries of a [`BitSlabMap`].
+///
+/// This struct is created by the [`iter`](BitSlabMap::iter).
+pub struct Iter<'a> {
+    occupied: &'a [u64],
+    values: &'a [u64],
+    word: usize,
+    bits: u64,
+    len: usize,
+}
+impl Iterator for Iter<'_> {
+    type Item = (usize, bool);
+    #[inline]
+    fn next(&mut self) -> Option<Self::Item> {
+        if self.len == 0 {
+            return None;
+        }
+        while self.bits == 0 {
+            self.word += 1;
+            self.bits = self.occupied[self.word];
+        }
+        let bit = self.bits.trailing_zeros() as usize;
+        self.bits &= self.bits - 1;
+        self.len -= 1;
+        let key = self.word * BITS + bit;
+        Some((key, self.values[self.word] & (1 << bit) != 0))
+    }
+    #[inline]
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        (self.len, Some(self.len))
+    }
+}
+impl FusedIterator for Iter<'_> {}
+impl ExactSizeIterator for Iter<'_> {}
+
+/// An iterator over the keys of a [`BitSlabMap`].
+///
+/// This struct is created by the [`keys`](BitSlabMap::keys).
+pub struct Keys<'a>(Iter<'a>);
+impl Iterator for Keys<'_> {
+    type Item = usize;
+    #[inline]
+    fn next(&mut self) -> Option<Self::Item> {
+        self.0.next().map(|(k, _)| k)
+    }
+    #[inline]
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        self.0.size_hint()
+    }
+}
+impl FusedIterator for Keys<'_> {}
+impl ExactSizeIterator for Keys<'_> {}
+
+/// An iterator over the values of a [`BitSlabMap`].
+///
+/// This struct is created by the [`values`](BitSlabMap::values).
+pub struct Values<'a>(Iter<'a>);
+impl Iterator for Values<'_> {
+    type Item = bool;
+    #[inline]
+    fn next(&mut self) -> Option<Self::Item> {
+        self.0.next().map(|(_, v)| v)
+    }
+    #[inline]
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        self.0.size_hint()
+    }
+}
+impl FusedIterator for Values<'_> {}
+impl ExactSizeIterator for Values<'_> {}
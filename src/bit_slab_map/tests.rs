@@ -0,0 +1,49 @@
+use std::collections::BTreeMap;
+
+use rand::{rngs::StdRng, Rng, SeedableRng};
+
+use crate::bit_slab_map::BitSlabMap;
+
+#[test]
+fn reuse_lowest_key() {
+    let mut s: BitSlabMap = (0..200).map(|i| i % 3 == 0).collect();
+    assert_eq!(s.remove(150), Some(true));
+    assert_eq!(s.remove(70), Some(false));
+    assert_eq!(s.insert(true), 70);
+    assert_eq!(s.insert(false), 150);
+    assert_eq!(s.insert(false), 200);
+    assert!(s[70]);
+    assert!(!s[150]);
+}
+
+#[test]
+fn random() {
+    let mut rng = StdRng::seed_from_u64(0);
+    let mut s = BitSlabMap::new();
+    let mut e = BTreeMap::new();
+    for _ in 0..10000 {
+        match rng.gen_range(0..10) {
+            0..=4 => {
+                let value = rng.gen_bool(0.5);
+                let key = s.insert(value);
+                assert!(e.insert(key, value).is_none());
+            }
+            5..=7 => {
+                let key = rng.gen_range(0..e.len() + 10);
+                assert_eq!(s.remove(key), e.remove(&key));
+            }
+            8 => {
+                let key = rng.gen_range(0..e.len() + 10);
+                let value = rng.gen_bool(0.5);
+                let old = e.get_mut(&key).map(|v| std::mem::replace(v, value));
+                assert_eq!(s.set(key, value), old);
+            }
+            _ => {
+                s.retain(|key, _| key % 5 != 0);
+                e.retain(|key, _| key % 5 != 0);
+            }
+        }
+        assert_eq!(s.len(), e.len());
+    }
+    assert!(s.iter().eq(e.iter().map(|(&k, &v)| (k, v))));
+}
@@ -0,0 +1,74 @@
+//! Cache-line alignment for the entries of a [`SlabMap`].
+
+use core::ops::{Deref, DerefMut};
+
+use crate::SlabMap;
+
+#[cfg(test)]
+mod tests;
+
+/// Pads and aligns a value to the length of a cache line.
+///
+/// Storing `CachePadded<T>` in a [`SlabMap`] places each entry on its own cache line,
+/// so entries updated from different threads do not slow each other down by false sharing.
+///
+/// The alignment is 128 bytes on x86-64 and AArch64, where adjacent cache lines are fetched in pairs, and 64 bytes elsewhere.
+///
+/// # Examples
+/// ```
+/// use slabmap::cache_padded::{AlignedSlabMap, CachePadded};
+///
+/// let mut s = AlignedSlabMap::new();
+/// let key = s.insert(CachePadded::new(10u8));
+/// *s[key] += 1;
+///
+/// assert_eq!(*s[key], 11);
+/// assert_eq!(&s[key] as *const _ as usize % CachePadded::<u8>::ALIGN, 0);
+/// ```
+#[cfg_attr(any(target_arch = "x86_64", target_arch = "aarch64"), repr(align(128)))]
+#[cfg_attr(
+    not(any(target_arch = "x86_64", target_arch = "aarch64")),
+    repr(align(64))
+)]
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct CachePadded<T>(T);
+
+impl<T> CachePadded<T> {
+    /// The alignment of `CachePadded<T>`.
+    pub const ALIGN: usize = core::mem::align_of::<Self>();
+
+    /// Wraps a value.
+    #[inline]
+    pub const fn new(value: T) -> Self {
+        Self(value)
+    }
+
+    /// Returns the wrapped value.
+    #[inline]
+    pub fn into_inner(self) -> T {
+        self.0
+    }
+}
+impl<T> Deref for CachePadded<T> {
+    type Target = T;
+
+    #[inline]
+    fn deref(&self) -> &T {
+        &self.0
+    }
+}
+impl<T> DerefMut for CachePadded<T> {
+    #[inline]
+    fn deref_mut(&mut self) -> &mut T {
+        &mut self.0
+    }
+}
+impl<T> From<T> for CachePadded<T> {
+    #[inline]
+    fn from(value: T) -> Self {
+        Self(value)
+    }
+}
+
+/// A [`SlabMap`] whose entries are each aligned to a cache line.
+pub type AlignedSlabMap<T> = SlabMap<CachePadded<T>>;
@@ -0,0 +1,15 @@
+use crate::cache_padded::{AlignedSlabMap, CachePadded};
+
+#[test]
+fn entries_do_not_share_cache_lines() {
+    let mut s = AlignedSlabMap::new();
+    let keys: Vec<_> = (0..16u32).map(|i| s.insert(CachePadded::new(i))).collect();
+    s.remove(keys[3]);
+    let addrs: Vec<_> = s.values().map(|v| v as *const _ as usize).collect();
+    for a in &addrs {
+        assert_eq!(a % CachePadded::<u32>::ALIGN, 0);
+    }
+    for w in addrs.windows(2) {
+        assert!(w[1] - w[0] >= CachePadded::<u32>::ALIGN);
+    }
+}
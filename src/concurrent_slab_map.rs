@@ -0,0 +1,222 @@
+//! A [`SlabMap`] split into shards that can be updated concurrently.
+
+use std::{
+    fmt::{self, Debug},
+    sync::{
+        atomic::{AtomicUsize, Ordering},
+        Mutex, MutexGuard, PoisonError, TryLockError,
+    },
+};
+
+use crate::SlabMap;
+
+#[cfg(test)]
+mod tests;
+
+const DEFAULT_SHARD_COUNT: usize = 16;
+
+/// A [`SlabMap`] whose key space is split into shards, each protected by its own lock.
+///
+/// The low bits of a key select the shard, so threads inserting and removing values at the same time rarely wait for each other.
+/// Inserts are spread over the shards in turn, skipping shards locked by other threads.
+///
+/// A ConcurrentSlabMap poisoned by a panic is still usable.
+///
+/// # Examples
+/// ```
+/// use slabmap::concurrent_slab_map::ConcurrentSlabMap;
+///
+/// let s = ConcurrentSlabMap::new();
+/// let keys: Vec<usize> = std::thread::scope(|scope| {
+///     let s = &s;
+///     let handles: Vec<_> = (0..4).map(|i| scope.spawn(move || s.insert(i))).collect();
+///     handles.into_iter().map(|h| h.join().unwrap()).collect()
+/// });
+///
+/// assert_eq!(s.len(), 4);
+/// for (i, &key) in keys.iter().enumerate() {
+///     assert_eq!(s.read(key, |v| *v), Some(i));
+/// }
+/// assert_eq!(s.remove(keys[0]), Some(0));
+/// ```
+pub struct ConcurrentSlabMap<T> {
+    shards: Box<[Mutex<SlabMap<T>>]>,
+    shard_bits: u32,
+    next_shard: AtomicUsize,
+}
+
+impl<T> ConcurrentSlabMap<T> {
+    /// Constructs a new, empty `ConcurrentSlabMap<T>` with the default number of shards.
+    pub fn new() -> Self {
+        Self::with_shard_count(DEFAULT_SHARD_COUNT)
+    }
+
+    /// Constructs a new, empty `ConcurrentSlabMap<T>` with at least the specified number of shards.
+    ///
+    /// The number of shards is rounded up to a power of two.
+    pub fn with_shard_count(shard_count: usize) -> Self {
+        let shard_count = shard_count.max(1).next_power_of_two();
+        Self {
+            shards: (0..shard_count)
+                .map(|_| Mutex::new(SlabMap::new()))
+                .collect(),
+            shard_bits: shard_count.trailing_zeros(),
+            next_shard: AtomicUsize::new(0),
+        }
+    }
+
+    /// Returns the number of shards.
+    #[inline]
+    pub fn shard_count(&self) -> usize {
+        self.shards.len()
+    }
+
+    fn lock(&self, shard: usize) -> MutexGuard<'_, SlabMap<T>> {
+        self.shards[shard]
+            .lock()
+            .unwrap_or_else(PoisonError::into_inner)
+    }
+    fn split_key(&self, key: usize) -> (usize, usize) {
+        (key & (self.shards.len() - 1), key >> self.shard_bits)
+    }
+    fn join_key(&self, shard: usize, local_key: usize) -> usize {
+        (local_key << self.shard_bits) | shard
+    }
+
+    /// Returns the number of elements in the ConcurrentSlabMap.
+    ///
+    /// The shards are counted one at a time, so the result may not reflect concurrent updates.
+    pub fn len(&self) -> usize {
+        (0..self.shards.len())
+            .map(|shard| self.lock(shard).len())
+            .sum()
+    }
+
+    /// Returns true if the ConcurrentSlabMap contains no elements.
+    pub fn is_empty(&self) -> bool {
+        (0..self.shards.len()).all(|shard| self.lock(shard).is_empty())
+    }
+
+    /// Returns true if the ConcurrentSlabMap contains a value for the specified key.
+    pub fn contains_key(&self, key: usize) -> bool {
+        let (shard, local_key) = self.split_key(key);
+        self.lock(shard).contains_key(local_key)
+    }
+
+    /// Inserts a value into the ConcurrentSlabMap.
+    ///
+    /// Returns the key associated with the value.
+    pub fn insert(&self, value: T) -> usize {
+        self.insert_with_key(|_| value)
+    }
+
+    /// Inserts a value given by `f` into the ConcurrentSlabMap. The key to be associated with the value is passed to `f`.
+    ///
+    /// `f` is called while the shard is locked.
+    ///
+    /// Returns the key associated with the value.
+    pub fn insert_with_key(&self, f: impl FnOnce(usize) -> T) -> usize {
+        let mask = self.shards.len() - 1;
+        let start = self.next_shard.fetch_add(1, Ordering::Relaxed);
+        for i in 0..self.shards.len() {
+            let shard = start.wrapping_add(i) & mask;
+            let mut s = match self.shards[shard].try_lock() {
+                Ok(s) => s,
+                Err(TryLockError::Poisoned(e)) => e.into_inner(),
+                Err(TryLockError::WouldBlock) => continue,
+            };
+            let local_key = s.insert_with_key(|local_key| f(self.join_key(shard, local_key)));
+            return self.join_key(shard, local_key);
+        }
+        let shard = start & mask;
+        let local_key = self
+            .lock(shard)
+            .insert_with_key(|local_key| f(self.join_key(shard, local_key)));
+        self.join_key(shard, local_key)
+    }
+
+    /// Removes a key from the ConcurrentSlabMap, returning the value at the key if the key was previously in the ConcurrentSlabMap.
+    pub fn remove(&self, key: usize) -> Option<T> {
+        let (shard, local_key) = self.split_key(key);
+        self.lock(shard).remove(local_key)
+    }
+
+    /// Calls `f` with a reference to the value corresponding to the key, while the shard is locked.
+    ///
+    /// Returns `None` if the key does not exist.
+    pub fn read<R>(&self, key: usize, f: impl FnOnce(&T) -> R) -> Option<R> {
+        let (shard, local_key) = self.split_key(key);
+        self.lock(shard).get(local_key).map(f)
+    }
+
+    /// Calls `f` with a mutable reference to the value corresponding to the key, while the shard is locked.
+    ///
+    /// Returns `None` if the key does not exist.
+    pub fn write<R>(&self, key: usize, f: impl FnOnce(&mut T) -> R) -> Option<R> {
+        let (shard, local_key) = self.split_key(key);
+        self.lock(shard).get_mut(local_key).map(f)
+    }
+
+    /// Calls `f` for each entry, locking one shard at a time.
+    ///
+    /// Entries are visited in unspecified order.
+    pub fn for_each(&self, mut f: impl FnMut(usize, &T)) {
+        for shard in 0..self.shards.len() {
+            for (local_key, value) in &*self.lock(shard) {
+                f(self.join_key(shard, local_key), value);
+            }
+        }
+    }
+
+    /// Retains only the elements specified by the predicate, locking one shard at a time.
+    pub fn retain(&self, mut f: impl FnMut(usize, &mut T) -> bool) {
+        for shard in 0..self.shards.len() {
+            self.lock(shard)
+                .retain(|local_key, value| f(self.join_key(shard, local_key), value));
+        }
+    }
+
+    /// Clears the ConcurrentSlabMap, removing all values.
+    pub fn clear(&self) {
+        for shard in 0..self.shards.len() {
+            self.lock(shard).clear();
+        }
+    }
+
+    /// Returns the keys and values as a [`SlabMap`] with the same keys.
+    pub fn into_slab_map(self) -> SlabMap<T> {
+        let shard_bits = self.shard_bits;
+        let mut entries = Vec::new();
+        for (shard, s) in self.shards.into_vec().into_iter().enumerate() {
+            let s = s.into_inner().unwrap_or_else(PoisonError::into_inner);
+            entries.extend(
+                s.into_iter()
+                    .map(|(local_key, value)| ((local_key << shard_bits) | shard, value)),
+            );
+        }
+        SlabMap::from_iter_with_capacity(entries, 0)
+    }
+}
+impl<T> Default for ConcurrentSlabMap<T> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+impl<T: Debug> Debug for ConcurrentSlabMap<T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let mut d = f.debug_map();
+        for shard in 0..self.shards.len() {
+            match self.shards[shard].try_lock() {
+                Ok(s) => {
+                    for (local_key, value) in &*s {
+                        d.entry(&self.join_key(shard, local_key), value);
+                    }
+                }
+                Err(_) => {
+                    d.entry(&shard, &format_args!("<locked>"));
+                }
+            }
+        }
+        d.finish()
+    }
+}
@@ -0,0 +1,67 @@
+use std::{collections::BTreeMap, thread};
+
+use crate::concurrent_slab_map::ConcurrentSlabMap;
+
+#[test]
+fn concurrent_insert_remove() {
+    let s = ConcurrentSlabMap::with_shard_count(4);
+    let keys: Vec<Vec<usize>> = thread::scope(|scope| {
+        let handles: Vec<_> = (0..8)
+            .map(|t| {
+                let s = &s;
+                scope.spawn(move || {
+                    let mut keys = Vec::new();
+                    for i in 0..1000 {
+                        let key = s.insert((t, i));
+                        assert_eq!(s.read(key, |v| *v), Some((t, i)));
+                        if i % 2 == 0 {
+                            assert_eq!(s.remove(key), Some((t, i)));
+                        } else {
+                            keys.push(key);
+                        }
+                    }
+                    keys
+                })
+            })
+            .collect();
+        handles.into_iter().map(|h| h.join().unwrap()).collect()
+    });
+    assert_eq!(s.len(), 8 * 500);
+
+    let mut e = BTreeMap::new();
+    for (t, keys) in keys.iter().enumerate() {
+        for (n, &key) in keys.iter().enumerate() {
+            assert!(e.insert(key, (t, n * 2 + 1)).is_none());
+        }
+    }
+    let mut a = BTreeMap::new();
+    s.for_each(|key, &v| {
+        a.insert(key, v);
+    });
+    assert_eq!(a, e);
+
+    let m = s.into_slab_map();
+    let a: BTreeMap<_, _> = m.iter().map(|(k, &v)| (k, v)).collect();
+    assert_eq!(a, e);
+}
+
+#[test]
+fn insert_with_key_and_retain() {
+    let s = ConcurrentSlabMap::with_shard_count(3);
+    assert_eq!(s.shard_count(), 4);
+    let keys: Vec<_> = (0..20).map(|_| s.insert_with_key(|key| key)).collect();
+    for &key in &keys {
+        assert_eq!(s.read(key, |v| *v), Some(key));
+        s.write(key, |v| *v += 1);
+    }
+    s.retain(|key, v| {
+        assert_eq!(*v, key + 1);
+        key % 2 == 0
+    });
+    for &key in &keys {
+        assert_eq!(s.contains_key(key), key % 2 == 0);
+    }
+    s.clear();
+    assert!(s.is_empty());
+    assert_eq!(format!("{s:?}"), "{}");
+}
@@ -0,0 +1,131 @@
+//! A [`SlabMap`] with copy-on-write cloning.
+
+use alloc::sync::Arc;
+use core::{
+    fmt::{self, Debug},
+    ops::Deref,
+};
+
+use crate::SlabMap;
+
+#[cfg(test)]
+mod tests;
+
+/// A [`SlabMap`] shared by [`Arc`], where `clone` takes O(1) time and the entries are copied on the first mutation of a shared map.
+///
+/// Read access is provided through `Deref<Target = SlabMap<T>>`, and write access through [`to_mut`](Self::to_mut).
+///
+/// # Examples
+/// ```
+/// use slabmap::cow_slab_map::CowSlabMap;
+///
+/// let mut s = CowSlabMap::new();
+/// let key = s.insert(10);
+///
+/// let snapshot = s.clone();
+/// assert!(s.is_shared());
+///
+/// *s.get_mut(key).unwrap() += 1;
+/// assert!(!s.is_shared());
+/// assert_eq!(s[key], 11);
+/// assert_eq!(snapshot[key], 10);
+/// ```
+pub struct CowSlabMap<T>(Arc<SlabMap<T>>);
+
+impl<T> CowSlabMap<T> {
+    /// Constructs a new, empty `CowSlabMap<T>`.
+    #[inline]
+    pub fn new() -> Self {
+        Self(Arc::new(SlabMap::new()))
+    }
+
+    /// Returns true if the entries are shared with other clones.
+    #[inline]
+    pub fn is_shared(&self) -> bool {
+        Arc::strong_count(&self.0) > 1
+    }
+}
+impl<T: Clone> CowSlabMap<T> {
+    /// Returns a mutable reference to the SlabMap, copying the entries if they are shared with other clones.
+    #[inline]
+    pub fn to_mut(&mut self) -> &mut SlabMap<T> {
+        Arc::make_mut(&mut self.0)
+    }
+
+    /// Returns the underlying [`SlabMap`], copying the entries if they are shared with other clones.
+    pub fn into_inner(self) -> SlabMap<T> {
+        Arc::unwrap_or_clone(self.0)
+    }
+
+    /// Returns a mutable reference to the value corresponding to the key.
+    ///
+    /// The entries are copied if the key exists and they are shared with other clones.
+    #[inline]
+    pub fn get_mut(&mut self, key: usize) -> Option<&mut T> {
+        if self.0.contains_key(key) {
+            self.to_mut().get_mut(key)
+        } else {
+            None
+        }
+    }
+
+    /// Inserts a value into the CowSlabMap.
+    ///
+    /// Returns the key associated with the value.
+    #[inline]
+    pub fn insert(&mut self, value: T) -> usize {
+        self.to_mut().insert(value)
+    }
+
+    /// Removes a key from the CowSlabMap, returning the value at the key if the key was previously in the CowSlabMap.
+    ///
+    /// The entries are copied only if the key exists and they are shared with other clones.
+    #[inline]
+    pub fn remove(&mut self, key: usize) -> Option<T> {
+        if self.0.contains_key(key) {
+            self.to_mut().remove(key)
+        } else {
+            None
+        }
+    }
+
+    /// Clears the CowSlabMap, removing all values.
+    ///
+    /// If the entries are shared with other clones, they are left to the other clones instead of being copied.
+    pub fn clear(&mut self) {
+        if self.is_shared() {
+            *self = Self::new();
+        } else {
+            self.to_mut().clear();
+        }
+    }
+}
+impl<T> Deref for CowSlabMap<T> {
+    type Target = SlabMap<T>;
+
+    #[inline]
+    fn deref(&self) -> &SlabMap<T> {
+        &self.0
+    }
+}
+impl<T> Clone for CowSlabMap<T> {
+    #[inline]
+    fn clone(&self) -> Self {
+        Self(self.0.clone())
+    }
+}
+impl<T> Default for CowSlabMap<T> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+impl<T> From<SlabMap<T>> for CowSlabMap<T> {
+    fn from(value: SlabMap<T>) -> Self {
+        Self(Arc::new(value))
+    }
+}
+impl<T: Debug> Debug for CowSlabMap<T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        self.0.fmt(f)
+    }
+}
@@ -0,0 +1,31 @@
+use crate::{cow_slab_map::CowSlabMap, SlabMap};
+
+#[test]
+fn copy_on_write() {
+    let mut s = CowSlabMap::new();
+    let keys: Vec<_> = (0..10).map(|i| s.insert(i)).collect();
+    let snapshot = s.clone();
+    assert!(s.is_shared());
+
+    assert_eq!(s.remove(100), None);
+    assert_eq!(s.get_mut(100), None);
+    assert!(s.is_shared());
+
+    assert_eq!(s.remove(keys[0]), Some(0));
+    assert!(!s.is_shared());
+    assert!(!snapshot.is_shared());
+    assert_eq!(s.len(), 9);
+    assert_eq!(snapshot.len(), 10);
+
+    let mut c = s.clone();
+    c.clear();
+    assert!(c.is_empty());
+    assert_eq!(s.len(), 9);
+    assert!(!s.is_shared());
+
+    let m: SlabMap<_> = snapshot.into_inner();
+    assert_eq!(m.len(), 10);
+    let s = CowSlabMap::from(s.into_inner());
+    assert!(!s.contains_key(keys[0]));
+    assert_eq!(format!("{s:?}"), format!("{:?}", &*s));
+}
@@ -0,0 +1,298 @@
+//! A slab that stores values contiguously.
+
+use alloc::vec::Vec;
+use core::{fmt::Debug, iter::FusedIterator};
+
+use derive_ex::derive_ex;
+
+#[cfg(test)]
+mod tests;
+
+const NONE: usize = usize::MAX;
+
+#[derive(Clone, Copy, Debug)]
+enum Slot {
+    Occupied { dense: usize },
+    Vacant { next: usize },
+}
+
+/// A slab that stores values contiguously in a `Vec`.
+///
+/// Keys are mapped to positions in the `Vec` through an indirection table (sparse set).
+/// Since values are stored without gaps, iteration never visits free space and [`SlabMap::optimize`](crate::SlabMap::optimize) is not needed,
+/// but removal is slightly slower and changes the order of values.
+///
+/// # Examples
+/// ```
+/// use slabmap::dense_slab_map::DenseSlabMap;
+///
+/// let mut s = DenseSlabMap::new();
+/// let a = s.insert("a");
+/// let b = s.insert("b");
+/// let c = s.insert("c");
+///
+/// assert_eq!(s.remove(a), Some("a"));
+/// assert_eq!(s.values(), ["c", "b"]);
+/// assert_eq!(s[b], "b");
+/// assert_eq!(s[c], "c");
+/// ```
+#[derive_ex(Clone(bound(T)), Default(bound()))]
+pub struct DenseSlabMap<T> {
+    slots: Vec<Slot>,
+    values: Vec<T>,
+    keys: Vec<usize>,
+    #[default(NONE)]
+    free_head: usize,
+}
+
+impl<T> DenseSlabMap<T> {
+    /// Constructs a new, empty `DenseSlabMap<T>`.
+    #[inline]
+    pub const fn new() -> Self {
+        Self {
+            slots: Vec::new(),
+            values: Vec::new(),
+            keys: Vec::new(),
+            free_head: NONE,
+        }
+    }
+
+    /// Constructs a new, empty `DenseSlabMap<T>` with the specified capacity.
+    pub fn with_capacity(capacity: usize) -> Self {
+        Self {
+            slots: Vec::with_capacity(capacity),
+            values: Vec::with_capacity(capacity),
+            keys: Vec::with_capacity(capacity),
+            free_head: NONE,
+        }
+    }
+
+    /// Returns the number of elements in the DenseSlabMap.
+    #[inline]
+    pub fn len(&self) -> usize {
+        self.values.len()
+    }
+
+    /// Returns true if the DenseSlabMap contains no elements.
+    #[inline]
+    pub fn is_empty(&self) -> bool {
+        self.values.is_empty()
+    }
+
+    fn dense_index(&self, key: usize) -> Option<usize> {
+        match self.slots.get(key)? {
+            Slot::Occupied { dense } => Some(*dense),
+            Slot::Vacant { .. } => None,
+        }
+    }
+
+    /// Returns a reference to the value corresponding to the key.
+    #[inline]
+    pub fn get(&self, key: usize) -> Option<&T> {
+        Some(&self.values[self.dense_index(key)?])
+    }
+
+    /// Returns a mutable reference to the value corresponding to the key.
+    #[inline]
+    pub fn get_mut(&mut self, key: usize) -> Option<&mut T> {
+        let dense = self.dense_index(key)?;
+        Some(&mut self.values[dense])
+    }
+
+    /// Returns true if the DenseSlabMap contains a value for the specified key.
+    #[inline]
+    pub fn contains_key(&self, key: usize) -> bool {
+        self.dense_index(key).is_some()
+    }
+
+    /// Inserts a value into the DenseSlabMap.
+    ///
+    /// Returns the key associated with the value.
+    pub fn insert(&mut self, value: T) -> usize {
+        let dense = self.values.len();
+        let key = if self.free_head != NONE {
+            let key = self.free_head;
+            let Slot::Vacant { next } = self.slots[key] else {
+                unreachable!()
+            };
+            self.free_head = next;
+            self.slots[key] = Slot::Occupied { dense };
+            key
+        } else {
+            self.slots.push(Slot::Occupied { dense });
+            self.slots.len() - 1
+        };
+        self.values.push(value);
+        self.keys.push(key);
+        key
+    }
+
+    /// Removes a key from the DenseSlabMap, returning the value at the key if the key was previously in the DenseSlabMap.
+    ///
+    /// The last value is moved to the position of the removed value.
+    pub fn remove(&mut self, key: usize) -> Option<T> {
+        let dense = self.dense_index(key)?;
+        let value = self.values.swap_remove(dense);
+        self.keys.swap_remove(dense);
+        if let Some(&moved) = self.keys.get(dense) {
+            self.slots[moved] = Slot::Occupied { dense };
+        }
+        if self.values.is_empty() {
+            self.clear();
+        } else {
+            self.slots[key] = Slot::Vacant {
+                next: self.free_head,
+            };
+            self.free_head = key;
+        }
+        Some(value)
+    }
+
+    /// Clears the DenseSlabMap, removing all values.
+    pub fn clear(&mut self) {
+        self.slots.clear();
+        self.values.clear();
+        self.keys.clear();
+        self.free_head = NONE;
+    }
+
+    /// Retains only the elements specified by the predicate.
+    pub fn retain(&mut self, mut f: impl FnMut(usize, &mut T) -> bool) {
+        let mut dense = 0;
+        while dense < self.values.len() {
+            let key = self.keys[dense];
+            if f(key, &mut self.values[dense]) {
+                dense += 1;
+            } else {
+                self.remove(key);
+            }
+        }
+    }
+
+    /// Returns the values as a slice.
+    ///
+    /// The order of values is unspecified and changes on removal.
+    #[inline]
+    pub fn values(&self) -> &[T] {
+        &self.values
+    }
+
+    /// Returns the values as a mutable slice.
+    ///
+    /// The order of values is unspecified and changes on removal.
+    #[inline]
+    pub fn values_mut(&mut self) -> &mut [T] {
+        &mut self.values
+    }
+
+    /// Gets an iterator over the entries of the DenseSlabMap.
+    ///
+    /// The order of entries is the same as [`values`](Self::values).
+    #[inline]
+    pub fn iter(&self) -> Iter<'_, T> {
+        Iter {
+            keys: self.keys.iter(),
+            values: self.values.iter(),
+        }
+    }
+
+    /// Gets a mutable iterator over the entries of the DenseSlabMap.
+    ///
+    /// The order of entries is the same as [`values`](Self::values).
+    #[inline]
+    pub fn iter_mut(&mut self) -> IterMut<'_, T> {
+        IterMut {
+            keys: self.keys.iter(),
+            values: self.values.iter_mut(),
+        }
+    }
+
+    /// Returns the keys as a slice.
+    ///
+    /// The order of keys is the same as [`values`](Self::values).
+    #[inline]
+    pub fn keys(&self) -> &[usize] {
+        &self.keys
+    }
+}
+impl<T: Debug> Debug for DenseSlabMap<T> {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        f.debug_map().entries(self.iter()).finish()
+    }
+}
+
+impl<T> core::ops::Index<usize> for DenseSlabMap<T> {
+    type Output = T;
+
+    #[inline]
+    fn index(&self, index: usize) -> &Self::Output {
+        self.get(index).expect("out of index.")
+    }
+}
+impl<T> core::ops::IndexMut<usize> for DenseSlabMap<T> {
+    #[inline]
+    fn index_mut(&mut self, index: usize) -> &mut Self::Output {
+        self.get_mut(index).expect("out of index.")
+    }
+}
+
+impl<'a, T> IntoIterator for &'a DenseSlabMap<T> {
+    type Item = (usize, &'a T);
+    type IntoIter = Iter<'a, T>;
+
+    #[inline]
+    fn into_iter(self) -> Self::IntoIter {
+        self.iter()
+    }
+}
+impl<'a, T> IntoIterator for &'a mut DenseSlabMap<T> {
+    type Item = (usize, &'a mut T);
+    type IntoIter = IterMut<'a, T>;
+
+    #[inline]
+    fn into_iter(self) -> Self::IntoIter {
+        self.iter_mut()
+    }
+}
+
+/// An iterator over the entries of a [`DenseSlabMap`].
+///
+/// This struct is created by the [`iter`](DenseSlabMap::iter).
+pub struct Iter<'a, T> {
+    keys: core::slice::Iter<'a, usize>,
+    values: core::slice::Iter<'a, T>,
+}
+impl<'a, T> Iterator for Iter<'a, T> {
+    type Item = (usize, &'a T);
+    #[inline]
+    fn next(&mut self) -> Option<Self::Item> {
+        Some((*self.keys.next()?, self.values.next()?))
+    }
+    #[inline]
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        self.values.size_hint()
+    }
+}
+impl<T> FusedIterator for Iter<'_, T> {}
+impl<T> ExactSizeIterator for Iter<'_, T> {}
+
+/// A mutable iterator over the entries of a [`DenseSlabMap`].
+///
+/// This struct is created by the [`iter_mut`](DenseSlabMap::iter_mut).
+pub struct IterMut<'a, T> {
+    keys: core::slice::Iter<'a, usize>,
+    values: core::slice::IterMut<'a, T>,
+}
+impl<'a, T> Iterator for IterMut<'a, T> {
+    type Item = (usize, &'a mut T);
+    #[inline]
+    fn next(&mut self) -> Option<Self::Item> {
+        Some((*self.keys.next()?, self.values.next()?))
+    }
+    #[inline]
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        self.values.size_hint()
+    }
+}
+impl<T> FusedIterator for IterMut<'_, T> {}
+impl<T> ExactSizeIterator for IterMut<'_, T> {}
@@ -0,0 +1,46 @@
+use std::collections::BTreeMap;
+
+use rand::{rngs::StdRng, Rng, SeedableRng};
+
+use crate::dense_slab_map::DenseSlabMap;
+
+#[test]
+fn reuse_key() {
+    let mut s = DenseSlabMap::new();
+    let a = s.insert(1);
+    let b = s.insert(2);
+    assert_eq!(s.remove(a), Some(1));
+    assert_eq!(s.remove(a), None);
+    assert_eq!(s.insert(3), a);
+    assert_eq!(s[b], 2);
+    s.remove(a);
+    s.remove(b);
+    assert!(s.is_empty());
+    assert_eq!(s.insert(4), 0);
+}
+
+#[test]
+fn random() {
+    let mut rng = StdRng::seed_from_u64(0);
+    let mut s = DenseSlabMap::new();
+    let mut m = BTreeMap::new();
+    for i in 0..10000 {
+        if rng.gen_bool(0.55) || m.is_empty() {
+            let key = s.insert(i);
+            assert_eq!(m.insert(key, i), None);
+        } else {
+            let key = *m.keys().nth(rng.gen_range(0..m.len())).unwrap();
+            assert_eq!(s.remove(key), m.remove(&key));
+        }
+        if i % 1000 == 0 {
+            s.retain(|key, _| key % 3 != 0);
+            m.retain(|key, _| key % 3 != 0);
+        }
+        assert_eq!(s.len(), m.len());
+    }
+    let mut a: Vec<_> = s.iter().map(|(k, v)| (k, *v)).collect();
+    a.sort();
+    let e: Vec<_> = m.into_iter().collect();
+    assert_eq!(a, e);
+    assert!(s.keys().iter().zip(s.values()).all(|(&k, v)| s[k] == *v));
+}
@@ -0,0 +1,403 @@
+//! A generational slab that stores values contiguously.
+
+use alloc::vec::Vec;
+use core::{fmt::Debug, iter::FusedIterator};
+
+use derive_ex::derive_ex;
+
+#[cfg(test)]
+mod tests;
+
+/// A key of [`DenseVersionedSlabMap`].
+///
+/// The key holds the version of the slot at the time of insertion,
+/// so a key whose value was removed is rejected even if the slot has been reused.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct VersionedKey {
+    /// The index of the slot.
+    pub index: usize,
+    /// The version of the slot. Odd while the slot is occupied.
+    pub version: u32,
+}
+
+const NONE: usize = usize::MAX;
+
+/// `dense` is the position in `values` while the slot is occupied, and the next free slot while vacant.
+#[derive(Clone, Copy, Debug)]
+struct Slot {
+    version: u32,
+    dense: usize,
+}
+impl Slot {
+    fn is_occupied(&self) -> bool {
+        self.version % 2 == 1
+    }
+}
+
+/// A generational slab that stores values contiguously in a `Vec`.
+///
+/// Values are accessed by [`VersionedKey`] through an indirection table, and stale keys are rejected by comparing versions.
+/// Since values are stored without gaps, iteration is as fast as iterating a `Vec`, but the order of values changes on removal.
+///
+/// # Examples
+/// ```
+/// use slabmap::dense_versioned_slab_map::DenseVersionedSlabMap;
+///
+/// let mut s = DenseVersionedSlabMap::new();
+/// let a = s.insert("a");
+/// let b = s.insert("b");
+///
+/// assert_eq!(s.remove(a), Some("a"));
+/// let c = s.insert("c");
+/// assert_eq!(a.index, c.index);
+/// assert_eq!(s.get(a), None);
+/// assert_eq!(s[c], "c");
+/// assert_eq!(s.values(), ["b", "c"]);
+/// ```
+#[derive_ex(Clone(bound(T)), Default(bound()))]
+pub struct DenseVersionedSlabMap<T> {
+    slots: Vec<Slot>,
+    values: Vec<T>,
+    keys: Vec<usize>,
+    #[default(NONE)]
+    free_head: usize,
+    /// Slots at or after this index were invalidated by [`invalidate_all`](Self::invalidate_all) and have not been reused yet.
+    valid_len: usize,
+}
+
+impl<T> DenseVersionedSlabMap<T> {
+    /// Constructs a new, empty `DenseVersionedSlabMap<T>`.
+    #[inline]
+    pub const fn new() -> Self {
+        Self {
+            slots: Vec::new(),
+            values: Vec::new(),
+            keys: Vec::new(),
+            free_head: NONE,
+            valid_len: 0,
+        }
+    }
+
+    /// Constructs a new, empty `DenseVersionedSlabMap<T>` with the specified capacity.
+    pub fn with_capacity(capacity: usize) -> Self {
+        Self {
+            slots: Vec::with_capacity(capacity),
+            values: Vec::with_capacity(capacity),
+            keys: Vec::with_capacity(capacity),
+            free_head: NONE,
+            valid_len: 0,
+        }
+    }
+
+    /// Returns the number of elements in the DenseVersionedSlabMap.
+    #[inline]
+    pub fn len(&self) -> usize {
+        self.values.len()
+    }
+
+    /// Returns true if the DenseVersionedSlabMap contains no elements.
+    #[inline]
+    pub fn is_empty(&self) -> bool {
+        self.values.is_empty()
+    }
+
+    fn dense_index(&self, key: VersionedKey) -> Option<usize> {
+        if key.index >= self.valid_len {
+            return None;
+        }
+        let slot = &self.slots[key.index];
+        if slot.version == key.version && slot.is_occupied() {
+            Some(slot.dense)
+        } else {
+            None
+        }
+    }
+
+    /// Returns a reference to the value corresponding to the key.
+    #[inline]
+    pub fn get(&self, key: VersionedKey) -> Option<&T> {
+        Some(&self.values[self.dense_index(key)?])
+    }
+
+    /// Returns a mutable reference to the value corresponding to the key.
+    #[inline]
+    pub fn get_mut(&mut self, key: VersionedKey) -> Option<&mut T> {
+        let dense = self.dense_index(key)?;
+        Some(&mut self.values[dense])
+    }
+
+    /// Returns true if the DenseVersionedSlabMap contains a value for the specified key.
+    #[inline]
+    pub fn contains_key(&self, key: VersionedKey) -> bool {
+        self.dense_index(key).is_some()
+    }
+
+    /// Inserts a value into the DenseVersionedSlabMap.
+    ///
+    /// Returns the key associated with the value.
+    pub fn insert(&mut self, value: T) -> VersionedKey {
+        let dense = self.values.len();
+        let index = if self.free_head != NONE {
+            let index = self.free_head;
+            self.free_head = self.slots[index].dense;
+            index
+        } else {
+            if self.valid_len == self.slots.len() {
+                self.slots.push(Slot {
+                    version: 0,
+                    dense: NONE,
+                });
+            } else if self.slots[self.valid_len].is_occupied() {
+                // Keep the version odd while skipping the version of the invalidated keys.
+                let slot = &mut self.slots[self.valid_len];
+                slot.version = slot.version.wrapping_add(1);
+            }
+            self.valid_len += 1;
+            self.valid_len - 1
+        };
+        let slot = &mut self.slots[index];
+        slot.version = slot.version.wrapping_add(1);
+        slot.dense = dense;
+        self.values.push(value);
+        self.keys.push(index);
+        VersionedKey {
+            index,
+            version: slot.version,
+        }
+    }
+
+    /// Removes a key from the DenseVersionedSlabMap, returning the value at the key if the key was previously in the DenseVersionedSlabMap.
+    ///
+    /// The last value is moved to the position of the removed value.
+    pub fn remove(&mut self, key: VersionedKey) -> Option<T> {
+        let dense = self.dense_index(key)?;
+        let value = self.values.swap_remove(dense);
+        self.keys.swap_remove(dense);
+        if let Some(&moved) = self.keys.get(dense) {
+            self.slots[moved].dense = dense;
+        }
+        self.free_slot(key.index);
+        Some(value)
+    }
+    fn free_slot(&mut self, index: usize) {
+        let slot = &mut self.slots[index];
+        slot.version = slot.version.wrapping_add(1);
+        slot.dense = self.free_head;
+        self.free_head = index;
+    }
+
+    /// Clears the DenseVersionedSlabMap, removing all values.
+    ///
+    /// All keys are invalidated, and the slots are kept for reuse.
+    pub fn clear(&mut self) {
+        self.values.clear();
+        for index in core::mem::take(&mut self.keys) {
+            self.free_slot(index);
+        }
+    }
+
+    /// Clears the DenseVersionedSlabMap and invalidates all outstanding keys without visiting each slot.
+    ///
+    /// Unlike [`clear`](Self::clear), the versions of the slots are not updated here.
+    /// Instead, the slots are marked as invalidated as a whole and their versions are updated lazily when they are reused.
+    /// The values are still dropped.
+    ///
+    /// # Examples
+    /// ```
+    /// use slabmap::dense_versioned_slab_map::DenseVersionedSlabMap;
+    ///
+    /// let mut s = DenseVersionedSlabMap::new();
+    /// let a = s.insert("a");
+    /// let b = s.insert("b");
+    ///
+    /// s.invalidate_all();
+    /// assert!(s.is_empty());
+    ///
+    /// let c = s.insert("c");
+    /// assert_eq!(c.index, a.index);
+    /// assert_eq!(s.get(a), None);
+    /// assert_eq!(s.get(b), None);
+    /// assert_eq!(s[c], "c");
+    /// ```
+    pub fn invalidate_all(&mut self) {
+        self.values.clear();
+        self.keys.clear();
+        self.free_head = NONE;
+        self.valid_len = 0;
+    }
+
+    /// Retains only the elements specified by the predicate.
+    pub fn retain(&mut self, mut f: impl FnMut(VersionedKey, &mut T) -> bool) {
+        let mut dense = 0;
+        while dense < self.values.len() {
+            let index = self.keys[dense];
+            let key = VersionedKey {
+                index,
+                version: self.slots[index].version,
+            };
+            if f(key, &mut self.values[dense]) {
+                dense += 1;
+            } else {
+                self.remove(key);
+            }
+        }
+    }
+
+    /// Returns the values as a slice.
+    ///
+    /// The order of values is unspecified and changes on removal.
+    #[inline]
+    pub fn values(&self) -> &[T] {
+        &self.values
+    }
+
+    /// Returns the values as a mutable slice.
+    ///
+    /// The order of values is unspecified and changes on removal.
+    #[inline]
+    pub fn values_mut(&mut self) -> &mut [T] {
+        &mut self.values
+    }
+
+    /// Gets an iterator over the entries of the DenseVersionedSlabMap.
+    ///
+    /// The order of entries is the same as [`values`](Self::values).
+    #[inline]
+    pub fn iter(&self) -> Iter<'_, T> {
+        Iter {
+            keys: self.keys.iter(),
+            values: self.values.iter(),
+            slots: &self.slots,
+        }
+    }
+
+    /// Gets a mutable iterator over the entries of the DenseVersionedSlabMap.
+    ///
+    /// The order of entries is the same as [`values`](Self::values).
+    #[inline]
+    pub fn iter_mut(&mut self) -> IterMut<'_, T> {
+        IterMut {
+            keys: self.keys.iter(),
+            values: self.values.iter_mut(),
+            slots: &self.slots,
+        }
+    }
+
+    /// Gets an iterator over the keys of the DenseVersionedSlabMap.
+    ///
+    /// The order of keys is the same as [`values`](Self::values).
+    #[inline]
+    pub fn keys(&self) -> Keys<'_, T> {
+        Keys(self.iter())
+    }
+}
+impl<T: Debug> Debug for DenseVersionedSlabMap<T> {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        f.debug_map().entries(self.iter()).finish()
+    }
+}
+
+impl<T> core::ops::Index<VersionedKey> for DenseVersionedSlabMap<T> {
+    type Output = T;
+
+    #[inline]
+    fn index(&self, index: VersionedKey) -> &Self::Output {
+        self.get(index).expect("out of index.")
+    }
+}
+impl<T> core::ops::IndexMut<VersionedKey> for DenseVersionedSlabMap<T> {
+    #[inline]
+    fn index_mut(&mut self, index: VersionedKey) -> &mut Self::Output {
+        self.get_mut(index).expect("out of index.")
+    }
+}
+
+impl<'a, T> IntoIterator for &'a DenseVersionedSlabMap<T> {
+    type Item = (VersionedKey, &'a T);
+    type IntoIter = Iter<'a, T>;
+
+    #[inline]
+    fn into_iter(self) -> Self::IntoIter {
+        self.iter()
+    }
+}
+impl<'a, T> IntoIterator for &'a mut DenseVersionedSlabMap<T> {
+    type Item = (VersionedKey, &'a mut T);
+    type IntoIter = IterMut<'a, T>;
+
+    #[inline]
+    fn into_iter(self) -> Self::IntoIter {
+        self.iter_mut()
+    }
+}
+
+fn make_key(slots: &[Slot], index: usize) -> VersionedKey {
+    VersionedKey {
+        index,
+        version: slots[index].version,
+    }
+}
+
+/// An iterator over the entries of a [`DenseVersionedSlabMap`].
+///
+/// This struct is created by the [`iter`](DenseVersionedSlabMap::iter).
+pub struct Iter<'a, T> {
+    keys: core::slice::Iter<'a, usize>,
+    values: core::slice::Iter<'a, T>,
+    slots: &'a [Slot],
+}
+impl<'a, T> Iterator for Iter<'a, T> {
+    type Item = (VersionedKey, &'a T);
+    #[inline]
+    fn next(&mut self) -> Option<Self::Item> {
+        let &index = self.keys.next()?;
+        Some((make_key(self.slots, index), self.values.next()?))
+    }
+    #[inline]
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        self.values.size_hint()
+    }
+}
+impl<T> FusedIterator for Iter<'_, T> {}
+impl<T> ExactSizeIterator for Iter<'_, T> {}
+
+/// A mutable iterator over the entries of a [`DenseVersionedSlabMap`].
+///
+/// This struct is created by the [`iter_mut`](DenseVersionedSlabMap::iter_mut).
+pub struct IterMut<'a, T> {
+    keys: core::slice::Iter<'a, usize>,
+    values: core::slice::IterMut<'a, T>,
+    slots: &'a [Slot],
+}
+impl<'a, T> Iterator for IterMut<'a, T> {
+    type Item = (VersionedKey, &'a mut T);
+    #[inline]
+    fn next(&mut self) -> Option<Self::Item> {
+        let &index = self.keys.next()?;
+        Some((make_key(self.slots, index), self.values.next()?))
+    }
+    #[inline]
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        self.values.size_hint()
+    }
+}
+impl<T> FusedIterator for IterMut<'_, T> {}
+impl<T> ExactSizeIterator for IterMut<'_, T> {}
+
+/// An iterator over the keys of a [`DenseVersionedSlabMap`].
+///
+/// This struct is created by the [`keys`](DenseVersionedSlabMap::keys).
+pub struct Keys<'a, T>(Iter<'a, T>);
+impl<T> Iterator for Keys<'_, T> {
+    type Item = VersionedKey;
+    #[inline]
+    fn next(&mut self) -> Option<Self::Item> {
+        self.0.next().map(|(k, _)| k)
+    }
+    #[inline]
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        self.0.size_hint()
+    }
+}
+impl<T> FusedIterator for Keys<'_, T> {}
+impl<T> ExactSizeIterator for Keys<'_, T> {}
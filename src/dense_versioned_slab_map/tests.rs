@@ -0,0 +1,92 @@
+use std::collections::HashMap;
+
+use rand::{rngs::StdRng, Rng, SeedableRng};
+
+use crate::dense_versioned_slab_map::DenseVersionedSlabMap;
+
+#[test]
+fn stale_key() {
+    let mut s = DenseVersionedSlabMap::new();
+    let a = s.insert(1);
+    s.remove(a);
+    let b = s.insert(2);
+    assert_eq!(a.index, b.index);
+    assert_ne!(a.version, b.version);
+    assert_eq!(s.get(a), None);
+    assert_eq!(s.remove(a), None);
+    assert_eq!(s.get(b), Some(&2));
+}
+
+#[test]
+fn clear_invalidates_keys() {
+    let mut s = DenseVersionedSlabMap::new();
+    let a = s.insert(1);
+    let b = s.insert(2);
+    s.clear();
+    assert!(s.is_empty());
+    assert!(!s.contains_key(a));
+    assert!(!s.contains_key(b));
+    let c = s.insert(3);
+    assert_eq!(s.len(), 1);
+    assert_eq!(s[c], 3);
+}
+
+#[test]
+fn random() {
+    let mut rng = StdRng::seed_from_u64(0);
+    let mut s = DenseVersionedSlabMap::new();
+    let mut e = HashMap::new();
+    let mut removed = Vec::new();
+    for i in 0..5000 {
+        match rng.gen_range(0..10) {
+            0..=4 => {
+                let key = s.insert(i);
+                e.insert(key, i);
+            }
+            5..=8 => {
+                if let Some(&key) = e.keys().next() {
+                    assert_eq!(s.remove(key), e.remove(&key));
+                    removed.push(key);
+                }
+            }
+            _ => {
+                s.retain(|_, v| *v % 7 != 0);
+                e.retain(|_, v| *v % 7 != 0);
+            }
+        }
+        assert_eq!(s.len(), e.len());
+    }
+    for (key, value) in &e {
+        assert_eq!(s.get(*key), Some(value));
+    }
+    for key in removed {
+        assert_eq!(s.get(key), None);
+    }
+    let mut a: Vec<_> = s.iter().map(|(k, &v)| (k, v)).collect();
+    let mut b: Vec<_> = e.into_iter().collect();
+    a.sort();
+    b.sort();
+    assert_eq!(a, b);
+    assert_eq!(s.values().len(), s.len());
+}
+
+#[test]
+fn invalidate_all() {
+    let mut s = DenseVersionedSlabMap::new();
+    let keys: Vec<_> = (0..10).map(|i| s.insert(i)).collect();
+    for &key in &keys[..5] {
+        s.remove(key);
+    }
+    s.invalidate_all();
+    assert!(s.is_empty());
+    assert!(keys.iter().all(|&key| !s.contains_key(key)));
+
+    let new_keys: Vec<_> = (0..12).map(|i| s.insert(i)).collect();
+    for (i, key) in new_keys.iter().enumerate() {
+        assert!(!keys.contains(key));
+        assert_eq!(key.version % 2, 1);
+        assert_eq!(s[*key], i);
+    }
+    assert_eq!(s.len(), 12);
+    assert!(keys.iter().all(|&key| !s.contains_key(key)));
+}
@@ -0,0 +1,325 @@
+//! A [`SlabMap`] handle with a stable ABI layout, for sharing a slab across a dynamic library boundary.
+//!
+//! [`FfiSlabMap`] does not depend on the layout of [`SlabMap`], which is not stable between compilers.
+//! All operations are dispatched through a table of `unsafe extern "C"` functions owned by the module that created the map,
+//! so a host and a plugin built as `cdylib` can both access the same map.
+//!
+//! # Layout
+//!
+//! ```text
+//! #[repr(C)] FfiSlabMap<T> { data: *mut c_void, vtable: *const FfiSlabMapVTable<T> }
+//! #[repr(C)] FfiSlabMapVTable<T> {
+//!     abi_version: u32,
+//!     value_size: usize,
+//!     value_align: usize,
+//!     len: unsafe extern "C" fn(*const c_void) -> usize,
+//!     get: unsafe extern "C" fn(*const c_void, FfiKey) -> *const T,
+//!     get_mut: unsafe extern "C" fn(*mut c_void, FfiKey) -> *mut T,
+//!     insert: unsafe extern "C" fn(*mut c_void, *mut T) -> FfiKey,
+//!     remove: unsafe extern "C" fn(*mut c_void, FfiKey, *mut T) -> FfiError,
+//!     try_reserve: unsafe extern "C" fn(*mut c_void, usize) -> FfiError,
+//!     drop: unsafe extern "C" fn(*mut c_void),
+//! }
+//! ```
+//!
+//! `T` itself must have the same layout on both sides, e.g. by being `#[repr(C)]`.
+
+use alloc::boxed::Box;
+use core::{
+    ffi::c_void,
+    fmt::{self, Debug},
+    marker::PhantomData,
+    mem::{align_of, size_of, MaybeUninit},
+};
+
+use crate::SlabMap;
+
+#[cfg(test)]
+mod tests;
+
+/// The version of the layout of [`FfiSlabMapVTable`].
+pub const FFI_ABI_VERSION: u32 = 1;
+
+/// A key of [`FfiSlabMap`] with a stable ABI layout.
+#[repr(transparent)]
+#[derive(Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct FfiKey(pub usize);
+
+impl From<usize> for FfiKey {
+    fn from(value: usize) -> Self {
+        Self(value)
+    }
+}
+impl From<FfiKey> for usize {
+    fn from(value: FfiKey) -> Self {
+        value.0
+    }
+}
+
+/// An error returned by [`FfiSlabMap`] with a stable ABI layout.
+#[repr(C)]
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum FfiError {
+    /// The operation succeeded. This is never returned as `Err`.
+    None = 0,
+    /// The key does not exist.
+    KeyNotFound = 1,
+    /// Memory allocation failed or the capacity overflowed.
+    AllocationFailed = 2,
+    /// The map was created with an incompatible layout.
+    IncompatibleLayout = 3,
+}
+impl FfiError {
+    fn into_result(self) -> Result<(), Self> {
+        match self {
+            Self::None => Ok(()),
+            e => Err(e),
+        }
+    }
+}
+impl fmt::Display for FfiError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::None => write!(f, "no error"),
+            Self::KeyNotFound => write!(f, "key not found"),
+            Self::AllocationFailed => write!(f, "memory allocation failed"),
+            Self::IncompatibleLayout => write!(f, "incompatible layout"),
+        }
+    }
+}
+impl core::error::Error for FfiError {}
+
+/// A table of functions used by [`FfiSlabMap`].
+///
+/// The functions are implemented by the module that created the map.
+#[repr(C)]
+pub struct FfiSlabMapVTable<T> {
+    /// Must be [`FFI_ABI_VERSION`].
+    pub abi_version: u32,
+    /// `size_of::<T>()` in the module that created the map.
+    pub value_size: usize,
+    /// `align_of::<T>()` in the module that created the map.
+    pub value_align: usize,
+    len: unsafe extern "C" fn(*const c_void) -> usize,
+    get: unsafe extern "C" fn(*const c_void, FfiKey) -> *const T,
+    get_mut: unsafe extern "C" fn(*mut c_void, FfiKey) -> *mut T,
+    insert: unsafe extern "C" fn(*mut c_void, *mut T) -> FfiKey,
+    remove: unsafe extern "C" fn(*mut c_void, FfiKey, *mut T) -> FfiError,
+    try_reserve: unsafe extern "C" fn(*mut c_void, usize) -> FfiError,
+    drop: unsafe extern "C" fn(*mut c_void),
+}
+
+impl<T> FfiSlabMapVTable<T> {
+    const VTABLE: Self = Self {
+        abi_version: FFI_ABI_VERSION,
+        value_size: size_of::<T>(),
+        value_align: align_of::<T>(),
+        len: Self::len,
+        get: Self::get,
+        get_mut: Self::get_mut,
+        insert: Self::insert,
+        remove: Self::remove,
+        try_reserve: Self::try_reserve,
+        drop: Self::drop,
+    };
+
+    fn is_compatible(&self) -> bool {
+        self.abi_version == FFI_ABI_VERSION
+            && self.value_size == size_of::<T>()
+            && self.value_align == align_of::<T>()
+    }
+
+    unsafe fn map<'a>(data: *const c_void) -> &'a SlabMap<T> {
+        &*(data as *const SlabMap<T>)
+    }
+    unsafe fn map_mut<'a>(data: *mut c_void) -> &'a mut SlabMap<T> {
+        &mut *(data as *mut SlabMap<T>)
+    }
+
+    unsafe extern "C" fn len(data: *const c_void) -> usize {
+        Self::map(data).len()
+    }
+    unsafe extern "C" fn get(data: *const c_void, key: FfiKey) -> *const T {
+        match Self::map(data).get(key.0) {
+            Some(value) => value,
+            None => core::ptr::null(),
+        }
+    }
+    unsafe extern "C" fn get_mut(data: *mut c_void, key: FfiKey) -> *mut T {
+        match Self::map_mut(data).get_mut(key.0) {
+            Some(value) => value,
+            None => core::ptr::null_mut(),
+        }
+    }
+    unsafe extern "C" fn insert(data: *mut c_void, value: *mut T) -> FfiKey {
+        FfiKey(Self::map_mut(data).insert(value.read()))
+    }
+    unsafe extern "C" fn remove(data: *mut c_void, key: FfiKey, value: *mut T) -> FfiError {
+        match Self::map_mut(data).remove(key.0) {
+            Some(v) => {
+                value.write(v);
+                FfiError::None
+            }
+            None => FfiError::KeyNotFound,
+        }
+    }
+    unsafe extern "C" fn try_reserve(data: *mut c_void, additional: usize) -> FfiError {
+        match Self::map_mut(data).try_reserve(additional) {
+            Ok(()) => FfiError::None,
+            Err(_) => FfiError::AllocationFailed,
+        }
+    }
+    unsafe extern "C" fn drop(data: *mut c_void) {
+        drop(Box::from_raw(data as *mut SlabMap<T>));
+    }
+}
+
+/// A [`SlabMap`] handle with a stable ABI layout.
+///
+/// See the [module-level documentation](self) for the layout.
+///
+/// # Examples
+/// ```
+/// use slabmap::ffi::FfiSlabMap;
+///
+/// let mut s = FfiSlabMap::new();
+/// let key = s.insert(10u32);
+///
+/// assert_eq!(s.get(key), Some(&10));
+/// assert_eq!(s.remove(key), Ok(10));
+/// assert!(s.remove(key).is_err());
+/// ```
+#[repr(C)]
+pub struct FfiSlabMap<T> {
+    data: *mut c_void,
+    vtable: *const FfiSlabMapVTable<T>,
+    _phantom: PhantomData<SlabMap<T>>,
+}
+
+unsafe impl<T: Send> Send for FfiSlabMap<T> {}
+unsafe impl<T: Sync> Sync for FfiSlabMap<T> {}
+
+impl<T> FfiSlabMap<T> {
+    /// Constructs a new, empty `FfiSlabMap<T>`.
+    pub fn new() -> Self {
+        Self::from(SlabMap::new())
+    }
+
+    /// Constructs a `FfiSlabMap<T>` from raw parts received from another module.
+    ///
+    /// Returns an error if the map was created with an incompatible layout.
+    ///
+    /// # Safety
+    /// `data` and `vtable` must be obtained from [`into_raw_parts`](Self::into_raw_parts) with the same `T`,
+    /// and the module that created them must remain loaded while the map is alive.
+    pub unsafe fn from_raw_parts(
+        data: *mut c_void,
+        vtable: *const FfiSlabMapVTable<T>,
+    ) -> Result<Self, FfiError> {
+        if !(*vtable).is_compatible() {
+            return Err(FfiError::IncompatibleLayout);
+        }
+        Ok(Self {
+            data,
+            vtable,
+            _phantom: PhantomData,
+        })
+    }
+
+    /// Decomposes the map into raw parts that can be passed across the ABI boundary.
+    pub fn into_raw_parts(self) -> (*mut c_void, *const FfiSlabMapVTable<T>) {
+        let parts = (self.data, self.vtable);
+        core::mem::forget(self);
+        parts
+    }
+
+    fn vtable(&self) -> &FfiSlabMapVTable<T> {
+        unsafe { &*self.vtable }
+    }
+
+    /// Returns the number of elements in the map.
+    pub fn len(&self) -> usize {
+        unsafe { (self.vtable().len)(self.data) }
+    }
+
+    /// Returns true if the map contains no elements.
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    /// Returns a reference to the value corresponding to the key.
+    pub fn get(&self, key: FfiKey) -> Option<&T> {
+        unsafe { (self.vtable().get)(self.data, key).as_ref() }
+    }
+
+    /// Returns a mutable reference to the value corresponding to the key.
+    pub fn get_mut(&mut self, key: FfiKey) -> Option<&mut T> {
+        unsafe { (self.vtable().get_mut)(self.data, key).as_mut() }
+    }
+
+    /// Returns true if the map contains a value for the specified key.
+    pub fn contains_key(&self, key: FfiKey) -> bool {
+        self.get(key).is_some()
+    }
+
+    /// Inserts a value into the map, returning the key associated with the value.
+    pub fn insert(&mut self, value: T) -> FfiKey {
+        let mut value = MaybeUninit::new(value);
+        unsafe { (self.vtable().insert)(self.data, value.as_mut_ptr()) }
+    }
+
+    /// Removes a key from the map, returning the value at the key.
+    pub fn remove(&mut self, key: FfiKey) -> Result<T, FfiError> {
+        let mut value = MaybeUninit::uninit();
+        unsafe {
+            (self.vtable().remove)(self.data, key, value.as_mut_ptr()).into_result()?;
+            Ok(value.assume_init())
+        }
+    }
+
+    /// Try to reserve capacity for at least additional more elements to be inserted in the map.
+    pub fn try_reserve(&mut self, additional: usize) -> Result<(), FfiError> {
+        unsafe { (self.vtable().try_reserve)(self.data, additional).into_result() }
+    }
+}
+impl<T> Default for FfiSlabMap<T> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+impl<T> From<SlabMap<T>> for FfiSlabMap<T> {
+    fn from(value: SlabMap<T>) -> Self {
+        Self {
+            data: Box::into_raw(Box::new(value)) as *mut c_void,
+            vtable: &FfiSlabMapVTable::<T>::VTABLE,
+            _phantom: PhantomData,
+        }
+    }
+}
+impl<T> Drop for FfiSlabMap<T> {
+    fn drop(&mut self) {
+        unsafe { (self.vtable().drop)(self.data) }
+    }
+}
+impl<T> Debug for FfiSlabMap<T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("FfiSlabMap")
+            .field("len", &self.len())
+            .finish_non_exhaustive()
+    }
+}
+
+impl<T> core::ops::Index<FfiKey> for FfiSlabMap<T> {
+    type Output = T;
+
+    #[inline]
+    fn index(&self, index: FfiKey) -> &Self::Output {
+        self.get(index).expect("out of index.")
+    }
+}
+impl<T> core::ops::IndexMut<FfiKey> for FfiSlabMap<T> {
+    #[inline]
+    fn index_mut(&mut self, index: FfiKey) -> &mut Self::Output {
+        self.get_mut(index).expect("out of index.")
+    }
+}
@@ -0,0 +1,49 @@
+use std::rc::Rc;
+
+use crate::{
+    ffi::{FfiError, FfiKey, FfiSlabMap},
+    SlabMap,
+};
+
+#[test]
+fn insert_remove() {
+    let mut s = FfiSlabMap::new();
+    let k0 = s.insert(10);
+    let k1 = s.insert(20);
+    assert_eq!(s.len(), 2);
+    assert_eq!(s[k0], 10);
+    s[k1] += 1;
+    assert_eq!(s.remove(k1), Ok(21));
+    assert_eq!(s.remove(k1), Err(FfiError::KeyNotFound));
+    assert_eq!(s.len(), 1);
+}
+
+#[test]
+fn from_slab_map() {
+    let mut m = SlabMap::new();
+    let key = m.insert("a");
+    let s = FfiSlabMap::from(m);
+    assert_eq!(s.get(FfiKey(key)), Some(&"a"));
+}
+
+#[test]
+fn raw_parts() {
+    let mut s = FfiSlabMap::new();
+    let key = s.insert(5u64);
+    let (data, vtable) = s.into_raw_parts();
+    let s = unsafe { FfiSlabMap::from_raw_parts(data, vtable) }.unwrap();
+    assert_eq!(s[key], 5);
+}
+
+#[test]
+fn drop_values() {
+    let value = Rc::new(());
+    let mut s = FfiSlabMap::new();
+    s.insert(value.clone());
+    let key = s.insert(value.clone());
+    assert_eq!(Rc::strong_count(&value), 3);
+    drop(s.remove(key));
+    assert_eq!(Rc::strong_count(&value), 2);
+    drop(s);
+    assert_eq!(Rc::strong_count(&value), 1);
+}
@@ -0,0 +1,107 @@
+//! A process-wide [`SlabMap`] for registering handles.
+
+use std::sync::{Mutex, MutexGuard, PoisonError};
+
+use crate::SlabMap;
+
+#[cfg(test)]
+mod tests;
+
+/// A [`SlabMap`] protected by a mutex that can be placed in a `static`.
+///
+/// This is useful for a global handle registry, e.g. for passing Rust objects to FFI callbacks as integer handles.
+///
+/// # Examples
+/// ```
+/// use slabmap::global::GlobalSlabMap;
+///
+/// static CALLBACKS: GlobalSlabMap<String> = GlobalSlabMap::new();
+///
+/// let key = CALLBACKS.register("hello".to_string());
+/// assert_eq!(CALLBACKS.with(key, |s| s.len()), Some(5));
+/// assert_eq!(CALLBACKS.unregister(key), Some("hello".to_string()));
+/// assert_eq!(CALLBACKS.with(key, |s| s.len()), None);
+/// ```
+pub struct GlobalSlabMap<T>(Mutex<SlabMap<T>>);
+
+impl<T> GlobalSlabMap<T> {
+    /// Constructs a new, empty `GlobalSlabMap<T>`.
+    pub const fn new() -> Self {
+        Self(Mutex::new(SlabMap::new()))
+    }
+
+    /// Locks the GlobalSlabMap and returns the underlying [`SlabMap`].
+    ///
+    /// A GlobalSlabMap poisoned by a panic is still usable.
+    pub fn lock(&self) -> MutexGuard<'_, SlabMap<T>> {
+        self.0.lock().unwrap_or_else(PoisonError::into_inner)
+    }
+
+    /// Inserts a value and returns the key associated with it.
+    pub fn register(&self, value: T) -> usize {
+        self.lock().insert(value)
+    }
+
+    /// Removes a key, returning the value at the key if the key was registered.
+    pub fn unregister(&self, key: usize) -> Option<T> {
+        self.lock().remove(key)
+    }
+
+    /// Calls `f` with a reference to the value corresponding to the key.
+    ///
+    /// Returns `None` if the key is not registered.
+    /// The GlobalSlabMap is locked while `f` is running, so `f` must not access the same GlobalSlabMap.
+    pub fn with<R>(&self, key: usize, f: impl FnOnce(&T) -> R) -> Option<R> {
+        self.lock().get(key).map(f)
+    }
+
+    /// Calls `f` with a mutable reference to the value corresponding to the key.
+    ///
+    /// Returns `None` if the key is not registered.
+    /// The GlobalSlabMap is locked while `f` is running, so `f` must not access the same GlobalSlabMap.
+    pub fn with_mut<R>(&self, key: usize, f: impl FnOnce(&mut T) -> R) -> Option<R> {
+        self.lock().get_mut(key).map(f)
+    }
+
+    /// Returns true if the key is registered.
+    pub fn contains_key(&self, key: usize) -> bool {
+        self.lock().contains_key(key)
+    }
+
+    /// Returns the number of registered values.
+    pub fn len(&self) -> usize {
+        self.lock().len()
+    }
+
+    /// Returns true if no values are registered.
+    pub fn is_empty(&self) -> bool {
+        self.lock().is_empty()
+    }
+}
+impl<T> Default for GlobalSlabMap<T> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// Declares a `static` [`GlobalSlabMap`].
+///
+/// # Examples
+/// ```
+/// slabmap::static_slabmap! {
+///     /// Handles passed to C.
+///     pub static HANDLES: Vec<u8>;
+/// }
+///
+/// let key = HANDLES.register(vec![1, 2, 3]);
+/// assert_eq!(HANDLES.with(key, |v| v.len()), Some(3));
+/// ```
+#[macro_export]
+macro_rules! static_slabmap {
+    ($($(#[$attr:meta])* $vis:vis static $name:ident : $t:ty;)*) => {
+        $(
+            $(#[$attr])*
+            $vis static $name: $crate::global::GlobalSlabMap<$t> = $crate::global::GlobalSlabMap::new();
+        )*
+    };
+}
@@ -0,0 +1,50 @@
+use std::thread;
+
+use crate::global::GlobalSlabMap;
+
+static MAP: GlobalSlabMap<usize> = GlobalSlabMap::new();
+
+#[test]
+fn threads() {
+    let keys: Vec<_> = (0..8)
+        .map(|i| {
+            thread::spawn(move || {
+                (0..100)
+                    .map(|j| MAP.register(i * 100 + j))
+                    .collect::<Vec<_>>()
+            })
+        })
+        .flat_map(|h| h.join().unwrap())
+        .collect();
+    assert_eq!(MAP.len(), 800);
+    for key in keys {
+        let value = MAP.with(key, |v| *v).unwrap();
+        assert_eq!(MAP.unregister(key), Some(value));
+    }
+    assert!(MAP.is_empty());
+}
+
+#[test]
+fn poisoned() {
+    let map = GlobalSlabMap::new();
+    let key = map.register(1);
+    let _ = thread::scope(|s| {
+        s.spawn(|| {
+            map.with_mut(key, |_| panic!("poison"));
+        })
+        .join()
+    });
+    assert_eq!(map.with(key, |v| *v), Some(1));
+}
+
+crate::static_slabmap! {
+    static DECLARED: String;
+}
+
+#[test]
+fn macro_declares_static() {
+    let key = DECLARED.register("a".to_string());
+    assert!(DECLARED.contains_key(key));
+    DECLARED.with_mut(key, |s| s.push('b'));
+    assert_eq!(DECLARED.unregister(key).as_deref(), Some("ab"));
+}
@@ -0,0 +1,556 @@
+//! [`SlabMap`]s that record and replay structural mutations deterministically.
+
+use alloc::vec::Vec;
+use core::{fmt, fmt::Debug};
+
+use derive_ex::derive_ex;
+
+use crate::{
+    slab_map::{Iter, IterMut, Keys, Values, ValuesMut},
+    SlabMap,
+};
+
+#[cfg(test)]
+mod tests;
+
+/// A structural mutation recorded by [`JournaledSlabMap`] or applied to a [`Replica`].
+///
+/// [`JournaledSlabMap`] records only keys and the kinds of operations, so `T` is `()` in its log.
+/// A [`Replica`] needs the inserted values, which can be attached with [`map`](Self::map).
+#[derive(Clone, Debug, PartialEq, Eq, Hash)]
+pub enum Op<T = ()> {
+    /// A value was inserted with the key.
+    Insert { key: usize, value: T },
+    /// The value at the key was removed.
+    Remove { key: usize },
+    /// The values at the keys were removed by [`retain`](JournaledSlabMap::retain). The keys are sorted.
+    Retain { removed: Vec<usize> },
+    /// All values were removed.
+    Clear,
+    /// The free space was optimized.
+    Optimize,
+}
+
+impl<T> Op<T> {
+    /// Converts the value of an [`Insert`](Self::Insert) with `f`, which is called with the key and the value.
+    ///
+    /// # Examples
+    /// ```
+    /// use slabmap::journal::Op;
+    ///
+    /// let op = Op::Insert { key: 3, value: () };
+    /// assert_eq!(op.map(|key, ()| key * 10), Op::Insert { key: 3, value: 30 });
+    /// ```
+    pub fn map<U>(self, f: impl FnOnce(usize, T) -> U) -> Op<U> {
+        match self {
+            Op::Insert { key, value } => Op::Insert {
+                key,
+                value: f(key, value),
+            },
+            Op::Remove { key } => Op::Remove { key },
+            Op::Retain { removed } => Op::Retain { removed },
+            Op::Clear => Op::Clear,
+            Op::Optimize => Op::Optimize,
+        }
+    }
+
+    /// Converts from `&Op<T>` to `Op<&T>`.
+    pub fn as_ref(&self) -> Op<&T> {
+        match self {
+            Op::Insert { key, value } => Op::Insert { key: *key, value },
+            Op::Remove { key } => Op::Remove { key: *key },
+            Op::Retain { removed } => Op::Retain {
+                removed: removed.clone(),
+            },
+            Op::Clear => Op::Clear,
+            Op::Optimize => Op::Optimize,
+        }
+    }
+}
+
+/// An error returned by [`SlabMap::replay`] when the log does not match the map.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub struct ReplayError {
+    /// The position of the operation that could not be replayed.
+    pub position: usize,
+}
+impl fmt::Display for ReplayError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "operation {} could not be replayed", self.position)
+    }
+}
+impl core::error::Error for ReplayError {}
+
+impl<T> SlabMap<T> {
+    /// Reconstructs a SlabMap from a log of operations.
+    ///
+    /// The resulting SlabMap has the same keys and reuses free space in the same order as the recorded one.
+    /// The value of each insertion is created by `value`, which is called with the key and a reference to the value in the log.
+    ///
+    /// The log of [`JournaledSlabMap`] does not contain values.
+    /// To transfer its state, values can be taken from the journaled map,
+    /// and any value can be used for a key that is removed later in the log.
+    ///
+    /// Returns an error if a key in the log does not match the key assigned during replay.
+    ///
+    /// # Examples
+    /// ```
+    /// use slabmap::{journal::JournaledSlabMap, SlabMap};
+    ///
+    /// let mut s = JournaledSlabMap::new();
+    /// let k0 = s.insert("a");
+    /// s.insert("b");
+    /// s.remove(k0);
+    ///
+    /// let mut r = SlabMap::replay(s.log(), |key, ()| s.get(key).copied().unwrap_or("")).unwrap();
+    /// assert_eq!(r.iter().collect::<Vec<_>>(), s.iter().collect::<Vec<_>>());
+    /// assert_eq!(r.insert("c"), s.insert("c"));
+    /// ```
+    pub fn replay<'a, V: 'a>(
+        log: impl IntoIterator<Item = &'a Op<V>>,
+        mut value: impl FnMut(usize, &'a V) -> T,
+    ) -> Result<Self, ReplayError> {
+        let mut r = Replica::new();
+        for op in log {
+            r.apply(op.as_ref().map(&mut value))?;
+        }
+        Ok(r.into_slab_map())
+    }
+}
+
+/// A [`SlabMap`] driven by a stream of operations, for keeping several maps in agreement.
+///
+/// Applying the same operations in the same order to two replicas always yields the same keys and the same layout,
+/// so peers can agree on keys without sending them.
+/// [`fingerprint`](Self::fingerprint) summarizes the applied operations and can be exchanged to detect divergence.
+///
+/// # Examples
+/// ```
+/// use slabmap::journal::Replica;
+///
+/// let mut server = Replica::new();
+/// let mut client = Replica::new();
+///
+/// let key = server.insert("a");
+/// assert_eq!(client.insert("a"), key);
+///
+/// server.remove(key);
+/// client.remove(key);
+/// assert_eq!(server.insert("b"), client.insert("b"));
+/// assert_eq!(server.fingerprint(), client.fingerprint());
+/// ```
+#[derive_ex(Clone(bound(T)), Default(bound()))]
+pub struct Replica<T> {
+    map: SlabMap<T>,
+    applied: usize,
+    #[default(FINGERPRINT_INIT)]
+    fingerprint: u64,
+}
+
+const FINGERPRINT_INIT: u64 = 0xcbf2_9ce4_8422_2325;
+
+impl<T> Replica<T> {
+    /// Constructs a new, empty `Replica<T>`.
+    #[inline]
+    pub const fn new() -> Self {
+        Self {
+            map: SlabMap::new(),
+            applied: 0,
+            fingerprint: FINGERPRINT_INIT,
+        }
+    }
+
+    /// Returns a reference to the underlying [`SlabMap`].
+    #[inline]
+    pub fn as_slab_map(&self) -> &SlabMap<T> {
+        &self.map
+    }
+
+    /// Returns the underlying [`SlabMap`].
+    #[inline]
+    pub fn into_slab_map(self) -> SlabMap<T> {
+        self.map
+    }
+
+    /// Returns the number of operations applied to the Replica.
+    #[inline]
+    pub fn applied_len(&self) -> usize {
+        self.applied
+    }
+
+    /// Returns a value that summarizes the operations applied to the Replica.
+    ///
+    /// Replicas that have applied the same operations have the same fingerprint.
+    /// The value does not depend on the platform or the compiler.
+    #[inline]
+    pub fn fingerprint(&self) -> u64 {
+        self.fingerprint
+    }
+
+    fn mix(&mut self, tag: u8, key: usize) {
+        for b in [tag as u64, key as u64] {
+            self.fingerprint = (self.fingerprint ^ b).wrapping_mul(0x0000_0100_0000_01b3);
+        }
+    }
+    fn record(&mut self, tag: u8, key: usize) {
+        self.mix(tag, key);
+        self.applied += 1;
+    }
+
+    /// Returns the number of elements in the Replica.
+    #[inline]
+    pub fn len(&self) -> usize {
+        self.map.len()
+    }
+
+    /// Returns true if the Replica contains no elements.
+    #[inline]
+    pub fn is_empty(&self) -> bool {
+        self.map.is_empty()
+    }
+
+    /// Returns a reference to the value corresponding to the key.
+    #[inline]
+    pub fn get(&self, key: usize) -> Option<&T> {
+        self.map.get(key)
+    }
+
+    /// Returns a mutable reference to the value corresponding to the key.
+    #[inline]
+    pub fn get_mut(&mut self, key: usize) -> Option<&mut T> {
+        self.map.get_mut(key)
+    }
+
+    /// Returns true if the Replica contains a value for the specified key.
+    #[inline]
+    pub fn contains_key(&self, key: usize) -> bool {
+        self.map.contains_key(key)
+    }
+
+    /// Inserts a value into the Replica.
+    ///
+    /// Returns the key associated with the value.
+    pub fn insert(&mut self, value: T) -> usize {
+        let key = self.map.insert(value);
+        self.record(0, key);
+        key
+    }
+
+    /// Removes a key from the Replica, returning the value at the key if the key was previously in the Replica.
+    ///
+    /// Removing a key that does not exist is not counted as an operation.
+    pub fn remove(&mut self, key: usize) -> Option<T> {
+        let value = self.map.remove(key)?;
+        self.record(1, key);
+        Some(value)
+    }
+
+    /// Clears the Replica, removing all values.
+    pub fn clear(&mut self) {
+        self.map.clear();
+        self.record(2, 0);
+    }
+
+    /// Optimizing the free space.
+    pub fn optimize(&mut self) {
+        self.map.optimize();
+        self.record(3, 0);
+    }
+
+    /// Applies an operation recorded by [`JournaledSlabMap`].
+    ///
+    /// Returns an error if a key in the operation does not match the Replica, or if the removed keys of [`Op::Retain`] are not sorted and unique.
+    /// In that case, the Replica has diverged from the recorded map, and the operation is not applied.
+    pub fn apply(&mut self, op: Op<T>) -> Result<(), ReplayError> {
+        let ok = match &op {
+            Op::Insert { key, .. } => self.map.next_key() == *key,
+            Op::Remove { key } => self.map.contains_key(*key),
+            Op::Retain { removed } => {
+                removed.windows(2).all(|w| w[0] < w[1])
+                    && removed.iter().all(|&key| self.map.contains_key(key))
+            }
+            Op::Clear | Op::Optimize => true,
+        };
+        if !ok {
+            return Err(ReplayError {
+                position: self.applied,
+            });
+        }
+        match op {
+            Op::Insert { value, .. } => {
+                self.insert(value);
+            }
+            Op::Remove { key } => {
+                self.remove(key);
+            }
+            Op::Retain { removed } => {
+                self.map
+                    .retain(|key, _| removed.binary_search(&key).is_err());
+                for &key in &removed {
+                    self.mix(1, key);
+                }
+                self.record(4, 0);
+            }
+            Op::Clear => self.clear(),
+            Op::Optimize => self.optimize(),
+        }
+        Ok(())
+    }
+
+    /// Gets an iterator over the entries of the Replica, sorted by key.
+    #[inline]
+    pub fn iter(&self) -> Iter<'_, T> {
+        self.map.iter()
+    }
+
+    /// Gets a mutable iterator over the entries of the Replica, sorted by key.
+    #[inline]
+    pub fn iter_mut(&mut self) -> IterMut<'_, T> {
+        self.map.iter_mut()
+    }
+
+    /// Gets an iterator over the keys of the Replica, in sorted order.
+    #[inline]
+    pub fn keys(&self) -> Keys<'_, T> {
+        self.map.keys()
+    }
+
+    /// Gets an iterator over the values of the Replica.
+    #[inline]
+    pub fn values(&self) -> Values<'_, T> {
+        self.map.values()
+    }
+
+    /// Gets a mutable iterator over the values of the Replica.
+    #[inline]
+    pub fn values_mut(&mut self) -> ValuesMut<'_, T> {
+        self.map.values_mut()
+    }
+}
+impl<T: Debug> Debug for Replica<T> {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        self.map.fmt(f)
+    }
+}
+
+impl<T> core::ops::Index<usize> for Replica<T> {
+    type Output = T;
+
+    #[inline]
+    fn index(&self, index: usize) -> &Self::Output {
+        self.get(index).expect("out of index.")
+    }
+}
+impl<T> core::ops::IndexMut<usize> for Replica<T> {
+    #[inline]
+    fn index_mut(&mut self, index: usize) -> &mut Self::Output {
+        self.get_mut(index).expect("out of index.")
+    }
+}
+
+/// A [`SlabMap`] that records every structural mutation into a log.
+///
+/// The log can be passed to [`SlabMap::replay`] to reconstruct an identical SlabMap,
+/// which is useful for reproducing bugs deterministically and for transferring state.
+///
+/// Only keys and the kinds of operations are recorded, so the log does not copy the values
+/// and its size does not depend on the size of the values.
+/// Mutations through [`get_mut`](Self::get_mut) and similar methods are not recorded.
+///
+/// # Examples
+/// ```
+/// use slabmap::journal::{JournaledSlabMap, Op};
+///
+/// let mut s = JournaledSlabMap::new();
+/// let key = s.insert(10);
+/// s.remove(key);
+///
+/// assert_eq!(s.log(), [Op::Insert { key, value: () }, Op::Remove { key }]);
+/// ```
+#[derive_ex(Clone(bound(T)), Default(bound()))]
+pub struct JournaledSlabMap<T> {
+    map: SlabMap<T>,
+    log: Vec<Op>,
+}
+
+impl<T> JournaledSlabMap<T> {
+    /// Constructs a new, empty `JournaledSlabMap<T>`.
+    #[inline]
+    pub const fn new() -> Self {
+        Self {
+            map: SlabMap::new(),
+            log: Vec::new(),
+        }
+    }
+
+    /// Returns the recorded log.
+    #[inline]
+    pub fn log(&self) -> &[Op] {
+        &self.log
+    }
+
+    /// Returns the recorded log, leaving an empty log in its place.
+    ///
+    /// Logs taken in sequence can be concatenated and replayed together.
+    pub fn take_log(&mut self) -> Vec<Op> {
+        core::mem::take(&mut self.log)
+    }
+
+    /// Returns a reference to the underlying [`SlabMap`].
+    #[inline]
+    pub fn as_slab_map(&self) -> &SlabMap<T> {
+        &self.map
+    }
+
+    /// Decomposes into the underlying [`SlabMap`] and the recorded log.
+    pub fn into_parts(self) -> (SlabMap<T>, Vec<Op>) {
+        (self.map, self.log)
+    }
+
+    /// Returns the number of elements in the JournaledSlabMap.
+    #[inline]
+    pub fn len(&self) -> usize {
+        self.map.len()
+    }
+
+    /// Returns true if the JournaledSlabMap contains no elements.
+    #[inline]
+    pub fn is_empty(&self) -> bool {
+        self.map.is_empty()
+    }
+
+    /// Returns a reference to the value corresponding to the key.
+    #[inline]
+    pub fn get(&self, key: usize) -> Option<&T> {
+        self.map.get(key)
+    }
+
+    /// Returns a mutable reference to the value corresponding to the key.
+    #[inline]
+    pub fn get_mut(&mut self, key: usize) -> Option<&mut T> {
+        self.map.get_mut(key)
+    }
+
+    /// Returns true if the JournaledSlabMap contains a value for the specified key.
+    #[inline]
+    pub fn contains_key(&self, key: usize) -> bool {
+        self.map.contains_key(key)
+    }
+
+    /// Inserts a value into the JournaledSlabMap.
+    ///
+    /// Returns the key associated with the value.
+    pub fn insert(&mut self, value: T) -> usize {
+        self.insert_with_key(|_| value)
+    }
+
+    /// Inserts a value given by `f` into the JournaledSlabMap. The key to be associated with the value is passed to `f`.
+    ///
+    /// Returns the key associated with the value.
+    pub fn insert_with_key(&mut self, f: impl FnOnce(usize) -> T) -> usize {
+        let key = self.map.insert_with_key(f);
+        self.log.push(Op::Insert { key, value: () });
+        key
+    }
+
+    /// Removes a key from the JournaledSlabMap, returning the value at the key if the key was previously in the JournaledSlabMap.
+    pub fn remove(&mut self, key: usize) -> Option<T> {
+        let value = self.map.remove(key)?;
+        self.log.push(Op::Remove { key });
+        Some(value)
+    }
+
+    /// Clears the JournaledSlabMap, removing all values.
+    pub fn clear(&mut self) {
+        self.map.clear();
+        self.log.push(Op::Clear);
+    }
+
+    /// Retains only the elements specified by the predicate and optimize free spaces.
+    pub fn retain(&mut self, mut f: impl FnMut(usize, &mut T) -> bool) {
+        let mut removed = Vec::new();
+        self.map.retain(|key, value| {
+            let retain = f(key, value);
+            if !retain {
+                removed.push(key);
+            }
+            retain
+        });
+        self.log.push(Op::Retain { removed });
+    }
+
+    /// Optimizing the free space.
+    pub fn optimize(&mut self) {
+        self.map.optimize();
+        self.log.push(Op::Optimize);
+    }
+
+    /// Gets an iterator over the entries of the JournaledSlabMap, sorted by key.
+    #[inline]
+    pub fn iter(&self) -> Iter<'_, T> {
+        self.map.iter()
+    }
+
+    /// Gets a mutable iterator over the entries of the JournaledSlabMap, sorted by key.
+    #[inline]
+    pub fn iter_mut(&mut self) -> IterMut<'_, T> {
+        self.map.iter_mut()
+    }
+
+    /// Gets an iterator over the keys of the JournaledSlabMap, in sorted order.
+    #[inline]
+    pub fn keys(&self) -> Keys<'_, T> {
+        self.map.keys()
+    }
+
+    /// Gets an iterator over the values of the JournaledSlabMap.
+    #[inline]
+    pub fn values(&self) -> Values<'_, T> {
+        self.map.values()
+    }
+
+    /// Gets a mutable iterator over the values of the JournaledSlabMap.
+    #[inline]
+    pub fn values_mut(&mut self) -> ValuesMut<'_, T> {
+        self.map.values_mut()
+    }
+}
+impl<T: Debug> Debug for JournaledSlabMap<T> {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        self.map.fmt(f)
+    }
+}
+
+impl<T> core::ops::Index<usize> for JournaledSlabMap<T> {
+    type Output = T;
+
+    #[inline]
+    fn index(&self, index: usize) -> &Self::Output {
+        self.get(index).expect("out of index.")
+    }
+}
+impl<T> core::ops::IndexMut<usize> for JournaledSlabMap<T> {
+    #[inline]
+    fn index_mut(&mut self, index: usize) -> &mut Self::Output {
+        self.get_mut(index).expect("out of index.")
+    }
+}
+
+impl<'a, T> IntoIterator for &'a JournaledSlabMap<T> {
+    type Item = (usize, &'a T);
+    type IntoIter = Iter<'a, T>;
+
+    #[inline]
+    fn into_iter(self) -> Self::IntoIter {
+        self.iter()
+    }
+}
+impl<'a, T> IntoIterator for &'a mut JournaledSlabMap<T> {
+    type Item = (usize, &'a mut T);
+    type IntoIter = IterMut<'a, T>;
+
+    #[inline]
+    fn into_iter(self) -> Self::IntoIter {
+        self.iter_mut()
+    }
+}
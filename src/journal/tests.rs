@@ -0,0 +1,203 @@
+use rand::{rngs::StdRng, Rng, SeedableRng};
+
+use crate::{
+    journal::{JournaledSlabMap, Op, ReplayError, Replica},
+    SlabMap,
+};
+
+fn assert_same(s: &SlabMap<usize>, j: &JournaledSlabMap<usize>) {
+    let a: Vec<_> = s.iter().map(|(k, v)| (k, *v)).collect();
+    let e: Vec<_> = j.iter().map(|(k, v)| (k, *v)).collect();
+    assert_eq!(a, e);
+}
+
+#[test]
+fn replay_random() {
+    let mut rng = StdRng::seed_from_u64(0);
+    let mut j = JournaledSlabMap::new();
+    let mut keys = Vec::new();
+    for i in 0..2000 {
+        match rng.gen_range(0..100) {
+            0..=49 => keys.push(j.insert(i)),
+            50..=94 => {
+                if !keys.is_empty() {
+                    let key = keys.swap_remove(rng.gen_range(0..keys.len()));
+                    j.remove(key);
+                }
+            }
+            95..=96 => {
+                j.retain(|_, v| *v % 3 != 0);
+                keys = j.keys().collect();
+            }
+            97..=98 => j.optimize(),
+            _ => {
+                j.clear();
+                keys.clear();
+            }
+        }
+    }
+    let mut s = SlabMap::replay(j.log(), |key, ()| j.get(key).copied().unwrap_or(0)).unwrap();
+    assert_same(&s, &j);
+    for i in 0..100 {
+        assert_eq!(s.insert(i), j.insert(i));
+    }
+}
+
+#[test]
+fn take_log() {
+    let mut j = JournaledSlabMap::new();
+    let k0 = j.insert(10);
+    j.insert(20);
+    let mut log = j.take_log();
+    assert!(j.log().is_empty());
+    j.remove(k0);
+    log.extend(j.take_log());
+
+    let s = SlabMap::replay(&log, |key, ()| j.get(key).copied().unwrap_or(0)).unwrap();
+    assert_same(&s, &j);
+}
+
+#[test]
+fn replay_error() {
+    let log = [Op::Insert { key: 0, value: 1 }, Op::Remove { key: 1 }];
+    assert_eq!(
+        SlabMap::replay(&log, |_, &v| v).err(),
+        Some(ReplayError { position: 1 })
+    );
+    let log = [Op::Insert { key: 1, value: 1 }];
+    assert_eq!(
+        SlabMap::replay(&log, |_, &v| v).err(),
+        Some(ReplayError { position: 0 })
+    );
+}
+
+#[test]
+fn journal_without_clone() {
+    #[derive(Debug, PartialEq)]
+    struct NotClone(u32);
+
+    let mut j = JournaledSlabMap::new();
+    let k0 = j.insert(NotClone(1));
+    let k1 = j.insert_with_key(|key| NotClone(key as u32));
+    j.remove(k0);
+    assert_eq!(
+        j.log(),
+        [
+            Op::Insert { key: k0, value: () },
+            Op::Insert { key: k1, value: () },
+            Op::Remove { key: k0 }
+        ]
+    );
+    let s = SlabMap::replay(j.log(), |key, ()| NotClone(key as u32)).unwrap();
+    assert_eq!(s.iter().collect::<Vec<_>>(), [(k1, &NotClone(1))]);
+}
+
+#[test]
+fn remove_missing_key_is_not_recorded() {
+    let mut j = JournaledSlabMap::<usize>::new();
+    assert_eq!(j.remove(0), None);
+    assert!(j.log().is_empty());
+}
+
+#[test]
+fn replica_apply() {
+    let mut j = JournaledSlabMap::new();
+    let mut a = Replica::new();
+    let mut b = Replica::new();
+    let mut rng = StdRng::seed_from_u64(1);
+    for i in 0..500 {
+        if rng.gen_bool(0.6) || j.is_empty() {
+            let key = j.insert(i);
+            assert_eq!(b.insert(i), key);
+        } else {
+            let keys: Vec<_> = j.keys().collect();
+            let key = keys[rng.gen_range(0..keys.len())];
+            j.remove(key);
+            b.remove(key);
+        }
+    }
+    for op in j.take_log() {
+        a.apply(op.map(|key, ()| key)).unwrap();
+    }
+    assert_eq!(a.fingerprint(), b.fingerprint());
+    assert_eq!(a.applied_len(), b.applied_len());
+
+    j.retain(|_, v| *v % 2 == 0);
+    j.optimize();
+    for op in j.take_log() {
+        a.apply(op.map(|key, ()| key)).unwrap();
+    }
+    let ka: Vec<_> = a.keys().collect();
+    let kj: Vec<_> = j.keys().collect();
+    assert_eq!(ka, kj);
+    assert_eq!(a.insert(0), j.insert(0));
+}
+
+#[test]
+fn replica_fingerprint() {
+    let mut a = Replica::new();
+    let mut b = Replica::new();
+    assert_eq!(a.fingerprint(), b.fingerprint());
+    let k = a.insert(1);
+    b.insert(1);
+    assert_eq!(a.fingerprint(), b.fingerprint());
+    a.remove(k);
+    assert_ne!(a.fingerprint(), b.fingerprint());
+    b.remove(k);
+    assert_eq!(a.fingerprint(), b.fingerprint());
+    assert_eq!(a.applied_len(), 2);
+}
+
+#[test]
+fn replica_apply_error() {
+    let mut a = Replica::new();
+    a.apply(Op::Insert { key: 0, value: 1 }).unwrap();
+    assert_eq!(
+        a.apply(Op::Remove { key: 5 }),
+        Err(ReplayError { position: 1 })
+    );
+
+    let fingerprint = a.fingerprint();
+    assert_eq!(
+        a.apply(Op::Insert { key: 3, value: 2 }),
+        Err(ReplayError { position: 1 })
+    );
+    assert_eq!(
+        a.apply(Op::Retain {
+            removed: vec![0, 5]
+        }),
+        Err(ReplayError { position: 1 })
+    );
+    assert_eq!(a.iter().collect::<Vec<_>>(), [(0, &1)]);
+    assert_eq!(a.fingerprint(), fingerprint);
+    assert_eq!(a.applied_len(), 1);
+    a.apply(Op::Insert { key: 1, value: 2 }).unwrap();
+}
+
+#[test]
+fn replica_apply_retain_unsorted() {
+    let mut a = Replica::new();
+    for value in 0..3 {
+        a.apply(Op::Insert { key: value, value }).unwrap();
+    }
+    let fingerprint = a.fingerprint();
+    assert_eq!(
+        a.apply(Op::Retain {
+            removed: vec![2, 0]
+        }),
+        Err(ReplayError { position: 3 })
+    );
+    assert_eq!(
+        a.apply(Op::Retain {
+            removed: vec![1, 1]
+        }),
+        Err(ReplayError { position: 3 })
+    );
+    assert_eq!(a.len(), 3);
+    assert_eq!(a.fingerprint(), fingerprint);
+    a.apply(Op::Retain {
+        removed: vec![0, 2],
+    })
+    .unwrap();
+    assert_eq!(a.iter().collect::<Vec<_>>(), [(1, &1)]);
+}
@@ -0,0 +1,166 @@
+//! A handle map for passing Rust-owned values to JavaScript as numbers.
+
+use std::{fmt::Debug, iter::FusedIterator};
+
+use derive_ex::derive_ex;
+use wasm_bindgen::JsValue;
+
+use crate::{slab_map, SlabMap};
+
+#[cfg(test)]
+mod tests;
+
+/// A [`SlabMap`] whose keys are `u32` handles that can be passed to and from JavaScript.
+///
+/// JavaScript code holds the handle as a plain number, and the value stays on the Rust side until it is released.
+/// Handles are guaranteed to fit in `u32`, so they are exactly representable as JavaScript numbers.
+///
+/// # Examples
+/// ```no_run
+/// use std::cell::RefCell;
+/// use slabmap::js_handle_map::JsHandleMap;
+/// use wasm_bindgen::prelude::*;
+///
+/// thread_local! {
+///     static CALLBACKS: RefCell<JsHandleMap> = RefCell::new(JsHandleMap::new());
+/// }
+///
+/// #[wasm_bindgen]
+/// pub fn register(callback: JsValue) -> u32 {
+///     CALLBACKS.with(|c| c.borrow_mut().insert(callback))
+/// }
+///
+/// #[wasm_bindgen]
+/// pub fn unregister(handle: u32) -> bool {
+///     CALLBACKS.with(|c| c.borrow_mut().release(handle).is_some())
+/// }
+/// ```
+#[derive_ex(Clone(bound(T)), Default(bound()))]
+pub struct JsHandleMap<T = JsValue> {
+    map: SlabMap<T>,
+}
+
+impl<T> JsHandleMap<T> {
+    /// Constructs a new, empty `JsHandleMap<T>`.
+    #[inline]
+    pub const fn new() -> Self {
+        Self {
+            map: SlabMap::new(),
+        }
+    }
+
+    /// Returns the number of elements in the JsHandleMap.
+    #[inline]
+    pub fn len(&self) -> usize {
+        self.map.len()
+    }
+
+    /// Returns true if the JsHandleMap contains no elements.
+    #[inline]
+    pub fn is_empty(&self) -> bool {
+        self.map.is_empty()
+    }
+
+    /// Returns a reference to the value corresponding to the handle.
+    #[inline]
+    pub fn get(&self, handle: u32) -> Option<&T> {
+        self.map.get(handle as usize)
+    }
+
+    /// Returns a mutable reference to the value corresponding to the handle.
+    #[inline]
+    pub fn get_mut(&mut self, handle: u32) -> Option<&mut T> {
+        self.map.get_mut(handle as usize)
+    }
+
+    /// Returns true if the JsHandleMap contains a value for the specified handle.
+    #[inline]
+    pub fn contains(&self, handle: u32) -> bool {
+        self.map.contains_key(handle as usize)
+    }
+
+    /// Inserts a value into the JsHandleMap.
+    ///
+    /// Returns the handle associated with the value.
+    ///
+    /// # Panics
+    /// Panics if the handle does not fit in `u32`.
+    pub fn insert(&mut self, value: T) -> u32 {
+        let key = self.map.insert(value);
+        match u32::try_from(key) {
+            Ok(handle) => handle,
+            Err(_) => {
+                self.map.remove(key);
+                panic!("too many handles.")
+            }
+        }
+    }
+
+    /// Releases a handle, returning the value associated with it if the handle was previously in the JsHandleMap.
+    ///
+    /// The handle may be reused by a later insertion.
+    #[inline]
+    pub fn release(&mut self, handle: u32) -> Option<T> {
+        self.map.remove(handle as usize)
+    }
+
+    /// Clears the JsHandleMap, releasing all handles.
+    pub fn clear(&mut self) {
+        self.map.clear();
+    }
+
+    /// Gets an iterator over the handles and values of the JsHandleMap, sorted by handle.
+    #[inline]
+    pub fn iter(&self) -> Iter<'_, T> {
+        Iter(self.map.iter())
+    }
+}
+impl<T: Debug> Debug for JsHandleMap<T> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        self.map.fmt(f)
+    }
+}
+
+impl<T> std::ops::Index<u32> for JsHandleMap<T> {
+    type Output = T;
+
+    #[inline]
+    fn index(&self, index: u32) -> &Self::Output {
+        self.get(index).expect("out of index.")
+    }
+}
+impl<T> std::ops::IndexMut<u32> for JsHandleMap<T> {
+    #[inline]
+    fn index_mut(&mut self, index: u32) -> &mut Self::Output {
+        self.get_mut(index).expect("out of index.")
+    }
+}
+
+impl<'a, T> IntoIterator for &'a JsHandleMap<T> {
+    type Item = (u32, &'a T);
+    type IntoIter = Iter<'a, T>;
+
+    #[inline]
+    fn into_iter(self) -> Self::IntoIter {
+        self.iter()
+    }
+}
+
+/// An iterator over the entries of a [`JsHandleMap`].
+///
+/// This struct is created by the [`iter`](JsHandleMap::iter).
+pub struct Iter<'a, T>(slab_map::Iter<'a, T>);
+impl<'a, T> Iterator for Iter<'a, T> {
+    type Item = (u32, &'a T);
+    #[inline]
+    fn next(&mut self) -> Option<Self::Item> {
+        let (key, value) = self.0.next()?;
+        Some((key as u32, value))
+    }
+    #[inline]
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        self.0.size_hint()
+    }
+}
+impl<T> FusedIterator for Iter<'_, T> {}
+impl<T> ExactSizeIterator for Iter<'_, T> {}
@@ -0,0 +1,17 @@
+use crate::js_handle_map::JsHandleMap;
+
+#[test]
+fn insert_release() {
+    let mut s = JsHandleMap::<&str>::new();
+    let a = s.insert("a");
+    let b = s.insert("b");
+    assert_eq!(s.len(), 2);
+    assert_eq!(s[a], "a");
+    assert_eq!(s.release(a), Some("a"));
+    assert_eq!(s.release(a), None);
+    assert!(!s.contains(a));
+    assert_eq!(s.insert("c"), a);
+    assert_eq!(s.iter().collect::<Vec<_>>(), [(a, &"c"), (b, &"b")]);
+    s.clear();
+    assert!(s.is_empty());
+}
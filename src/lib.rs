@@ -21,12 +21,62 @@ assert_eq!(s.remove(key_a), Some("aaa"));
 assert_eq!(s.remove(key_a), None);
 ```
 */
+#![cfg_attr(not(any(feature = "std", test)), no_std)]
 
+extern crate alloc;
+
+pub mod append_only_slab_map;
+pub mod bit_slab_map;
+pub mod cache_padded;
+#[cfg(feature = "std")]
+pub mod concurrent_slab_map;
+pub mod cow_slab_map;
+pub mod dense_slab_map;
+pub mod dense_versioned_slab_map;
+pub mod ffi;
+#[cfg(feature = "global")]
+pub mod global;
+pub mod journal;
+#[cfg(feature = "wasm")]
+pub mod js_handle_map;
+pub mod memory_budget;
+pub mod persistent_slab_map;
+pub mod pinned_slab_map;
+#[cfg(feature = "std")]
+pub mod scrambled_slab_map;
+pub mod secondary_map;
+#[cfg(feature = "std")]
+pub mod shared_slab_map;
+#[cfg(feature = "petgraph")]
+pub mod slab_graph;
+pub mod slab_heap;
+pub mod slab_key;
 pub mod slab_map;
+pub mod slab_map_ref;
+pub mod slab_pool;
 pub mod small_slab_map;
+pub mod sorted_index;
+#[cfg(feature = "std")]
+pub mod sync_slab_map;
+#[cfg(feature = "std")]
+pub mod traced_slab_map;
+pub mod unit_slab_map;
+pub mod versioned_slab_map;
+
+#[doc(inline)]
+pub use slab_key::SlabKey;
 
 #[doc(inline)]
 pub use slab_map::SlabMap;
 
 #[doc(inline)]
 pub use small_slab_map::SmallSlabMap;
+
+#[cfg(feature = "derive")]
+pub use slabmap_derive::SlabStored;
+
+#[doc(hidden)]
+pub mod __private {
+    #[cfg(feature = "serde")]
+    pub use serde;
+}
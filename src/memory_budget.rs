@@ -0,0 +1,354 @@
+//! A memory budget shared by multiple [`SlabMap`]s.
+
+use alloc::{collections::TryReserveError, sync::Arc};
+use core::{
+    fmt::{self, Debug},
+    sync::atomic::{AtomicUsize, Ordering},
+};
+
+use crate::{
+    slab_map::{Iter, IterMut, Keys, Values, ValuesMut},
+    SlabMap,
+};
+
+#[cfg(test)]
+mod tests;
+
+/// A limit on the total number of bytes allocated by the [`BudgetedSlabMap`]s sharing it.
+///
+/// Cloning a `MemoryBudget` returns a handle to the same budget.
+///
+/// # Examples
+/// ```
+/// use slabmap::memory_budget::{BudgetedSlabMap, MemoryBudget};
+///
+/// let budget = MemoryBudget::new(1024);
+/// let mut a = BudgetedSlabMap::<u64>::new(budget.clone());
+/// let mut b = BudgetedSlabMap::<u64>::new(budget.clone());
+///
+/// while a.try_insert(0).is_ok() {}
+/// assert!(b.try_insert(0).is_err());
+///
+/// drop(a);
+/// assert!(b.try_insert(0).is_ok());
+/// ```
+#[derive(Clone)]
+pub struct MemoryBudget(Arc<BudgetInner>);
+
+struct BudgetInner {
+    limit: usize,
+    used: AtomicUsize,
+}
+
+impl MemoryBudget {
+    /// Constructs a new budget that allows up to `limit` bytes.
+    pub fn new(limit: usize) -> Self {
+        Self(Arc::new(BudgetInner {
+            limit,
+            used: AtomicUsize::new(0),
+        }))
+    }
+
+    /// Returns the maximum number of bytes allowed by the budget.
+    #[inline]
+    pub fn limit(&self) -> usize {
+        self.0.limit
+    }
+
+    /// Returns the number of bytes currently charged to the budget.
+    #[inline]
+    pub fn used(&self) -> usize {
+        self.0.used.load(Ordering::Relaxed)
+    }
+
+    /// Returns the number of bytes that can still be charged to the budget.
+    #[inline]
+    pub fn available(&self) -> usize {
+        self.limit().saturating_sub(self.used())
+    }
+
+    fn try_charge(&self, bytes: usize) -> bool {
+        let limit = self.0.limit;
+        self.0
+            .used
+            .fetch_update(Ordering::Relaxed, Ordering::Relaxed, |used| {
+                used.checked_add(bytes).filter(|&used| used <= limit)
+            })
+            .is_ok()
+    }
+    fn charge(&self, bytes: usize) {
+        self.0.used.fetch_add(bytes, Ordering::Relaxed);
+    }
+    fn release(&self, bytes: usize) {
+        self.0.used.fetch_sub(bytes, Ordering::Relaxed);
+    }
+}
+impl Debug for MemoryBudget {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("MemoryBudget")
+            .field("limit", &self.limit())
+            .field("used", &self.used())
+            .finish()
+    }
+}
+
+/// An error returned when a [`BudgetedSlabMap`] cannot grow.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum BudgetError {
+    /// Growing would exceed the [`MemoryBudget`].
+    Exceeded,
+    /// The allocator returned an error.
+    Alloc(TryReserveError),
+}
+impl fmt::Display for BudgetError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Exceeded => write!(f, "memory budget exceeded"),
+            Self::Alloc(e) => fmt::Display::fmt(e, f),
+        }
+    }
+}
+impl core::error::Error for BudgetError {}
+
+/// An error returned by [`BudgetedSlabMap::try_insert`], containing the value that could not be inserted.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct InsertError<T> {
+    /// The value that could not be inserted.
+    pub value: T,
+    /// The reason why the value could not be inserted.
+    pub error: BudgetError,
+}
+impl<T> fmt::Display for InsertError<T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        fmt::Display::fmt(&self.error, f)
+    }
+}
+impl<T: Debug> core::error::Error for InsertError<T> {}
+
+/// A [`SlabMap`] whose allocation is charged to a [`MemoryBudget`].
+///
+/// The memory is charged when the SlabMap grows and returned to the budget when it shrinks or is dropped.
+pub struct BudgetedSlabMap<T> {
+    map: SlabMap<T>,
+    budget: MemoryBudget,
+    charged: usize,
+}
+
+impl<T> BudgetedSlabMap<T> {
+    /// Constructs a new, empty `BudgetedSlabMap<T>` charged to `budget`.
+    pub fn new(budget: MemoryBudget) -> Self {
+        Self {
+            map: SlabMap::new(),
+            budget,
+            charged: 0,
+        }
+    }
+
+    /// Returns the budget the BudgetedSlabMap is charged to.
+    #[inline]
+    pub fn budget(&self) -> &MemoryBudget {
+        &self.budget
+    }
+
+    /// Returns the number of bytes charged to the budget by the BudgetedSlabMap.
+    #[inline]
+    pub fn charged(&self) -> usize {
+        self.charged
+    }
+
+    /// Returns a reference to the underlying [`SlabMap`].
+    #[inline]
+    pub fn as_slab_map(&self) -> &SlabMap<T> {
+        &self.map
+    }
+
+    /// Returns the number of elements in the BudgetedSlabMap.
+    #[inline]
+    pub fn len(&self) -> usize {
+        self.map.len()
+    }
+
+    /// Returns true if the BudgetedSlabMap contains no elements.
+    #[inline]
+    pub fn is_empty(&self) -> bool {
+        self.map.is_empty()
+    }
+
+    /// Returns the number of elements the BudgetedSlabMap can hold without reallocating.
+    #[inline]
+    pub fn capacity(&self) -> usize {
+        self.map.capacity()
+    }
+
+    /// Try to reserve capacity for at least additional more elements to be inserted.
+    ///
+    /// Returns [`BudgetError::Exceeded`] without allocating if the growth would exceed the budget.
+    pub fn try_reserve(&mut self, additional: usize) -> Result<(), BudgetError> {
+        let capacity = self.map.capacity();
+        let required = self
+            .map
+            .key_bound()
+            .checked_add(self.map.entries_additional(additional))
+            .ok_or(BudgetError::Exceeded)?;
+        if required <= capacity {
+            return Ok(());
+        }
+        let mut new_capacity = required.max(capacity.saturating_mul(2)).max(4);
+        if !self.try_charge_capacity(capacity, new_capacity) {
+            new_capacity = required;
+            if !self.try_charge_capacity(capacity, new_capacity) {
+                return Err(BudgetError::Exceeded);
+            }
+        }
+        let bytes = (new_capacity - capacity) * SlabMap::<T>::entry_size();
+        self.charged += bytes;
+        if let Err(e) = self.map.try_grow_exact(new_capacity) {
+            self.charged -= bytes;
+            self.budget.release(bytes);
+            return Err(BudgetError::Alloc(e));
+        }
+        self.sync();
+        Ok(())
+    }
+    fn try_charge_capacity(&self, capacity: usize, new_capacity: usize) -> bool {
+        match (new_capacity - capacity).checked_mul(SlabMap::<T>::entry_size()) {
+            Some(bytes) => self.budget.try_charge(bytes),
+            None => false,
+        }
+    }
+    fn sync(&mut self) {
+        let bytes = self.map.allocated_bytes();
+        if bytes > self.charged {
+            self.budget.charge(bytes - self.charged);
+        } else {
+            self.budget.release(self.charged - bytes);
+        }
+        self.charged = bytes;
+    }
+
+    /// Shrinks the capacity as much as possible and returns the released memory to the budget.
+    pub fn shrink_to_fit(&mut self) {
+        self.map.shrink_to(0);
+        self.sync();
+    }
+
+    /// Returns a reference to the value corresponding to the key.
+    #[inline]
+    pub fn get(&self, key: usize) -> Option<&T> {
+        self.map.get(key)
+    }
+
+    /// Returns a mutable reference to the value corresponding to the key.
+    #[inline]
+    pub fn get_mut(&mut self, key: usize) -> Option<&mut T> {
+        self.map.get_mut(key)
+    }
+
+    /// Returns true if the BudgetedSlabMap contains a value for the specified key.
+    #[inline]
+    pub fn contains_key(&self, key: usize) -> bool {
+        self.map.contains_key(key)
+    }
+
+    /// Inserts a value into the BudgetedSlabMap.
+    ///
+    /// Returns the key associated with the value, or an error containing the value if the BudgetedSlabMap cannot grow.
+    pub fn try_insert(&mut self, value: T) -> Result<usize, InsertError<T>> {
+        match self.try_reserve(1) {
+            Ok(()) => Ok(self.map.insert(value)),
+            Err(error) => Err(InsertError { value, error }),
+        }
+    }
+
+    /// Removes a key from the BudgetedSlabMap, returning the value at the key if the key was previously in the BudgetedSlabMap.
+    #[inline]
+    pub fn remove(&mut self, key: usize) -> Option<T> {
+        self.map.remove(key)
+    }
+
+    /// Clears the BudgetedSlabMap, removing all values.
+    ///
+    /// The allocated memory is kept. Use [`shrink_to_fit`](Self::shrink_to_fit) to return it to the budget.
+    pub fn clear(&mut self) {
+        self.map.clear();
+    }
+
+    /// Optimizing the free space.
+    pub fn optimize(&mut self) {
+        self.map.optimize();
+    }
+
+    /// Gets an iterator over the entries of the BudgetedSlabMap, sorted by key.
+    #[inline]
+    pub fn iter(&self) -> Iter<'_, T> {
+        self.map.iter()
+    }
+
+    /// Gets a mutable iterator over the entries of the BudgetedSlabMap, sorted by key.
+    #[inline]
+    pub fn iter_mut(&mut self) -> IterMut<'_, T> {
+        self.map.iter_mut()
+    }
+
+    /// Gets an iterator over the keys of the BudgetedSlabMap, in sorted order.
+    #[inline]
+    pub fn keys(&self) -> Keys<'_, T> {
+        self.map.keys()
+    }
+
+    /// Gets an iterator over the values of the BudgetedSlabMap.
+    #[inline]
+    pub fn values(&self) -> Values<'_, T> {
+        self.map.values()
+    }
+
+    /// Gets a mutable iterator over the values of the BudgetedSlabMap.
+    #[inline]
+    pub fn values_mut(&mut self) -> ValuesMut<'_, T> {
+        self.map.values_mut()
+    }
+}
+impl<T> Drop for BudgetedSlabMap<T> {
+    fn drop(&mut self) {
+        self.budget.release(self.charged);
+    }
+}
+impl<T: Debug> Debug for BudgetedSlabMap<T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        self.map.fmt(f)
+    }
+}
+
+impl<T> core::ops::Index<usize> for BudgetedSlabMap<T> {
+    type Output = T;
+
+    #[inline]
+    fn index(&self, index: usize) -> &Self::Output {
+        self.get(index).expect("out of index.")
+    }
+}
+impl<T> core::ops::IndexMut<usize> for BudgetedSlabMap<T> {
+    #[inline]
+    fn index_mut(&mut self, index: usize) -> &mut Self::Output {
+        self.get_mut(index).expect("out of index.")
+    }
+}
+
+impl<'a, T> IntoIterator for &'a BudgetedSlabMap<T> {
+    type Item = (usize, &'a T);
+    type IntoIter = Iter<'a, T>;
+
+    #[inline]
+    fn into_iter(self) -> Self::IntoIter {
+        self.iter()
+    }
+}
+impl<'a, T> IntoIterator for &'a mut BudgetedSlabMap<T> {
+    type Item = (usize, &'a mut T);
+    type IntoIter = IterMut<'a, T>;
+
+    #[inline]
+    fn into_iter(self) -> Self::IntoIter {
+        self.iter_mut()
+    }
+}
@@ -0,0 +1,64 @@
+use crate::memory_budget::{BudgetError, BudgetedSlabMap, MemoryBudget};
+
+#[test]
+fn shared_budget() {
+    let budget = MemoryBudget::new(4096);
+    let mut a = BudgetedSlabMap::new(budget.clone());
+    let mut b = BudgetedSlabMap::new(budget.clone());
+    let mut n = 0;
+    loop {
+        match a.try_insert(n) {
+            Ok(_) => n += 1,
+            Err(e) => {
+                assert_eq!(e.value, n);
+                assert_eq!(e.error, BudgetError::Exceeded);
+                break;
+            }
+        }
+        match b.try_insert(n) {
+            Ok(_) => n += 1,
+            Err(_) => break,
+        }
+    }
+    assert!(n > 0);
+    assert_eq!(budget.used(), a.charged() + b.charged());
+    assert!(budget.used() <= budget.limit());
+    assert_eq!(a.len() + b.len(), n);
+}
+
+#[test]
+fn release_on_shrink_and_drop() {
+    let budget = MemoryBudget::new(1 << 20);
+    let mut a = BudgetedSlabMap::new(budget.clone());
+    let keys: Vec<_> = (0..100).map(|i| a.try_insert(i).unwrap()).collect();
+    let used = budget.used();
+    assert!(used > 0);
+    for &key in &keys[10..] {
+        a.remove(key);
+    }
+    a.shrink_to_fit();
+    assert!(budget.used() < used);
+    assert_eq!(budget.used(), a.charged());
+    drop(a);
+    assert_eq!(budget.used(), 0);
+}
+
+#[test]
+fn reuse_vacant_without_charge() {
+    let budget = MemoryBudget::new(1 << 20);
+    let mut a = BudgetedSlabMap::new(budget.clone());
+    let keys: Vec<_> = (0..10).map(|i| a.try_insert(i).unwrap()).collect();
+    a.remove(keys[3]);
+    let used = budget.used();
+    assert_eq!(a.try_insert(10).unwrap(), keys[3]);
+    assert_eq!(budget.used(), used);
+}
+
+#[test]
+fn try_reserve_exceeded() {
+    let budget = MemoryBudget::new(64);
+    let mut a = BudgetedSlabMap::<u64>::new(budget.clone());
+    assert_eq!(a.try_reserve(1000), Err(BudgetError::Exceeded));
+    assert_eq!(budget.used(), 0);
+    assert_eq!(a.capacity(), 0);
+}
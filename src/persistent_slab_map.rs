@@ -0,0 +1,294 @@
+//! A persistent slab whose updates return a new map sharing structure with the old one.
+
+use alloc::{sync::Arc, vec::Vec};
+use core::{
+    fmt::{self, Debug},
+    iter::FusedIterator,
+    mem::replace,
+};
+
+#[cfg(test)]
+mod tests;
+
+const BITS: u32 = 5;
+const BRANCH: usize = 1 << BITS;
+const MASK: usize = BRANCH - 1;
+
+#[derive(Clone)]
+enum Node<T> {
+    Branch(Vec<Option<Arc<Node<T>>>>),
+    Leaf(Vec<Option<T>>),
+}
+impl<T> Node<T> {
+    fn new(level: u32) -> Self {
+        if level == 0 {
+            Node::Leaf((0..BRANCH).map(|_| None).collect())
+        } else {
+            Node::Branch((0..BRANCH).map(|_| None).collect())
+        }
+    }
+}
+
+struct FreeKey {
+    key: usize,
+    next: Option<Arc<FreeKey>>,
+}
+impl Drop for FreeKey {
+    fn drop(&mut self) {
+        let mut next = self.next.take();
+        while let Some(Ok(mut free)) = next.map(Arc::try_unwrap) {
+            next = free.next.take();
+        }
+    }
+}
+
+/// A persistent slab, where [`insert`](Self::insert) and [`remove`](Self::remove) return a new map and leave the original unchanged.
+///
+/// Entries are stored in a tree of chunks shared by [`Arc`], and an update copies only the chunks on the path to the key,
+/// so keeping many versions of the map, as in an undo stack, is cheap. `clone` takes O(1) time.
+///
+/// Like [`SlabMap`](crate::SlabMap), keys of removed values are reused.
+///
+/// # Examples
+/// ```
+/// use slabmap::persistent_slab_map::PersistentSlabMap;
+///
+/// let v0 = PersistentSlabMap::new();
+/// let (v1, a) = v0.insert("a");
+/// let (v2, b) = v1.insert("b");
+/// let v3 = v2.remove(a).unwrap();
+///
+/// assert_eq!(v1.get(a), Some(&"a"));
+/// assert_eq!(v1.get(b), None);
+/// assert_eq!(v2.len(), 2);
+/// assert_eq!(v3.get(a), None);
+/// assert_eq!(v3[b], "b");
+/// ```
+pub struct PersistentSlabMap<T> {
+    root: Option<Arc<Node<T>>>,
+    height: u32,
+    len: usize,
+    next_key: usize,
+    free: Option<Arc<FreeKey>>,
+}
+
+impl<T> PersistentSlabMap<T> {
+    /// Constructs a new, empty `PersistentSlabMap<T>`.
+    #[inline]
+    pub const fn new() -> Self {
+        Self {
+            root: None,
+            height: 0,
+            len: 0,
+            next_key: 0,
+            free: None,
+        }
+    }
+
+    /// Returns the number of elements in the PersistentSlabMap.
+    #[inline]
+    pub fn len(&self) -> usize {
+        self.len
+    }
+
+    /// Returns true if the PersistentSlabMap contains no elements.
+    #[inline]
+    pub fn is_empty(&self) -> bool {
+        self.len == 0
+    }
+
+    fn leaf(&self, key: usize) -> Option<&[Option<T>]> {
+        if key >= self.next_key {
+            return None;
+        }
+        let mut node = self.root.as_deref()?;
+        for level in (1..=self.height).rev() {
+            let Node::Branch(children) = node else {
+                unreachable!()
+            };
+            node = children[(key >> (level * BITS)) & MASK].as_deref()?;
+        }
+        let Node::Leaf(values) = node else {
+            unreachable!()
+        };
+        Some(values)
+    }
+
+    /// Returns a reference to the value corresponding to the key.
+    #[inline]
+    pub fn get(&self, key: usize) -> Option<&T> {
+        self.leaf(key)?[key & MASK].as_ref()
+    }
+
+    /// Returns true if the PersistentSlabMap contains a value for the specified key.
+    #[inline]
+    pub fn contains_key(&self, key: usize) -> bool {
+        self.get(key).is_some()
+    }
+
+    /// Gets an iterator over the entries of the PersistentSlabMap, sorted by key.
+    #[inline]
+    pub fn iter(&self) -> Iter<'_, T> {
+        Iter {
+            map: self,
+            leaf: &[],
+            key: 0,
+            len: self.len,
+        }
+    }
+}
+
+impl<T: Clone> PersistentSlabMap<T> {
+    fn set(&mut self, key: usize, value: Option<T>) -> Option<T> {
+        while key >> ((self.height + 1) * BITS) != 0 {
+            if let Some(root) = self.root.take() {
+                let mut children: Vec<_> = (0..BRANCH).map(|_| None).collect();
+                children[0] = Some(root);
+                self.root = Some(Arc::new(Node::Branch(children)));
+            }
+            self.height += 1;
+        }
+        let height = self.height;
+        let mut node = Arc::make_mut(self.root.get_or_insert_with(|| Arc::new(Node::new(height))));
+        for level in (1..=height).rev() {
+            let Node::Branch(children) = node else {
+                unreachable!()
+            };
+            let child = children[(key >> (level * BITS)) & MASK]
+                .get_or_insert_with(|| Arc::new(Node::new(level - 1)));
+            node = Arc::make_mut(child);
+        }
+        let Node::Leaf(values) = node else {
+            unreachable!()
+        };
+        replace(&mut values[key & MASK], value)
+    }
+
+    /// Returns a new PersistentSlabMap with the value inserted, and the key associated with the value.
+    pub fn insert(&self, value: T) -> (Self, usize) {
+        let mut this = self.clone();
+        let key = if let Some(free) = &self.free {
+            this.free = free.next.clone();
+            free.key
+        } else {
+            this.next_key += 1;
+            self.next_key
+        };
+        this.set(key, Some(value));
+        this.len += 1;
+        (this, key)
+    }
+
+    /// Returns a new PersistentSlabMap with the value corresponding to the key replaced.
+    ///
+    /// Returns `None` if the key does not exist.
+    pub fn update(&self, key: usize, value: T) -> Option<Self> {
+        if !self.contains_key(key) {
+            return None;
+        }
+        let mut this = self.clone();
+        this.set(key, Some(value));
+        Some(this)
+    }
+
+    /// Returns a new PersistentSlabMap with the key removed.
+    ///
+    /// Returns `None` if the key does not exist.
+    pub fn remove(&self, key: usize) -> Option<Self> {
+        if !self.contains_key(key) {
+            return None;
+        }
+        let mut this = self.clone();
+        this.set(key, None);
+        this.len -= 1;
+        this.free = Some(Arc::new(FreeKey {
+            key,
+            next: this.free.take(),
+        }));
+        Some(this)
+    }
+}
+impl<T> Clone for PersistentSlabMap<T> {
+    fn clone(&self) -> Self {
+        Self {
+            root: self.root.clone(),
+            height: self.height,
+            len: self.len,
+            next_key: self.next_key,
+            free: self.free.clone(),
+        }
+    }
+}
+impl<T> Default for PersistentSlabMap<T> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+impl<T: Debug> Debug for PersistentSlabMap<T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_map().entries(self.iter()).finish()
+    }
+}
+
+impl<T> core::ops::Index<usize> for PersistentSlabMap<T> {
+    type Output = T;
+
+    #[inline]
+    fn index(&self, index: usize) -> &Self::Output {
+        self.get(index).expect("out of index.")
+    }
+}
+
+impl<'a, T> IntoIterator for &'a PersistentSlabMap<T> {
+    type Item = (usize, &'a T);
+    type IntoIter = Iter<'a, T>;
+
+    #[inline]
+    fn into_iter(self) -> Self::IntoIter {
+        self.iter()
+    }
+}
+
+/// An iterator over the entries of a [`PersistentSlabMap`].
+///
+/// This struct is created by the [`iter`](PersistentSlabMap::iter).
+pub struct Iter<'a, T> {
+    map: &'a PersistentSlabMap<T>,
+    leaf: &'a [Option<T>],
+    key: usize,
+    len: usize,
+}
+impl<'a, T> Iterator for Iter<'a, T> {
+    type Item = (usize, &'a T);
+    #[inline]
+    fn next(&mut self) -> Option<Self::Item> {
+        while self.len != 0 {
+            if self.leaf.is_empty() {
+                match self.map.leaf(self.key) {
+                    Some(leaf) => self.leaf = leaf,
+                    None => {
+                        self.key += BRANCH;
+                        continue;
+                    }
+                }
+            }
+            let key = self.key;
+            let value = &self.leaf[key & MASK];
+            self.key += 1;
+            if self.key & MASK == 0 {
+                self.leaf = &[];
+            }
+            if let Some(value) = value {
+                self.len -= 1;
+                return Some((key, value));
+            }
+        }
+        None
+    }
+    #[inline]
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        (self.len, Some(self.len))
+    }
+}
+impl<T> FusedIterator for Iter<'_, T> {}
+impl<T> ExactSizeIterator for Iter<'_, T> {}
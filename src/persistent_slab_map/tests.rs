@@ -0,0 +1,58 @@
+use std::collections::BTreeMap;
+
+use rand::{rngs::StdRng, Rng, SeedableRng};
+
+use crate::persistent_slab_map::PersistentSlabMap;
+
+#[test]
+fn versions_are_unchanged() {
+    let mut versions = vec![PersistentSlabMap::new()];
+    let mut expected = vec![BTreeMap::<usize, i32>::new()];
+    let mut rng = StdRng::seed_from_u64(0);
+    for i in 0..3000 {
+        let v = versions.last().unwrap();
+        let mut e = expected.last().unwrap().clone();
+        let keys: Vec<_> = e.keys().copied().collect();
+        let v = match rng.gen_range(0..4) {
+            0 if !keys.is_empty() => {
+                let key = keys[rng.gen_range(0..keys.len())];
+                e.remove(&key);
+                v.remove(key).unwrap()
+            }
+            1 if !keys.is_empty() => {
+                let key = keys[rng.gen_range(0..keys.len())];
+                e.insert(key, i);
+                v.update(key, i).unwrap()
+            }
+            _ => {
+                let (v, key) = v.insert(i);
+                assert!(!e.contains_key(&key));
+                e.insert(key, i);
+                v
+            }
+        };
+        versions.push(v);
+        expected.push(e);
+    }
+    for (v, e) in versions.iter().zip(&expected) {
+        assert_eq!(v.len(), e.len());
+        assert_eq!(
+            v.iter().collect::<Vec<_>>(),
+            e.iter().map(|(k, v)| (*k, v)).collect::<Vec<_>>()
+        );
+        assert_eq!(v.iter().len(), e.len());
+    }
+}
+
+#[test]
+fn remove_missing_key() {
+    let (v, key) = PersistentSlabMap::new().insert(1);
+    assert!(v.remove(key + 1).is_none());
+    assert!(v.update(key + 100, 1).is_none());
+    let v = v.remove(key).unwrap();
+    assert!(v.remove(key).is_none());
+    assert!(v.is_empty());
+    let (v, key2) = v.insert(2);
+    assert_eq!(key, key2);
+    assert_eq!(format!("{v:?}"), "{0: 2}");
+}
@@ -0,0 +1,273 @@
+//! A slab whose values never move, for values that must be pinned.
+
+use alloc::{boxed::Box, vec::Vec};
+use core::{fmt::Debug, iter::FusedIterator, pin::Pin};
+
+#[cfg(test)]
+mod tests;
+
+const NONE: usize = usize::MAX;
+pub(crate) const FIRST_CHUNK_LEN: usize = 16;
+
+enum Slot<T> {
+    Occupied(T),
+    Vacant { next: usize },
+}
+
+/// A slab that stores values in fixed-size chunks, so that values never move until they are removed.
+///
+/// Chunks are allocated with doubling sizes and are never reallocated, so growing the slab does not move existing values.
+/// A removed value is dropped in place.
+/// Therefore values can be accessed through [`Pin`] with [`get_pin`](Self::get_pin),
+/// and self-referential values, intrusive list nodes and futures can live inside the slab.
+///
+/// Since a pinned value must not be moved out, [`get_mut`](Self::get_mut) and [`take`](Self::take) require `T: Unpin`.
+///
+/// # Examples
+/// ```
+/// use std::future::Future;
+/// use std::pin::Pin;
+/// use std::task::{Context, Poll, Waker};
+/// use slabmap::pinned_slab_map::PinnedSlabMap;
+///
+/// let mut s = PinnedSlabMap::new();
+/// let key = s.insert(async { 10 });
+///
+/// let mut cx = Context::from_waker(Waker::noop());
+/// assert_eq!(s.get_pin(key).unwrap().poll(&mut cx), Poll::Ready(10));
+/// assert!(s.remove(key));
+/// ```
+pub struct PinnedSlabMap<T> {
+    chunks: Vec<Box<[Slot<T>]>>,
+    len: usize,
+    used: usize,
+    free_head: usize,
+}
+
+pub(crate) fn chunk_index(key: usize) -> (usize, usize) {
+    let n = key + FIRST_CHUNK_LEN;
+    let chunk =
+        (usize::BITS - 1 - n.leading_zeros()) as usize - FIRST_CHUNK_LEN.trailing_zeros() as usize;
+    (chunk, n - (FIRST_CHUNK_LEN << chunk))
+}
+
+impl<T> PinnedSlabMap<T> {
+    /// Constructs a new, empty `PinnedSlabMap<T>`.
+    #[inline]
+    pub const fn new() -> Self {
+        Self {
+            chunks: Vec::new(),
+            len: 0,
+            used: 0,
+            free_head: NONE,
+        }
+    }
+
+    /// Returns the number of elements in the PinnedSlabMap.
+    #[inline]
+    pub fn len(&self) -> usize {
+        self.len
+    }
+
+    /// Returns true if the PinnedSlabMap contains no elements.
+    #[inline]
+    pub fn is_empty(&self) -> bool {
+        self.len == 0
+    }
+
+    fn slot(&self, key: usize) -> Option<&Slot<T>> {
+        if key >= self.used {
+            return None;
+        }
+        let (chunk, offset) = chunk_index(key);
+        Some(&self.chunks[chunk][offset])
+    }
+    fn slot_mut(&mut self, key: usize) -> Option<&mut Slot<T>> {
+        if key >= self.used {
+            return None;
+        }
+        let (chunk, offset) = chunk_index(key);
+        Some(&mut self.chunks[chunk][offset])
+    }
+
+    /// Returns a reference to the value corresponding to the key.
+    #[inline]
+    pub fn get(&self, key: usize) -> Option<&T> {
+        if let Some(Slot::Occupied(value)) = self.slot(key) {
+            Some(value)
+        } else {
+            None
+        }
+    }
+
+    /// Returns a pinned mutable reference to the value corresponding to the key.
+    #[inline]
+    pub fn get_pin(&mut self, key: usize) -> Option<Pin<&mut T>> {
+        if let Some(Slot::Occupied(value)) = self.slot_mut(key) {
+            // SAFETY: Values are never moved until they are dropped in place, see `remove` and `clear`.
+            // Values are only moved out by `get_mut` and `take`, which require `T: Unpin`.
+            Some(unsafe { Pin::new_unchecked(value) })
+        } else {
+            None
+        }
+    }
+
+    /// Returns true if the PinnedSlabMap contains a value for the specified key.
+    #[inline]
+    pub fn contains_key(&self, key: usize) -> bool {
+        self.get(key).is_some()
+    }
+
+    /// Inserts a value into the PinnedSlabMap.
+    ///
+    /// Returns the key associated with the value.
+    pub fn insert(&mut self, value: T) -> usize {
+        let key = if self.free_head != NONE {
+            let key = self.free_head;
+            let Some(Slot::Vacant { next }) = self.slot(key) else {
+                unreachable!()
+            };
+            self.free_head = *next;
+            key
+        } else {
+            if chunk_index(self.used).0 == self.chunks.len() {
+                let chunk_len = FIRST_CHUNK_LEN << self.chunks.len();
+                self.chunks.push(
+                    (0..chunk_len)
+                        .map(|_| Slot::Vacant { next: NONE })
+                        .collect(),
+                );
+            }
+            self.used += 1;
+            self.used - 1
+        };
+        *self.slot_mut(key).unwrap() = Slot::Occupied(value);
+        self.len += 1;
+        key
+    }
+
+    /// Removes a key from the PinnedSlabMap, dropping the value in place.
+    ///
+    /// Returns true if the key was previously in the PinnedSlabMap.
+    pub fn remove(&mut self, key: usize) -> bool {
+        let free_head = self.free_head;
+        let Some(slot @ Slot::Occupied(_)) = self.slot_mut(key) else {
+            return false;
+        };
+        *slot = Slot::Vacant { next: free_head };
+        self.free_head = key;
+        self.len -= 1;
+        true
+    }
+
+    /// Clears the PinnedSlabMap, dropping all values in place.
+    ///
+    /// The allocated chunks are kept.
+    pub fn clear(&mut self) {
+        for chunk in &mut self.chunks {
+            for slot in chunk.iter_mut() {
+                *slot = Slot::Vacant { next: NONE };
+            }
+        }
+        self.len = 0;
+        self.used = 0;
+        self.free_head = NONE;
+    }
+
+    /// Gets an iterator over the entries of the PinnedSlabMap, sorted by key.
+    #[inline]
+    pub fn iter(&self) -> Iter<'_, T> {
+        Iter {
+            chunks: self.chunks.iter(),
+            slots: [].iter(),
+            key: 0,
+            len: self.len,
+        }
+    }
+}
+impl<T: Unpin> PinnedSlabMap<T> {
+    /// Returns a mutable reference to the value corresponding to the key.
+    #[inline]
+    pub fn get_mut(&mut self, key: usize) -> Option<&mut T> {
+        Some(self.get_pin(key)?.get_mut())
+    }
+
+    /// Removes a key from the PinnedSlabMap, returning the value at the key if the key was previously in the PinnedSlabMap.
+    pub fn take(&mut self, key: usize) -> Option<T> {
+        let free_head = self.free_head;
+        let slot = self.slot_mut(key)?;
+        if !matches!(slot, Slot::Occupied(_)) {
+            return None;
+        }
+        let Slot::Occupied(value) = core::mem::replace(slot, Slot::Vacant { next: free_head })
+        else {
+            unreachable!()
+        };
+        self.free_head = key;
+        self.len -= 1;
+        Some(value)
+    }
+}
+impl<T> Default for PinnedSlabMap<T> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+impl<T: Debug> Debug for PinnedSlabMap<T> {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        f.debug_map().entries(self.iter()).finish()
+    }
+}
+
+impl<T> core::ops::Index<usize> for PinnedSlabMap<T> {
+    type Output = T;
+
+    #[inline]
+    fn index(&self, index: usize) -> &Self::Output {
+        self.get(index).expect("out of index.")
+    }
+}
+
+impl<'a, T> IntoIterator for &'a PinnedSlabMap<T> {
+    type Item = (usize, &'a T);
+    type IntoIter = Iter<'a, T>;
+
+    #[inline]
+    fn into_iter(self) -> Self::IntoIter {
+        self.iter()
+    }
+}
+
+/// An iterator over the entries of a [`PinnedSlabMap`].
+///
+/// This struct is created by the [`iter`](PinnedSlabMap::iter).
+pub struct Iter<'a, T> {
+    chunks: core::slice::Iter<'a, Box<[Slot<T>]>>,
+    slots: core::slice::Iter<'a, Slot<T>>,
+    key: usize,
+    len: usize,
+}
+impl<'a, T> Iterator for Iter<'a, T> {
+    type Item = (usize, &'a T);
+    #[inline]
+    fn next(&mut self) -> Option<Self::Item> {
+        while self.len > 0 {
+            let Some(slot) = self.slots.next() else {
+                self.slots = self.chunks.next()?.iter();
+                continue;
+            };
+            self.key += 1;
+            if let Slot::Occupied(value) = slot {
+                self.len -= 1;
+                return Some((self.key - 1, value));
+            }
+        }
+        None
+    }
+    #[inline]
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        (self.len, Some(self.len))
+    }
+}
+impl<T> FusedIterator for Iter<'_, T> {}
+impl<T> ExactSizeIterator for Iter<'_, T> {}
@@ -0,0 +1,49 @@
+use std::{collections::BTreeMap, marker::PhantomPinned};
+
+use rand::{rngs::StdRng, Rng, SeedableRng};
+
+use crate::pinned_slab_map::PinnedSlabMap;
+
+#[test]
+fn address_is_stable() {
+    let mut s = PinnedSlabMap::new();
+    let key = s.insert((0, PhantomPinned));
+    let p: *const _ = s.get(key).unwrap();
+    for i in 1..1000 {
+        s.insert((i, PhantomPinned));
+    }
+    assert_eq!(p, s.get(key).unwrap() as *const _);
+    assert_eq!(s.get_pin(key).unwrap().0, 0);
+}
+
+#[test]
+fn take_unpin() {
+    let mut s = PinnedSlabMap::new();
+    let a = s.insert(String::from("a"));
+    s.get_mut(a).unwrap().push('b');
+    assert_eq!(s.take(a).as_deref(), Some("ab"));
+    assert_eq!(s.take(a), None);
+    assert_eq!(s.insert(String::new()), a);
+}
+
+#[test]
+fn random() {
+    let mut rng = StdRng::seed_from_u64(0);
+    let mut s = PinnedSlabMap::new();
+    let mut e = BTreeMap::new();
+    for i in 0..5000 {
+        if rng.gen_bool(0.6) {
+            let key = s.insert(i);
+            assert!(e.insert(key, i).is_none());
+        } else if !e.is_empty() {
+            let key = rng.gen_range(0..s.used);
+            assert_eq!(s.remove(key), e.remove(&key).is_some());
+        }
+        if i % 1000 == 999 {
+            s.clear();
+            e.clear();
+        }
+        assert_eq!(s.len(), e.len());
+        assert!(s.iter().eq(e.iter().map(|(&k, v)| (k, v))));
+    }
+}
@@ -0,0 +1,306 @@
+//! A variant of [`SlabMap`] whose keys are hard to guess.
+
+use std::{
+    collections::hash_map::RandomState,
+    fmt::Debug,
+    hash::{BuildHasher, Hasher},
+    iter::FusedIterator,
+};
+
+use derive_ex::derive_ex;
+
+use crate::{slab_map, SlabMap};
+
+#[cfg(test)]
+mod tests;
+
+const ROUNDS: usize = 4;
+const HALF_BITS: u32 = usize::BITS / 2;
+const HALF_MASK: usize = (1 << HALF_BITS) - 1;
+
+/// A keyed permutation of `usize` implemented as a Feistel network.
+#[derive(Clone, Copy, Debug)]
+struct KeyCipher {
+    round_keys: [u64; ROUNDS],
+}
+impl KeyCipher {
+    fn new(seed: u64) -> Self {
+        let mut state = seed;
+        Self {
+            round_keys: [(); ROUNDS].map(|_| {
+                state = state.wrapping_add(0x9e37_79b9_7f4a_7c15);
+                mix(state)
+            }),
+        }
+    }
+    fn round(&self, i: usize, x: usize) -> usize {
+        mix(x as u64 ^ self.round_keys[i]) as usize & HALF_MASK
+    }
+    fn encrypt(&self, idx: usize) -> usize {
+        let mut l = idx >> HALF_BITS;
+        let mut r = idx & HALF_MASK;
+        for i in 0..ROUNDS {
+            (l, r) = (r, l ^ self.round(i, r));
+        }
+        (l << HALF_BITS) | r
+    }
+    fn decrypt(&self, key: usize) -> usize {
+        let mut l = key >> HALF_BITS;
+        let mut r = key & HALF_MASK;
+        for i in (0..ROUNDS).rev() {
+            (l, r) = (r ^ self.round(i, l), l);
+        }
+        (l << HALF_BITS) | r
+    }
+}
+fn mix(mut x: u64) -> u64 {
+    x = (x ^ (x >> 30)).wrapping_mul(0xbf58_476d_1ce4_e5b9);
+    x = (x ^ (x >> 27)).wrapping_mul(0x94d0_49bb_1331_11eb);
+    x ^ (x >> 31)
+}
+
+/// A variant of [`SlabMap`] that maps slot indices through a per-map random permutation before exposing them as keys.
+///
+/// Keys are not sequential and do not reveal the number of elements, which makes them hard to guess.
+/// The permutation is not cryptographically secure, so keys must not be used as proof of authorization.
+///
+/// # Examples
+/// ```
+/// use slabmap::scrambled_slab_map::ScrambledSlabMap;
+///
+/// let mut s = ScrambledSlabMap::new();
+/// let key_a = s.insert("aaa");
+/// let key_b = s.insert("bbb");
+///
+/// assert_eq!(s[key_a], "aaa");
+/// assert_eq!(s[key_b], "bbb");
+/// assert_eq!(s.remove(key_a), Some("aaa"));
+/// assert_eq!(s.get(key_a), None);
+/// ```
+#[derive_ex(Clone(bound(T)))]
+pub struct ScrambledSlabMap<T> {
+    map: SlabMap<T>,
+    cipher: KeyCipher,
+}
+
+impl<T> ScrambledSlabMap<T> {
+    /// Constructs a new, empty `ScrambledSlabMap<T>` with a random permutation.
+    pub fn new() -> Self {
+        Self::with_seed(RandomState::new().build_hasher().finish())
+    }
+
+    /// Constructs a new, empty `ScrambledSlabMap<T>` with the permutation determined by `seed`.
+    pub fn with_seed(seed: u64) -> Self {
+        Self {
+            map: SlabMap::new(),
+            cipher: KeyCipher::new(seed),
+        }
+    }
+
+    /// Returns the number of elements in the ScrambledSlabMap.
+    #[inline]
+    pub fn len(&self) -> usize {
+        self.map.len()
+    }
+
+    /// Returns true if the ScrambledSlabMap contains no elements.
+    #[inline]
+    pub fn is_empty(&self) -> bool {
+        self.map.is_empty()
+    }
+
+    /// Returns a reference to the value corresponding to the key.
+    #[inline]
+    pub fn get(&self, key: usize) -> Option<&T> {
+        self.map.get(self.cipher.decrypt(key))
+    }
+
+    /// Returns a mutable reference to the value corresponding to the key.
+    #[inline]
+    pub fn get_mut(&mut self, key: usize) -> Option<&mut T> {
+        self.map.get_mut(self.cipher.decrypt(key))
+    }
+
+    /// Returns true if the ScrambledSlabMap contains a value for the specified key.
+    #[inline]
+    pub fn contains_key(&self, key: usize) -> bool {
+        self.get(key).is_some()
+    }
+
+    /// Inserts a value into the ScrambledSlabMap.
+    ///
+    /// Returns the key associated with the value.
+    pub fn insert(&mut self, value: T) -> usize {
+        self.insert_with_key(|_| value)
+    }
+
+    /// Inserts a value given by `f` into the ScrambledSlabMap. The key to be associated with the value is passed to `f`.
+    ///
+    /// Returns the key associated with the value.
+    pub fn insert_with_key(&mut self, f: impl FnOnce(usize) -> T) -> usize {
+        let cipher = self.cipher;
+        let idx = self.map.insert_with_key(|idx| f(cipher.encrypt(idx)));
+        cipher.encrypt(idx)
+    }
+
+    /// Removes a key from the ScrambledSlabMap, returning the value at the key if the key was previously in the ScrambledSlabMap.
+    pub fn remove(&mut self, key: usize) -> Option<T> {
+        self.map.remove(self.cipher.decrypt(key))
+    }
+
+    /// Clears the ScrambledSlabMap, removing all values.
+    pub fn clear(&mut self) {
+        self.map.clear();
+    }
+
+    /// Optimizing the free space.
+    pub fn optimize(&mut self) {
+        self.map.optimize();
+    }
+
+    /// Gets an iterator over the entries of the ScrambledSlabMap.
+    ///
+    /// The order of the entries is unspecified.
+    #[inline]
+    pub fn iter(&self) -> Iter<'_, T> {
+        Iter {
+            iter: self.map.iter(),
+            cipher: self.cipher,
+        }
+    }
+
+    /// Gets a mutable iterator over the entries of the ScrambledSlabMap.
+    ///
+    /// The order of the entries is unspecified.
+    #[inline]
+    pub fn iter_mut(&mut self) -> IterMut<'_, T> {
+        IterMut {
+            iter: self.map.iter_mut(),
+            cipher: self.cipher,
+        }
+    }
+
+    /// Gets an iterator over the keys of the ScrambledSlabMap.
+    #[inline]
+    pub fn keys(&self) -> Keys<'_, T> {
+        Keys(self.iter())
+    }
+
+    /// Gets an iterator over the values of the ScrambledSlabMap.
+    #[inline]
+    pub fn values(&self) -> slab_map::Values<'_, T> {
+        self.map.values()
+    }
+
+    /// Gets a mutable iterator over the values of the ScrambledSlabMap.
+    #[inline]
+    pub fn values_mut(&mut self) -> slab_map::ValuesMut<'_, T> {
+        self.map.values_mut()
+    }
+}
+impl<T> Default for ScrambledSlabMap<T> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+impl<T: Debug> Debug for ScrambledSlabMap<T> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_map().entries(self.iter()).finish()
+    }
+}
+
+impl<T> std::ops::Index<usize> for ScrambledSlabMap<T> {
+    type Output = T;
+
+    #[inline]
+    fn index(&self, index: usize) -> &Self::Output {
+        self.get(index).expect("out of index.")
+    }
+}
+impl<T> std::ops::IndexMut<usize> for ScrambledSlabMap<T> {
+    #[inline]
+    fn index_mut(&mut self, index: usize) -> &mut Self::Output {
+        self.get_mut(index).expect("out of index.")
+    }
+}
+
+impl<'a, T> IntoIterator for &'a ScrambledSlabMap<T> {
+    type Item = (usize, &'a T);
+    type IntoIter = Iter<'a, T>;
+
+    #[inline]
+    fn into_iter(self) -> Self::IntoIter {
+        self.iter()
+    }
+}
+impl<'a, T> IntoIterator for &'a mut ScrambledSlabMap<T> {
+    type Item = (usize, &'a mut T);
+    type IntoIter = IterMut<'a, T>;
+
+    #[inline]
+    fn into_iter(self) -> Self::IntoIter {
+        self.iter_mut()
+    }
+}
+
+/// An iterator over the entries of a [`ScrambledSlabMap`].
+///
+/// This struct is created by the [`iter`](ScrambledSlabMap::iter).
+pub struct Iter<'a, T> {
+    iter: slab_map::Iter<'a, T>,
+    cipher: KeyCipher,
+}
+impl<'a, T> Iterator for Iter<'a, T> {
+    type Item = (usize, &'a T);
+    #[inline]
+    fn next(&mut self) -> Option<Self::Item> {
+        let (idx, value) = self.iter.next()?;
+        Some((self.cipher.encrypt(idx), value))
+    }
+    #[inline]
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        self.iter.size_hint()
+    }
+}
+impl<T> FusedIterator for Iter<'_, T> {}
+impl<T> ExactSizeIterator for Iter<'_, T> {}
+
+/// A mutable iterator over the entries of a [`ScrambledSlabMap`].
+///
+/// This struct is created by the [`iter_mut`](ScrambledSlabMap::iter_mut).
+pub struct IterMut<'a, T> {
+    iter: slab_map::IterMut<'a, T>,
+    cipher: KeyCipher,
+}
+impl<'a, T> Iterator for IterMut<'a, T> {
+    type Item = (usize, &'a mut T);
+    #[inline]
+    fn next(&mut self) -> Option<Self::Item> {
+        let (idx, value) = self.iter.next()?;
+        Some((self.cipher.encrypt(idx), value))
+    }
+    #[inline]
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        self.iter.size_hint()
+    }
+}
+impl<T> FusedIterator for IterMut<'_, T> {}
+impl<T> ExactSizeIterator for IterMut<'_, T> {}
+
+/// An iterator over the keys of a [`ScrambledSlabMap`].
+///
+/// This struct is created by the [`keys`](ScrambledSlabMap::keys).
+pub struct Keys<'a, T>(Iter<'a, T>);
+impl<T> Iterator for Keys<'_, T> {
+    type Item = usize;
+    #[inline]
+    fn next(&mut self) -> Option<Self::Item> {
+        self.0.next().map(|(k, _)| k)
+    }
+    #[inline]
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        self.0.size_hint()
+    }
+}
+impl<T> FusedIterator for Keys<'_, T> {}
+impl<T> ExactSizeIterator for Keys<'_, T> {}
@@ -0,0 +1,61 @@
+use std::collections::HashSet;
+
+use crate::scrambled_slab_map::{KeyCipher, ScrambledSlabMap};
+
+#[test]
+fn cipher_roundtrip() {
+    let c = KeyCipher::new(12345);
+    for idx in (0..1000).chain([usize::MAX, usize::MAX - 1, 1 << 40]) {
+        assert_eq!(c.decrypt(c.encrypt(idx)), idx);
+    }
+}
+
+#[test]
+fn cipher_is_injective() {
+    let c = KeyCipher::new(1);
+    let keys: HashSet<_> = (0..10000).map(|idx| c.encrypt(idx)).collect();
+    assert_eq!(keys.len(), 10000);
+}
+
+#[test]
+fn keys_are_not_sequential() {
+    let mut s = ScrambledSlabMap::with_seed(7);
+    let k0 = s.insert(0);
+    let k1 = s.insert(1);
+    assert_ne!(k0, 0);
+    assert_ne!(k1, k0.wrapping_add(1));
+}
+
+#[test]
+fn seed_is_deterministic() {
+    let mut a = ScrambledSlabMap::with_seed(42);
+    let mut b = ScrambledSlabMap::with_seed(42);
+    for i in 0..10 {
+        assert_eq!(a.insert(i), b.insert(i));
+    }
+}
+
+#[test]
+fn insert_get_remove() {
+    let mut s = ScrambledSlabMap::new();
+    let keys: Vec<_> = (0..100).map(|i| s.insert(i)).collect();
+    for (i, &key) in keys.iter().enumerate() {
+        assert_eq!(s[key], i);
+    }
+    for &key in keys.iter().step_by(2) {
+        assert!(s.remove(key).is_some());
+        assert_eq!(s.get(key), None);
+    }
+    let mut a: Vec<_> = s.iter().map(|(k, v)| (k, *v)).collect();
+    let mut e: Vec<_> = keys.iter().copied().zip(0..).skip(1).step_by(2).collect();
+    a.sort();
+    e.sort();
+    assert_eq!(a, e);
+}
+
+#[test]
+fn insert_with_key() {
+    let mut s = ScrambledSlabMap::new();
+    let key = s.insert_with_key(|key| key);
+    assert_eq!(s[key], key);
+}
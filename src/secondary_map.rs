@@ -0,0 +1,472 @@
+//! Maps that attach additional data to the keys of a [`SlabMap`](crate::SlabMap).
+
+use alloc::vec::Vec;
+use core::{fmt::Debug, iter::FusedIterator, mem::take};
+#[cfg(feature = "std")]
+use std::collections::{hash_map, HashMap};
+
+use derive_ex::derive_ex;
+
+#[cfg(test)]
+mod tests;
+
+/// A structure keyed by the keys of a [`SlabMap`](crate::SlabMap) that can follow reassignment of the keys.
+///
+/// Pass implementors to [`SlabMap::compact_remap`](crate::SlabMap::compact_remap) to keep them consistent with the primary map.
+/// Closures taking the old key and the new key also implement this trait.
+pub trait Remap {
+    /// Moves the data at each old key to the corresponding new key.
+    ///
+    /// `remap` contains pairs of an old key and a new key. Data at keys not listed as an old key is discarded.
+    fn remap(&mut self, remap: &[(usize, usize)]);
+}
+impl<F: FnMut(usize, usize)> Remap for F {
+    fn remap(&mut self, remap: &[(usize, usize)]) {
+        for &(old, new) in remap {
+            self(old, new);
+        }
+    }
+}
+
+/// A map that attaches values to the keys of a [`SlabMap`](crate::SlabMap), stored densely in a `Vec` indexed by key.
+///
+/// The memory usage is proportional to the largest key.
+///
+/// # Examples
+/// ```
+/// use slabmap::{secondary_map::SecondaryMap, SlabMap};
+///
+/// let mut names = SlabMap::new();
+/// let mut ages = SecondaryMap::new();
+/// let a = names.insert("a");
+/// let b = names.insert("b");
+/// ages.insert(b, 20);
+///
+/// names.remove(a);
+/// names.compact_remap(&mut [&mut ages]);
+///
+/// assert_eq!(names[0], "b");
+/// assert_eq!(ages[0], 20);
+/// ```
+#[derive_ex(Clone(bound(T)), Default(bound()))]
+pub struct SecondaryMap<T> {
+    values: Vec<Option<T>>,
+    len: usize,
+}
+
+impl<T> SecondaryMap<T> {
+    /// Constructs a new, empty `SecondaryMap<T>`.
+    #[inline]
+    pub const fn new() -> Self {
+        Self {
+            values: Vec::new(),
+            len: 0,
+        }
+    }
+
+    /// Returns the number of elements in the SecondaryMap.
+    #[inline]
+    pub fn len(&self) -> usize {
+        self.len
+    }
+
+    /// Returns true if the SecondaryMap contains no elements.
+    #[inline]
+    pub fn is_empty(&self) -> bool {
+        self.len == 0
+    }
+
+    /// Returns a reference to the value corresponding to the key.
+    #[inline]
+    pub fn get(&self, key: usize) -> Option<&T> {
+        self.values.get(key)?.as_ref()
+    }
+
+    /// Returns a mutable reference to the value corresponding to the key.
+    #[inline]
+    pub fn get_mut(&mut self, key: usize) -> Option<&mut T> {
+        self.values.get_mut(key)?.as_mut()
+    }
+
+    /// Returns true if the SecondaryMap contains a value for the specified key.
+    #[inline]
+    pub fn contains_key(&self, key: usize) -> bool {
+        self.get(key).is_some()
+    }
+
+    /// Inserts a value at the key, returning the old value.
+    pub fn insert(&mut self, key: usize, value: T) -> Option<T> {
+        if key >= self.values.len() {
+            self.values.resize_with(key + 1, || None);
+        }
+        let old = self.values[key].replace(value);
+        if old.is_none() {
+            self.len += 1;
+        }
+        old
+    }
+
+    /// Removes a key from the SecondaryMap, returning the value at the key if the key was previously in the SecondaryMap.
+    pub fn remove(&mut self, key: usize) -> Option<T> {
+        let value = self.values.get_mut(key)?.take()?;
+        self.len -= 1;
+        while let Some(None) = self.values.last() {
+            self.values.pop();
+        }
+        Some(value)
+    }
+
+    /// Clears the SecondaryMap, removing all values.
+    pub fn clear(&mut self) {
+        self.values.clear();
+        self.len = 0;
+    }
+
+    /// Retains only the elements specified by the predicate.
+    pub fn retain(&mut self, mut f: impl FnMut(usize, &mut T) -> bool) {
+        for (key, value) in self.values.iter_mut().enumerate() {
+            if let Some(v) = value {
+                if !f(key, v) {
+                    *value = None;
+                    self.len -= 1;
+                }
+            }
+        }
+        while let Some(None) = self.values.last() {
+            self.values.pop();
+        }
+    }
+
+    /// Gets an iterator over the entries of the SecondaryMap, sorted by key.
+    #[inline]
+    pub fn iter(&self) -> Iter<'_, T> {
+        Iter {
+            iter: self.values.iter().enumerate(),
+            len: self.len,
+        }
+    }
+
+    /// Gets a mutable iterator over the entries of the SecondaryMap, sorted by key.
+    #[inline]
+    pub fn iter_mut(&mut self) -> IterMut<'_, T> {
+        IterMut {
+            iter: self.values.iter_mut().enumerate(),
+            len: self.len,
+        }
+    }
+}
+impl<T> Remap for SecondaryMap<T> {
+    fn remap(&mut self, remap: &[(usize, usize)]) {
+        let mut values = take(&mut self.values);
+        self.len = 0;
+        for &(old, new) in remap {
+            if let Some(value) = values.get_mut(old).and_then(Option::take) {
+                self.insert(new, value);
+            }
+        }
+    }
+}
+impl<T: Debug> Debug for SecondaryMap<T> {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        f.debug_map().entries(self.iter()).finish()
+    }
+}
+
+impl<T> core::ops::Index<usize> for SecondaryMap<T> {
+    type Output = T;
+
+    #[inline]
+    fn index(&self, index: usize) -> &Self::Output {
+        self.get(index).expect("out of index.")
+    }
+}
+impl<T> core::ops::IndexMut<usize> for SecondaryMap<T> {
+    #[inline]
+    fn index_mut(&mut self, index: usize) -> &mut Self::Output {
+        self.get_mut(index).expect("out of index.")
+    }
+}
+
+impl<'a, T> IntoIterator for &'a SecondaryMap<T> {
+    type Item = (usize, &'a T);
+    type IntoIter = Iter<'a, T>;
+
+    #[inline]
+    fn into_iter(self) -> Self::IntoIter {
+        self.iter()
+    }
+}
+impl<'a, T> IntoIterator for &'a mut SecondaryMap<T> {
+    type Item = (usize, &'a mut T);
+    type IntoIter = IterMut<'a, T>;
+
+    #[inline]
+    fn into_iter(self) -> Self::IntoIter {
+        self.iter_mut()
+    }
+}
+
+/// An iterator over the entries of a [`SecondaryMap`].
+///
+/// This struct is created by the [`iter`](SecondaryMap::iter).
+pub struct Iter<'a, T> {
+    iter: core::iter::Enumerate<core::slice::Iter<'a, Option<T>>>,
+    len: usize,
+}
+impl<'a, T> Iterator for Iter<'a, T> {
+    type Item = (usize, &'a T);
+    #[inline]
+    fn next(&mut self) -> Option<Self::Item> {
+        for (key, value) in self.iter.by_ref() {
+            if let Some(value) = value {
+                self.len -= 1;
+                return Some((key, value));
+            }
+        }
+        None
+    }
+    #[inline]
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        (self.len, Some(self.len))
+    }
+}
+impl<T> FusedIterator for Iter<'_, T> {}
+impl<T> ExactSizeIterator for Iter<'_, T> {}
+
+/// A mutable iterator over the entries of a [`SecondaryMap`].
+///
+/// This struct is created by the [`iter_mut`](SecondaryMap::iter_mut).
+pub struct IterMut<'a, T> {
+    iter: core::iter::Enumerate<core::slice::IterMut<'a, Option<T>>>,
+    len: usize,
+}
+impl<'a, T> Iterator for IterMut<'a, T> {
+    type Item = (usize, &'a mut T);
+    #[inline]
+    fn next(&mut self) -> Option<Self::Item> {
+        for (key, value) in self.iter.by_ref() {
+            if let Some(value) = value {
+                self.len -= 1;
+                return Some((key, value));
+            }
+        }
+        None
+    }
+    #[inline]
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        (self.len, Some(self.len))
+    }
+}
+impl<T> FusedIterator for IterMut<'_, T> {}
+impl<T> ExactSizeIterator for IterMut<'_, T> {}
+
+/// A map that attaches values to the keys of a [`SlabMap`](crate::SlabMap), stored sparsely in a `HashMap`.
+///
+/// The memory usage is proportional to the number of elements, not to the largest key.
+/// This is suitable for data attached to only a small fraction of keys.
+///
+/// # Examples
+/// ```
+/// use slabmap::{secondary_map::SparseSecondaryMap, SlabMap};
+///
+/// let mut names = SlabMap::new();
+/// let mut notes = SparseSecondaryMap::new();
+/// for i in 0..1000 {
+///     names.insert(i);
+/// }
+/// notes.insert(999, "last");
+///
+/// names.retain(|key, _| key >= 998);
+/// names.compact_remap(&mut [&mut notes]);
+///
+/// assert_eq!(notes[1], "last");
+/// ```
+#[cfg(feature = "std")]
+#[derive_ex(Clone(bound(T)), Default(bound()))]
+pub struct SparseSecondaryMap<T> {
+    values: HashMap<usize, T>,
+}
+
+#[cfg(feature = "std")]
+impl<T> SparseSecondaryMap<T> {
+    /// Constructs a new, empty `SparseSecondaryMap<T>`.
+    #[inline]
+    pub fn new() -> Self {
+        Self {
+            values: HashMap::new(),
+        }
+    }
+
+    /// Constructs a new, empty `SparseSecondaryMap<T>` with the specified capacity.
+    #[inline]
+    pub fn with_capacity(capacity: usize) -> Self {
+        Self {
+            values: HashMap::with_capacity(capacity),
+        }
+    }
+
+    /// Returns the number of elements in the SparseSecondaryMap.
+    #[inline]
+    pub fn len(&self) -> usize {
+        self.values.len()
+    }
+
+    /// Returns true if the SparseSecondaryMap contains no elements.
+    #[inline]
+    pub fn is_empty(&self) -> bool {
+        self.values.is_empty()
+    }
+
+    /// Returns a reference to the value corresponding to the key.
+    #[inline]
+    pub fn get(&self, key: usize) -> Option<&T> {
+        self.values.get(&key)
+    }
+
+    /// Returns a mutable reference to the value corresponding to the key.
+    #[inline]
+    pub fn get_mut(&mut self, key: usize) -> Option<&mut T> {
+        self.values.get_mut(&key)
+    }
+
+    /// Returns true if the SparseSecondaryMap contains a value for the specified key.
+    #[inline]
+    pub fn contains_key(&self, key: usize) -> bool {
+        self.values.contains_key(&key)
+    }
+
+    /// Inserts a value at the key, returning the old value.
+    #[inline]
+    pub fn insert(&mut self, key: usize, value: T) -> Option<T> {
+        self.values.insert(key, value)
+    }
+
+    /// Removes a key from the SparseSecondaryMap, returning the value at the key if the key was previously in the SparseSecondaryMap.
+    #[inline]
+    pub fn remove(&mut self, key: usize) -> Option<T> {
+        self.values.remove(&key)
+    }
+
+    /// Clears the SparseSecondaryMap, removing all values.
+    #[inline]
+    pub fn clear(&mut self) {
+        self.values.clear();
+    }
+
+    /// Retains only the elements specified by the predicate.
+    pub fn retain(&mut self, mut f: impl FnMut(usize, &mut T) -> bool) {
+        self.values.retain(|&key, value| f(key, value));
+    }
+
+    /// Gets an iterator over the entries of the SparseSecondaryMap in arbitrary order.
+    #[inline]
+    pub fn iter(&self) -> SparseIter<'_, T> {
+        SparseIter(self.values.iter())
+    }
+
+    /// Gets a mutable iterator over the entries of the SparseSecondaryMap in arbitrary order.
+    #[inline]
+    pub fn iter_mut(&mut self) -> SparseIterMut<'_, T> {
+        SparseIterMut(self.values.iter_mut())
+    }
+}
+#[cfg(feature = "std")]
+impl<T> Remap for SparseSecondaryMap<T> {
+    fn remap(&mut self, remap: &[(usize, usize)]) {
+        let mut values = take(&mut self.values);
+        self.values.reserve(values.len().min(remap.len()));
+        for &(old, new) in remap {
+            if let Some(value) = values.remove(&old) {
+                self.values.insert(new, value);
+            }
+        }
+    }
+}
+#[cfg(feature = "std")]
+impl<T: Debug> Debug for SparseSecondaryMap<T> {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        f.debug_map().entries(self.iter()).finish()
+    }
+}
+
+#[cfg(feature = "std")]
+impl<T> core::ops::Index<usize> for SparseSecondaryMap<T> {
+    type Output = T;
+
+    #[inline]
+    fn index(&self, index: usize) -> &Self::Output {
+        self.get(index).expect("out of index.")
+    }
+}
+#[cfg(feature = "std")]
+impl<T> core::ops::IndexMut<usize> for SparseSecondaryMap<T> {
+    #[inline]
+    fn index_mut(&mut self, index: usize) -> &mut Self::Output {
+        self.get_mut(index).expect("out of index.")
+    }
+}
+
+#[cfg(feature = "std")]
+impl<'a, T> IntoIterator for &'a SparseSecondaryMap<T> {
+    type Item = (usize, &'a T);
+    type IntoIter = SparseIter<'a, T>;
+
+    #[inline]
+    fn into_iter(self) -> Self::IntoIter {
+        self.iter()
+    }
+}
+#[cfg(feature = "std")]
+impl<'a, T> IntoIterator for &'a mut SparseSecondaryMap<T> {
+    type Item = (usize, &'a mut T);
+    type IntoIter = SparseIterMut<'a, T>;
+
+    #[inline]
+    fn into_iter(self) -> Self::IntoIter {
+        self.iter_mut()
+    }
+}
+
+/// An iterator over the entries of a [`SparseSecondaryMap`].
+///
+/// This struct is created by the [`iter`](SparseSecondaryMap::iter).
+#[cfg(feature = "std")]
+pub struct SparseIter<'a, T>(hash_map::Iter<'a, usize, T>);
+#[cfg(feature = "std")]
+impl<'a, T> Iterator for SparseIter<'a, T> {
+    type Item = (usize, &'a T);
+    #[inline]
+    fn next(&mut self) -> Option<Self::Item> {
+        self.0.next().map(|(&key, value)| (key, value))
+    }
+    #[inline]
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        self.0.size_hint()
+    }
+}
+#[cfg(feature = "std")]
+impl<T> FusedIterator for SparseIter<'_, T> {}
+#[cfg(feature = "std")]
+impl<T> ExactSizeIterator for SparseIter<'_, T> {}
+
+/// A mutable iterator over the entries of a [`SparseSecondaryMap`].
+///
+/// This struct is created by the [`iter_mut`](SparseSecondaryMap::iter_mut).
+#[cfg(feature = "std")]
+pub struct SparseIterMut<'a, T>(hash_map::IterMut<'a, usize, T>);
+#[cfg(feature = "std")]
+impl<'a, T> Iterator for SparseIterMut<'a, T> {
+    type Item = (usize, &'a mut T);
+    #[inline]
+    fn next(&mut self) -> Option<Self::Item> {
+        self.0.next().map(|(&key, value)| (key, value))
+    }
+    #[inline]
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        self.0.size_hint()
+    }
+}
+#[cfg(feature = "std")]
+impl<T> FusedIterator for SparseIterMut<'_, T> {}
+#[cfg(feature = "std")]
+impl<T> ExactSizeIterator for SparseIterMut<'_, T> {}
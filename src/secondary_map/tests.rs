@@ -0,0 +1,111 @@
+use std::collections::BTreeMap;
+
+use rand::{rngs::StdRng, Rng, SeedableRng};
+
+use crate::{
+    secondary_map::{Remap, SecondaryMap},
+    SlabMap,
+};
+
+#[test]
+fn insert_remove() {
+    let mut s = SecondaryMap::new();
+    assert_eq!(s.insert(5, "a"), None);
+    assert_eq!(s.insert(5, "b"), Some("a"));
+    assert_eq!(s.insert(2, "c"), None);
+    assert_eq!(s.len(), 2);
+    assert_eq!(s.iter().collect::<Vec<_>>(), [(2, &"c"), (5, &"b")]);
+    assert_eq!(s.remove(5), Some("b"));
+    assert_eq!(s.remove(5), None);
+    assert_eq!(s.values.len(), 3);
+    s.retain(|_, _| false);
+    assert!(s.is_empty());
+    assert!(s.values.is_empty());
+}
+
+#[test]
+fn remap_swap() {
+    let mut s = SecondaryMap::new();
+    s.insert(0, "a");
+    s.insert(1, "b");
+    s.insert(2, "c");
+    s.remap(&[(0, 1), (1, 0)]);
+    assert_eq!(s.iter().collect::<Vec<_>>(), [(0, &"b"), (1, &"a")]);
+}
+
+#[test]
+fn compact_remap() {
+    let mut rng = StdRng::seed_from_u64(0);
+    let mut s = SlabMap::new();
+    let mut a = SecondaryMap::new();
+    let mut b = SecondaryMap::new();
+    for i in 0..300 {
+        let key = s.insert(i);
+        if rng.gen_bool(0.5) {
+            a.insert(key, i * 2);
+        }
+        if rng.gen_bool(0.1) {
+            b.insert(key, i * 3);
+        }
+    }
+    for key in 0..300 {
+        if rng.gen_bool(0.4) {
+            s.remove(key);
+        }
+    }
+    let old_a: BTreeMap<_, _> = a.iter().map(|(k, &v)| (s.get(k).copied(), v)).collect();
+    let mut log = Vec::new();
+    s.compact_remap(&mut [&mut a, &mut b, &mut |old, new| log.push((old, new))]);
+
+    assert_eq!(log.len(), s.len());
+    assert!(log.iter().enumerate().all(|(i, &(_, new))| i == new));
+    for (key, &value) in &a {
+        assert_eq!(value, s[key] * 2);
+        assert_eq!(old_a[&Some(s[key])], value);
+    }
+    for (key, &value) in &b {
+        assert_eq!(value, s[key] * 3);
+    }
+    assert!(a.iter().all(|(key, _)| key < s.len()));
+}
+
+#[cfg(feature = "std")]
+#[test]
+fn sparse_insert_remove() {
+    let mut s = crate::secondary_map::SparseSecondaryMap::new();
+    assert_eq!(s.insert(1 << 40, "a"), None);
+    assert_eq!(s.insert(1 << 40, "b"), Some("a"));
+    assert_eq!(s.insert(2, "c"), None);
+    assert_eq!(s.len(), 2);
+    assert_eq!(s[1 << 40], "b");
+    assert_eq!(s.remove(1 << 40), Some("b"));
+    assert_eq!(s.remove(1 << 40), None);
+    s.retain(|_, _| false);
+    assert!(s.is_empty());
+}
+
+#[cfg(feature = "std")]
+#[test]
+fn sparse_compact_remap() {
+    let mut rng = StdRng::seed_from_u64(0);
+    let mut s = SlabMap::new();
+    let mut a = crate::secondary_map::SparseSecondaryMap::new();
+    for i in 0..300 {
+        let key = s.insert(i);
+        if rng.gen_bool(0.05) {
+            a.insert(key, i * 2);
+        }
+    }
+    s.retain(|_, _| rng.gen_bool(0.5));
+    let expected: BTreeMap<_, _> = a
+        .iter()
+        .filter(|&(k, _)| s.contains_key(k))
+        .map(|(_, &v)| (v / 2, v))
+        .collect();
+    s.compact_remap(&mut [&mut a]);
+
+    assert_eq!(a.len(), expected.len());
+    for (key, &value) in &a {
+        assert_eq!(expected[&s[key]], value);
+    }
+}
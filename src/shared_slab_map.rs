@@ -0,0 +1,122 @@
+//! A [`SlabMap`] protected by a [`RwLock`].
+
+use std::{
+    fmt::{self, Debug},
+    sync::{PoisonError, RwLock, RwLockReadGuard, RwLockWriteGuard},
+};
+
+use crate::SlabMap;
+
+#[cfg(test)]
+mod tests;
+
+/// A [`SlabMap`] protected by a [`RwLock`], for sharing between threads where reads are frequent and writes are rare.
+///
+/// A SharedSlabMap poisoned by a panic is still usable.
+///
+/// # Examples
+/// ```
+/// use slabmap::shared_slab_map::SharedSlabMap;
+/// use std::sync::Arc;
+///
+/// let s = Arc::new(SharedSlabMap::new());
+/// let key = s.insert(10);
+///
+/// let t = s.clone();
+/// std::thread::spawn(move || t.write(key, |v| *v += 1)).join().unwrap();
+///
+/// assert_eq!(s.read(key, |v| *v), Some(11));
+/// assert_eq!(s.remove(key), Some(11));
+/// ```
+pub struct SharedSlabMap<T>(RwLock<SlabMap<T>>);
+
+impl<T> SharedSlabMap<T> {
+    /// Constructs a new, empty `SharedSlabMap<T>`.
+    pub const fn new() -> Self {
+        Self(RwLock::new(SlabMap::new()))
+    }
+
+    /// Returns the underlying [`SlabMap`].
+    pub fn into_inner(self) -> SlabMap<T> {
+        self.0.into_inner().unwrap_or_else(PoisonError::into_inner)
+    }
+
+    /// Locks the SharedSlabMap with shared read access.
+    pub fn read_lock(&self) -> RwLockReadGuard<'_, SlabMap<T>> {
+        self.0.read().unwrap_or_else(PoisonError::into_inner)
+    }
+
+    /// Locks the SharedSlabMap with exclusive write access.
+    pub fn write_lock(&self) -> RwLockWriteGuard<'_, SlabMap<T>> {
+        self.0.write().unwrap_or_else(PoisonError::into_inner)
+    }
+
+    /// Returns the number of elements in the SharedSlabMap.
+    pub fn len(&self) -> usize {
+        self.read_lock().len()
+    }
+
+    /// Returns true if the SharedSlabMap contains no elements.
+    pub fn is_empty(&self) -> bool {
+        self.read_lock().is_empty()
+    }
+
+    /// Returns true if the SharedSlabMap contains a value for the specified key.
+    pub fn contains_key(&self, key: usize) -> bool {
+        self.read_lock().contains_key(key)
+    }
+
+    /// Inserts a value into the SharedSlabMap.
+    ///
+    /// Returns the key associated with the value.
+    pub fn insert(&self, value: T) -> usize {
+        self.write_lock().insert(value)
+    }
+
+    /// Removes a key from the SharedSlabMap, returning the value at the key if the key was previously in the SharedSlabMap.
+    pub fn remove(&self, key: usize) -> Option<T> {
+        self.write_lock().remove(key)
+    }
+
+    /// Calls `f` with a reference to the value corresponding to the key, under a read lock.
+    ///
+    /// Returns `None` if the key does not exist.
+    pub fn read<R>(&self, key: usize, f: impl FnOnce(&T) -> R) -> Option<R> {
+        self.read_lock().get(key).map(f)
+    }
+
+    /// Calls `f` with a mutable reference to the value corresponding to the key, under a write lock.
+    ///
+    /// Returns `None` if the key does not exist.
+    pub fn write<R>(&self, key: usize, f: impl FnOnce(&mut T) -> R) -> Option<R> {
+        self.write_lock().get_mut(key).map(f)
+    }
+
+    /// Returns a clone of the current contents.
+    ///
+    /// The read lock is held only while cloning, so the snapshot can be iterated without blocking writers.
+    pub fn snapshot(&self) -> SlabMap<T>
+    where
+        T: Clone,
+    {
+        self.read_lock().clone()
+    }
+}
+impl<T> Default for SharedSlabMap<T> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+impl<T> From<SlabMap<T>> for SharedSlabMap<T> {
+    fn from(value: SlabMap<T>) -> Self {
+        Self(RwLock::new(value))
+    }
+}
+impl<T: Debug> Debug for SharedSlabMap<T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self.0.try_read() {
+            Ok(m) => m.fmt(f),
+            Err(_) => f.write_str("SharedSlabMap { <locked> }"),
+        }
+    }
+}
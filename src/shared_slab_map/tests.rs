@@ -0,0 +1,38 @@
+use std::thread;
+
+use crate::{shared_slab_map::SharedSlabMap, SlabMap};
+
+#[test]
+fn readers_and_writers() {
+    let s = SharedSlabMap::new();
+    let keys: Vec<_> = (0..100).map(|i| s.insert(i)).collect();
+    thread::scope(|scope| {
+        for _ in 0..4 {
+            scope.spawn(|| {
+                for &key in &keys {
+                    s.write(key, |v| *v += 1);
+                }
+            });
+            scope.spawn(|| {
+                for &key in &keys {
+                    assert!(s.read(key, |v| *v).is_some());
+                }
+            });
+        }
+    });
+    let a: Vec<_> = s.snapshot().into_iter().map(|(_, v)| v).collect();
+    let e: Vec<_> = (4..104).collect();
+    assert_eq!(a, e);
+}
+
+#[test]
+fn from_slab_map() {
+    let mut m = SlabMap::new();
+    let key = m.insert(1);
+    let s = SharedSlabMap::from(m);
+    assert!(s.contains_key(key));
+    assert_eq!(s.len(), 1);
+    assert_eq!(format!("{s:?}"), "{0: 1}");
+    let m = s.into_inner();
+    assert_eq!(m[key], 1);
+}
@@ -0,0 +1,523 @@
+//! A directed graph backed by [`SlabMap`], usable with the algorithms of [`petgraph`].
+//!
+//! Node and edge identifiers are the keys of the underlying `SlabMap`s,
+//! so they stay valid when other nodes or edges are removed.
+//! Since keys are `usize`, they can also be used directly as [`petgraph::graph::IndexType`].
+
+use std::{collections::HashSet, fmt::Debug, iter::FusedIterator, slice};
+
+use derive_ex::derive_ex;
+use petgraph::{
+    visit::{
+        Data, EdgeCount, EdgeIndexable, EdgeRef, GraphBase, GraphProp, IntoEdgeReferences,
+        IntoEdges, IntoEdgesDirected, IntoNeighbors, IntoNeighborsDirected, IntoNodeIdentifiers,
+        IntoNodeReferences, NodeCount, NodeIndexable, Visitable,
+    },
+    Directed, Direction,
+};
+
+use crate::{slab_map, SlabMap};
+
+#[cfg(test)]
+mod tests;
+
+#[derive(Clone, Debug)]
+struct Node<N> {
+    weight: N,
+    outgoing: Vec<usize>,
+    incoming: Vec<usize>,
+}
+
+#[derive(Clone, Debug)]
+struct Edge<E> {
+    weight: E,
+    source: usize,
+    target: usize,
+}
+
+/// A directed graph that stores node and edge weights in [`SlabMap`]s.
+///
+/// Unlike [`petgraph::Graph`], removing a node or an edge does not change the identifiers of other nodes or edges.
+///
+/// # Examples
+/// ```
+/// use slabmap::slab_graph::SlabGraph;
+/// use petgraph::visit::Dfs;
+///
+/// let mut g = SlabGraph::new();
+/// let a = g.add_node("a");
+/// let b = g.add_node("b");
+/// let c = g.add_node("c");
+/// g.add_edge(a, b, 1);
+/// g.add_edge(b, c, 2);
+///
+/// g.remove_node(b);
+/// assert_eq!(g[c], "c");
+///
+/// let mut dfs = Dfs::new(&g, a);
+/// assert_eq!(dfs.next(&g), Some(a));
+/// assert_eq!(dfs.next(&g), None);
+/// ```
+#[derive_ex(Clone(bound(N, E)), Default(bound()))]
+pub struct SlabGraph<N, E> {
+    nodes: SlabMap<Node<N>>,
+    edges: SlabMap<Edge<E>>,
+}
+
+impl<N, E> SlabGraph<N, E> {
+    /// Constructs a new, empty `SlabGraph<N, E>`.
+    #[inline]
+    pub const fn new() -> Self {
+        Self {
+            nodes: SlabMap::new(),
+            edges: SlabMap::new(),
+        }
+    }
+
+    /// Returns the number of nodes in the graph.
+    #[inline]
+    pub fn node_count(&self) -> usize {
+        self.nodes.len()
+    }
+
+    /// Returns the number of edges in the graph.
+    #[inline]
+    pub fn edge_count(&self) -> usize {
+        self.edges.len()
+    }
+
+    /// Adds a node to the graph and returns its identifier.
+    pub fn add_node(&mut self, weight: N) -> usize {
+        self.nodes.insert(Node {
+            weight,
+            outgoing: Vec::new(),
+            incoming: Vec::new(),
+        })
+    }
+
+    /// Adds an edge from `source` to `target` and returns its identifier.
+    ///
+    /// # Panics
+    /// Panics if `source` or `target` does not exist.
+    pub fn add_edge(&mut self, source: usize, target: usize, weight: E) -> usize {
+        assert!(
+            self.nodes.contains_key(source),
+            "source node does not exist."
+        );
+        assert!(
+            self.nodes.contains_key(target),
+            "target node does not exist."
+        );
+        let edge = self.edges.insert(Edge {
+            weight,
+            source,
+            target,
+        });
+        self.nodes[source].outgoing.push(edge);
+        self.nodes[target].incoming.push(edge);
+        edge
+    }
+
+    /// Removes a node and all edges connected to it, returning the weight of the node.
+    pub fn remove_node(&mut self, node: usize) -> Option<N> {
+        let n = self.nodes.remove(node)?;
+        for edge in n.outgoing.into_iter().chain(n.incoming) {
+            self.remove_edge(edge);
+        }
+        Some(n.weight)
+    }
+
+    /// Removes an edge, returning the weight of the edge.
+    pub fn remove_edge(&mut self, edge: usize) -> Option<E> {
+        let e = self.edges.remove(edge)?;
+        if let Some(n) = self.nodes.get_mut(e.source) {
+            remove_item(&mut n.outgoing, edge);
+        }
+        if let Some(n) = self.nodes.get_mut(e.target) {
+            remove_item(&mut n.incoming, edge);
+        }
+        Some(e.weight)
+    }
+
+    /// Returns true if the graph contains the node.
+    #[inline]
+    pub fn contains_node(&self, node: usize) -> bool {
+        self.nodes.contains_key(node)
+    }
+
+    /// Returns true if the graph contains the edge.
+    #[inline]
+    pub fn contains_edge(&self, edge: usize) -> bool {
+        self.edges.contains_key(edge)
+    }
+
+    /// Returns a reference to the weight of the node.
+    #[inline]
+    pub fn node_weight(&self, node: usize) -> Option<&N> {
+        Some(&self.nodes.get(node)?.weight)
+    }
+
+    /// Returns a mutable reference to the weight of the node.
+    #[inline]
+    pub fn node_weight_mut(&mut self, node: usize) -> Option<&mut N> {
+        Some(&mut self.nodes.get_mut(node)?.weight)
+    }
+
+    /// Returns a reference to the weight of the edge.
+    #[inline]
+    pub fn edge_weight(&self, edge: usize) -> Option<&E> {
+        Some(&self.edges.get(edge)?.weight)
+    }
+
+    /// Returns a mutable reference to the weight of the edge.
+    #[inline]
+    pub fn edge_weight_mut(&mut self, edge: usize) -> Option<&mut E> {
+        Some(&mut self.edges.get_mut(edge)?.weight)
+    }
+
+    /// Returns the source and target of the edge.
+    #[inline]
+    pub fn edge_endpoints(&self, edge: usize) -> Option<(usize, usize)> {
+        let e = self.edges.get(edge)?;
+        Some((e.source, e.target))
+    }
+
+    /// Returns an edge from `source` to `target`, if any.
+    pub fn find_edge(&self, source: usize, target: usize) -> Option<usize> {
+        self.nodes
+            .get(source)?
+            .outgoing
+            .iter()
+            .copied()
+            .find(|&edge| self.edges[edge].target == target)
+    }
+
+    /// Removes all nodes and edges.
+    pub fn clear(&mut self) {
+        self.nodes.clear();
+        self.edges.clear();
+    }
+}
+fn remove_item(items: &mut Vec<usize>, item: usize) {
+    if let Some(index) = items.iter().position(|&x| x == item) {
+        items.swap_remove(index);
+    }
+}
+
+impl<N: Debug, E: Debug> Debug for SlabGraph<N, E> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("SlabGraph")
+            .field("nodes", &NodesDebug(&self.nodes))
+            .field("edges", &EdgesDebug(&self.edges))
+            .finish()
+    }
+}
+struct NodesDebug<'a, N>(&'a SlabMap<Node<N>>);
+impl<N: Debug> Debug for NodesDebug<'_, N> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_map()
+            .entries(self.0.iter().map(|(key, n)| (key, &n.weight)))
+            .finish()
+    }
+}
+struct EdgesDebug<'a, E>(&'a SlabMap<Edge<E>>);
+impl<E: Debug> Debug for EdgesDebug<'_, E> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_map()
+            .entries(
+                self.0
+                    .iter()
+                    .map(|(key, e)| (key, (e.source, e.target, &e.weight))),
+            )
+            .finish()
+    }
+}
+
+impl<N, E> std::ops::Index<usize> for SlabGraph<N, E> {
+    type Output = N;
+
+    #[inline]
+    fn index(&self, index: usize) -> &Self::Output {
+        self.node_weight(index).expect("out of index.")
+    }
+}
+impl<N, E> std::ops::IndexMut<usize> for SlabGraph<N, E> {
+    #[inline]
+    fn index_mut(&mut self, index: usize) -> &mut Self::Output {
+        self.node_weight_mut(index).expect("out of index.")
+    }
+}
+
+impl<N, E> GraphBase for SlabGraph<N, E> {
+    type EdgeId = usize;
+    type NodeId = usize;
+}
+impl<N, E> GraphProp for SlabGraph<N, E> {
+    type EdgeType = Directed;
+}
+impl<N, E> Data for SlabGraph<N, E> {
+    type NodeWeight = N;
+    type EdgeWeight = E;
+}
+impl<N, E> NodeCount for SlabGraph<N, E> {
+    #[inline]
+    fn node_count(&self) -> usize {
+        self.nodes.len()
+    }
+}
+impl<N, E> EdgeCount for SlabGraph<N, E> {
+    #[inline]
+    fn edge_count(&self) -> usize {
+        self.edges.len()
+    }
+}
+impl<N, E> NodeIndexable for SlabGraph<N, E> {
+    #[inline]
+    fn node_bound(&self) -> usize {
+        self.nodes.key_bound()
+    }
+    #[inline]
+    fn to_index(&self, a: usize) -> usize {
+        a
+    }
+    #[inline]
+    fn from_index(&self, i: usize) -> usize {
+        i
+    }
+}
+impl<N, E> EdgeIndexable for SlabGraph<N, E> {
+    #[inline]
+    fn edge_bound(&self) -> usize {
+        self.edges.key_bound()
+    }
+    #[inline]
+    fn to_index(&self, a: usize) -> usize {
+        a
+    }
+    #[inline]
+    fn from_index(&self, i: usize) -> usize {
+        i
+    }
+}
+impl<N, E> Visitable for SlabGraph<N, E> {
+    type Map = HashSet<usize>;
+
+    fn visit_map(&self) -> Self::Map {
+        HashSet::with_capacity(self.nodes.len())
+    }
+    fn reset_map(&self, map: &mut Self::Map) {
+        map.clear();
+    }
+}
+
+impl<'a, N, E> IntoNeighbors for &'a SlabGraph<N, E> {
+    type Neighbors = Neighbors<'a, E>;
+
+    fn neighbors(self, a: usize) -> Self::Neighbors {
+        self.neighbors_directed(a, Direction::Outgoing)
+    }
+}
+impl<'a, N, E> IntoNeighborsDirected for &'a SlabGraph<N, E> {
+    type NeighborsDirected = Neighbors<'a, E>;
+
+    fn neighbors_directed(self, n: usize, d: Direction) -> Self::NeighborsDirected {
+        Neighbors(self.edges_directed(n, d))
+    }
+}
+impl<'a, N, E> IntoEdgeReferences for &'a SlabGraph<N, E> {
+    type EdgeRef = EdgeReference<'a, E>;
+    type EdgeReferences = EdgeReferences<'a, E>;
+
+    fn edge_references(self) -> Self::EdgeReferences {
+        EdgeReferences(self.edges.iter())
+    }
+}
+impl<'a, N, E> IntoEdges for &'a SlabGraph<N, E> {
+    type Edges = Edges<'a, E>;
+
+    fn edges(self, a: usize) -> Self::Edges {
+        self.edges_directed(a, Direction::Outgoing)
+    }
+}
+impl<'a, N, E> IntoEdgesDirected for &'a SlabGraph<N, E> {
+    type EdgesDirected = Edges<'a, E>;
+
+    fn edges_directed(self, a: usize, dir: Direction) -> Self::EdgesDirected {
+        let ids = match self.nodes.get(a) {
+            Some(n) => match dir {
+                Direction::Outgoing => &n.outgoing[..],
+                Direction::Incoming => &n.incoming[..],
+            },
+            None => &[],
+        };
+        Edges {
+            ids: ids.iter(),
+            edges: &self.edges,
+            dir,
+        }
+    }
+}
+impl<'a, N, E> IntoNodeIdentifiers for &'a SlabGraph<N, E> {
+    type NodeIdentifiers = NodeIdentifiers<'a, N>;
+
+    fn node_identifiers(self) -> Self::NodeIdentifiers {
+        NodeIdentifiers(self.nodes.keys())
+    }
+}
+impl<'a, N, E> IntoNodeReferences for &'a SlabGraph<N, E> {
+    type NodeRef = (usize, &'a N);
+    type NodeReferences = NodeReferences<'a, N>;
+
+    fn node_references(self) -> Self::NodeReferences {
+        NodeReferences(self.nodes.iter())
+    }
+}
+
+/// A reference to an edge of a [`SlabGraph`].
+#[derive(Debug)]
+pub struct EdgeReference<'a, E> {
+    id: usize,
+    source: usize,
+    target: usize,
+    weight: &'a E,
+}
+impl<E> Clone for EdgeReference<'_, E> {
+    fn clone(&self) -> Self {
+        *self
+    }
+}
+impl<E> Copy for EdgeReference<'_, E> {}
+impl<E> EdgeRef for EdgeReference<'_, E> {
+    type NodeId = usize;
+    type EdgeId = usize;
+    type Weight = E;
+
+    #[inline]
+    fn source(&self) -> usize {
+        self.source
+    }
+    #[inline]
+    fn target(&self) -> usize {
+        self.target
+    }
+    #[inline]
+    fn weight(&self) -> &E {
+        self.weight
+    }
+    #[inline]
+    fn id(&self) -> usize {
+        self.id
+    }
+}
+
+/// An iterator over the edges of a node in a [`SlabGraph`].
+///
+/// This struct is created by the [`edges`](IntoEdges::edges) and [`edges_directed`](IntoEdgesDirected::edges_directed).
+pub struct Edges<'a, E> {
+    ids: slice::Iter<'a, usize>,
+    edges: &'a SlabMap<Edge<E>>,
+    dir: Direction,
+}
+impl<'a, E> Iterator for Edges<'a, E> {
+    type Item = EdgeReference<'a, E>;
+    #[inline]
+    fn next(&mut self) -> Option<Self::Item> {
+        let id = *self.ids.next()?;
+        let e = &self.edges[id];
+        Some(EdgeReference {
+            id,
+            source: e.source,
+            target: e.target,
+            weight: &e.weight,
+        })
+    }
+    #[inline]
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        self.ids.size_hint()
+    }
+}
+impl<E> FusedIterator for Edges<'_, E> {}
+impl<E> ExactSizeIterator for Edges<'_, E> {}
+
+/// An iterator over the neighbors of a node in a [`SlabGraph`].
+///
+/// This struct is created by the [`neighbors`](IntoNeighbors::neighbors) and [`neighbors_directed`](IntoNeighborsDirected::neighbors_directed).
+pub struct Neighbors<'a, E>(Edges<'a, E>);
+impl<E> Iterator for Neighbors<'_, E> {
+    type Item = usize;
+    #[inline]
+    fn next(&mut self) -> Option<Self::Item> {
+        let e = self.0.next()?;
+        Some(match self.0.dir {
+            Direction::Outgoing => e.target,
+            Direction::Incoming => e.source,
+        })
+    }
+    #[inline]
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        self.0.size_hint()
+    }
+}
+impl<E> FusedIterator for Neighbors<'_, E> {}
+impl<E> ExactSizeIterator for Neighbors<'_, E> {}
+
+/// An iterator over all edges of a [`SlabGraph`].
+///
+/// This struct is created by the [`edge_references`](IntoEdgeReferences::edge_references).
+pub struct EdgeReferences<'a, E>(slab_map::Iter<'a, Edge<E>>);
+impl<'a, E> Iterator for EdgeReferences<'a, E> {
+    type Item = EdgeReference<'a, E>;
+    #[inline]
+    fn next(&mut self) -> Option<Self::Item> {
+        let (id, e) = self.0.next()?;
+        Some(EdgeReference {
+            id,
+            source: e.source,
+            target: e.target,
+            weight: &e.weight,
+        })
+    }
+    #[inline]
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        self.0.size_hint()
+    }
+}
+impl<E> FusedIterator for EdgeReferences<'_, E> {}
+impl<E> ExactSizeIterator for EdgeReferences<'_, E> {}
+
+/// An iterator over the node identifiers of a [`SlabGraph`].
+///
+/// This struct is created by the [`node_identifiers`](IntoNodeIdentifiers::node_identifiers).
+pub struct NodeIdentifiers<'a, N>(slab_map::Keys<'a, Node<N>>);
+impl<N> Iterator for NodeIdentifiers<'_, N> {
+    type Item = usize;
+    #[inline]
+    fn next(&mut self) -> Option<Self::Item> {
+        self.0.next()
+    }
+    #[inline]
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        self.0.size_hint()
+    }
+}
+impl<N> FusedIterator for NodeIdentifiers<'_, N> {}
+impl<N> ExactSizeIterator for NodeIdentifiers<'_, N> {}
+
+/// An iterator over the nodes of a [`SlabGraph`].
+///
+/// This struct is created by the [`node_references`](IntoNodeReferences::node_references).
+pub struct NodeReferences<'a, N>(slab_map::Iter<'a, Node<N>>);
+impl<'a, N> Iterator for NodeReferences<'a, N> {
+    type Item = (usize, &'a N);
+    #[inline]
+    fn next(&mut self) -> Option<Self::Item> {
+        let (key, n) = self.0.next()?;
+        Some((key, &n.weight))
+    }
+    #[inline]
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        self.0.size_hint()
+    }
+}
+impl<N> FusedIterator for NodeReferences<'_, N> {}
+impl<N> ExactSizeIterator for NodeReferences<'_, N> {}
@@ -0,0 +1,114 @@
+use petgraph::{
+    algo::dijkstra,
+    visit::{
+        Bfs, EdgeRef, IntoEdgesDirected, IntoNeighbors, IntoNeighborsDirected, IntoNodeReferences,
+        NodeIndexable,
+    },
+    Direction,
+};
+
+use crate::slab_graph::SlabGraph;
+
+#[test]
+fn add_remove() {
+    let mut g = SlabGraph::new();
+    let a = g.add_node("a");
+    let b = g.add_node("b");
+    let c = g.add_node("c");
+    let ab = g.add_edge(a, b, 1);
+    let bc = g.add_edge(b, c, 2);
+    let ca = g.add_edge(c, a, 3);
+
+    assert_eq!(g.node_count(), 3);
+    assert_eq!(g.edge_count(), 3);
+    assert_eq!(g.edge_endpoints(bc), Some((b, c)));
+    assert_eq!(g.find_edge(c, a), Some(ca));
+
+    assert_eq!(g.remove_node(b), Some("b"));
+    assert_eq!(g.node_count(), 2);
+    assert_eq!(g.edge_count(), 1);
+    assert!(!g.contains_edge(ab));
+    assert!(!g.contains_edge(bc));
+    assert_eq!(g.edge_weight(ca), Some(&3));
+    assert_eq!(g[a], "a");
+    assert_eq!(g[c], "c");
+}
+
+#[test]
+fn keys_are_stable() {
+    let mut g = SlabGraph::<_, ()>::new();
+    let nodes: Vec<_> = (0..10).map(|i| g.add_node(i)).collect();
+    for &n in nodes.iter().step_by(2) {
+        g.remove_node(n);
+    }
+    for &n in nodes.iter().skip(1).step_by(2) {
+        assert_eq!(g[n], n);
+    }
+    let mut a: Vec<_> = g.node_references().map(|(k, v)| (k, *v)).collect();
+    a.sort();
+    let e: Vec<_> = nodes.iter().skip(1).step_by(2).map(|&n| (n, n)).collect();
+    assert_eq!(a, e);
+}
+
+#[test]
+fn neighbors() {
+    let mut g = SlabGraph::new();
+    let a = g.add_node(());
+    let b = g.add_node(());
+    let c = g.add_node(());
+    g.add_edge(a, b, ());
+    g.add_edge(a, c, ());
+    g.add_edge(b, c, ());
+
+    let mut n: Vec<_> = g.neighbors(a).collect();
+    n.sort();
+    assert_eq!(n, vec![b, c]);
+
+    let mut n: Vec<_> = g
+        .edges_directed(c, Direction::Incoming)
+        .map(|e| (e.source(), e.target()))
+        .collect();
+    n.sort();
+    assert_eq!(n, vec![(a, c), (b, c)]);
+
+    let mut n: Vec<_> = g.neighbors_directed(c, Direction::Incoming).collect();
+    n.sort();
+    assert_eq!(n, vec![a, b]);
+}
+
+#[test]
+fn bfs() {
+    let mut g = SlabGraph::new();
+    let a = g.add_node(());
+    let b = g.add_node(());
+    let c = g.add_node(());
+    let d = g.add_node(());
+    g.add_edge(a, b, ());
+    g.add_edge(b, c, ());
+    g.add_edge(c, d, ());
+    g.remove_node(c);
+
+    let mut bfs = Bfs::new(&g, a);
+    let mut visited = Vec::new();
+    while let Some(n) = bfs.next(&g) {
+        visited.push(n);
+    }
+    assert_eq!(visited, vec![a, b]);
+    assert!(g.node_bound() > d);
+}
+
+#[test]
+fn shortest_path() {
+    let mut g = SlabGraph::new();
+    let a = g.add_node(());
+    let b = g.add_node(());
+    let c = g.add_node(());
+    let d = g.add_node(());
+    g.add_edge(a, b, 1);
+    g.add_edge(b, d, 1);
+    g.add_edge(a, c, 5);
+    g.add_edge(c, d, 1);
+
+    let costs = dijkstra(&g, a, Some(d), |e| *e.weight());
+    assert_eq!(costs[&d], 2);
+}
@@ -0,0 +1,268 @@
+//! A priority queue whose elements are addressed by [`SlabMap`] keys.
+
+use alloc::vec::Vec;
+use core::{fmt::Debug, iter::FusedIterator};
+
+use derive_ex::derive_ex;
+
+use crate::{slab_map, SlabMap};
+
+#[cfg(test)]
+mod tests;
+
+#[derive_ex(Clone(bound(T, P)))]
+struct Node<T, P> {
+    value: T,
+    priority: P,
+    pos: usize,
+}
+
+/// A min-heap whose elements are stored in a [`SlabMap`] and can be accessed, reprioritized and removed by key.
+///
+/// # Examples
+/// ```
+/// use slabmap::slab_heap::SlabHeap;
+///
+/// let mut s = SlabHeap::new();
+/// let a = s.insert(30, "a");
+/// let b = s.insert(10, "b");
+/// let c = s.insert(20, "c");
+///
+/// s.decrease_key(a, 5);
+/// s.remove(c);
+///
+/// assert_eq!(s.pop(), Some((a, 5, "a")));
+/// assert_eq!(s.pop(), Some((b, 10, "b")));
+/// assert_eq!(s.pop(), None);
+/// ```
+#[derive_ex(Clone(bound(T, P)), Default(bound()))]
+pub struct SlabHeap<T, P> {
+    map: SlabMap<Node<T, P>>,
+    heap: Vec<usize>,
+}
+
+impl<T, P: Ord> SlabHeap<T, P> {
+    /// Constructs a new, empty `SlabHeap<T, P>`.
+    #[inline]
+    pub const fn new() -> Self {
+        Self {
+            map: SlabMap::new(),
+            heap: Vec::new(),
+        }
+    }
+
+    /// Returns the number of elements in the SlabHeap.
+    #[inline]
+    pub fn len(&self) -> usize {
+        self.heap.len()
+    }
+
+    /// Returns true if the SlabHeap contains no elements.
+    #[inline]
+    pub fn is_empty(&self) -> bool {
+        self.heap.is_empty()
+    }
+
+    /// Returns a reference to the value corresponding to the key.
+    #[inline]
+    pub fn get(&self, key: usize) -> Option<&T> {
+        Some(&self.map.get(key)?.value)
+    }
+
+    /// Returns a mutable reference to the value corresponding to the key.
+    #[inline]
+    pub fn get_mut(&mut self, key: usize) -> Option<&mut T> {
+        Some(&mut self.map.get_mut(key)?.value)
+    }
+
+    /// Returns the priority of the value corresponding to the key.
+    #[inline]
+    pub fn priority(&self, key: usize) -> Option<&P> {
+        Some(&self.map.get(key)?.priority)
+    }
+
+    /// Returns true if the SlabHeap contains a value for the specified key.
+    #[inline]
+    pub fn contains_key(&self, key: usize) -> bool {
+        self.map.contains_key(key)
+    }
+
+    /// Inserts a value with the priority into the SlabHeap.
+    ///
+    /// Returns the key associated with the value.
+    pub fn insert(&mut self, priority: P, value: T) -> usize {
+        let pos = self.heap.len();
+        let key = self.map.insert(Node {
+            value,
+            priority,
+            pos,
+        });
+        self.heap.push(key);
+        self.sift_up(pos);
+        key
+    }
+
+    /// Returns the entry with the lowest priority.
+    ///
+    /// If several entries have the lowest priority, which one is returned is unspecified.
+    pub fn peek(&self) -> Option<(usize, &P, &T)> {
+        let key = *self.heap.first()?;
+        let node = &self.map[key];
+        Some((key, &node.priority, &node.value))
+    }
+
+    /// Removes the entry with the lowest priority and returns it.
+    pub fn pop(&mut self) -> Option<(usize, P, T)> {
+        let key = *self.heap.first()?;
+        let (priority, value) = self.remove(key)?;
+        Some((key, priority, value))
+    }
+
+    /// Removes a key from the SlabHeap, returning the priority and the value at the key if the key was previously in the SlabHeap.
+    pub fn remove(&mut self, key: usize) -> Option<(P, T)> {
+        let node = self.map.remove(key)?;
+        let last = self.heap.pop().unwrap();
+        if node.pos < self.heap.len() {
+            self.heap[node.pos] = last;
+            self.map[last].pos = node.pos;
+            self.sift(node.pos);
+        }
+        Some((node.priority, node.value))
+    }
+
+    /// Sets the priority of the value corresponding to the key, returning the old priority.
+    ///
+    /// Returns `None` if the key does not exist.
+    pub fn set_priority(&mut self, key: usize, priority: P) -> Option<P> {
+        let node = self.map.get_mut(key)?;
+        let old = core::mem::replace(&mut node.priority, priority);
+        let pos = node.pos;
+        self.sift(pos);
+        Some(old)
+    }
+
+    /// Lowers the priority of the value corresponding to the key.
+    ///
+    /// Returns true if the priority was lowered.
+    /// If the key does not exist or `priority` is not lower than the current priority, this method does nothing.
+    pub fn decrease_key(&mut self, key: usize, priority: P) -> bool {
+        let Some(node) = self.map.get_mut(key) else {
+            return false;
+        };
+        if priority >= node.priority {
+            return false;
+        }
+        node.priority = priority;
+        let pos = node.pos;
+        self.sift_up(pos);
+        true
+    }
+
+    /// Clears the SlabHeap, removing all values.
+    pub fn clear(&mut self) {
+        self.map.clear();
+        self.heap.clear();
+    }
+
+    /// Gets an iterator over the entries of the SlabHeap, sorted by key.
+    #[inline]
+    pub fn iter(&self) -> Iter<'_, T, P> {
+        Iter(self.map.iter())
+    }
+
+    fn less(&self, a: usize, b: usize) -> bool {
+        self.map[self.heap[a]].priority < self.map[self.heap[b]].priority
+    }
+    fn swap(&mut self, a: usize, b: usize) {
+        self.heap.swap(a, b);
+        self.map[self.heap[a]].pos = a;
+        self.map[self.heap[b]].pos = b;
+    }
+    fn sift(&mut self, pos: usize) {
+        if pos > 0 && self.less(pos, (pos - 1) / 2) {
+            self.sift_up(pos);
+        } else {
+            self.sift_down(pos);
+        }
+    }
+    fn sift_up(&mut self, mut pos: usize) {
+        while pos > 0 {
+            let parent = (pos - 1) / 2;
+            if !self.less(pos, parent) {
+                break;
+            }
+            self.swap(pos, parent);
+            pos = parent;
+        }
+    }
+    fn sift_down(&mut self, mut pos: usize) {
+        loop {
+            let mut min = pos;
+            for child in [pos * 2 + 1, pos * 2 + 2] {
+                if child < self.heap.len() && self.less(child, min) {
+                    min = child;
+                }
+            }
+            if min == pos {
+                break;
+            }
+            self.swap(pos, min);
+            pos = min;
+        }
+    }
+}
+impl<T: Debug, P: Debug> Debug for SlabHeap<T, P> {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        f.debug_map()
+            .entries(
+                self.map
+                    .iter()
+                    .map(|(key, node)| (key, (&node.priority, &node.value))),
+            )
+            .finish()
+    }
+}
+
+impl<T, P: Ord> core::ops::Index<usize> for SlabHeap<T, P> {
+    type Output = T;
+
+    #[inline]
+    fn index(&self, index: usize) -> &Self::Output {
+        self.get(index).expect("out of index.")
+    }
+}
+impl<T, P: Ord> core::ops::IndexMut<usize> for SlabHeap<T, P> {
+    #[inline]
+    fn index_mut(&mut self, index: usize) -> &mut Self::Output {
+        self.get_mut(index).expect("out of index.")
+    }
+}
+
+impl<'a, T, P: Ord> IntoIterator for &'a SlabHeap<T, P> {
+    type Item = (usize, &'a P, &'a T);
+    type IntoIter = Iter<'a, T, P>;
+
+    #[inline]
+    fn into_iter(self) -> Self::IntoIter {
+        self.iter()
+    }
+}
+
+/// An iterator over the entries of a [`SlabHeap`].
+///
+/// This struct is created by the [`iter`](SlabHeap::iter).
+pub struct Iter<'a, T, P>(slab_map::Iter<'a, Node<T, P>>);
+impl<'a, T, P> Iterator for Iter<'a, T, P> {
+    type Item = (usize, &'a P, &'a T);
+    #[inline]
+    fn next(&mut self) -> Option<Self::Item> {
+        let (key, node) = self.0.next()?;
+        Some((key, &node.priority, &node.value))
+    }
+    #[inline]
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        self.0.size_hint()
+    }
+}
+impl<T, P> FusedIterator for Iter<'_, T, P> {}
+impl<T, P> ExactSizeIterator for Iter<'_, T, P> {}
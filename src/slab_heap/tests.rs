@@ -0,0 +1,65 @@
+use std::collections::BTreeMap;
+
+use rand::{rngs::StdRng, Rng, SeedableRng};
+
+use crate::slab_heap::SlabHeap;
+
+#[test]
+fn random() {
+    let mut rng = StdRng::seed_from_u64(0);
+    let mut s = SlabHeap::new();
+    let mut e = BTreeMap::new();
+    for i in 0..5000 {
+        match rng.gen_range(0..10) {
+            0..=3 => {
+                let priority = rng.gen_range(0..100);
+                let key = s.insert(priority, i);
+                e.insert(key, (priority, i));
+            }
+            4..=5 => {
+                let a = s.pop();
+                let min = e.values().map(|&(p, _)| p).min();
+                assert_eq!(a.as_ref().map(|&(_, p, _)| p), min);
+                if let Some((key, p, v)) = a {
+                    assert_eq!(e.remove(&key), Some((p, v)));
+                }
+            }
+            6..=7 => {
+                if let Some(&key) = e.keys().nth(rng.gen_range(0..e.len().max(1))) {
+                    assert_eq!(s.remove(key), e.remove(&key));
+                }
+            }
+            8 => {
+                if let Some(&key) = e.keys().nth(rng.gen_range(0..e.len().max(1))) {
+                    let priority = rng.gen_range(0..100);
+                    let old = e.get_mut(&key).unwrap();
+                    assert_eq!(s.set_priority(key, priority), Some(old.0));
+                    old.0 = priority;
+                }
+            }
+            _ => {
+                if let Some(&key) = e.keys().nth(rng.gen_range(0..e.len().max(1))) {
+                    let priority = rng.gen_range(0..100);
+                    let old = e.get_mut(&key).unwrap();
+                    assert_eq!(s.decrease_key(key, priority), priority < old.0);
+                    old.0 = old.0.min(priority);
+                }
+            }
+        }
+        assert_eq!(s.len(), e.len());
+    }
+    let a: Vec<_> = s.iter().map(|(k, &p, &v)| (k, (p, v))).collect();
+    let e: Vec<_> = e.into_iter().collect();
+    assert_eq!(a, e);
+}
+
+#[test]
+fn peek() {
+    let mut s = SlabHeap::new();
+    assert_eq!(s.peek(), None);
+    let a = s.insert(2, "a");
+    assert_eq!(s.peek(), Some((a, &2, &"a")));
+    let b = s.insert(1, "b");
+    assert_eq!(s.peek(), Some((b, &1, &"b")));
+    assert_eq!(s.priority(a), Some(&2));
+}
@@ -0,0 +1,205 @@
+//! Keys of [`SlabMap`](crate::SlabMap).
+
+use core::{cmp::Ordering, fmt, num::NonZeroUsize};
+
+#[cfg(test)]
+mod tests;
+
+/// A type that can be used as a key of [`SlabMap`](crate::SlabMap).
+///
+/// A key is converted to and from the index of the entry that holds the value.
+/// Using a distinct key type for each SlabMap prevents a key of one SlabMap from being used with another.
+///
+/// # Examples
+/// ```
+/// use slabmap::{SlabKey, SlabMap};
+///
+/// #[derive(Clone, Copy, Debug, PartialEq, Eq)]
+/// struct ConnId(usize);
+///
+/// impl SlabKey for ConnId {
+///     fn from_index(index: usize) -> Self {
+///         ConnId(index)
+///     }
+///     fn into_index(self) -> usize {
+///         self.0
+///     }
+/// }
+///
+/// let mut s = SlabMap::<&str, ConnId>::with_key();
+/// let id = s.insert("conn");
+/// assert_eq!(id, ConnId(0));
+/// assert_eq!(s[id], "conn");
+/// ```
+pub trait SlabKey: Copy {
+    /// Constructs a key from the index of an entry.
+    fn from_index(index: usize) -> Self;
+
+    /// Returns the index of the entry that the key refers to.
+    fn into_index(self) -> usize;
+}
+
+impl SlabKey for usize {
+    #[inline]
+    fn from_index(index: usize) -> Self {
+        index
+    }
+    #[inline]
+    fn into_index(self) -> usize {
+        self
+    }
+}
+
+/// A key that cannot be `usize::MAX`, so that `Option<NonMaxKey>` is the same size as `NonMaxKey`.
+///
+/// The index is stored inverted in a [`NonZeroUsize`]. Since a SlabMap never has an entry at `usize::MAX`, every key of a SlabMap can be represented.
+///
+/// # Examples
+/// ```
+/// use slabmap::{slab_key::NonMaxKey, SlabMap};
+///
+/// assert_eq!(size_of::<Option<NonMaxKey>>(), size_of::<usize>());
+///
+/// let mut s = SlabMap::<_, NonMaxKey>::with_key();
+/// let key = s.insert("a");
+/// assert_eq!(key.get(), 0);
+/// assert_eq!(s[key], "a");
+/// ```
+#[derive(Clone, Copy, PartialEq, Eq, Hash)]
+pub struct NonMaxKey(NonZeroUsize);
+
+impl NonMaxKey {
+    /// Constructs a key from an index, or returns `None` if the index is `usize::MAX`.
+    #[inline]
+    pub const fn new(index: usize) -> Option<Self> {
+        match NonZeroUsize::new(!index) {
+            Some(value) => Some(Self(value)),
+            None => None,
+        }
+    }
+
+    /// Returns the index of the key.
+    #[inline]
+    pub const fn get(self) -> usize {
+        !self.0.get()
+    }
+}
+impl SlabKey for NonMaxKey {
+    #[inline]
+    fn from_index(index: usize) -> Self {
+        Self::new(index).expect("index is usize::MAX.")
+    }
+    #[inline]
+    fn into_index(self) -> usize {
+        self.get()
+    }
+}
+impl PartialOrd for NonMaxKey {
+    #[inline]
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+impl Ord for NonMaxKey {
+    #[inline]
+    fn cmp(&self, other: &Self) -> Ordering {
+        self.get().cmp(&other.get())
+    }
+}
+impl fmt::Debug for NonMaxKey {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_tuple("NonMaxKey").field(&self.get()).finish()
+    }
+}
+#[cfg(feature = "serde")]
+impl serde::Serialize for NonMaxKey {
+    fn serialize<S: serde::Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        self.get().serialize(serializer)
+    }
+}
+#[cfg(feature = "serde")]
+impl<'de> serde::Deserialize<'de> for NonMaxKey {
+    fn deserialize<D: serde::Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        let index = usize::deserialize(deserializer)?;
+        Self::new(index).ok_or_else(|| serde::de::Error::custom("index is usize::MAX"))
+    }
+}
+
+/// Declares new key types that implement [`SlabKey`].
+///
+/// Each key type is a newtype of `usize` and implements `Clone`, `Copy`, `Debug`, `PartialEq`, `Eq`, `PartialOrd`, `Ord` and `Hash`.
+/// With the `serde` feature, it also implements `Serialize` and `Deserialize` as a `usize`.
+///
+/// # Examples
+/// ```
+/// use slabmap::{new_key_type, SlabMap};
+///
+/// new_key_type! {
+///     /// The key of a connection.
+///     pub struct ConnId;
+///     struct TimerId;
+/// }
+///
+/// let mut conns = SlabMap::<&str, ConnId>::with_key();
+/// let mut timers = SlabMap::<u64, TimerId>::with_key();
+/// let conn = conns.insert("conn");
+/// let timer = timers.insert(100);
+///
+/// assert_eq!(conns[conn], "conn");
+/// assert_eq!(timers[timer], 100);
+/// assert_eq!(format!("{conn:?}"), "ConnId(0)");
+/// ```
+#[macro_export]
+macro_rules! new_key_type {
+    ($($(#[$attr:meta])* $vis:vis struct $name:ident;)*) => {
+        $(
+            $(#[$attr])*
+            #[derive(Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord, Hash)]
+            $vis struct $name(usize);
+
+            impl $crate::SlabKey for $name {
+                #[inline]
+                fn from_index(index: usize) -> Self {
+                    Self(index)
+                }
+                #[inline]
+                fn into_index(self) -> usize {
+                    self.0
+                }
+            }
+
+            $crate::__impl_key_serde!($name);
+        )*
+    };
+}
+
+#[cfg(feature = "serde")]
+#[doc(hidden)]
+#[macro_export]
+macro_rules! __impl_key_serde {
+    ($name:ident) => {
+        impl $crate::__private::serde::Serialize for $name {
+            fn serialize<S: $crate::__private::serde::Serializer>(
+                &self,
+                serializer: S,
+            ) -> ::core::result::Result<S::Ok, S::Error> {
+                $crate::__private::serde::Serialize::serialize(&self.0, serializer)
+            }
+        }
+        impl<'de> $crate::__private::serde::Deserialize<'de> for $name {
+            fn deserialize<D: $crate::__private::serde::Deserializer<'de>>(
+                deserializer: D,
+            ) -> ::core::result::Result<Self, D::Error> {
+                <usize as $crate::__private::serde::Deserialize>::deserialize(deserializer)
+                    .map(Self)
+            }
+        }
+    };
+}
+
+#[cfg(not(feature = "serde"))]
+#[doc(hidden)]
+#[macro_export]
+macro_rules! __impl_key_serde {
+    ($name:ident) => {};
+}
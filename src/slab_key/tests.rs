@@ -0,0 +1,61 @@
+use std::collections::BTreeSet;
+
+use crate::{slab_key::NonMaxKey, SlabKey, SlabMap};
+
+crate::new_key_type! {
+    struct A;
+    pub(crate) struct B;
+}
+
+#[test]
+fn new_key_type() {
+    let mut a = SlabMap::<_, A>::with_key();
+    let mut b = SlabMap::<_, B>::with_key();
+    let ka = a.insert(1);
+    let kb = b.insert(2);
+    assert_eq!(ka.into_index(), 0);
+    assert_eq!(kb, B::from_index(0));
+    assert_eq!(a[ka] + b[kb], 3);
+
+    let keys: BTreeSet<_> = (0..3).map(|_| a.insert(0)).collect();
+    assert_eq!(keys.first(), Some(&A(1)));
+}
+
+#[cfg(feature = "serde")]
+#[test]
+fn new_key_type_serde() {
+    let key = A(5);
+    let json = serde_json::to_string(&key).unwrap();
+    assert_eq!(json, "5");
+    assert_eq!(serde_json::from_str::<A>(&json).unwrap(), key);
+}
+
+#[test]
+fn non_max_key() {
+    assert_eq!(size_of::<Option<NonMaxKey>>(), size_of::<usize>());
+    assert_eq!(NonMaxKey::new(usize::MAX), None);
+    assert_eq!(
+        NonMaxKey::new(usize::MAX - 1).unwrap().get(),
+        usize::MAX - 1
+    );
+    assert!(NonMaxKey::from_index(1) < NonMaxKey::from_index(2));
+    assert_eq!(format!("{:?}", NonMaxKey::from_index(3)), "NonMaxKey(3)");
+
+    let mut s = SlabMap::<_, NonMaxKey>::with_key();
+    let keys: Vec<_> = (0..5).map(|i| s.insert(i)).collect();
+    s.remove(keys[2]);
+    assert_eq!(s.insert(10), keys[2]);
+    assert_eq!(
+        s.keys().map(NonMaxKey::get).collect::<Vec<_>>(),
+        [0, 1, 2, 3, 4]
+    );
+}
+
+#[cfg(feature = "serde")]
+#[test]
+fn non_max_key_serde() {
+    let key = NonMaxKey::from_index(5);
+    let json = serde_json::to_string(&key).unwrap();
+    assert_eq!(json, "5");
+    assert_eq!(serde_json::from_str::<NonMaxKey>(&json).unwrap(), key);
+}
@@ -1,32 +1,216 @@
 //! A fast HashMap-like collection that automatically determines the key.
 
-use std::{
-    collections::TryReserveError,
+use alloc::{
+    boxed::Box,
+    collections::{TryReserveError, VecDeque},
+    vec::{self, Vec},
+};
+use core::{
+    cmp::Ordering,
     fmt::Debug,
-    iter::{Enumerate, FusedIterator},
+    iter::FusedIterator,
+    marker::PhantomData,
     mem::replace,
+    ops::{self, Bound, RangeBounds},
 };
 
 use derive_ex::derive_ex;
 
+use crate::{secondary_map::Remap, SlabKey};
+
+mod deferred_drop;
+mod incremental;
+mod integrity;
+mod layout;
+#[cfg(feature = "rayon")]
+mod rayon;
+#[cfg(feature = "rkyv")]
+mod rkyv;
+#[cfg(feature = "serde")]
+mod serde;
+#[cfg(feature = "serde")]
+pub mod serde_repr;
+mod span;
+mod storage;
 #[cfg(test)]
 mod tests;
+#[cfg(feature = "zeroize")]
+mod zeroize;
+
+#[cfg(feature = "rkyv")]
+pub use self::rkyv::{ArchivedIter, ArchivedSlabMap, SlabMapResolver};
+#[cfg(feature = "serde")]
+pub use self::serde::ReloadSeed;
+#[cfg(feature = "zeroize")]
+pub use self::zeroize::ZeroizingSlabMap;
+pub use deferred_drop::DeferredDrop;
+pub use integrity::IntegrityError;
+pub use layout::{Layout, Run, RunKind};
+pub use span::{Span, SpanIter, SpanIterMut, SpanMut};
+
+use incremental::OptimizeProgress;
+use storage::{Entries, MAX_RUN_LEN};
 
 /// A fast HashMap-like collection that automatically determines the key.
+///
+/// The type of the key can be changed with `K`, see [`SlabKey`].
+///
+/// Adjacent free spaces are merged into runs, so iterating over a SlabMap takes time proportional to the number of elements and free space runs, not to the capacity.
 #[derive_ex(Clone(bound(T)), Default(bound()))]
-pub struct SlabMap<T> {
-    entries: Vec<Entry<T>>,
-    next_vacant_idx: usize,
+pub struct SlabMap<T, K = usize> {
+    entries: Entries<T>,
     len: usize,
-    non_optimized_count: usize,
+    extras: Option<Box<Extras>>,
+    _key: PhantomData<fn(K) -> K>,
+}
+
+/// A policy for automatically shrinking the capacity of a [`SlabMap`].
+///
+/// The SlabMap is considered underused while its capacity exceeds `len * max_capacity_ratio`.
+/// If it stays underused for `patience` consecutive insertions and removals, free space at the end is released
+/// and the capacity is shrunk to `len * 2` (but not below `min_capacity`).
+/// Only free space after the last element can be released, because keys are never moved.
+/// If a large key keeps the capacity from being reduced enough, the SlabMap is not shrunk,
+/// so a SlabMap that cannot shrink does not rebuild its free space over and over.
+///
+/// Since the capacity after shrinking is well below the threshold, a SlabMap that hovers around the threshold does not shrink repeatedly.
+/// For this reason, `max_capacity_ratio` must be greater than 2 and `patience` must not be 0.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub struct ShrinkPolicy {
+    /// The ratio of capacity to length above which the SlabMap is considered underused. Must be greater than 2.
+    pub max_capacity_ratio: usize,
+    /// The number of consecutive insertions and removals in the underused state required to shrink. Must not be 0.
+    pub patience: usize,
+    /// The capacity is never shrunk below this value.
+    pub min_capacity: usize,
+}
+impl Default for ShrinkPolicy {
+    fn default() -> Self {
+        Self {
+            max_capacity_ratio: 4,
+            patience: 1024,
+            min_capacity: 16,
+        }
+    }
+}
+
+/// The decision for each element returned by the closure of [`SlabMap::visit_mut`].
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum Visit<T> {
+    /// Keeps the element.
+    Keep,
+    /// Removes the element.
+    Remove,
+    /// Replaces the element with the value.
+    Replace(T),
 }
-const INVALID_INDEX: usize = usize::MAX;
 
+/// The state of a [`SlabMap`] that is needed only after free space is made or optional features are enabled.
+///
+/// It is boxed and allocated on first use, so that it does not increase the size of every SlabMap.
 #[derive(Clone, Debug)]
+struct Extras {
+    vacant_heads: Vec<usize>,
+    non_optimized_count: usize,
+    auto_shrink: Option<AutoShrink>,
+    quarantine: Quarantine,
+    claimed: usize,
+    optimize_progress: Option<OptimizeProgress>,
+    /// The smallest occupied index, or `usize::MAX` if there is none.
+    min: usize,
+    /// The largest occupied index, or `0` if there is none.
+    max: usize,
+}
+
+impl Extras {
+    /// Creates the state for `entries`, which have had no free space until now.
+    ///
+    /// Since the entries were all occupied before the current operation, the bounds are found after a few steps.
+    fn for_entries<T>(entries: &Entries<T>) -> Self {
+        let is_occupied = |idx: &usize| matches!(entries.entry(*idx), Entry::Occupied(_));
+        Self {
+            min: (0..entries.len()).find(is_occupied).unwrap_or(usize::MAX),
+            max: (0..entries.len()).rev().find(is_occupied).unwrap_or(0),
+            ..Self::new()
+        }
+    }
+
+    const fn new() -> Self {
+        Self {
+            vacant_heads: Vec::new(),
+            non_optimized_count: 0,
+            auto_shrink: None,
+            quarantine: Quarantine {
+                window: 0,
+                inserts: 0,
+                keys: VecDeque::new(),
+            },
+            claimed: 0,
+            optimize_progress: None,
+            min: usize::MAX,
+            max: 0,
+        }
+    }
+
+    /// Forgets all free space, reserved keys and elements, keeping the settings.
+    fn clear(&mut self) {
+        self.vacant_heads.clear();
+        self.non_optimized_count = 0;
+        self.quarantine.keys.clear();
+        self.claimed = 0;
+        self.optimize_progress = None;
+        self.min = usize::MAX;
+        self.max = 0;
+    }
+}
+
+static EMPTY_EXTRAS: Extras = Extras::new();
+
+#[derive(Clone, Copy, Debug)]
+struct AutoShrink {
+    policy: ShrinkPolicy,
+    underused_count: usize,
+}
+
+#[derive(Clone, Debug, Default)]
+struct Quarantine {
+    window: usize,
+    inserts: usize,
+    keys: VecDeque<(usize, usize)>,
+}
+
+/// The type of the length of a run of vacant entries.
+///
+/// With the `u32-index` feature, this is `u32`, which halves the size of the state of each entry on 64-bit targets.
+#[cfg(not(feature = "u32-index"))]
+type RunLen = usize;
+#[cfg(feature = "u32-index")]
+type RunLen = u32;
+
+#[inline]
+#[allow(clippy::unnecessary_cast)]
+fn run_len(len: RunLen) -> usize {
+    len as usize
+}
+
+/// Adjacent vacant entries are always merged into a single run.
+///
+/// The first entry of a run is `VacantHead` and the last entry of a run longer than 1 is `VacantTail`.
+/// Both hold the length of the run. The value of the other entries in the run is unspecified.
+///
+/// `Quarantined` entries are free but not part of any run until they are released.
+/// `Claimed` entries are reserved by [`SlabMap::claim_key`] and not part of any run until they are abandoned.
+///
+/// Entries are not stored as `Entry<T>`. The values and the states are stored in separate arrays by [`Entries`],
+/// which returns `Entry<&T>` and `Entry<&mut T>` as views of an entry.
+#[derive(Clone, Copy, Debug)]
 enum Entry<T> {
     Occupied(T),
-    VacantHead { vacant_body_len: usize },
-    VacantTail { next_vacant_idx: usize },
+    VacantHead { vacant_len: RunLen },
+    VacantTail { vacant_len: RunLen },
+    VacantBody,
+    Quarantined,
+    Claimed,
 }
 
 impl<T> SlabMap<T> {
@@ -34,44 +218,211 @@ impl<T> SlabMap<T> {
     /// The SlabMap will not allocate until elements are pushed onto it.
     #[inline]
     pub const fn new() -> Self {
+        Self::with_key()
+    }
+
+    /// Constructs a new, empty `SlabMap<T>` with the specified capacity.
+    #[inline]
+    pub fn with_capacity(capacity: usize) -> Self {
+        Self::with_capacity_and_key(capacity)
+    }
+}
+impl<T, K> SlabMap<T, K> {
+    #[inline]
+    fn extras(&self) -> &Extras {
+        self.extras.as_deref().unwrap_or(&EMPTY_EXTRAS)
+    }
+
+    #[inline]
+    fn extras_mut(&mut self) -> &mut Extras {
+        let entries = &self.entries;
+        self.extras
+            .get_or_insert_with(|| Box::new(Extras::for_entries(entries)))
+    }
+
+    /// Returns the smallest and the largest occupied indexes.
+    ///
+    /// While there is no extra state, all entries are occupied.
+    #[inline]
+    fn index_bounds(&self) -> Option<(usize, usize)> {
+        if self.len == 0 {
+            return None;
+        }
+        Some(match &self.extras {
+            Some(x) => (x.min, x.max),
+            None => (0, self.entries.len() - 1),
+        })
+    }
+
+    /// Updates the bounds of occupied indexes after the entry at `idx` became occupied.
+    #[inline]
+    fn on_occupied(&mut self, idx: usize) {
+        if let Some(x) = &mut self.extras {
+            x.min = x.min.min(idx);
+            x.max = x.max.max(idx);
+        }
+    }
+
+    /// Updates the bounds of occupied indexes after the entries at `start..end` became unoccupied.
+    ///
+    /// The free space must have been merged, so that the new bounds are found by skipping runs.
+    fn on_vacated(&mut self, start: usize, end: usize) {
+        let Some(x) = &self.extras else {
+            return;
+        };
+        let (min, max) = (x.min, x.max);
+        let min = if (start..end).contains(&min) {
+            self.next_occupied_idx(end).unwrap_or(usize::MAX)
+        } else {
+            min
+        };
+        let max = if (start..end).contains(&max) {
+            self.prev_occupied_idx(start).unwrap_or(0)
+        } else {
+            max
+        };
+        if let Some(x) = &mut self.extras {
+            x.min = min;
+            x.max = max;
+        }
+    }
+
+    /// Returns the smallest occupied index at or after `idx`.
+    fn next_occupied_idx(&self, mut idx: usize) -> Option<usize> {
+        while let Some(e) = self.entries.get(idx) {
+            match e {
+                Entry::Occupied(_) => return Some(idx),
+                Entry::VacantHead { vacant_len } => idx += run_len(vacant_len),
+                Entry::VacantTail { .. }
+                | Entry::VacantBody
+                | Entry::Quarantined
+                | Entry::Claimed => idx = self.entries.skip_passive(idx + 1),
+            }
+        }
+        None
+    }
+
+    /// Returns the largest occupied index before `end`.
+    fn prev_occupied_idx(&self, end: usize) -> Option<usize> {
+        let mut idx = end.min(self.entries.len()).checked_sub(1)?;
+        loop {
+            match self.entries.entry(idx) {
+                Entry::Occupied(_) => return Some(idx),
+                Entry::VacantTail { vacant_len } => idx = idx.checked_sub(run_len(vacant_len))?,
+                Entry::VacantHead { .. }
+                | Entry::VacantBody
+                | Entry::Quarantined
+                | Entry::Claimed => idx = idx.checked_sub(1)?,
+            }
+        }
+    }
+}
+impl<T, K: SlabKey> SlabMap<T, K> {
+    /// Constructs a new, empty `SlabMap<T, K>` with a custom key type.
+    /// The SlabMap will not allocate until elements are pushed onto it.
+    #[inline]
+    pub const fn with_key() -> Self {
         Self {
-            entries: Vec::new(),
-            next_vacant_idx: INVALID_INDEX,
+            entries: Entries::new(),
             len: 0,
-            non_optimized_count: 0,
+            extras: None,
+            _key: PhantomData,
         }
     }
 
-    /// Constructs a new, empty `SlabMap<T>` with the specified capacity.
+    /// Constructs a new, empty `SlabMap<T, K>` with a custom key type and the specified capacity.
     #[inline]
-    pub fn with_capacity(capacity: usize) -> Self {
+    pub fn with_capacity_and_key(capacity: usize) -> Self {
         Self {
-            entries: Vec::with_capacity(capacity),
-            next_vacant_idx: INVALID_INDEX,
+            entries: Entries::with_capacity(capacity),
             len: 0,
-            non_optimized_count: 0,
+            extras: None,
+            _key: PhantomData,
+        }
+    }
+
+    /// Constructs a new `SlabMap<T, K>` from values, assigning keys `0..values.len()` in order.
+    ///
+    /// Since there is no free space, the values are stored without any bookkeeping of free space.
+    ///
+    /// # Examples
+    /// ```
+    /// use slabmap::SlabMap;
+    ///
+    /// let s = SlabMap::<_>::from_vec(vec!["a", "b"]);
+    /// assert_eq!(s.iter().collect::<Vec<_>>(), [(0, &"a"), (1, &"b")]);
+    /// ```
+    pub fn from_vec(values: Vec<T>) -> Self {
+        Self {
+            len: values.len(),
+            entries: values.into_iter().map(Entry::Occupied).collect(),
+            ..Self::with_key()
         }
     }
 
-    /// Constructs as new `SlabMap<T>` from keys and values with at least the specified capacity.
+    /// Constructs a new `SlabMap<T, K>` from slots, where the index of each slot is the key and `None` is free space.
+    ///
+    /// # Examples
+    /// ```
+    /// use slabmap::SlabMap;
+    ///
+    /// let mut s = SlabMap::<_>::from_slots(vec![Some("a"), None, Some("c"), None]);
+    /// assert_eq!(s.iter().collect::<Vec<_>>(), [(0, &"a"), (2, &"c")]);
+    /// assert_eq!(s.insert("b"), 1);
+    /// ```
+    pub fn from_slots(slots: Vec<Option<T>>) -> Self {
+        let mut this = Self {
+            entries: slots
+                .into_iter()
+                .map(|slot| slot.map_or(Entry::VacantBody, Entry::Occupied))
+                .collect(),
+            ..Self::with_key()
+        };
+        this.rebuild_vacants();
+        this
+    }
+
+    /// Constructs as new `SlabMap<T, K>` from keys and values with at least the specified capacity.
     pub fn from_iter_with_capacity(
-        iter: impl IntoIterator<Item = (usize, T)>,
+        iter: impl IntoIterator<Item = (K, T)>,
         capacity: usize,
     ) -> Self {
-        let mut this = Self::with_capacity(capacity);
+        let mut this = Self::with_capacity_and_key(capacity);
         for (key, value) in iter {
-            this.set(key, value);
+            this.set(key.into_index(), value);
         }
         this.rebuild_vacants();
         this
     }
+
+    /// Replaces the contents with keys and values from `iter`, reusing the allocation.
+    ///
+    /// Free space is rebuilt once at the end. If a key appears more than once, the last value is kept.
+    ///
+    /// # Examples
+    /// ```
+    /// use slabmap::SlabMap;
+    ///
+    /// let mut s = SlabMap::new();
+    /// s.insert("a");
+    /// s.insert("b");
+    ///
+    /// s.reload([(1, "x"), (3, "y")]);
+    /// assert_eq!(s.iter().collect::<Vec<_>>(), [(1, &"x"), (3, &"y")]);
+    /// ```
+    pub fn reload(&mut self, iter: impl IntoIterator<Item = (K, T)>) {
+        self.clear();
+        for (key, value) in iter {
+            self.set(key.into_index(), value);
+        }
+        self.rebuild_vacants();
+    }
+
     pub(crate) fn set(&mut self, key: usize, value: T) {
         if key >= self.entries.len() {
-            self.entries.resize_with(key + 1, || Entry::VacantTail {
-                next_vacant_idx: INVALID_INDEX,
-            });
+            self.entries.resize_with(key + 1, || Entry::VacantBody);
         }
-        self.entries[key] = Entry::Occupied(value);
+        self.entries.set(key, Entry::Occupied(value));
     }
 
     /// Returns the number of elements the SlabMap can hold without reallocating.
@@ -112,9 +463,36 @@ impl<T> SlabMap<T> {
             .try_reserve_exact(self.entries_additional(additional))
     }
 
+    /// Returns an upper bound of the keys in use.
+    #[inline]
+    pub(crate) fn key_bound(&self) -> usize {
+        self.entries.len()
+    }
+
+    /// Returns the number of bytes allocated for the entries.
+    #[inline]
+    pub(crate) fn allocated_bytes(&self) -> usize {
+        self.entries.allocated_bytes()
+    }
+
+    /// Returns the number of bytes allocated for each entry.
+    #[inline]
+    pub(crate) const fn entry_size() -> usize {
+        Entries::<T>::entry_size()
+    }
+
+    /// Ensures the capacity is at least `capacity` without over-allocating.
+    #[inline]
+    pub(crate) fn try_grow_exact(&mut self, capacity: usize) -> Result<(), TryReserveError> {
+        self.entries
+            .try_reserve_exact(capacity.saturating_sub(self.entries.len()))
+    }
+
     #[inline]
-    fn entries_additional(&self, additional: usize) -> usize {
-        additional.saturating_sub(self.entries.len() - self.len)
+    pub(crate) fn entries_additional(&self, additional: usize) -> usize {
+        let x = self.extras();
+        additional
+            .saturating_sub(self.entries.len() - self.len - x.quarantine.keys.len() - x.claimed)
     }
 
     /// Returns the number of elements in the SlabMap.
@@ -162,6 +540,31 @@ impl<T> SlabMap<T> {
         self.len == 0
     }
 
+    /// Returns true if the keys of the SlabMap are exactly `0..len`.
+    ///
+    /// Completes in O(1). A SlabMap becomes dense after [`defragment`](Self::defragment).
+    ///
+    /// # Examples
+    /// ```
+    /// use slabmap::SlabMap;
+    ///
+    /// let mut s = SlabMap::new();
+    /// s.insert(10);
+    /// s.insert(20);
+    /// s.insert(30);
+    /// assert!(s.is_dense());
+    ///
+    /// s.remove(1);
+    /// assert!(!s.is_dense());
+    ///
+    /// s.defragment();
+    /// assert!(s.is_dense());
+    /// ```
+    #[inline]
+    pub fn is_dense(&self) -> bool {
+        self.len == self.entries.len()
+    }
+
     /// Returns a reference to the value corresponding to the key.
     ///
     /// # Examples
@@ -175,8 +578,8 @@ impl<T> SlabMap<T> {
     /// assert_eq!(s.get(key + 1), None);
     /// ```
     #[inline]
-    pub fn get(&self, key: usize) -> Option<&T> {
-        if let Entry::Occupied(value) = self.entries.get(key)? {
+    pub fn get(&self, key: K) -> Option<&T> {
+        if let Entry::Occupied(value) = self.entries.get(key.into_index())? {
             Some(value)
         } else {
             None
@@ -185,185 +588,1059 @@ impl<T> SlabMap<T> {
 
     /// Returns a mutable reference to the value corresponding to the key.
     #[inline]
-    pub fn get_mut(&mut self, key: usize) -> Option<&mut T> {
-        if let Entry::Occupied(value) = self.entries.get_mut(key)? {
+    pub fn get_mut(&mut self, key: K) -> Option<&mut T> {
+        if let Entry::Occupied(value) = self.entries.get_mut(key.into_index())? {
             Some(value)
         } else {
             None
         }
     }
 
-    /// Returns true if the SlabMap contains a value for the specified key.
+    /// Returns mutable references to the values corresponding to multiple keys at once.
+    ///
+    /// Returns `None` if any key does not exist or if any two keys are the same.
     ///
     /// # Examples
     /// ```
     /// use slabmap::SlabMap;
     ///
     /// let mut s = SlabMap::new();
-    /// let key = s.insert(100);
+    /// let a = s.insert(1);
+    /// let b = s.insert(2);
+    /// let c = s.insert(3);
     ///
-    /// assert_eq!(s.contains_key(key), true);
-    /// assert_eq!(s.contains_key(key + 1), false);
+    /// let [x, y, z] = s.get_disjoint_mut([a, b, c]).unwrap();
+    /// *x += *z;
+    /// *y += *z;
+    /// assert_eq!(s[a], 4);
+    /// assert_eq!(s[b], 5);
+    ///
+    /// assert!(s.get_disjoint_mut([a, a]).is_none());
+    /// assert!(s.get_disjoint_mut([a, 10]).is_none());
     /// ```
-    #[inline]
-    pub fn contains_key(&self, key: usize) -> bool {
-        self.get(key).is_some()
+    pub fn get_disjoint_mut<const N: usize>(&mut self, keys: [K; N]) -> Option<[&mut T; N]> {
+        self.entries.get_disjoint_mut(keys.map(K::into_index))
     }
 
-    /// Inserts a value into the SlabMap.
+    /// Returns mutable references to the values corresponding to two keys at once.
     ///
-    /// Returns the key associated with the value.
+    /// Returns `None` if either key does not exist or if the keys are the same.
+    /// Unlike [`slab::Slab::get2_mut`](https://docs.rs/slab/latest/slab/struct.Slab.html#method.get2_mut), the same keys do not cause a panic.
     ///
     /// # Examples
     /// ```
     /// use slabmap::SlabMap;
     ///
     /// let mut s = SlabMap::new();
-    /// let key_abc = s.insert("abc");
-    /// let key_xyz = s.insert("xyz");
+    /// let a = s.insert(1);
+    /// let b = s.insert(2);
     ///
-    /// assert_eq!(s[key_abc], "abc");
-    /// assert_eq!(s[key_xyz], "xyz");
+    /// let (x, y) = s.get2_mut(a, b).unwrap();
+    /// std::mem::swap(x, y);
+    /// assert_eq!(s[a], 2);
+    /// assert!(s.get2_mut(a, a).is_none());
     /// ```
-    pub fn insert(&mut self, value: T) -> usize {
-        self.insert_raw(|_| value)
+    #[inline]
+    pub fn get2_mut(&mut self, key_a: K, key_b: K) -> Option<(&mut T, &mut T)> {
+        let [a, b] = self.get_disjoint_mut([key_a, key_b])?;
+        Some((a, b))
     }
 
-    /// Inserts a value given by `f` into the SlabMap. The key to be associated with the value is passed to `f`.
-    ///
-    /// Returns the key associated with the value.
+    /// Returns true if the SlabMap contains a value for the specified key.
     ///
     /// # Examples
     /// ```
     /// use slabmap::SlabMap;
     ///
     /// let mut s = SlabMap::new();
-    /// let key = s.insert_with_key(|key| format!("my key is {}", key));
+    /// let key = s.insert(100);
     ///
-    /// assert_eq!(s[key], format!("my key is {}", key));
+    /// assert_eq!(s.contains_key(key), true);
+    /// assert_eq!(s.contains_key(key + 1), false);
     /// ```
-    pub fn insert_with_key(&mut self, f: impl FnOnce(usize) -> T) -> usize {
-        self.insert_raw(f)
-    }
-
     #[inline]
-    pub fn insert_raw(&mut self, f: impl FnOnce(usize) -> T) -> usize {
-        let idx;
-        if self.next_vacant_idx < self.entries.len() {
-            idx = self.next_vacant_idx;
-            self.next_vacant_idx = match self.entries[idx] {
-                Entry::VacantHead { vacant_body_len } => {
-                    if vacant_body_len > 0 {
-                        self.entries[idx + 1] = Entry::VacantHead {
-                            vacant_body_len: vacant_body_len - 1,
-                        };
-                    }
-                    idx + 1
-                }
-                Entry::VacantTail { next_vacant_idx } => next_vacant_idx,
-                Entry::Occupied(_) => unreachable!(),
-            };
-            self.entries[idx] = Entry::Occupied(f(idx));
-            self.non_optimized_count = self.non_optimized_count.saturating_sub(1);
-        } else {
-            idx = self.entries.len();
-            self.entries.push(Entry::Occupied(f(idx)));
-        }
-        self.len += 1;
-        idx
+    pub fn contains_key(&self, key: K) -> bool {
+        self.get(key).is_some()
     }
 
-    /// Removes a key from the SlabMap, returning the value at the key if the key was previously in the SlabMap.
+    /// Returns the smallest key in the SlabMap.
+    ///
+    /// Completes in O(1). The smallest and the largest keys are maintained on insertion and removal.
     ///
     /// # Examples
     /// ```
     /// use slabmap::SlabMap;
     ///
     /// let mut s = SlabMap::new();
-    /// let key = s.insert("a");
-    /// assert_eq!(s.remove(key), Some("a"));
-    /// assert_eq!(s.remove(key), None);
+    /// assert_eq!(s.min_key(), None);
+    ///
+    /// let k0 = s.insert(10);
+    /// let k1 = s.insert(20);
+    /// let k2 = s.insert(30);
+    /// s.remove(k0);
+    /// s.remove(k1);
+    /// assert_eq!(s.min_key(), Some(k2));
     /// ```
-    pub fn remove(&mut self, key: usize) -> Option<T> {
-        let is_last = key + 1 == self.entries.len();
-        let e = self.entries.get_mut(key)?;
-        if !matches!(e, Entry::Occupied(..)) {
-            return None;
-        }
-        self.len -= 1;
-        let e = if is_last {
-            self.entries.pop().unwrap()
-        } else {
-            let e = replace(
-                e,
-                Entry::VacantTail {
-                    next_vacant_idx: self.next_vacant_idx,
-                },
-            );
-            self.next_vacant_idx = key;
-            self.non_optimized_count += 1;
-            e
-        };
-        if self.is_empty() {
-            self.clear();
-        }
-        if let Entry::Occupied(value) = e {
-            Some(value)
-        } else {
-            unreachable!()
-        }
+    #[inline]
+    pub fn min_key(&self) -> Option<K> {
+        Some(K::from_index(self.index_bounds()?.0))
     }
 
-    /// Clears the SlabMap, removing all values and optimize free spaces.
+    /// Returns the largest key in the SlabMap.
+    ///
+    /// Completes in O(1). The smallest and the largest keys are maintained on insertion and removal.
     ///
     /// # Examples
     /// ```
     /// use slabmap::SlabMap;
     ///
     /// let mut s = SlabMap::new();
-    /// s.insert(1);
-    /// s.insert(2);
-    ///
-    /// s.clear();
+    /// assert_eq!(s.max_key(), None);
     ///
-    /// assert_eq!(s.is_empty(), true);
+    /// let k0 = s.insert(10);
+    /// let k1 = s.insert(20);
+    /// let k2 = s.insert(30);
+    /// s.remove(k1);
+    /// s.remove(k2);
+    /// assert_eq!(s.max_key(), Some(k0));
     /// ```
-    pub fn clear(&mut self) {
-        self.entries.clear();
-        self.len = 0;
-        self.next_vacant_idx = INVALID_INDEX;
-        self.non_optimized_count = 0;
+    #[inline]
+    pub fn max_key(&self) -> Option<K> {
+        Some(K::from_index(self.index_bounds()?.1))
     }
 
-    /// Clears the SlabMap, returning all values as an iterator and optimize free spaces.
+    /// Returns the smallest key in the SlabMap that is greater than `after`.
+    ///
+    /// Runs of vacant entries are skipped in O(1), so the time taken is proportional to the number of runs between the keys.
     ///
     /// # Examples
     /// ```
     /// use slabmap::SlabMap;
     ///
     /// let mut s = SlabMap::new();
-    /// let k0 = s.insert(10);
-    /// let k1 = s.insert(20);
-    ///
-    /// let d: Vec<_> = s.drain().collect();
-    /// let mut e = vec![(k0, 10), (k1, 20)];
-    /// e.sort();
-    ///
-    /// assert_eq!(s.is_empty(), true);
-    /// assert_eq!(d, e);
+    /// for i in 0..6 {
+    ///     s.insert(i);
+    /// }
+    /// s.remove(2);
+    /// s.remove(3);
+    /// assert_eq!(s.next_occupied_key(1), Some(4));
+    /// assert_eq!(s.next_occupied_key(5), None);
     /// ```
-    pub fn drain(&mut self) -> Drain<T> {
-        let len = self.len;
-        self.len = 0;
-        self.next_vacant_idx = INVALID_INDEX;
-        self.non_optimized_count = 0;
-        Drain {
-            iter: self.entries.drain(..).enumerate(),
-            len,
-        }
+    pub fn next_occupied_key(&self, after: K) -> Option<K> {
+        let idx = self.next_occupied_idx(after.into_index().checked_add(1)?)?;
+        Some(K::from_index(idx))
     }
 
-    /// Retains only the elements specified by the predicate and optimize free spaces.
+    /// Returns the largest key in the SlabMap that is less than `before`.
+    ///
+    /// Runs of vacant entries are skipped in O(1), so the time taken is proportional to the number of runs between the keys.
+    ///
+    /// # Examples
+    /// ```
+    /// use slabmap::SlabMap;
+    ///
+    /// let mut s = SlabMap::new();
+    /// for i in 0..6 {
+    ///     s.insert(i);
+    /// }
+    /// s.remove(2);
+    /// s.remove(3);
+    /// assert_eq!(s.prev_occupied_key(4), Some(1));
+    /// assert_eq!(s.prev_occupied_key(100), Some(5));
+    /// assert_eq!(s.prev_occupied_key(0), None);
+    /// ```
+    pub fn prev_occupied_key(&self, before: K) -> Option<K> {
+        Some(K::from_index(self.prev_occupied_idx(before.into_index())?))
+    }
+
+    /// Returns the smallest key whose value satisfies the predicate.
+    ///
+    /// Runs of vacant entries are skipped in the same way as [`iter`](Self::iter).
+    ///
+    /// # Examples
+    /// ```
+    /// use slabmap::SlabMap;
+    ///
+    /// let mut s = SlabMap::new();
+    /// s.insert("a");
+    /// s.insert("bb");
+    /// s.insert("cc");
+    /// assert_eq!(s.find_key(|v| v.len() == 2), Some(1));
+    /// assert_eq!(s.find_key(|v| v.is_empty()), None);
+    /// ```
+    pub fn find_key(&self, pred: impl FnMut(&T) -> bool) -> Option<K> {
+        Some(self.find_entry(pred)?.0)
+    }
+
+    /// Returns the entry with the smallest key whose value satisfies the predicate.
+    ///
+    /// # Examples
+    /// ```
+    /// use slabmap::SlabMap;
+    ///
+    /// let mut s = SlabMap::new();
+    /// s.insert(1);
+    /// s.insert(20);
+    /// s.insert(30);
+    /// assert_eq!(s.find_entry(|v| *v >= 10), Some((1, &20)));
+    /// ```
+    pub fn find_entry(&self, mut pred: impl FnMut(&T) -> bool) -> Option<(K, &T)> {
+        let mut idx = 0;
+        while let Some(e) = self.entries.get(idx) {
+            match e {
+                Entry::Occupied(value) if pred(value) => return Some((K::from_index(idx), value)),
+                Entry::VacantHead { vacant_len } => idx += run_len(vacant_len),
+                _ => idx += 1,
+            }
+        }
+        None
+    }
+
+    /// Returns the entry with the smallest key.
+    ///
+    /// # Examples
+    /// ```
+    /// use slabmap::SlabMap;
+    ///
+    /// let mut s = SlabMap::new();
+    /// assert_eq!(s.first(), None);
+    /// s.insert("a");
+    /// s.insert("b");
+    /// assert_eq!(s.first(), Some((0, &"a")));
+    /// ```
+    pub fn first(&self) -> Option<(K, &T)> {
+        let key = self.min_key()?;
+        Some((key, self.get(key)?))
+    }
+
+    /// Returns the entry with the smallest key as a mutable reference.
+    pub fn first_mut(&mut self) -> Option<(K, &mut T)> {
+        let key = self.min_key()?;
+        Some((key, self.get_mut(key)?))
+    }
+
+    /// Returns the entry with the largest key.
+    ///
+    /// # Examples
+    /// ```
+    /// use slabmap::SlabMap;
+    ///
+    /// let mut s = SlabMap::new();
+    /// assert_eq!(s.last(), None);
+    /// s.insert("a");
+    /// s.insert("b");
+    /// assert_eq!(s.last(), Some((1, &"b")));
+    /// ```
+    pub fn last(&self) -> Option<(K, &T)> {
+        let key = self.max_key()?;
+        Some((key, self.get(key)?))
+    }
+
+    /// Returns the entry with the largest key as a mutable reference.
+    pub fn last_mut(&mut self) -> Option<(K, &mut T)> {
+        let key = self.max_key()?;
+        Some((key, self.get_mut(key)?))
+    }
+
+    /// Removes the value with the smallest key and returns it with its key.
+    ///
+    /// # Examples
+    /// ```
+    /// use slabmap::SlabMap;
+    ///
+    /// let mut s = SlabMap::new();
+    /// s.insert("a");
+    /// s.insert("b");
+    /// assert_eq!(s.pop_first(), Some((0, "a")));
+    /// assert_eq!(s.pop_first(), Some((1, "b")));
+    /// assert_eq!(s.pop_first(), None);
+    /// ```
+    pub fn pop_first(&mut self) -> Option<(K, T)> {
+        let key = self.min_key()?;
+        Some((key, self.remove(key)?))
+    }
+
+    /// Removes the value with the largest key and returns it with its key.
+    ///
+    /// # Examples
+    /// ```
+    /// use slabmap::SlabMap;
+    ///
+    /// let mut s = SlabMap::new();
+    /// s.insert("a");
+    /// s.insert("b");
+    /// assert_eq!(s.pop_last(), Some((1, "b")));
+    /// assert_eq!(s.pop_last(), Some((0, "a")));
+    /// assert_eq!(s.pop_last(), None);
+    /// ```
+    pub fn pop_last(&mut self) -> Option<(K, T)> {
+        let key = self.max_key()?;
+        Some((key, self.remove(key)?))
+    }
+
+    /// Returns a mutable reference to the value corresponding to the key, inserting the value returned by `f` at the key if it does not exist.
+    ///
+    /// If `f` returns an error, the SlabMap is left untouched.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the key is claimed.
+    ///
+    /// # Examples
+    /// ```
+    /// use slabmap::SlabMap;
+    ///
+    /// let mut s = SlabMap::new();
+    /// assert_eq!(s.get_or_try_insert_with(3, || "3".parse::<i32>()), Ok(&mut 3));
+    /// assert_eq!(s.get_or_try_insert_with(3, || "x".parse::<i32>()), Ok(&mut 3));
+    /// assert!(s.get_or_try_insert_with(5, || "x".parse::<i32>()).is_err());
+    /// assert_eq!(s.keys().collect::<Vec<_>>(), [3]);
+    ///
+    /// assert_eq!(s.insert(0), 0);
+    /// ```
+    pub fn get_or_try_insert_with<E>(
+        &mut self,
+        key: K,
+        f: impl FnOnce() -> Result<T, E>,
+    ) -> Result<&mut T, E> {
+        if !self.contains_key(key) {
+            let value = f()?;
+            self.occupy(key.into_index(), value);
+        }
+        Ok(self.get_mut(key).unwrap())
+    }
+
+    /// Inserts a value into the SlabMap.
+    ///
+    /// Returns the key associated with the value.
+    ///
+    /// # Examples
+    /// ```
+    /// use slabmap::SlabMap;
+    ///
+    /// let mut s = SlabMap::new();
+    /// let key_abc = s.insert("abc");
+    /// let key_xyz = s.insert("xyz");
+    ///
+    /// assert_eq!(s[key_abc], "abc");
+    /// assert_eq!(s[key_xyz], "xyz");
+    /// ```
+    pub fn insert(&mut self, value: T) -> K {
+        K::from_index(self.insert_raw(|_| value))
+    }
+
+    /// Inserts a value given by `f` into the SlabMap. The key to be associated with the value is passed to `f`.
+    ///
+    /// Returns the key associated with the value.
+    ///
+    /// # Examples
+    /// ```
+    /// use slabmap::SlabMap;
+    ///
+    /// let mut s = SlabMap::new();
+    /// let key = s.insert_with_key(|key| format!("my key is {}", key));
+    ///
+    /// assert_eq!(s[key], format!("my key is {}", key));
+    /// ```
+    pub fn insert_with_key(&mut self, f: impl FnOnce(K) -> T) -> K {
+        K::from_index(self.insert_raw(|idx| f(K::from_index(idx))))
+    }
+
+    /// Inserts a value into the SlabMap and returns the key with a mutable reference to the value.
+    ///
+    /// # Examples
+    /// ```
+    /// use slabmap::SlabMap;
+    ///
+    /// let mut s = SlabMap::new();
+    /// let (key, value) = s.insert_mut(vec![1]);
+    /// value.push(2);
+    ///
+    /// assert_eq!(s[key], [1, 2]);
+    /// ```
+    pub fn insert_mut(&mut self, value: T) -> (K, &mut T) {
+        self.insert_mut_with_key(|_| value)
+    }
+
+    /// Inserts a value given by `f` into the SlabMap and returns the key with a mutable reference to the value. The key to be associated with the value is passed to `f`.
+    ///
+    /// # Examples
+    /// ```
+    /// use slabmap::SlabMap;
+    ///
+    /// let mut s = SlabMap::new();
+    /// let (key, value) = s.insert_mut_with_key(|key| vec![key]);
+    /// value.push(10);
+    ///
+    /// assert_eq!(s[key], [key, 10]);
+    /// ```
+    pub fn insert_mut_with_key(&mut self, f: impl FnOnce(K) -> T) -> (K, &mut T) {
+        let idx = self.insert_raw(|idx| f(K::from_index(idx)));
+        let Some(Entry::Occupied(value)) = self.entries.get_mut(idx) else {
+            unreachable!()
+        };
+        (K::from_index(idx), value)
+    }
+
+    /// Inserts a value given by the fallible function `f` into the SlabMap. The key to be associated with the value is passed to `f`.
+    ///
+    /// If `f` returns an error, the SlabMap is left unchanged and the error is returned.
+    ///
+    /// # Examples
+    /// ```
+    /// use slabmap::SlabMap;
+    ///
+    /// let mut s = SlabMap::new();
+    /// let r = s.try_insert_with(|_| "x".parse::<u32>());
+    /// assert!(r.is_err());
+    /// assert!(s.is_empty());
+    ///
+    /// let key = s.try_insert_with(|_| "10".parse::<u32>()).unwrap();
+    /// assert_eq!(s[key], 10);
+    /// ```
+    pub fn try_insert_with<E>(&mut self, f: impl FnOnce(K) -> Result<T, E>) -> Result<K, E> {
+        let e = self.vacant_entry();
+        let key = e.key();
+        e.insert(f(key)?);
+        Ok(key)
+    }
+
+    /// Returns a handle to the vacant entry that the next insertion will use.
+    ///
+    /// The key is available through [`VacantEntry::key`] before the value is inserted.
+    ///
+    /// # Examples
+    /// ```
+    /// use slabmap::SlabMap;
+    ///
+    /// let mut s = SlabMap::new();
+    /// let e = s.vacant_entry();
+    /// let key = e.key();
+    /// e.insert((key, "a"));
+    ///
+    /// assert_eq!(s[key], (key, "a"));
+    /// ```
+    pub fn vacant_entry(&mut self) -> VacantEntry<T, K> {
+        let key = K::from_index(self.next_vacant_idx().unwrap_or(self.entries.len()));
+        VacantEntry { map: self, key }
+    }
+
+    /// Inserts a value at the specified key.
+    ///
+    /// If the key is beyond the end of the SlabMap, the SlabMap grows and the keys in between become free space.
+    /// If the key is already occupied, the value is replaced and the old value is returned.
+    /// A key in quarantine can also be specified, in which case it is taken out of quarantine.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the key is claimed. Use [`fill`](Self::fill) to insert a value at a claimed key.
+    ///
+    /// # Examples
+    /// ```
+    /// use slabmap::SlabMap;
+    ///
+    /// let mut s = SlabMap::new();
+    /// assert_eq!(s.insert_at(3, "a"), None);
+    /// assert_eq!(s.insert_at(3, "b"), Some("a"));
+    /// assert_eq!(s.len(), 1);
+    /// assert_eq!(s.insert("c"), 0);
+    /// ```
+    pub fn insert_at(&mut self, key: K, value: T) -> Option<T> {
+        let key = key.into_index();
+        if let Some(Entry::Occupied(old)) = self.entries.get_mut(key) {
+            return Some(replace(old, value));
+        }
+        self.occupy(key, value);
+        None
+    }
+
+    /// Inserts a value at the specified key if the key is not occupied.
+    ///
+    /// Unlike [`insert_at`](Self::insert_at), an occupied key is not overwritten, and the value is returned in the error.
+    /// A claimed key is treated as occupied, since it is reserved for [`fill`](Self::fill).
+    ///
+    /// # Examples
+    /// ```
+    /// use slabmap::SlabMap;
+    ///
+    /// let mut s = SlabMap::new();
+    /// assert!(s.try_insert_at(3, "a").is_ok());
+    ///
+    /// let e = s.try_insert_at(3, "b").unwrap_err();
+    /// assert_eq!(e.key, 3);
+    /// assert_eq!(e.value, "b");
+    /// assert_eq!(s[3], "a");
+    /// ```
+    pub fn try_insert_at(&mut self, key: K, value: T) -> Result<(), OccupiedError<T, K>> {
+        if let Some(Entry::Occupied(_) | Entry::Claimed) = self.entries.get(key.into_index()) {
+            return Err(OccupiedError { key, value });
+        }
+        self.occupy(key.into_index(), value);
+        Ok(())
+    }
+
+    /// Returns the key that the next [`insert`](Self::insert) will return, without inserting.
+    ///
+    /// # Examples
+    /// ```
+    /// use slabmap::SlabMap;
+    ///
+    /// let mut s = SlabMap::new();
+    /// let key = s.next_key();
+    /// assert_eq!(s.insert("a"), key);
+    ///
+    /// s.insert("b");
+    /// s.remove(key);
+    /// assert_eq!(s.next_key(), key);
+    /// ```
+    pub fn next_key(&self) -> K {
+        let idx = self
+            .extras()
+            .vacant_heads
+            .iter()
+            .rev()
+            .find(|&&idx| matches!(self.entries.get(idx), Some(Entry::VacantHead { .. })));
+        K::from_index(idx.copied().unwrap_or(self.entries.len()))
+    }
+
+    #[inline]
+    pub fn insert_raw(&mut self, f: impl FnOnce(usize) -> T) -> usize {
+        let idx = self.insert_entry(|idx| Entry::Occupied(f(idx)));
+        self.len += 1;
+        self.on_occupied(idx);
+        self.count_insert();
+        idx
+    }
+    #[inline]
+    fn insert_entry(&mut self, f: impl FnOnce(usize) -> Entry<T>) -> usize {
+        if let Some(idx) = self.next_vacant_idx() {
+            let e = f(idx);
+            self.take_vacant(idx);
+            self.entries.set(idx, e);
+            let x = self.extras_mut();
+            x.non_optimized_count = x.non_optimized_count.saturating_sub(1);
+            idx
+        } else {
+            let idx = self.entries.len();
+            self.entries.push(f(idx));
+            idx
+        }
+    }
+    fn count_insert(&mut self) {
+        if self.extras().quarantine.window != 0 || !self.extras().quarantine.keys.is_empty() {
+            // Keys are released as soon as they become due, so that `next_key` does not need to look at the quarantine.
+            let q = &mut self.extras_mut().quarantine;
+            q.inserts = q.inserts.wrapping_add(1);
+            self.release_quarantine(self.extras().quarantine.inserts);
+        }
+        self.count_operation();
+    }
+    /// Counts an insertion or a removal for the shrink policy.
+    #[inline]
+    fn count_operation(&mut self) {
+        if self.extras().auto_shrink.is_some() {
+            self.apply_shrink_policy();
+        }
+    }
+
+    /// Reserves a key without inserting a value.
+    ///
+    /// The key is not reused until the value is stored with [`fill`](Self::fill) or the key is released with [`abandon`](Self::abandon).
+    /// While the key is claimed, it is not counted in [`len`](Self::len) and [`get`](Self::get) returns `None`.
+    ///
+    /// # Examples
+    /// ```
+    /// use slabmap::SlabMap;
+    ///
+    /// let mut s = SlabMap::new();
+    /// let key = s.claim_key();
+    /// assert_eq!(s.get(key), None);
+    /// assert_ne!(s.insert("b"), key);
+    ///
+    /// s.fill(key, "a");
+    /// assert_eq!(s[key], "a");
+    /// ```
+    pub fn claim_key(&mut self) -> K {
+        let idx = self.insert_entry(|_| Entry::Claimed);
+        self.extras_mut().claimed += 1;
+        self.count_insert();
+        K::from_index(idx)
+    }
+
+    /// Stores a value at a key reserved by [`claim_key`](Self::claim_key) and returns a mutable reference to it.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the key is not claimed.
+    pub fn fill(&mut self, key: K, value: T) -> &mut T {
+        let idx = key.into_index();
+        let Some(Entry::Claimed) = self.entries.get(idx) else {
+            panic!("key is not claimed.")
+        };
+        self.extras_mut().claimed -= 1;
+        self.len += 1;
+        self.on_occupied(idx);
+        self.entries.set_value(idx, value)
+    }
+
+    /// Releases a key reserved by [`claim_key`](Self::claim_key) without storing a value.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the key is not claimed.
+    ///
+    /// # Examples
+    /// ```
+    /// use slabmap::SlabMap;
+    ///
+    /// let mut s = SlabMap::<&str>::new();
+    /// let key = s.claim_key();
+    /// s.abandon(key);
+    /// assert_eq!(s.claim_key(), key);
+    /// ```
+    pub fn abandon(&mut self, key: K) {
+        let key = key.into_index();
+        assert!(
+            matches!(self.entries.get(key), Some(Entry::Claimed)),
+            "key is not claimed."
+        );
+        self.extras_mut().claimed -= 1;
+        if key + 1 == self.entries.len() {
+            self.entries.pop();
+            self.truncate_vacant_tail();
+        } else {
+            self.entries.set(key, Entry::VacantBody);
+            self.merge_vacant(key);
+            self.extras_mut().non_optimized_count += 1;
+        }
+        if self.is_unused() {
+            self.clear();
+        }
+    }
+    fn next_vacant_idx(&mut self) -> Option<usize> {
+        while let Some(&idx) = self.extras().vacant_heads.last() {
+            if let Some(Entry::VacantHead { .. }) = self.entries.get(idx) {
+                return Some(idx);
+            }
+            self.extras_mut().vacant_heads.pop();
+            self.on_vacant_heads_pop();
+        }
+        None
+    }
+    fn take_vacant(&mut self, idx: usize) {
+        let Entry::VacantHead { vacant_len } = self.entries.entry(idx) else {
+            unreachable!()
+        };
+        self.extras_mut().vacant_heads.pop();
+        self.on_vacant_heads_pop();
+        if vacant_len > 1 {
+            self.set_vacant_run(idx + 1, run_len(vacant_len) - 1);
+            self.extras_mut().vacant_heads.push(idx + 1);
+        }
+    }
+    /// Inserts values at the specified keys, merging them into existing values with `on_conflict`.
+    ///
+    /// Keys that do not exist are inserted, and for keys that exist, `on_conflict` is called with the existing value and the new value.
+    ///
+    /// # Panics
+    ///
+    /// Panics if a key that does not exist is claimed.
+    ///
+    /// # Examples
+    /// ```
+    /// use slabmap::SlabMap;
+    ///
+    /// let mut s = SlabMap::new();
+    /// s.insert(10);
+    ///
+    /// s.upsert_from([(0, 1), (2, 20), (2, 2)], |v, n| *v += n);
+    /// assert_eq!(s.iter().collect::<Vec<_>>(), [(0, &11), (2, &22)]);
+    /// ```
+    pub fn upsert_from(
+        &mut self,
+        iter: impl IntoIterator<Item = (K, T)>,
+        mut on_conflict: impl FnMut(&mut T, T),
+    ) {
+        let iter = iter.into_iter();
+        self.reserve(iter.size_hint().0);
+        for (key, value) in iter {
+            if let Some(old) = self.get_mut(key) {
+                on_conflict(old, value);
+            } else {
+                self.occupy(key.into_index(), value);
+            }
+        }
+    }
+
+    /// Moves all entries of `other` into `self`, keeping their keys.
+    ///
+    /// If a key of `other` is occupied or claimed in `self`, an error is returned and neither SlabMap is modified.
+    /// To merge values with the same key instead, use [`upsert_from`](Self::upsert_from) with [`drain`](Self::drain).
+    ///
+    /// # Examples
+    /// ```
+    /// use slabmap::SlabMap;
+    ///
+    /// let mut a = SlabMap::from_slots(vec![Some("a"), None]);
+    /// let mut b = SlabMap::from_slots(vec![None, Some("b")]);
+    /// a.append(&mut b).unwrap();
+    /// assert_eq!(a.iter().collect::<Vec<_>>(), [(0, &"a"), (1, &"b")]);
+    /// assert!(b.is_empty());
+    ///
+    /// let mut c = SlabMap::from_slots(vec![Some("c")]);
+    /// assert_eq!(a.append(&mut c).unwrap_err().key, 0);
+    /// assert_eq!(c.len(), 1);
+    /// ```
+    pub fn append(&mut self, other: &mut Self) -> Result<(), KeyConflict<K>> {
+        for key in other.keys() {
+            if let Some(Entry::Occupied(_) | Entry::Claimed) = self.entries.get(key.into_index()) {
+                return Err(KeyConflict { key });
+            }
+        }
+        self.reserve(other.len);
+        for (key, value) in other.drain() {
+            self.occupy(key.into_index(), value);
+        }
+        Ok(())
+    }
+
+    /// Inserts all values of `other` with newly assigned keys, and returns the pairs of old and new indexes.
+    ///
+    /// The pairs are sorted by old index and can be passed to [`Remap::remap`] to rewrite structures keyed by `other`.
+    ///
+    /// # Examples
+    /// ```
+    /// use slabmap::SlabMap;
+    ///
+    /// let mut a = SlabMap::new();
+    /// a.insert("a");
+    /// let mut b = SlabMap::new();
+    /// b.insert("b");
+    /// b.insert("c");
+    ///
+    /// let remap = a.merge_remap(b);
+    /// assert_eq!(remap, [(0, 1), (1, 2)]);
+    /// assert_eq!(a[2], "c");
+    /// ```
+    pub fn merge_remap(&mut self, other: Self) -> Vec<(usize, usize)> {
+        self.reserve(other.len);
+        other
+            .into_iter()
+            .map(|(key, value)| (key.into_index(), self.insert(value).into_index()))
+            .collect()
+    }
+
+    /// Stores a value at a key that is not occupied, splitting the free space around it.
+    ///
+    /// Panics before modifying the SlabMap if the key is claimed, since the claim must be resolved by [`fill`](Self::fill) or [`abandon`](Self::abandon).
+    fn occupy(&mut self, key: usize, value: T) {
+        let entries_len = self.entries.len();
+        if key >= entries_len {
+            for idx in entries_len..key {
+                self.entries.push(Entry::VacantBody);
+                self.merge_vacant(idx);
+            }
+            self.entries.push(Entry::Occupied(value));
+            self.len += 1;
+            self.on_occupied(key);
+            self.count_operation();
+            return;
+        }
+        let is_free =
+            |e: Entry<&T>| !matches!(e, Entry::Occupied(_) | Entry::Quarantined | Entry::Claimed);
+        let (head_idx, end_idx) = match self.entries.entry(key) {
+            Entry::Occupied(_) => unreachable!(),
+            Entry::Quarantined => {
+                self.extras_mut().quarantine.keys.retain(|&(k, _)| k != key);
+                (key, key + 1)
+            }
+            Entry::Claimed => panic!("key is claimed."),
+            Entry::VacantHead { vacant_len } => (key, key + run_len(vacant_len)),
+            Entry::VacantTail { vacant_len } => (key + 1 - run_len(vacant_len), key + 1),
+            Entry::VacantBody => {
+                let head_idx = (0..key)
+                    .rev()
+                    .find(|&idx| !is_free(self.entries.entry(idx)))
+                    .map_or(0, |idx| idx + 1);
+                let end_idx = (key + 1..entries_len)
+                    .find(|&idx| !is_free(self.entries.entry(idx)))
+                    .unwrap_or(entries_len);
+                (head_idx, end_idx)
+            }
+        };
+        if !matches!(self.entries.entry(key), Entry::Quarantined | Entry::Claimed) {
+            if head_idx < key {
+                self.set_vacant_run(head_idx, key - head_idx);
+            }
+            if key + 1 < end_idx {
+                self.set_vacant_run(key + 1, end_idx - key - 1);
+                self.extras_mut().vacant_heads.push(key + 1);
+                if self.extras().vacant_heads.len() > self.entries.len() {
+                    self.rebuild_vacant_heads();
+                }
+            }
+            let x = self.extras_mut();
+            x.non_optimized_count = x.non_optimized_count.saturating_sub(1);
+        }
+        self.entries.set(key, Entry::Occupied(value));
+        self.len += 1;
+        self.on_occupied(key);
+        self.count_operation();
+    }
+    fn set_vacant_run(&mut self, head_idx: usize, len: usize) {
+        let vacant_len = RunLen::try_from(len)
+            .ok()
+            .filter(|&len| len <= MAX_RUN_LEN)
+            .expect("capacity overflow");
+        self.entries.set(head_idx, Entry::VacantHead { vacant_len });
+        if len > 1 {
+            self.entries
+                .set(head_idx + len - 1, Entry::VacantTail { vacant_len });
+        }
+    }
+    fn merge_vacant(&mut self, idx: usize) {
+        let mut head_idx = idx;
+        let mut vacant_len = 1;
+        if let Some(Entry::VacantHead { vacant_len: len } | Entry::VacantTail { vacant_len: len }) =
+            idx.checked_sub(1).map(|i| self.entries.entry(i))
+        {
+            head_idx -= run_len(len);
+            vacant_len += run_len(len);
+            self.entries.set(idx - 1, Entry::VacantBody);
+        }
+        if let Some(Entry::VacantHead { vacant_len: len }) = self.entries.get(idx + 1) {
+            vacant_len += run_len(len);
+            self.entries.set(idx + 1, Entry::VacantBody);
+        }
+        self.set_vacant_run(head_idx, vacant_len);
+        if head_idx == idx {
+            self.extras_mut().vacant_heads.push(idx);
+            if self.extras().vacant_heads.len() > self.entries.len() {
+                self.rebuild_vacant_heads();
+            }
+        }
+    }
+    fn truncate_vacant_tail(&mut self) {
+        if let Some(Entry::VacantHead { vacant_len } | Entry::VacantTail { vacant_len }) =
+            self.entries.last()
+        {
+            let len = self.entries.len() - run_len(vacant_len);
+            self.entries.truncate(len);
+            let free = len - self.len;
+            if let Some(x) = &mut self.extras {
+                let vacant = free - x.claimed - x.quarantine.keys.len();
+                x.non_optimized_count = x.non_optimized_count.min(vacant);
+            }
+        }
+    }
+    fn rebuild_vacant_heads(&mut self) {
+        self.extras_mut().vacant_heads.clear();
+        self.on_vacant_heads_pop();
+        let mut idx = 0;
+        while let Some(e) = self.entries.get(idx) {
+            if let Entry::VacantHead { vacant_len } = e {
+                self.extras_mut().vacant_heads.push(idx);
+                idx += run_len(vacant_len);
+            } else {
+                idx += 1;
+            }
+        }
+        self.extras_mut().vacant_heads.reverse();
+    }
+
+    /// Removes a key from the SlabMap, returning the value at the key if the key was previously in the SlabMap.
+    ///
+    /// If the key is the last entry, the free space run before it is also released.
+    ///
+    /// # Examples
+    /// ```
+    /// use slabmap::SlabMap;
+    ///
+    /// let mut s = SlabMap::new();
+    /// let key = s.insert("a");
+    /// assert_eq!(s.remove(key), Some("a"));
+    /// assert_eq!(s.remove(key), None);
+    /// ```
+    pub fn remove(&mut self, key: K) -> Option<T> {
+        let key = key.into_index();
+        let is_last = key + 1 == self.entries.len();
+        if !matches!(self.entries.get(key)?, Entry::Occupied(..)) {
+            return None;
+        }
+        self.len -= 1;
+        let e = if self.extras().quarantine.window != 0 {
+            let q = &mut self.extras_mut().quarantine;
+            q.keys.push_back((key, q.inserts.wrapping_add(q.window)));
+            self.entries.replace(key, Entry::Quarantined)
+        } else if is_last {
+            let e = self.entries.pop().unwrap();
+            self.truncate_vacant_tail();
+            e
+        } else {
+            let e = self.entries.replace(key, Entry::VacantBody);
+            self.merge_vacant(key);
+            self.extras_mut().non_optimized_count += 1;
+            e
+        };
+        self.on_vacated(key, key + 1);
+        if self.is_unused() {
+            self.clear();
+        }
+        self.count_operation();
+        if let Entry::Occupied(value) = e {
+            Some(value)
+        } else {
+            unreachable!()
+        }
+    }
+
+    fn is_unused(&self) -> bool {
+        self.is_empty() && self.extras().quarantine.keys.is_empty() && self.extras().claimed == 0
+    }
+
+    /// Clears the SlabMap, removing all values and optimize free spaces.
+    ///
+    /// Keys in quarantine and claimed keys are also released.
+    ///
+    /// # Examples
+    /// ```
+    /// use slabmap::SlabMap;
+    ///
+    /// let mut s = SlabMap::new();
+    /// s.insert(1);
+    /// s.insert(2);
+    ///
+    /// s.clear();
+    ///
+    /// assert_eq!(s.is_empty(), true);
+    /// ```
+    pub fn clear(&mut self) {
+        self.entries.clear();
+        self.len = 0;
+        if let Some(x) = &mut self.extras {
+            x.clear();
+        }
+    }
+
+    /// Clears the SlabMap, returning all values as an iterator and optimize free spaces.
+    ///
+    /// # Examples
+    /// ```
+    /// use slabmap::SlabMap;
+    ///
+    /// let mut s = SlabMap::new();
+    /// let k0 = s.insert(10);
+    /// let k1 = s.insert(20);
+    ///
+    /// let d: Vec<_> = s.drain().collect();
+    /// let mut e = vec![(k0, 10), (k1, 20)];
+    /// e.sort();
+    ///
+    /// assert_eq!(s.is_empty(), true);
+    /// assert_eq!(d, e);
+    /// ```
+    pub fn drain(&mut self) -> Drain<T, K> {
+        let len = self.len;
+        self.len = 0;
+        if let Some(x) = &mut self.extras {
+            x.clear();
+        }
+        Drain {
+            iter: self.entries.drain(),
+            len,
+            _key: PhantomData,
+        }
+    }
+
+    /// Removes the entries whose keys are in the specified range, returning them as an iterator.
+    ///
+    /// The free space of the range is merged into a single run when the iterator is dropped,
+    /// so removing a block of keys does not fragment the free space as [`remove`](Self::remove) for each key does.
+    /// If the iterator is dropped before it is exhausted, the remaining entries in the range are also removed.
+    ///
+    /// # Examples
+    /// ```
+    /// use slabmap::SlabMap;
+    ///
+    /// let mut s = SlabMap::new();
+    /// for i in 0..10 {
+    ///     s.insert(i * 10);
+    /// }
+    /// s.remove(4);
+    ///
+    /// let d: Vec<_> = s.drain_range(3..6).collect();
+    /// assert_eq!(d, [(3, 30), (5, 50)]);
+    /// assert_eq!(s.len(), 7);
+    /// assert_eq!(s.insert(100), 3);
+    /// ```
+    pub fn drain_range(&mut self, range: impl RangeBounds<K>) -> DrainRange<T, K> {
+        let ops::Range { start, end } = self.index_range(range);
+        DrainRange {
+            map: self,
+            idx: start,
+            start,
+            end,
+            drained: 0,
+        }
+    }
+    fn index_range(&self, range: impl RangeBounds<K>) -> ops::Range<usize> {
+        let start = match range.start_bound() {
+            Bound::Included(key) => key.into_index(),
+            Bound::Excluded(key) => key.into_index().saturating_add(1),
+            Bound::Unbounded => 0,
+        };
+        let end = match range.end_bound() {
+            Bound::Included(key) => key.into_index().saturating_add(1),
+            Bound::Excluded(key) => key.into_index(),
+            Bound::Unbounded => usize::MAX,
+        };
+        let end = end.min(self.entries.len());
+        start.min(end)..end
+    }
+    /// Rebuilds the free space runs around `start..end`, where occupied entries have been replaced by `VacantBody`.
+    fn rebuild_vacant_span(&mut self, start: usize, end: usize) {
+        let is_free =
+            |e: Entry<&T>| !matches!(e, Entry::Occupied(_) | Entry::Quarantined | Entry::Claimed);
+        let mut head_idx = start;
+        while let Some(e) = head_idx.checked_sub(1).map(|idx| self.entries.entry(idx)) {
+            head_idx -= match e {
+                Entry::VacantTail { vacant_len } => run_len(vacant_len),
+                Entry::VacantHead { .. } | Entry::VacantBody => 1,
+                Entry::Occupied(_) | Entry::Quarantined | Entry::Claimed => break,
+            };
+        }
+        let mut end_idx = end;
+        while let Some(e) = self.entries.get(end_idx) {
+            end_idx += match e {
+                Entry::VacantHead { vacant_len } => run_len(vacant_len),
+                Entry::VacantTail { .. } | Entry::VacantBody => 1,
+                Entry::Occupied(_) | Entry::Quarantined | Entry::Claimed => break,
+            };
+        }
+        let normalize_end = (end + 1).min(self.entries.len());
+        for idx in start.saturating_sub(1)..normalize_end {
+            if is_free(self.entries.entry(idx)) {
+                self.entries.set(idx, Entry::VacantBody);
+            }
+        }
+        let mut run_head = head_idx;
+        for idx in start..=end {
+            if idx == end || matches!(self.entries.entry(idx), Entry::Quarantined | Entry::Claimed)
+            {
+                let run_end = if idx == end { end_idx } else { idx };
+                if run_head < run_end {
+                    self.set_vacant_run(run_head, run_end - run_head);
+                    self.extras_mut().vacant_heads.push(run_head);
+                }
+                run_head = idx + 1;
+            }
+        }
+        if self.extras().vacant_heads.len() > self.entries.len() {
+            self.rebuild_vacant_heads();
+        }
+    }
+
+    /// Retains only the elements specified by the predicate and optimize free spaces.
     ///
     /// # Examples
     /// ```
@@ -375,213 +1652,1055 @@ impl<T> SlabMap<T> {
     /// s.insert(20);
     /// s.insert(25);
     ///
-    /// s.retain(|_idx, value| *value % 2 == 0);
+    /// s.retain(|_idx, value| *value % 2 == 0);
+    ///
+    /// let value: Vec<_> = s.values().cloned().collect();
+    /// assert_eq!(value, vec![10, 20]);
+    /// ```
+    pub fn retain(&mut self, mut f: impl FnMut(K, &mut T) -> bool) {
+        self.rebuild_vacants_with(|idx, value| f(K::from_index(idx), value))
+    }
+
+    /// Visits all elements in one pass, keeping, removing or replacing each of them as decided by `f`, and optimize free spaces.
+    ///
+    /// # Examples
+    /// ```
+    /// use slabmap::{slab_map::Visit, SlabMap};
+    ///
+    /// let mut s = SlabMap::new();
+    /// s.insert(10);
+    /// s.insert(15);
+    /// s.insert(20);
+    ///
+    /// s.visit_mut(|_key, value| match *value {
+    ///     10 => Visit::Keep,
+    ///     15 => Visit::Remove,
+    ///     _ => Visit::Replace(*value + 1),
+    /// });
+    ///
+    /// assert_eq!(s.iter().collect::<Vec<_>>(), [(0, &10), (2, &21)]);
+    /// ```
+    pub fn visit_mut(&mut self, mut f: impl FnMut(K, &mut T) -> Visit<T>) {
+        self.rebuild_vacants_with(|idx, value| match f(K::from_index(idx), value) {
+            Visit::Keep => true,
+            Visit::Remove => false,
+            Visit::Replace(new_value) => {
+                *value = new_value;
+                true
+            }
+        })
+    }
+    pub(crate) fn rebuild_vacants(&mut self) {
+        self.rebuild_vacants_with(|_, _| true);
+    }
+    fn rebuild_vacants_with(&mut self, mut f: impl FnMut(usize, &mut T) -> bool) {
+        let mut idx = 0;
+        let mut vacant_head_idx = 0;
+        let mut len = 0;
+        let mut bounds = (usize::MAX, 0);
+        if let Some(x) = &mut self.extras {
+            x.vacant_heads.clear();
+        }
+        while let Some(e) = self.entries.get_mut(idx) {
+            match e {
+                Entry::VacantTail { .. } | Entry::VacantBody => {
+                    self.entries.set(idx, Entry::VacantBody);
+                    idx += 1;
+                }
+                Entry::VacantHead { vacant_len } => {
+                    let end = idx + run_len(vacant_len);
+                    self.entries.set(idx, Entry::VacantBody);
+                    self.entries.set(end - 1, Entry::VacantBody);
+                    idx = end;
+                }
+                Entry::Quarantined | Entry::Claimed => {
+                    if vacant_head_idx < idx {
+                        self.set_vacant_run(vacant_head_idx, idx - vacant_head_idx);
+                        self.extras_mut().vacant_heads.push(vacant_head_idx);
+                    }
+                    idx += 1;
+                    vacant_head_idx = idx;
+                }
+                Entry::Occupied(value) => {
+                    if f(idx, value) {
+                        if vacant_head_idx < idx {
+                            self.set_vacant_run(vacant_head_idx, idx - vacant_head_idx);
+                            self.extras_mut().vacant_heads.push(vacant_head_idx);
+                        }
+                        bounds = (bounds.0.min(idx), idx);
+                        idx += 1;
+                        len += 1;
+                        vacant_head_idx = idx;
+                    } else {
+                        self.entries.set(idx, Entry::VacantBody);
+                        idx += 1;
+                    }
+                }
+            }
+        }
+        self.entries.truncate(vacant_head_idx);
+        if let Some(x) = &mut self.extras {
+            x.vacant_heads.reverse();
+            x.non_optimized_count = 0;
+            x.optimize_progress = None;
+            (x.min, x.max) = bounds;
+        }
+        self.len = len;
+    }
+
+    /// Optimizing the free space.
+    ///
+    /// Free space at the end is released, and subsequent insertions reuse free space in ascending order of key.
+    ///
+    /// If the free space has already been optimized, this method does nothing and completes with O(1).
+    ///
+    /// # Examples
+    /// ```
+    /// use slabmap::SlabMap;
+    /// use std::time::Instant;
+    ///
+    /// let mut s = SlabMap::new();
+    /// const COUNT: usize = 1000000;
+    /// for i in 0..COUNT {
+    ///     s.insert(i);
+    /// }
+    /// let keys: Vec<_> = s.keys().take(COUNT - 1).collect();
+    /// for key in keys {
+    ///     s.remove(key);
+    /// }
+    ///
+    /// s.optimize();
+    ///
+    /// let begin = Instant::now();
+    /// let sum: usize = s.values().sum();
+    /// println!("sum : {}", sum);
+    /// println!("duration : {} ms", (Instant::now() - begin).as_millis());
+    /// ```
+    pub fn optimize(&mut self) {
+        if !self.is_optimized() {
+            self.rebuild_vacants();
+        }
+    }
+
+    /// Optimizes the free space like [`optimize`](Self::optimize), and then shrinks the capacity.
+    ///
+    /// Free space at the end is always released, even if the free space has already been optimized.
+    /// If `min_capacity` is `Some`, the capacity is shrunk as much as possible, but not below `min_capacity`.
+    /// If `min_capacity` is `None`, the allocation is kept.
+    ///
+    /// # Examples
+    /// ```
+    /// use slabmap::SlabMap;
+    ///
+    /// let mut s = SlabMap::new();
+    /// let keys: Vec<_> = (0..100).map(|i| s.insert(i)).collect();
+    /// for &key in &keys[10..] {
+    ///     s.remove(key);
+    /// }
+    ///
+    /// s.optimize_and_shrink(Some(0));
+    /// assert!(s.capacity() < 100);
+    /// assert_eq!(s.insert(100), 10);
+    /// ```
+    pub fn optimize_and_shrink(&mut self, min_capacity: Option<usize>) {
+        let has_trailing_vacant = matches!(
+            self.entries.last(),
+            Some(Entry::VacantHead { .. } | Entry::VacantTail { .. })
+        );
+        if !self.is_optimized() || has_trailing_vacant {
+            self.rebuild_vacants();
+        }
+        if let Some(min_capacity) = min_capacity {
+            self.entries.shrink_to(min_capacity);
+        }
+    }
+
+    /// Moves values with large keys into free space with small keys, and shrinks the capacity as much as possible.
+    ///
+    /// Before each value is moved, `rekey` is called with the value, the old key and the new key.
+    /// If `rekey` returns false, the value is not moved and stays at the old key.
+    /// Keys in quarantine and claimed keys are not reused.
+    ///
+    /// # Examples
+    /// ```
+    /// use slabmap::SlabMap;
+    ///
+    /// let mut s = SlabMap::new();
+    /// for i in 0..5 {
+    ///     s.insert(i);
+    /// }
+    /// s.remove(0);
+    /// s.remove(2);
+    ///
+    /// let mut moves = Vec::new();
+    /// s.compact(|_, old_key, new_key| {
+    ///     moves.push((old_key, new_key));
+    ///     true
+    /// });
+    /// assert_eq!(moves, [(4, 0), (3, 2)]);
+    /// assert_eq!(s.iter().collect::<Vec<_>>(), [(0, &4), (1, &1), (2, &3)]);
+    /// ```
+    pub fn compact(&mut self, mut rekey: impl FnMut(&mut T, K, K) -> bool) {
+        let is_free = |e: Entry<&T>| {
+            matches!(
+                e,
+                Entry::VacantHead { .. } | Entry::VacantTail { .. } | Entry::VacantBody
+            )
+        };
+        let mut new_idx = 0;
+        let mut old_idx = self.entries.len();
+        loop {
+            while new_idx < old_idx && !is_free(self.entries.entry(new_idx)) {
+                new_idx += 1;
+            }
+            while new_idx < old_idx
+                && !matches!(self.entries.entry(old_idx - 1), Entry::Occupied(_))
+            {
+                old_idx -= 1;
+            }
+            if new_idx >= old_idx {
+                break;
+            }
+            old_idx -= 1;
+            let Some(Entry::Occupied(value)) = self.entries.get_mut(old_idx) else {
+                unreachable!()
+            };
+            if rekey(value, K::from_index(old_idx), K::from_index(new_idx)) {
+                let e = self.entries.replace(old_idx, Entry::VacantBody);
+                self.entries.set(new_idx, e);
+                new_idx += 1;
+            }
+        }
+        self.shrink_to(0);
+    }
+
+    /// Returns true if both SlabMaps contain the same values the same number of times, regardless of their keys.
+    ///
+    /// # Examples
+    /// ```
+    /// use slabmap::SlabMap;
+    ///
+    /// let mut a = SlabMap::new();
+    /// a.insert("x");
+    /// a.insert("y");
+    /// a.insert("x");
+    ///
+    /// let mut b = SlabMap::new();
+    /// b.insert("y");
+    /// let key = b.insert("x");
+    /// assert!(!a.eq_values_ignoring_keys(&b));
+    ///
+    /// b.remove(key);
+    /// b.insert("x");
+    /// b.insert("x");
+    /// assert!(a.eq_values_ignoring_keys(&b));
+    /// ```
+    #[cfg(feature = "std")]
+    pub fn eq_values_ignoring_keys(&self, other: &Self) -> bool
+    where
+        T: Eq + core::hash::Hash,
+    {
+        if self.len != other.len {
+            return false;
+        }
+        let mut counts = std::collections::HashMap::<&T, usize>::with_capacity(self.len);
+        for value in self.values() {
+            *counts.entry(value).or_default() += 1;
+        }
+        for value in other.values() {
+            match counts.get_mut(value) {
+                Some(0) | None => return false,
+                Some(count) => *count -= 1,
+            }
+        }
+        true
+    }
+
+    /// Returns a new SlabMap containing clones of the values at the specified keys.
+    ///
+    /// Keys are preserved. Keys that do not exist are ignored.
+    ///
+    /// # Examples
+    /// ```
+    /// use slabmap::SlabMap;
+    ///
+    /// let mut s = SlabMap::new();
+    /// let a = s.insert("a");
+    /// let b = s.insert("b");
+    /// let c = s.insert("c");
+    ///
+    /// let t = s.clone_subset([a, c]);
+    /// assert_eq!(t.iter().collect::<Vec<_>>(), [(a, &"a"), (c, &"c")]);
+    /// assert_eq!(s.len(), 3);
+    /// ```
+    pub fn clone_subset(&self, keys: impl IntoIterator<Item = K>) -> Self
+    where
+        T: Clone,
+    {
+        Self::from_iter_with_capacity(
+            keys.into_iter()
+                .filter_map(|key| Some((key, self.get(key)?.clone()))),
+            0,
+        )
+    }
+
+    /// Removes the values at the specified keys and returns them as a new SlabMap.
+    ///
+    /// Keys are preserved. Keys that do not exist are ignored.
+    ///
+    /// # Examples
+    /// ```
+    /// use slabmap::SlabMap;
+    ///
+    /// let mut s = SlabMap::new();
+    /// let a = s.insert("a");
+    /// let b = s.insert("b");
+    /// let c = s.insert("c");
+    ///
+    /// let t = s.take_subset([a, c]);
+    /// assert_eq!(t.iter().collect::<Vec<_>>(), [(a, &"a"), (c, &"c")]);
+    /// assert_eq!(s.iter().collect::<Vec<_>>(), [(b, &"b")]);
+    /// ```
+    pub fn take_subset(&mut self, keys: impl IntoIterator<Item = K>) -> Self {
+        Self::from_iter_with_capacity(
+            keys.into_iter()
+                .filter_map(|key| Some((key, self.remove(key)?))),
+            0,
+        )
+    }
+
+    /// Reassigns keys so that the elements occupy keys `0..len` in the order given by `cmp`, and releases all free space.
+    ///
+    /// `remap` is called with the old key and the new key of each element, after all elements have been moved.
+    /// If `cmp` panics, the SlabMap is left unchanged.
+    /// If `remap` panics, the SlabMap still holds all elements at their new keys.
+    /// The sort is stable, so elements that compare equal keep their relative key order.
+    /// Keys in quarantine and claimed keys are released.
+    ///
+    /// # Examples
+    /// ```
+    /// use slabmap::SlabMap;
+    ///
+    /// let mut s = SlabMap::new();
+    /// let a = s.insert(30);
+    /// let b = s.insert(10);
+    /// let c = s.insert(20);
+    /// s.remove(a);
+    ///
+    /// let mut remap = Vec::new();
+    /// s.compact_sorted_by(|x, y| x.cmp(y), |old, new| remap.push((old, new)));
+    ///
+    /// assert_eq!(remap, [(b, 0), (c, 1)]);
+    /// assert_eq!(s.iter().collect::<Vec<_>>(), [(0, &10), (1, &20)]);
+    /// ```
+    pub fn compact_sorted_by(
+        &mut self,
+        mut cmp: impl FnMut(&T, &T) -> Ordering,
+        mut remap: impl FnMut(K, K),
+    ) {
+        // The order is determined before the values are moved, so that a panic in `cmp` leaves the SlabMap unchanged.
+        let mut order: Vec<_> = self.iter().enumerate().collect();
+        order.sort_by(|(_, a), (_, b)| cmp(a.1, b.1));
+        let mut ranks = alloc::vec![0; order.len()];
+        let mut old_keys = Vec::with_capacity(order.len());
+        for (rank, (idx, (old_key, _))) in order.into_iter().enumerate() {
+            ranks[idx] = rank;
+            old_keys.push(old_key);
+        }
+        let mut items: Vec<_> = self.drain().map(|(_, value)| value).zip(ranks).collect();
+        items.sort_unstable_by_key(|&(_, rank)| rank);
+        self.entries.reserve(items.len());
+        for (value, _) in items {
+            self.entries.push(Entry::Occupied(value));
+            self.len += 1;
+        }
+        if let Some(x) = &mut self.extras {
+            (x.min, x.max) = (0, self.len.saturating_sub(1));
+        }
+        // `remap` is called after the SlabMap is consistent, so that a panic in it leaves the elements in place.
+        for (new_key, old_key) in old_keys.into_iter().enumerate() {
+            remap(old_key, K::from_index(new_key));
+        }
+    }
+
+    /// Reassigns keys so that the elements occupy keys `0..len` in key order, and remaps `targets` in the same operation.
+    ///
+    /// Each target receives the pairs of old and new indexes of all elements, see [`Remap`].
+    /// Keys in quarantine and claimed keys are released.
+    ///
+    /// # Examples
+    /// ```
+    /// use slabmap::{secondary_map::SecondaryMap, SlabMap};
+    ///
+    /// let mut s = SlabMap::new();
+    /// let mut colors = SecondaryMap::new();
+    /// let a = s.insert("a");
+    /// let b = s.insert("b");
+    /// colors.insert(b, "red");
+    /// s.remove(a);
+    ///
+    /// let mut handles = vec![b];
+    /// s.compact_remap(&mut [&mut colors, &mut |old, new| {
+    ///     for h in &mut handles {
+    ///         if *h == old {
+    ///             *h = new;
+    ///         }
+    ///     }
+    /// }]);
     ///
-    /// let value: Vec<_> = s.values().cloned().collect();
-    /// assert_eq!(value, vec![10, 20]);
+    /// assert_eq!(handles, [0]);
+    /// assert_eq!(s[0], "b");
+    /// assert_eq!(colors[0], "red");
     /// ```
-    pub fn retain(&mut self, f: impl FnMut(usize, &mut T) -> bool) {
-        self.rebuild_vacants_with(f)
+    pub fn compact_remap(&mut self, targets: &mut [&mut dyn Remap]) {
+        let remap = self.defragment();
+        for target in targets {
+            target.remap(&remap);
+        }
     }
-    pub(crate) fn rebuild_vacants(&mut self) {
-        self.rebuild_vacants_with(|_, _| true);
+
+    /// Reassigns keys so that the elements occupy keys `0..len` in key order, and returns the pairs of old and new indexes of all elements.
+    ///
+    /// The pairs are sorted by old index, so the new index for an old index can be found with a binary search.
+    /// They can also be passed to [`Remap::remap`] later to fix up other structures in bulk.
+    /// Keys in quarantine and claimed keys are released.
+    ///
+    /// # Examples
+    /// ```
+    /// use slabmap::SlabMap;
+    ///
+    /// let mut s = SlabMap::new();
+    /// for i in 0..4 {
+    ///     s.insert(i);
+    /// }
+    /// s.remove(1);
+    ///
+    /// let remap = s.defragment();
+    /// assert_eq!(remap, [(0, 0), (2, 1), (3, 2)]);
+    ///
+    /// let new = remap.binary_search_by_key(&3, |&(old, _)| old).map(|i| remap[i].1);
+    /// assert_eq!(new, Ok(2));
+    /// assert_eq!(s[2], 3);
+    /// ```
+    pub fn defragment(&mut self) -> Vec<(usize, usize)> {
+        let mut remap = Vec::with_capacity(self.len);
+        self.compact_sorted_by(
+            |_, _| Ordering::Equal,
+            |old, new| remap.push((old.into_index(), new.into_index())),
+        );
+        remap
     }
-    fn rebuild_vacants_with(&mut self, mut f: impl FnMut(usize, &mut T) -> bool) {
-        let mut idx = 0;
-        let mut vacant_head_idx = 0;
-        let mut prev_vacant_tail_idx = None;
-        let mut len = 0;
-        self.next_vacant_idx = INVALID_INDEX;
-        while let Some(e) = self.entries.get_mut(idx) {
-            match e {
-                Entry::VacantTail { .. } => {
-                    idx += 1;
-                }
-                Entry::VacantHead { vacant_body_len } => {
-                    idx += *vacant_body_len + 2;
-                }
-                Entry::Occupied(value) => {
-                    if f(idx, value) {
-                        self.set_vacants(vacant_head_idx, idx, &mut prev_vacant_tail_idx);
-                        idx += 1;
-                        len += 1;
-                        vacant_head_idx = idx;
-                    } else {
-                        self.entries[idx] = Entry::VacantTail {
-                            next_vacant_idx: INVALID_INDEX,
-                        };
-                        idx += 1;
-                    }
-                }
-            }
+
+    #[inline]
+    fn is_optimized(&self) -> bool {
+        self.extras().non_optimized_count == 0
+    }
+
+    /// Sets the policy for automatically shrinking the capacity.
+    ///
+    /// If `None` is specified, the capacity is never shrunk automatically. This is the default.
+    ///
+    /// # Panics
+    /// Panics if `max_capacity_ratio` is 2 or less, or `patience` is 0.
+    ///
+    /// # Examples
+    /// ```
+    /// use slabmap::{slab_map::ShrinkPolicy, SlabMap};
+    ///
+    /// let mut s = SlabMap::new();
+    /// s.set_shrink_policy(Some(ShrinkPolicy {
+    ///     max_capacity_ratio: 4,
+    ///     patience: 10,
+    ///     min_capacity: 16,
+    /// }));
+    /// let keys: Vec<_> = (0..1000).map(|i| s.insert(i)).collect();
+    /// for &key in keys[10..].iter().rev() {
+    ///     s.remove(key);
+    /// }
+    /// assert!(s.capacity() < 100);
+    /// ```
+    pub fn set_shrink_policy(&mut self, policy: Option<ShrinkPolicy>) {
+        if let Some(p) = &policy {
+            assert!(
+                p.max_capacity_ratio > 2,
+                "max_capacity_ratio must be greater than 2."
+            );
+            assert!(p.patience != 0, "patience must not be 0.");
         }
-        self.entries.truncate(vacant_head_idx);
-        self.non_optimized_count = 0;
-        self.len = len;
+        self.extras_mut().auto_shrink = policy.map(|policy| AutoShrink {
+            policy,
+            underused_count: 0,
+        });
     }
-    fn set_vacants(
-        &mut self,
-        vacant_head_idx: usize,
-        vacant_end_idx: usize,
-        prev_vacant_tail_idx: &mut Option<usize>,
-    ) {
-        if vacant_head_idx >= vacant_end_idx {
+
+    /// Returns the policy for automatically shrinking the capacity.
+    #[inline]
+    pub fn shrink_policy(&self) -> Option<&ShrinkPolicy> {
+        self.extras().auto_shrink.as_ref().map(|a| &a.policy)
+    }
+
+    fn apply_shrink_policy(&mut self) {
+        let capacity = self.entries.capacity();
+        let len = self.len;
+        let Some(a) = &mut self.extras_mut().auto_shrink else {
+            return;
+        };
+        let p = a.policy;
+        if capacity <= p.min_capacity || len.saturating_mul(p.max_capacity_ratio) >= capacity {
+            a.underused_count = 0;
             return;
         }
-        if self.next_vacant_idx == INVALID_INDEX {
-            self.next_vacant_idx = vacant_head_idx;
+        a.underused_count += 1;
+        if a.underused_count < p.patience {
+            return;
         }
-        if vacant_head_idx + 2 <= vacant_end_idx {
-            self.entries[vacant_head_idx] = Entry::VacantHead {
-                vacant_body_len: vacant_end_idx - (vacant_head_idx + 2),
-            };
+        a.underused_count = 0;
+        let target = len.saturating_mul(2).max(p.min_capacity);
+        let new_capacity = self.trimmed_len().max(target);
+        // Shrink only if it brings the SlabMap out of the underused state or at least halves the capacity,
+        // so that the rebuild of free space is amortized even if the capacity cannot be reduced enough.
+        if new_capacity <= len.saturating_mul(p.max_capacity_ratio) || new_capacity <= capacity / 2
+        {
+            self.shrink_to(target);
         }
-        self.entries[vacant_end_idx - 1] = Entry::VacantTail {
-            next_vacant_idx: INVALID_INDEX,
-        };
-        if let Some(prev_vacant_tail_idx) = *prev_vacant_tail_idx {
-            self.entries[prev_vacant_tail_idx] = Entry::VacantTail {
-                next_vacant_idx: vacant_head_idx,
-            };
+    }
+
+    /// Returns the length of the entries without the free space at the end.
+    fn trimmed_len(&self) -> usize {
+        match self.entries.last() {
+            Some(Entry::VacantHead { vacant_len } | Entry::VacantTail { vacant_len }) => {
+                self.entries.len() - run_len(vacant_len)
+            }
+            _ => self.entries.len(),
         }
-        *prev_vacant_tail_idx = Some(vacant_end_idx - 1);
     }
 
-    /// Optimizing the free space for speeding up iterations.
+    /// Releases free space at the end and shrinks the capacity with a lower bound.
+    pub(crate) fn shrink_to(&mut self, min_capacity: usize) {
+        self.rebuild_vacants();
+        self.entries.shrink_to(min_capacity);
+    }
+
+    /// Sets the number of insertions for which removed keys are kept in quarantine.
     ///
-    /// If the free space has already been optimized, this method does nothing and completes with O(1).
+    /// A key in quarantine is not reused until at least `window` subsequent insertions have been made,
+    /// which bounds the window in which a stale key can refer to a new value.
+    /// If `0` is specified, removed keys are reused immediately. This is the default.
     ///
     /// # Examples
     /// ```
     /// use slabmap::SlabMap;
-    /// use std::time::Instant;
     ///
     /// let mut s = SlabMap::new();
-    /// const COUNT: usize = 1000000;
-    /// for i in 0..COUNT {
+    /// s.set_quarantine(2);
+    /// let k0 = s.insert(0);
+    /// s.insert(1);
+    /// s.remove(k0);
+    ///
+    /// assert_ne!(s.insert(2), k0);
+    /// assert_ne!(s.insert(3), k0);
+    /// assert_eq!(s.insert(4), k0);
+    /// ```
+    pub fn set_quarantine(&mut self, window: usize) {
+        let q = &mut self.extras_mut().quarantine;
+        for (_, release_at) in &mut q.keys {
+            let rest = release_at.wrapping_sub(q.inserts).min(window);
+            *release_at = q.inserts.wrapping_add(rest);
+        }
+        q.window = window;
+        let inserts = q.inserts;
+        self.release_quarantine(inserts);
+    }
+
+    /// Returns the number of insertions for which removed keys are kept in quarantine.
+    #[inline]
+    pub fn quarantine(&self) -> usize {
+        self.extras().quarantine.window
+    }
+
+    /// Returns the number of keys currently in quarantine.
+    #[inline]
+    pub fn quarantined_len(&self) -> usize {
+        self.extras().quarantine.keys.len()
+    }
+
+    /// Releases all keys in quarantine so that they can be reused immediately.
+    pub fn flush_quarantine(&mut self) {
+        self.release_quarantine(
+            self.extras()
+                .quarantine
+                .inserts
+                .wrapping_add(self.extras().quarantine.window),
+        );
+    }
+
+    fn release_quarantine(&mut self, inserts: usize) {
+        while let Some(&(key, release_at)) = self.extras().quarantine.keys.front() {
+            if inserts.wrapping_sub(release_at) > usize::MAX / 2 {
+                break;
+            }
+            self.extras_mut().quarantine.keys.pop_front();
+            self.entries.set(key, Entry::VacantBody);
+            self.merge_vacant(key);
+            self.extras_mut().non_optimized_count += 1;
+        }
+        if self.is_unused() {
+            self.clear();
+        }
+    }
+
+    /// Gets an iterator over the entries of the SlabMap, sorted by key.
+    #[inline]
+    pub fn iter(&self) -> Iter<T, K> {
+        Iter {
+            iter: self.entries.iter(),
+            len: self.len,
+            _key: PhantomData,
+        }
+    }
+
+    /// Gets an iterator over the entries whose keys are greater than or equal to `token`, sorted by key.
+    ///
+    /// The returned [`Cursor`] reports a resume token with [`Cursor::token`].
+    /// Since the token is just the next key to visit, it stays valid across insertions and removals of other keys
+    /// and can be stored outside the process, for example in paginated APIs.
+    /// Entries inserted after the token was taken are visited if their keys are not less than the token.
+    ///
+    /// # Examples
+    /// ```
+    /// use slabmap::SlabMap;
+    ///
+    /// let mut s = SlabMap::new();
+    /// for i in 0..10 {
     ///     s.insert(i);
     /// }
-    /// let keys: Vec<_> = s.keys().take(COUNT - 1).collect();
-    /// for key in keys {
-    ///     s.remove(key);
-    /// }
     ///
-    /// s.optimize(); // if comment out this line, `s.values().sum()` to be slow.
+    /// let mut page = s.iter_from_token(0);
+    /// let first: Vec<_> = page.by_ref().take(4).map(|(_, v)| *v).collect();
+    /// let token = page.token();
+    /// assert_eq!(first, [0, 1, 2, 3]);
     ///
-    /// let begin = Instant::now();
-    /// let sum: usize = s.values().sum();
-    /// println!("sum : {}", sum);
-    /// println!("duration : {} ms", (Instant::now() - begin).as_millis());
+    /// s.remove(1);
+    /// s.remove(5);
+    /// let rest: Vec<_> = s.iter_from_token(token).map(|(_, v)| *v).collect();
+    /// assert_eq!(rest, [4, 6, 7, 8, 9]);
     /// ```
-    pub fn optimize(&mut self) {
-        if !self.is_optimized() {
-            self.rebuild_vacants();
+    #[inline]
+    pub fn iter_from_token(&self, token: usize) -> Cursor<T, K> {
+        Cursor {
+            entries: &self.entries,
+            pos: token,
+            _key: PhantomData,
+        }
+    }
+
+    /// Gets a mutable iterator over the entries of the slab, sorted by key.
+    #[inline]
+    pub fn iter_mut(&mut self) -> IterMut<T, K> {
+        IterMut {
+            iter: self.entries.iter_mut(),
+            len: self.len,
+            _key: PhantomData,
+        }
+    }
+
+    /// Gets a mutable iterator over the entries with the specified keys, sorted by key.
+    ///
+    /// The keys are sorted and deduplicated, and keys that do not exist are skipped.
+    ///
+    /// # Examples
+    /// ```
+    /// use slabmap::SlabMap;
+    ///
+    /// let mut s = SlabMap::new();
+    /// for i in 0..10 {
+    ///     s.insert(i);
+    /// }
+    /// for (_, value) in s.iter_keys_mut([7, 2, 7, 100, 5]) {
+    ///     *value *= 10;
+    /// }
+    ///
+    /// assert_eq!(s.values().copied().collect::<Vec<_>>(), [0, 1, 20, 3, 4, 50, 6, 70, 8, 9]);
+    /// ```
+    pub fn iter_keys_mut(&mut self, keys: impl IntoIterator<Item = K>) -> IterKeysMut<T, K> {
+        let mut keys: Vec<usize> = keys.into_iter().map(K::into_index).collect();
+        keys.sort_unstable();
+        keys.dedup();
+        IterKeysMut {
+            keys: keys.into_iter(),
+            iter: self.entries.iter_mut(),
+            offset: 0,
+            _key: PhantomData,
         }
     }
 
+    /// Gets an iterator over the entries whose keys are in the specified range, sorted by key.
+    ///
+    /// Free space runs are skipped in the same way as [`iter`](Self::iter).
+    ///
+    /// # Examples
+    /// ```
+    /// use slabmap::SlabMap;
+    ///
+    /// let mut s = SlabMap::new();
+    /// for i in 0..10 {
+    ///     s.insert(i * 10);
+    /// }
+    /// s.remove(4);
+    ///
+    /// let r: Vec<_> = s.range(3..6).collect();
+    /// assert_eq!(r, [(3, &30), (5, &50)]);
+    /// assert_eq!(s.range(8..).count(), 2);
+    /// ```
     #[inline]
-    fn is_optimized(&self) -> bool {
-        self.non_optimized_count == 0
+    pub fn range(&self, range: impl RangeBounds<K>) -> Range<T, K> {
+        let ops::Range { start, end } = self.index_range(range);
+        Range {
+            iter: self.entries.range(start..end),
+            _key: PhantomData,
+        }
     }
 
-    /// Gets an iterator over the entries of the SlabMap, sorted by key.
+    /// Gets a mutable iterator over the entries whose keys are in the specified range, sorted by key.
+    ///
+    /// # Examples
+    /// ```
+    /// use slabmap::SlabMap;
+    ///
+    /// let mut s = SlabMap::new();
+    /// for i in 0..5 {
+    ///     s.insert(i);
+    /// }
+    /// for (_, value) in s.range_mut(1..=2) {
+    ///     *value *= 10;
+    /// }
     ///
-    /// If you make a large number of [`remove`](SlabMap::remove) calls, [`optimize`](SlabMap::optimize) should be called before calling this function.
+    /// assert_eq!(s.values().copied().collect::<Vec<_>>(), [0, 10, 20, 3, 4]);
+    /// ```
+    #[inline]
+    pub fn range_mut(&mut self, range: impl RangeBounds<K>) -> RangeMut<T, K> {
+        let ops::Range { start, end } = self.index_range(range);
+        RangeMut {
+            iter: self.entries.range_mut(start..end),
+            _key: PhantomData,
+        }
+    }
+
+    /// Gets an iterator over the keys of the SlabMap, in sorted order.
+    #[inline]
+    pub fn keys(&self) -> Keys<T, K> {
+        Keys(self.iter())
+    }
+
+    /// Gets an iterator over the values of the SlabMap.
     #[inline]
-    pub fn iter(&self) -> Iter<T> {
+    pub fn values(&self) -> Values<T> {
+        Values(self.raw_iter())
+    }
+    fn raw_iter(&self) -> Iter<T> {
         Iter {
-            iter: self.entries.iter().enumerate(),
+            iter: self.entries.iter(),
             len: self.len,
+            _key: PhantomData,
         }
     }
 
-    /// Gets a mutable iterator over the entries of the slab, sorted by key.
+    /// Gets an iterator over the values of the SlabMap in unspecified order.
+    ///
+    /// The order may change between versions. Currently, values are visited from the largest key,
+    /// and the iteration ends as soon as all values have been visited, so free space before the smallest key is never scanned.
+    ///
+    /// # Examples
+    /// ```
+    /// use slabmap::SlabMap;
+    ///
+    /// let mut s = SlabMap::new();
+    /// for i in 0..100 {
+    ///     s.insert(i);
+    /// }
+    /// s.retain(|key, _| key >= 90);
     ///
-    /// If you make a large number of [`remove`](SlabMap::remove) calls, [`optimize`](SlabMap::optimize) should be called before calling this function.
+    /// assert_eq!(s.values_unordered().sum::<i32>(), (90..100).sum::<i32>());
+    /// ```
     #[inline]
-    pub fn iter_mut(&mut self) -> IterMut<T> {
-        IterMut {
-            iter: self.entries.iter_mut().enumerate(),
+    pub fn values_unordered(&self) -> ValuesUnordered<T> {
+        ValuesUnordered {
+            entries: &self.entries,
+            end: self.entries.len(),
+            len: self.len,
+        }
+    }
+
+    /// Calls `f` for each value of the SlabMap in unspecified order.
+    ///
+    /// See [`values_unordered`](Self::values_unordered) for the order.
+    #[inline]
+    pub fn for_each_unordered(&self, f: impl FnMut(&T)) {
+        self.values_unordered().for_each(f)
+    }
+
+    /// Gets a mutable iterator over the values of the SlabMap.
+    #[inline]
+    pub fn values_mut(&mut self) -> ValuesMut<T> {
+        ValuesMut(IterMut {
+            iter: self.entries.iter_mut(),
+            len: self.len,
+            _key: PhantomData,
+        })
+    }
+
+    /// Returns a copy of the SlabMap by copying the entry buffer as a whole.
+    ///
+    /// Unlike [`clone`](Clone::clone), this never calls `T::clone` and keeps the layout of free spaces as is.
+    ///
+    /// # Examples
+    /// ```
+    /// use slabmap::SlabMap;
+    ///
+    /// let mut s = SlabMap::new();
+    /// let key = s.insert(10);
+    /// s.insert(20);
+    /// s.remove(key);
+    ///
+    /// let c = s.clone_copy();
+    /// assert_eq!(c.iter().collect::<Vec<_>>(), s.iter().collect::<Vec<_>>());
+    /// ```
+    pub fn clone_copy(&self) -> Self
+    where
+        T: Copy,
+    {
+        let mut entries = Entries::with_capacity(self.entries.len());
+        entries.extend_from_entries(&self.entries);
+        let mut extras = self.extras.clone();
+        if let Some(x) = &mut extras {
+            x.optimize_progress = None;
+        }
+        Self {
+            entries,
             len: self.len,
+            extras,
+            _key: PhantomData,
+        }
+    }
+
+    /// Overwrites the SlabMap with a copy of `source`, reusing the allocation of `self`.
+    ///
+    /// This is the [`clone_from`](Clone::clone_from) counterpart of [`clone_copy`](Self::clone_copy).
+    pub fn clone_from_copy(&mut self, source: &Self)
+    where
+        T: Copy,
+    {
+        self.entries.clear();
+        self.entries.extend_from_entries(&source.entries);
+        self.len = source.len;
+        self.extras.clone_from(&source.extras);
+        if let Some(x) = &mut self.extras {
+            x.optimize_progress = None;
         }
     }
 
-    /// Gets an iterator over the keys of the SlabMap, in sorted order.
+    /// Clears the SlabMap, appending copies of all values to `out` in key order.
+    ///
+    /// Unlike [`drain`](Self::drain), the keys are not returned,
+    /// and each run of values at consecutive keys is copied as a whole instead of one by one.
+    ///
+    /// # Examples
+    /// ```
+    /// use slabmap::SlabMap;
+    ///
+    /// let mut s = SlabMap::new();
+    /// for i in 0..5 {
+    ///     s.insert(i);
+    /// }
+    /// s.remove(2);
+    ///
+    /// let mut out = vec![9];
+    /// s.drain_copy_into(&mut out);
+    /// assert_eq!(out, [9, 0, 1, 3, 4]);
+    /// assert!(s.is_empty());
+    /// ```
+    pub fn drain_copy_into(&mut self, out: &mut Vec<T>)
+    where
+        T: Copy,
+    {
+        out.reserve(self.len);
+        self.entries.copy_occupied_into(out);
+        self.clear();
+    }
+
+    /// Creates a consuming iterator over the keys of the SlabMap, in sorted order.
+    ///
+    /// # Examples
+    /// ```
+    /// use slabmap::SlabMap;
+    ///
+    /// let mut s = SlabMap::new();
+    /// s.insert("a");
+    /// s.insert("b");
+    /// assert_eq!(s.into_keys().collect::<Vec<_>>(), [0, 1]);
+    /// ```
+    #[inline]
+    pub fn into_keys(self) -> IntoKeys<T, K> {
+        IntoKeys(self.into_iter())
+    }
+
+    /// Creates a consuming iterator over the values of the SlabMap, sorted by key.
+    ///
+    /// # Examples
+    /// ```
+    /// use slabmap::SlabMap;
     ///
-    /// If you make a large number of [`remove`](SlabMap::remove) calls, [`optimize`](SlabMap::optimize) should be called before calling this function.
+    /// let mut s = SlabMap::new();
+    /// s.insert("a");
+    /// s.insert("b");
+    /// assert_eq!(s.into_values().collect::<Vec<_>>(), ["a", "b"]);
+    /// ```
     #[inline]
-    pub fn keys(&self) -> Keys<T> {
-        Keys(self.iter())
+    pub fn into_values(self) -> IntoValues<T, K> {
+        IntoValues(self.into_iter())
     }
 
-    /// Gets an iterator over the values of the SlabMap.
+    /// Consumes the SlabMap and returns its slots, where the index of each slot is the key and `None` is free space.
     ///
-    /// If you make a large number of [`remove`](SlabMap::remove) calls, [`optimize`](SlabMap::optimize) should be called before calling this function.
-    #[inline]
-    pub fn values(&self) -> Values<T> {
-        Values(self.iter())
+    /// The slots may end with `None`. This is the inverse of [`from_slots`](Self::from_slots).
+    ///
+    /// # Examples
+    /// ```
+    /// use slabmap::SlabMap;
+    ///
+    /// let mut s = SlabMap::new();
+    /// s.insert("a");
+    /// s.insert("b");
+    /// s.insert("c");
+    /// s.remove(1);
+    /// assert_eq!(s.into_vec(), [Some("a"), None, Some("c")]);
+    /// ```
+    pub fn into_vec(self) -> Vec<Option<T>> {
+        self.entries
+            .into_iter()
+            .map(|(_, e)| match e {
+                Entry::Occupied(value) => Some(value),
+                _ => None,
+            })
+            .collect()
     }
 
-    /// Gets a mutable iterator over the values of the SlabMap.
+    /// Consumes the SlabMap and leaks it, returning a mutable reference with `'static` lifetime.
     ///
-    /// If you make a large number of [`remove`](SlabMap::remove) calls, [`optimize`](SlabMap::optimize) should be called before calling this function.
-    #[inline]
-    pub fn values_mut(&mut self) -> ValuesMut<T> {
-        ValuesMut(self.iter_mut())
+    /// This is useful for data that lives for the remainder of the program's life, such as a global registry built at startup.
+    /// Dropping the returned reference will cause a memory leak.
+    ///
+    /// # Examples
+    /// ```
+    /// use slabmap::SlabMap;
+    ///
+    /// let mut s = SlabMap::new();
+    /// let key = s.insert("a");
+    ///
+    /// let s: &'static mut SlabMap<&str> = s.leak();
+    /// assert_eq!(s[key], "a");
+    /// ```
+    pub fn leak(self) -> &'static mut Self
+    where
+        T: 'static,
+    {
+        Box::leak(Box::new(self))
     }
 }
-impl<T: Debug> Debug for SlabMap<T> {
-    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+impl<T: Debug, K: SlabKey + Debug> Debug for SlabMap<T, K> {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
         f.debug_map().entries(self.iter()).finish()
     }
 }
 
-impl<T> std::ops::Index<usize> for SlabMap<T> {
+impl<T, K: SlabKey> core::ops::Index<K> for SlabMap<T, K> {
     type Output = T;
 
     #[inline]
-    fn index(&self, index: usize) -> &Self::Output {
+    fn index(&self, index: K) -> &Self::Output {
         self.get(index).expect("out of index.")
     }
 }
-impl<T> std::ops::IndexMut<usize> for SlabMap<T> {
+impl<T, K: SlabKey> core::ops::IndexMut<K> for SlabMap<T, K> {
     #[inline]
-    fn index_mut(&mut self, index: usize) -> &mut Self::Output {
+    fn index_mut(&mut self, index: K) -> &mut Self::Output {
         self.get_mut(index).expect("out of index.")
     }
 }
 
-impl<T> FromIterator<(usize, T)> for SlabMap<T> {
-    fn from_iter<I: IntoIterator<Item = (usize, T)>>(iter: I) -> Self {
+impl<T, K: SlabKey> From<Vec<T>> for SlabMap<T, K> {
+    fn from(values: Vec<T>) -> Self {
+        Self::from_vec(values)
+    }
+}
+
+impl<T, K: SlabKey> From<Vec<Option<T>>> for SlabMap<T, K> {
+    fn from(slots: Vec<Option<T>>) -> Self {
+        Self::from_slots(slots)
+    }
+}
+
+impl<T, K: SlabKey> FromIterator<(K, T)> for SlabMap<T, K> {
+    fn from_iter<I: IntoIterator<Item = (K, T)>>(iter: I) -> Self {
         Self::from_iter_with_capacity(iter, 0)
     }
 }
 
-impl<T> IntoIterator for SlabMap<T> {
-    type Item = (usize, T);
-    type IntoIter = IntoIter<T>;
+/// Inserts the values in order, filling free space first.
+///
+/// Once there is no free space left, the remaining values are appended to the end.
+impl<T, K: SlabKey> Extend<T> for SlabMap<T, K> {
+    fn extend<I: IntoIterator<Item = T>>(&mut self, iter: I) {
+        let mut iter = iter.into_iter();
+        self.reserve(iter.size_hint().0);
+        if self.extras().quarantine.window != 0 || !self.extras().quarantine.keys.is_empty() {
+            for value in iter {
+                self.insert(value);
+            }
+            return;
+        }
+        while self.next_vacant_idx().is_some() {
+            let Some(value) = iter.next() else {
+                return;
+            };
+            self.insert(value);
+        }
+        // The SlabMap is updated for each value, so that it stays consistent if the iterator panics.
+        for value in iter {
+            let idx = self.entries.len();
+            self.entries.push(Entry::Occupied(value));
+            self.len += 1;
+            self.on_occupied(idx);
+        }
+    }
+}
+
+impl<T, K: SlabKey> IntoIterator for SlabMap<T, K> {
+    type Item = (K, T);
+    type IntoIter = IntoIter<T, K>;
 
     #[inline]
     fn into_iter(self) -> Self::IntoIter {
         IntoIter {
-            iter: self.entries.into_iter().enumerate(),
+            iter: self.entries.into_iter(),
             len: self.len,
+            _key: PhantomData,
         }
     }
 }
 
-impl<'a, T> IntoIterator for &'a SlabMap<T> {
-    type Item = (usize, &'a T);
-    type IntoIter = Iter<'a, T>;
+impl<'a, T, K: SlabKey> IntoIterator for &'a SlabMap<T, K> {
+    type Item = (K, &'a T);
+    type IntoIter = Iter<'a, T, K>;
 
     #[inline]
     fn into_iter(self) -> Self::IntoIter {
         self.iter()
     }
 }
-impl<'a, T> IntoIterator for &'a mut SlabMap<T> {
-    type Item = (usize, &'a mut T);
-    type IntoIter = IterMut<'a, T>;
+impl<'a, T, K: SlabKey> IntoIterator for &'a mut SlabMap<T, K> {
+    type Item = (K, &'a mut T);
+    type IntoIter = IterMut<'a, T, K>;
 
     #[inline]
     fn into_iter(self) -> Self::IntoIter {
@@ -589,29 +2708,100 @@ impl<'a, T> IntoIterator for &'a mut SlabMap<T> {
     }
 }
 
+/// A handle to a vacant entry in a [`SlabMap`].
+///
+/// This struct is created by the [`vacant_entry`](SlabMap::vacant_entry).
+pub struct VacantEntry<'a, T, K = usize> {
+    map: &'a mut SlabMap<T, K>,
+    key: K,
+}
+impl<'a, T, K: SlabKey> VacantEntry<'a, T, K> {
+    /// Returns the key that the value will be associated with.
+    #[inline]
+    pub fn key(&self) -> K {
+        self.key
+    }
+
+    /// Inserts a value into the entry and returns a mutable reference to it.
+    pub fn insert(self, value: T) -> &'a mut T {
+        let (key, value) = self.map.insert_mut(value);
+        debug_assert_eq!(key.into_index(), self.key.into_index());
+        value
+    }
+}
+impl<T, K: Debug> Debug for VacantEntry<'_, T, K> {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        f.debug_struct("VacantEntry")
+            .field("key", &self.key)
+            .finish()
+    }
+}
+
+/// An error returned by [`SlabMap::try_insert_at`] when the key is already occupied or claimed, containing the value that could not be inserted.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct OccupiedError<T, K = usize> {
+    /// The key that is already occupied.
+    pub key: K,
+    /// The value that could not be inserted.
+    pub value: T,
+}
+impl<T, K: Debug> core::fmt::Display for OccupiedError<T, K> {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        write!(f, "key {:?} is already occupied", self.key)
+    }
+}
+impl<T: Debug, K: Debug> core::error::Error for OccupiedError<T, K> {}
+
+/// An error returned by [`SlabMap::append`] when a key is used in both SlabMaps.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct KeyConflict<K = usize> {
+    /// The key that is used in both SlabMaps.
+    pub key: K,
+}
+impl<K: Debug> core::fmt::Display for KeyConflict<K> {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        write!(f, "key {:?} is used in both maps", self.key)
+    }
+}
+impl<K: Debug> core::error::Error for KeyConflict<K> {}
+
 /// An owning iterator over the values of a [`SlabMap`].
 ///
 /// This struct is created by the [`into_iter`](SlabMap::into_iter).
-pub struct IntoIter<T> {
-    iter: Enumerate<std::vec::IntoIter<Entry<T>>>,
+pub struct IntoIter<T, K = usize> {
+    iter: storage::IntoIter<T>,
     len: usize,
+    _key: PhantomData<fn() -> K>,
 }
-impl<T> Iterator for IntoIter<T> {
-    type Item = (usize, T);
+impl<T, K: SlabKey> Iterator for IntoIter<T, K> {
+    type Item = (K, T);
     #[inline]
     fn next(&mut self) -> Option<Self::Item> {
-        let mut e_opt = self.iter.next();
-        while let Some(e) = e_opt {
-            e_opt = match e.1 {
-                Entry::Occupied(value) => {
-                    self.len -= 1;
-                    return Some((e.0, value));
-                }
-                Entry::VacantHead { vacant_body_len } => self.iter.nth(vacant_body_len + 1),
-                Entry::VacantTail { .. } => self.iter.next(),
-            }
+        let (key, value) = self.iter.next_occupied()?;
+        self.len -= 1;
+        Some((K::from_index(key), value))
+    }
+    #[inline]
+    fn nth(&mut self, n: usize) -> Option<Self::Item> {
+        let e = self.iter.nth_occupied(n, self.len);
+        self.len = self.len.saturating_sub(n).saturating_sub(1);
+        e.map(|(key, value)| (K::from_index(key), value))
+    }
+    #[inline]
+    fn last(mut self) -> Option<Self::Item> {
+        let n = self.len.checked_sub(1)?;
+        self.nth(n)
+    }
+    #[inline]
+    fn fold<B, F>(mut self, init: B, mut f: F) -> B
+    where
+        F: FnMut(B, Self::Item) -> B,
+    {
+        let mut acc = init;
+        while let Some((key, value)) = self.iter.next_occupied() {
+            acc = f(acc, (K::from_index(key), value));
         }
-        None
+        acc
     }
     #[inline]
     fn size_hint(&self) -> (usize, Option<usize>) {
@@ -625,18 +2815,69 @@ impl<T> Iterator for IntoIter<T> {
         self.len
     }
 }
-impl<T> FusedIterator for IntoIter<T> {}
-impl<T> ExactSizeIterator for IntoIter<T> {}
+impl<T, K: SlabKey> FusedIterator for IntoIter<T, K> {}
+impl<T, K: SlabKey> ExactSizeIterator for IntoIter<T, K> {}
+
+/// An owning iterator over the keys of a [`SlabMap`].
+///
+/// This struct is created by the [`into_keys`](SlabMap::into_keys).
+pub struct IntoKeys<T, K = usize>(IntoIter<T, K>);
+impl<T, K: SlabKey> Iterator for IntoKeys<T, K> {
+    type Item = K;
+    #[inline]
+    fn next(&mut self) -> Option<Self::Item> {
+        self.0.next().map(|(k, _)| k)
+    }
+    #[inline]
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        self.0.size_hint()
+    }
+    #[inline]
+    fn count(self) -> usize
+    where
+        Self: Sized,
+    {
+        self.0.count()
+    }
+}
+impl<T, K: SlabKey> FusedIterator for IntoKeys<T, K> {}
+impl<T, K: SlabKey> ExactSizeIterator for IntoKeys<T, K> {}
+
+/// An owning iterator over the values of a [`SlabMap`].
+///
+/// This struct is created by the [`into_values`](SlabMap::into_values).
+pub struct IntoValues<T, K = usize>(IntoIter<T, K>);
+impl<T, K: SlabKey> Iterator for IntoValues<T, K> {
+    type Item = T;
+    #[inline]
+    fn next(&mut self) -> Option<Self::Item> {
+        self.0.next().map(|(_, v)| v)
+    }
+    #[inline]
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        self.0.size_hint()
+    }
+    #[inline]
+    fn count(self) -> usize
+    where
+        Self: Sized,
+    {
+        self.0.count()
+    }
+}
+impl<T, K: SlabKey> FusedIterator for IntoValues<T, K> {}
+impl<T, K: SlabKey> ExactSizeIterator for IntoValues<T, K> {}
 
 /// A draining iterator for [`SlabMap`].
 ///
 /// This struct is created by the [`drain`](SlabMap::drain).
-pub struct Drain<'a, T> {
-    iter: Enumerate<std::vec::Drain<'a, Entry<T>>>,
+pub struct Drain<'a, T, K = usize> {
+    iter: storage::Drain<'a, T>,
     len: usize,
+    _key: PhantomData<fn() -> K>,
 }
-impl<'a, T> Iterator for Drain<'a, T> {
-    type Item = (usize, T);
+impl<T, K: SlabKey> Iterator for Drain<'_, T, K> {
+    type Item = (K, T);
     #[inline]
     fn next(&mut self) -> Option<Self::Item> {
         let (mut key, mut value) = self.iter.next()?;
@@ -644,10 +2885,19 @@ impl<'a, T> Iterator for Drain<'a, T> {
             (key, value) = match value {
                 Entry::Occupied(value) => {
                     self.len -= 1;
-                    return Some((key, value));
+                    return Some((K::from_index(key), value));
+                }
+                Entry::VacantHead { vacant_len } => {
+                    self.iter.skip_vacant(run_len(vacant_len) - 1);
+                    self.iter.next()?
+                }
+                Entry::VacantTail { .. }
+                | Entry::VacantBody
+                | Entry::Quarantined
+                | Entry::Claimed => {
+                    self.iter.skip_passive();
+                    self.iter.next()?
                 }
-                Entry::VacantHead { vacant_body_len } => self.iter.nth(vacant_body_len + 1)?,
-                Entry::VacantTail { .. } => self.iter.next()?,
             }
         }
     }
@@ -663,31 +2913,110 @@ impl<'a, T> Iterator for Drain<'a, T> {
         self.len
     }
 }
-impl<'a, T> FusedIterator for Drain<'a, T> {}
-impl<'a, T> ExactSizeIterator for Drain<'a, T> {}
+impl<T, K: SlabKey> FusedIterator for Drain<'_, T, K> {}
+impl<T, K: SlabKey> ExactSizeIterator for Drain<'_, T, K> {}
+
+/// A draining iterator over the entries of a [`SlabMap`] whose keys are in a range.
+///
+/// This struct is created by the [`drain_range`](SlabMap::drain_range).
+pub struct DrainRange<'a, T, K: SlabKey = usize> {
+    map: &'a mut SlabMap<T, K>,
+    idx: usize,
+    start: usize,
+    end: usize,
+    drained: usize,
+}
+impl<T, K: SlabKey> Iterator for DrainRange<'_, T, K> {
+    type Item = (K, T);
+    fn next(&mut self) -> Option<Self::Item> {
+        while self.idx < self.end {
+            let idx = self.idx;
+            match self.map.entries.entry(idx) {
+                Entry::Occupied(_) => {
+                    self.idx += 1;
+                    let q = &mut self.map.extras_mut().quarantine;
+                    let e = if q.window != 0 {
+                        q.keys.push_back((idx, q.inserts.wrapping_add(q.window)));
+                        Entry::Quarantined
+                    } else {
+                        self.drained += 1;
+                        Entry::VacantBody
+                    };
+                    self.map.len -= 1;
+                    let Entry::Occupied(value) = self.map.entries.replace(idx, e) else {
+                        unreachable!()
+                    };
+                    return Some((K::from_index(idx), value));
+                }
+                Entry::VacantHead { vacant_len } => self.idx += run_len(vacant_len),
+                Entry::VacantTail { .. }
+                | Entry::VacantBody
+                | Entry::Quarantined
+                | Entry::Claimed => {
+                    self.idx = self.map.entries.skip_passive(self.idx + 1).min(self.end)
+                }
+            }
+        }
+        None
+    }
+    #[inline]
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        (0, Some(self.end - self.idx))
+    }
+}
+impl<T, K: SlabKey> FusedIterator for DrainRange<'_, T, K> {}
+impl<T, K: SlabKey> Drop for DrainRange<'_, T, K> {
+    fn drop(&mut self) {
+        self.for_each(drop);
+        if self.drained != 0 {
+            self.map.rebuild_vacant_span(self.start, self.end);
+            self.map.extras_mut().non_optimized_count += self.drained;
+        }
+        self.map.on_vacated(self.start, self.end);
+        if self.map.is_unused() {
+            self.map.clear();
+        }
+        self.map.count_operation();
+    }
+}
 
 /// An iterator over the entries of a [`SlabMap`].
 ///
 /// This struct is created by the [`iter`](SlabMap::iter).
-pub struct Iter<'a, T> {
-    iter: std::iter::Enumerate<std::slice::Iter<'a, Entry<T>>>,
+pub struct Iter<'a, T, K = usize> {
+    iter: storage::Iter<'a, T>,
     len: usize,
+    _key: PhantomData<fn() -> K>,
 }
-impl<'a, T> Iterator for Iter<'a, T> {
-    type Item = (usize, &'a T);
+impl<'a, T, K: SlabKey> Iterator for Iter<'a, T, K> {
+    type Item = (K, &'a T);
     #[inline]
     fn next(&mut self) -> Option<Self::Item> {
-        let (mut key, mut value) = self.iter.next()?;
-        loop {
-            (key, value) = match value {
-                Entry::Occupied(value) => {
-                    self.len -= 1;
-                    return Some((key, value));
-                }
-                Entry::VacantHead { vacant_body_len } => self.iter.nth(*vacant_body_len + 1)?,
-                Entry::VacantTail { .. } => self.iter.next()?,
-            }
+        let (key, value) = self.iter.next_occupied()?;
+        self.len -= 1;
+        Some((K::from_index(key), value))
+    }
+    #[inline]
+    fn nth(&mut self, n: usize) -> Option<Self::Item> {
+        let e = self.iter.nth_occupied(n, self.len);
+        self.len = self.len.saturating_sub(n).saturating_sub(1);
+        e.map(|(key, value)| (K::from_index(key), value))
+    }
+    #[inline]
+    fn last(mut self) -> Option<Self::Item> {
+        let n = self.len.checked_sub(1)?;
+        self.nth(n)
+    }
+    #[inline]
+    fn fold<B, F>(mut self, init: B, mut f: F) -> B
+    where
+        F: FnMut(B, Self::Item) -> B,
+    {
+        let mut acc = init;
+        while let Some((key, value)) = self.iter.next_occupied() {
+            acc = f(acc, (K::from_index(key), value));
         }
+        acc
     }
     #[inline]
     fn size_hint(&self) -> (usize, Option<usize>) {
@@ -701,31 +3030,88 @@ impl<'a, T> Iterator for Iter<'a, T> {
         self.len
     }
 }
-impl<'a, T> FusedIterator for Iter<'a, T> {}
-impl<'a, T> ExactSizeIterator for Iter<'a, T> {}
+impl<T, K: SlabKey> FusedIterator for Iter<'_, T, K> {}
+impl<T, K: SlabKey> ExactSizeIterator for Iter<'_, T, K> {}
 
-/// A mutable iterator over the entries of a [`SlabMap`].
+/// An iterator over the entries of a [`SlabMap`] that can be resumed by a token.
 ///
-/// This struct is created by the [`iter_mut`](SlabMap::iter_mut).
-pub struct IterMut<'a, T> {
-    iter: std::iter::Enumerate<std::slice::IterMut<'a, Entry<T>>>,
-    len: usize,
+/// This struct is created by the [`iter_from_token`](SlabMap::iter_from_token).
+pub struct Cursor<'a, T, K = usize> {
+    entries: &'a Entries<T>,
+    pos: usize,
+    _key: PhantomData<fn() -> K>,
+}
+impl<T, K> Cursor<'_, T, K> {
+    /// Returns the token to resume the iteration with [`SlabMap::iter_from_token`].
+    #[inline]
+    pub fn token(&self) -> usize {
+        self.pos
+    }
 }
-impl<'a, T> Iterator for IterMut<'a, T> {
-    type Item = (usize, &'a mut T);
+impl<'a, T, K: SlabKey> Iterator for Cursor<'a, T, K> {
+    type Item = (K, &'a T);
     #[inline]
     fn next(&mut self) -> Option<Self::Item> {
-        let (mut key, mut value) = self.iter.next()?;
-        loop {
-            (key, value) = match value {
+        while let Some(e) = self.entries.get(self.pos) {
+            let key = self.pos;
+            match e {
                 Entry::Occupied(value) => {
-                    self.len -= 1;
-                    return Some((key, value));
+                    self.pos += 1;
+                    return Some((K::from_index(key), value));
                 }
-                Entry::VacantHead { vacant_body_len } => self.iter.nth(*vacant_body_len + 1)?,
-                Entry::VacantTail { .. } => self.iter.next()?,
+                Entry::VacantHead { vacant_len } => self.pos += run_len(vacant_len),
+                Entry::VacantTail { .. }
+                | Entry::VacantBody
+                | Entry::Quarantined
+                | Entry::Claimed => self.pos = self.entries.skip_passive(self.pos + 1),
             }
         }
+        None
+    }
+    #[inline]
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        (0, Some(self.entries.len().saturating_sub(self.pos)))
+    }
+}
+impl<T, K: SlabKey> FusedIterator for Cursor<'_, T, K> {}
+
+/// A mutable iterator over the entries of a [`SlabMap`].
+///
+/// This struct is created by the [`iter_mut`](SlabMap::iter_mut).
+pub struct IterMut<'a, T, K = usize> {
+    iter: storage::IterMut<'a, T>,
+    len: usize,
+    _key: PhantomData<fn() -> K>,
+}
+impl<'a, T, K: SlabKey> Iterator for IterMut<'a, T, K> {
+    type Item = (K, &'a mut T);
+    #[inline]
+    fn next(&mut self) -> Option<Self::Item> {
+        let (key, value) = self.iter.next_occupied()?;
+        self.len -= 1;
+        Some((K::from_index(key), value))
+    }
+    #[inline]
+    fn nth(&mut self, n: usize) -> Option<Self::Item> {
+        let e = self.iter.nth_occupied(n, self.len);
+        self.len = self.len.saturating_sub(n).saturating_sub(1);
+        e.map(|(key, value)| (K::from_index(key), value))
+    }
+    #[inline]
+    fn last(mut self) -> Option<Self::Item> {
+        let n = self.len.checked_sub(1)?;
+        self.nth(n)
+    }
+    #[inline]
+    fn fold<B, F>(mut self, init: B, mut f: F) -> B
+    where
+        F: FnMut(B, Self::Item) -> B,
+    {
+        let mut acc = init;
+        while let Some((key, value)) = self.iter.next_occupied() {
+            acc = f(acc, (K::from_index(key), value));
+        }
+        acc
     }
     #[inline]
     fn size_hint(&self) -> (usize, Option<usize>) {
@@ -739,15 +3125,113 @@ impl<'a, T> Iterator for IterMut<'a, T> {
         self.len
     }
 }
-impl<'a, T> FusedIterator for IterMut<'a, T> {}
-impl<'a, T> ExactSizeIterator for IterMut<'a, T> {}
+impl<T, K: SlabKey> FusedIterator for IterMut<'_, T, K> {}
+impl<T, K: SlabKey> ExactSizeIterator for IterMut<'_, T, K> {}
+
+/// A mutable iterator over the entries of a [`SlabMap`] with the specified keys.
+///
+/// This struct is created by the [`iter_keys_mut`](SlabMap::iter_keys_mut).
+pub struct IterKeysMut<'a, T, K = usize> {
+    keys: vec::IntoIter<usize>,
+    iter: storage::IterMut<'a, T>,
+    offset: usize,
+    _key: PhantomData<fn() -> K>,
+}
+impl<'a, T, K: SlabKey> Iterator for IterKeysMut<'a, T, K> {
+    type Item = (K, &'a mut T);
+    #[inline]
+    fn next(&mut self) -> Option<Self::Item> {
+        for key in self.keys.by_ref() {
+            let Some((_, e)) = self.iter.nth(key - self.offset) else {
+                break;
+            };
+            self.offset = key + 1;
+            if let Entry::Occupied(value) = e {
+                return Some((K::from_index(key), value));
+            }
+        }
+        self.keys = Vec::new().into_iter();
+        None
+    }
+    #[inline]
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        (0, Some(self.keys.len()))
+    }
+}
+impl<T, K: SlabKey> FusedIterator for IterKeysMut<'_, T, K> {}
+
+/// An iterator over the entries of a [`SlabMap`] whose keys are in a range.
+///
+/// This struct is created by the [`range`](SlabMap::range).
+pub struct Range<'a, T, K = usize> {
+    iter: storage::Iter<'a, T>,
+    _key: PhantomData<fn() -> K>,
+}
+impl<'a, T, K: SlabKey> Iterator for Range<'a, T, K> {
+    type Item = (K, &'a T);
+    #[inline]
+    fn next(&mut self) -> Option<Self::Item> {
+        let (mut key, mut value) = self.iter.next()?;
+        loop {
+            (key, value) = match value {
+                Entry::Occupied(value) => return Some((K::from_index(key), value)),
+                Entry::VacantHead { vacant_len } => self.iter.nth(run_len(vacant_len) - 1)?,
+                Entry::VacantTail { .. }
+                | Entry::VacantBody
+                | Entry::Quarantined
+                | Entry::Claimed => {
+                    self.iter.skip_passive();
+                    self.iter.next()?
+                }
+            }
+        }
+    }
+    #[inline]
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        (0, Some(self.iter.len()))
+    }
+}
+impl<T, K: SlabKey> FusedIterator for Range<'_, T, K> {}
+
+/// A mutable iterator over the entries of a [`SlabMap`] whose keys are in a range.
+///
+/// This struct is created by the [`range_mut`](SlabMap::range_mut).
+pub struct RangeMut<'a, T, K = usize> {
+    iter: storage::IterMut<'a, T>,
+    _key: PhantomData<fn() -> K>,
+}
+impl<'a, T, K: SlabKey> Iterator for RangeMut<'a, T, K> {
+    type Item = (K, &'a mut T);
+    #[inline]
+    fn next(&mut self) -> Option<Self::Item> {
+        let (mut key, mut value) = self.iter.next()?;
+        loop {
+            (key, value) = match value {
+                Entry::Occupied(value) => return Some((K::from_index(key), value)),
+                Entry::VacantHead { vacant_len } => self.iter.nth(run_len(vacant_len) - 1)?,
+                Entry::VacantTail { .. }
+                | Entry::VacantBody
+                | Entry::Quarantined
+                | Entry::Claimed => {
+                    self.iter.skip_passive();
+                    self.iter.next()?
+                }
+            }
+        }
+    }
+    #[inline]
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        (0, Some(self.iter.len()))
+    }
+}
+impl<T, K: SlabKey> FusedIterator for RangeMut<'_, T, K> {}
 
 /// An iterator over the keys of a [`SlabMap`].
 ///
 /// This struct is created by the [`keys`](SlabMap::keys).
-pub struct Keys<'a, T>(Iter<'a, T>);
-impl<'a, T> Iterator for Keys<'a, T> {
-    type Item = usize;
+pub struct Keys<'a, T, K = usize>(Iter<'a, T, K>);
+impl<T, K: SlabKey> Iterator for Keys<'_, T, K> {
+    type Item = K;
     #[inline]
     fn next(&mut self) -> Option<Self::Item> {
         self.0.next().map(|(k, _)| k)
@@ -764,8 +3248,8 @@ impl<'a, T> Iterator for Keys<'a, T> {
         self.0.count()
     }
 }
-impl<'a, T> FusedIterator for Keys<'a, T> {}
-impl<'a, T> ExactSizeIterator for Keys<'a, T> {}
+impl<T, K: SlabKey> FusedIterator for Keys<'_, T, K> {}
+impl<T, K: SlabKey> ExactSizeIterator for Keys<'_, T, K> {}
 
 /// An iterator over the values of a [`SlabMap`]`.
 ///
@@ -792,6 +3276,43 @@ impl<'a, T> Iterator for Values<'a, T> {
 impl<'a, T> FusedIterator for Values<'a, T> {}
 impl<'a, T> ExactSizeIterator for Values<'a, T> {}
 
+/// An iterator over the values of a [`SlabMap`] in unspecified order.
+///
+/// This struct is created by the [`values_unordered`](SlabMap::values_unordered).
+pub struct ValuesUnordered<'a, T> {
+    entries: &'a Entries<T>,
+    end: usize,
+    len: usize,
+}
+impl<'a, T> Iterator for ValuesUnordered<'a, T> {
+    type Item = &'a T;
+    #[inline]
+    fn next(&mut self) -> Option<Self::Item> {
+        while self.len > 0 {
+            let idx = self.end.checked_sub(1)?;
+            match self.entries.entry(idx) {
+                Entry::Occupied(value) => {
+                    self.end = idx;
+                    self.len -= 1;
+                    return Some(value);
+                }
+                Entry::VacantTail { vacant_len } => self.end -= run_len(vacant_len),
+                Entry::VacantHead { .. }
+                | Entry::VacantBody
+                | Entry::Quarantined
+                | Entry::Claimed => self.end = idx,
+            }
+        }
+        None
+    }
+    #[inline]
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        (self.len, Some(self.len))
+    }
+}
+impl<T> FusedIterator for ValuesUnordered<'_, T> {}
+impl<T> ExactSizeIterator for ValuesUnordered<'_, T> {}
+
 /// A mutable iterator over the values of a [`SlabMap`].
 ///
 /// This struct is created by the [`values_mut`](SlabMap::values_mut).
@@ -0,0 +1,83 @@
+use core::{fmt, mem::take};
+
+use super::{Entries, SlabMap};
+use crate::SlabKey;
+
+impl<T, K: SlabKey> SlabMap<T, K> {
+    /// Clears the SlabMap and returns the removed values without dropping them.
+    ///
+    /// Dropping a SlabMap with many values whose `Drop` is not trivial takes time.
+    /// The returned [`DeferredDrop`] can be dropped later or sent to another thread or an executor.
+    /// The allocation is moved to the `DeferredDrop`, so the capacity of the SlabMap becomes 0.
+    ///
+    /// # Examples
+    /// ```
+    /// use slabmap::SlabMap;
+    ///
+    /// let mut s = SlabMap::new();
+    /// for i in 0..1000 {
+    ///     s.insert(vec![i; 10]);
+    /// }
+    /// let garbage = s.clear_deferred();
+    /// assert!(s.is_empty());
+    /// assert_eq!(garbage.len(), 1000);
+    ///
+    /// std::thread::spawn(move || drop(garbage));
+    /// ```
+    pub fn clear_deferred(&mut self) -> DeferredDrop<T> {
+        let entries = take(&mut self.entries);
+        let len = self.len;
+        self.clear();
+        DeferredDrop {
+            _entries: entries,
+            len,
+        }
+    }
+}
+
+#[cfg(feature = "background-drop")]
+impl<T: Send + 'static, K: SlabKey> SlabMap<T, K> {
+    /// Drops the SlabMap on a newly spawned thread.
+    pub fn drop_in_background(mut self) {
+        self.clear_deferred().drop_in_background();
+    }
+}
+
+/// Values removed by [`SlabMap::clear_deferred`] that are dropped when this is dropped.
+pub struct DeferredDrop<T> {
+    _entries: Entries<T>,
+    len: usize,
+}
+
+impl<T> DeferredDrop<T> {
+    /// Returns the number of values to be dropped.
+    #[inline]
+    pub fn len(&self) -> usize {
+        self.len
+    }
+
+    /// Returns true if there are no values to be dropped.
+    #[inline]
+    pub fn is_empty(&self) -> bool {
+        self.len == 0
+    }
+}
+
+#[cfg(feature = "background-drop")]
+impl<T: Send + 'static> DeferredDrop<T> {
+    /// Drops the values on a newly spawned thread.
+    ///
+    /// If there are no values, nothing is spawned.
+    pub fn drop_in_background(self) {
+        if !self.is_empty() {
+            std::thread::spawn(move || drop(self));
+        }
+    }
+}
+impl<T> fmt::Debug for DeferredDrop<T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("DeferredDrop")
+            .field("len", &self.len)
+            .finish_non_exhaustive()
+    }
+}
@@ -0,0 +1,116 @@
+use alloc::vec::Vec;
+
+use super::{run_len, Entry, SlabMap};
+use crate::SlabKey;
+
+/// The state of an optimization in progress by [`SlabMap::optimize_step`].
+#[derive(Clone, Debug)]
+pub(super) struct OptimizeProgress {
+    /// Entries at or after this index have been scanned.
+    idx: usize,
+    /// Heads of the free space runs found so far, in descending order.
+    heads: Vec<usize>,
+    /// The stack of free space runs below this length has not changed since the scan started.
+    stack_base: usize,
+}
+
+impl<T, K> SlabMap<T, K> {
+    pub(super) fn on_vacant_heads_pop(&mut self) {
+        let x = self.extras_mut();
+        if let Some(p) = &mut x.optimize_progress {
+            p.stack_base = p.stack_base.min(x.vacant_heads.len());
+        }
+    }
+}
+
+impl<T, K: SlabKey> SlabMap<T, K> {
+    /// Optimizes the free space incrementally, scanning at most `max_slots` slots per call.
+    ///
+    /// The position is remembered between calls, and the SlabMap can be modified freely between them.
+    /// When the scan completes, the result is the same as [`optimize`](Self::optimize).
+    ///
+    /// Returns `true` if the optimization is complete.
+    ///
+    /// # Examples
+    /// ```
+    /// use slabmap::SlabMap;
+    ///
+    /// let mut s = SlabMap::new();
+    /// for i in 0..100 {
+    ///     s.insert(i);
+    /// }
+    /// for key in (0..100).step_by(2) {
+    ///     s.remove(key);
+    /// }
+    ///
+    /// while !s.optimize_step(16) {
+    ///     s.insert(100);
+    /// }
+    /// assert_eq!(s.insert(101), 0);
+    /// assert_eq!(s.insert(102), 2);
+    /// ```
+    pub fn optimize_step(&mut self, max_slots: usize) -> bool {
+        if self.extras().optimize_progress.is_none() {
+            if self.is_optimized() {
+                return true;
+            }
+            let idx = self.entries.len();
+            let x = self.extras_mut();
+            x.optimize_progress = Some(OptimizeProgress {
+                idx,
+                heads: Vec::new(),
+                stack_base: x.vacant_heads.len(),
+            });
+        }
+        let Some(x) = &mut self.extras else {
+            unreachable!()
+        };
+        let p = x.optimize_progress.as_mut().unwrap();
+        let mut idx = p.idx.min(self.entries.len());
+        let mut slots = 0;
+        while idx > 0 && slots < max_slots {
+            idx -= 1;
+            slots += 1;
+            match self.entries.entry(idx) {
+                Entry::VacantHead { .. } => p.heads.push(idx),
+                Entry::VacantTail { vacant_len } => {
+                    idx = idx + 1 - run_len(vacant_len);
+                    p.heads.push(idx);
+                }
+                _ => {}
+            }
+        }
+        p.idx = idx;
+        if idx > 0 {
+            return false;
+        }
+        self.finish_optimize();
+        true
+    }
+    fn finish_optimize(&mut self) {
+        let p = self.extras_mut().optimize_progress.take().unwrap();
+        if let Some(Entry::VacantHead { vacant_len } | Entry::VacantTail { vacant_len }) =
+            self.entries.last()
+        {
+            let len = self.entries.len() - run_len(vacant_len);
+            self.entries.truncate(len);
+        }
+        let mut heads = p.heads;
+        let vacant_heads = &self.extras().vacant_heads;
+        let base = p.stack_base.min(vacant_heads.len());
+        let is_head = |idx: usize| matches!(self.entries.get(idx), Some(Entry::VacantHead { .. }));
+        let pushed = vacant_heads[base..]
+            .iter()
+            .copied()
+            .filter(|&idx| is_head(idx));
+        let len = heads.len();
+        heads.extend(pushed);
+        if heads.len() > len {
+            heads.sort_by(|a, b| b.cmp(a));
+            heads.dedup();
+        }
+        let x = self.extras_mut();
+        x.vacant_heads = heads;
+        x.non_optimized_count = 0;
+    }
+}
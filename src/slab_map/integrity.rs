@@ -0,0 +1,202 @@
+use core::fmt;
+
+use super::{run_len, Entry, SlabMap};
+
+impl<T, K> SlabMap<T, K> {
+    /// Verifies the internal invariants of the SlabMap.
+    ///
+    /// This checks that `len` matches the number of occupied entries, that every free space run has a consistent head and tail,
+    /// that adjacent free space runs are merged, that every free space run can be reused,
+    /// and that the count of free space waiting for optimization does not exceed the free space.
+    ///
+    /// This method takes O(n) time and is intended for debug assertions and fuzz targets.
+    ///
+    /// # Examples
+    /// ```
+    /// use slabmap::SlabMap;
+    ///
+    /// let mut s = SlabMap::new();
+    /// let key = s.insert(10);
+    /// s.insert(20);
+    /// s.remove(key);
+    ///
+    /// assert_eq!(s.check_integrity(), Ok(()));
+    /// ```
+    pub fn check_integrity(&self) -> Result<(), IntegrityError> {
+        let mut len = 0;
+        let mut bounds = None;
+        let mut claimed = 0;
+        let mut vacant = 0;
+        let mut prev_vacant = false;
+        let mut idx = 0;
+        while let Some(e) = self.entries.get(idx) {
+            match e {
+                Entry::Occupied(_) => {
+                    len += 1;
+                    bounds = Some((bounds.map_or(idx, |(min, _)| min), idx));
+                }
+                Entry::Claimed => claimed += 1,
+                Entry::Quarantined => {
+                    if !self
+                        .extras()
+                        .quarantine
+                        .keys
+                        .iter()
+                        .any(|&(key, _)| key == idx)
+                    {
+                        return Err(IntegrityError::Quarantine { index: idx });
+                    }
+                }
+                Entry::VacantHead { vacant_len } => {
+                    let end = idx + run_len(vacant_len);
+                    if prev_vacant {
+                        return Err(IntegrityError::UnmergedRuns { index: idx });
+                    }
+                    if run_len(vacant_len) == 0 || end > self.entries.len() {
+                        return Err(IntegrityError::BrokenRun { index: idx });
+                    }
+                    if end - idx > 1 {
+                        let is_tail = matches!(self.entries.entry(end - 1), Entry::VacantTail { vacant_len: l } if l == vacant_len);
+                        let is_body = (idx + 1..end - 1)
+                            .all(|idx| matches!(self.entries.entry(idx), Entry::VacantBody));
+                        if !is_tail || !is_body {
+                            return Err(IntegrityError::BrokenRun { index: idx });
+                        }
+                    }
+                    if !self.extras().vacant_heads.contains(&idx) {
+                        return Err(IntegrityError::UnreachableRun { index: idx });
+                    }
+                    vacant += end - idx;
+                    prev_vacant = true;
+                    idx = end;
+                    continue;
+                }
+                Entry::VacantTail { .. } | Entry::VacantBody => {
+                    return Err(IntegrityError::BrokenRun { index: idx });
+                }
+            }
+            prev_vacant = false;
+            idx += 1;
+        }
+        if len != self.len {
+            return Err(IntegrityError::Len {
+                expected: self.len,
+                actual: len,
+            });
+        }
+        if bounds != self.index_bounds() {
+            return Err(IntegrityError::KeyBounds {
+                expected: self.index_bounds(),
+                actual: bounds,
+            });
+        }
+        if claimed != self.extras().claimed {
+            return Err(IntegrityError::Claimed {
+                expected: self.extras().claimed,
+                actual: claimed,
+            });
+        }
+        for &(key, _) in &self.extras().quarantine.keys {
+            if !matches!(self.entries.get(key), Some(Entry::Quarantined)) {
+                return Err(IntegrityError::Quarantine { index: key });
+            }
+        }
+        if self.extras().non_optimized_count > vacant {
+            return Err(IntegrityError::NonOptimizedCount {
+                count: self.extras().non_optimized_count,
+                vacant,
+            });
+        }
+        Ok(())
+    }
+}
+
+/// An error returned by [`SlabMap::check_integrity`] describing the first broken invariant found.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum IntegrityError {
+    /// The number of occupied entries differs from [`SlabMap::len`].
+    Len {
+        /// The length recorded in the SlabMap.
+        expected: usize,
+        /// The number of occupied entries.
+        actual: usize,
+    },
+    /// The number of claimed entries differs from the recorded count.
+    Claimed {
+        /// The count recorded in the SlabMap.
+        expected: usize,
+        /// The number of claimed entries.
+        actual: usize,
+    },
+    /// An entry in quarantine is not tracked, or a tracked key is not in quarantine.
+    Quarantine {
+        /// The index of the entry.
+        index: usize,
+    },
+    /// A free space run has an inconsistent head, body or tail.
+    BrokenRun {
+        /// The index of the first broken entry.
+        index: usize,
+    },
+    /// Two free space runs are adjacent without being merged.
+    UnmergedRuns {
+        /// The index of the head of the second run.
+        index: usize,
+    },
+    /// A free space run is not reachable from the list of reusable runs.
+    UnreachableRun {
+        /// The index of the head of the run.
+        index: usize,
+    },
+    /// The count of free space waiting for optimization exceeds the free space.
+    NonOptimizedCount {
+        /// The count recorded in the SlabMap.
+        count: usize,
+        /// The number of free entries.
+        vacant: usize,
+    },
+    /// The recorded smallest or largest key differs from the occupied entries.
+    KeyBounds {
+        /// The smallest and the largest keys recorded in the SlabMap.
+        expected: Option<(usize, usize)>,
+        /// The smallest and the largest occupied keys.
+        actual: Option<(usize, usize)>,
+    },
+}
+impl fmt::Display for IntegrityError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match *self {
+            Self::Len { expected, actual } => {
+                write!(f, "len is {expected} but {actual} entries are occupied")
+            }
+            Self::Claimed { expected, actual } => {
+                write!(
+                    f,
+                    "{expected} keys are claimed but {actual} entries are claimed"
+                )
+            }
+            Self::Quarantine { index } => write!(f, "quarantine is inconsistent at {index}"),
+            Self::BrokenRun { index } => write!(f, "free space run is broken at {index}"),
+            Self::UnmergedRuns { index } => {
+                write!(
+                    f,
+                    "free space run at {index} is not merged with the previous run"
+                )
+            }
+            Self::UnreachableRun { index } => {
+                write!(f, "free space run at {index} is not reusable")
+            }
+            Self::NonOptimizedCount { count, vacant } => write!(
+                f,
+                "non-optimized count {count} exceeds {vacant} free entries"
+            ),
+            Self::KeyBounds { expected, actual } => {
+                write!(
+                    f,
+                    "key bounds are recorded as {expected:?} but occupied keys span {actual:?}"
+                )
+            }
+        }
+    }
+}
+impl core::error::Error for IntegrityError {}
@@ -0,0 +1,96 @@
+use alloc::vec::Vec;
+
+use super::{run_len, Entry, SlabMap};
+use crate::SlabKey;
+
+impl<T, K: SlabKey> SlabMap<T, K> {
+    /// Returns a description of the internal layout for diagnostics.
+    ///
+    /// The layout lists runs of consecutive entries of the same kind and the stack of free space runs to be reused.
+    /// The format of the layout is not stable and may change between versions.
+    ///
+    /// # Examples
+    /// ```
+    /// use slabmap::{slab_map::RunKind, SlabMap};
+    ///
+    /// let mut s = SlabMap::new();
+    /// for i in 0..5 {
+    ///     s.insert(i);
+    /// }
+    /// s.remove(1);
+    /// s.remove(2);
+    ///
+    /// let layout = s.debug_layout();
+    /// let runs: Vec<_> = layout.runs.iter().map(|r| (r.start, r.len, r.kind)).collect();
+    /// assert_eq!(
+    ///     runs,
+    ///     [(0, 1, RunKind::Occupied), (1, 2, RunKind::Vacant), (3, 2, RunKind::Occupied)]
+    /// );
+    /// assert_eq!(layout.vacant_heads, [1]);
+    /// ```
+    pub fn debug_layout(&self) -> Layout {
+        let mut runs: Vec<Run> = Vec::new();
+        let mut idx = 0;
+        while let Some(e) = self.entries.get(idx) {
+            let (kind, len) = match e {
+                Entry::Occupied(_) => (RunKind::Occupied, 1),
+                Entry::VacantHead { vacant_len } => (RunKind::Vacant, run_len(vacant_len)),
+                Entry::VacantTail { .. } | Entry::VacantBody => (RunKind::Vacant, 1),
+                Entry::Quarantined => (RunKind::Quarantined, 1),
+                Entry::Claimed => (RunKind::Claimed, 1),
+            };
+            match runs.last_mut() {
+                Some(last) if last.kind == kind && kind != RunKind::Vacant => last.len += len,
+                _ => runs.push(Run {
+                    start: idx,
+                    len,
+                    kind,
+                }),
+            }
+            idx += len;
+        }
+        Layout {
+            runs,
+            vacant_heads: self.extras().vacant_heads.iter().rev().copied().collect(),
+        }
+    }
+}
+
+/// A description of the internal layout of a [`SlabMap`].
+///
+/// This struct is created by the [`debug_layout`](SlabMap::debug_layout).
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Layout {
+    /// The runs of consecutive entries of the same kind, sorted by start.
+    ///
+    /// Adjacent runs of free space are reported separately, so two adjacent `Vacant` runs indicate that they were not merged.
+    pub runs: Vec<Run>,
+    /// The starts of the runs of free space to be reused, in the order of reuse.
+    ///
+    /// This may contain stale entries, which are skipped when free space is reused.
+    pub vacant_heads: Vec<usize>,
+}
+
+/// A run of consecutive entries of the same kind in a [`Layout`].
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub struct Run {
+    /// The first key of the run.
+    pub start: usize,
+    /// The number of entries in the run.
+    pub len: usize,
+    /// The kind of the entries.
+    pub kind: RunKind,
+}
+
+/// The kind of entries in a [`Run`].
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum RunKind {
+    /// Entries with values.
+    Occupied,
+    /// Free space.
+    Vacant,
+    /// Removed keys that are not reused until released from quarantine.
+    Quarantined,
+    /// Keys reserved by [`claim_key`](SlabMap::claim_key).
+    Claimed,
+}
@@ -0,0 +1,79 @@
+use alloc::vec::Vec;
+
+use rayon::iter::{
+    FromParallelIterator, IndexedParallelIterator, IntoParallelIterator, IntoParallelRefIterator,
+    IntoParallelRefMutIterator, ParallelExtend, ParallelIterator,
+};
+
+use super::SlabMap;
+use crate::SlabKey;
+
+impl<T: Send, K: SlabKey> SlabMap<T, K> {
+    /// Retains only the elements specified by the predicate, calling the predicate in parallel.
+    ///
+    /// The predicate is called on all elements in parallel, and free space is rebuilt once at the end.
+    ///
+    /// # Examples
+    /// ```
+    /// use slabmap::SlabMap;
+    ///
+    /// let mut s = SlabMap::new();
+    /// for i in 0..100 {
+    ///     s.insert(i);
+    /// }
+    /// s.par_retain(|key, value| {
+    ///     *value *= 2;
+    ///     key % 10 == 0
+    /// });
+    ///
+    /// assert_eq!(s.len(), 10);
+    /// assert_eq!(s[30], 60);
+    /// ```
+    pub fn par_retain(&mut self, f: impl Fn(K, &mut T) -> bool + Sync + Send) {
+        let (metas, values) = self.entries.split_mut();
+        let retains: Vec<bool> = metas
+            .par_iter()
+            .zip(values.par_iter_mut())
+            .enumerate()
+            .map(|(idx, (meta, value))| {
+                if meta.is_occupied() {
+                    // SAFETY: The value of an occupied entry is initialized.
+                    f(K::from_index(idx), unsafe { value.assume_init_mut() })
+                } else {
+                    true
+                }
+            })
+            .collect();
+        self.rebuild_vacants_with(|idx, _| retains[idx]);
+    }
+}
+
+/// Inserts keys and values produced by a parallel iterator.
+///
+/// If a key is already occupied, the value is replaced. If a key appears more than once, the last value is kept.
+///
+/// # Examples
+/// ```
+/// use rayon::prelude::*;
+/// use slabmap::SlabMap;
+///
+/// let mut s = SlabMap::new();
+/// s.insert(0);
+/// s.par_extend((1..5).into_par_iter().map(|i| (i * 2, i)));
+///
+/// assert_eq!(s.iter().collect::<Vec<_>>(), [(0, &0), (2, &1), (4, &2), (6, &3), (8, &4)]);
+/// ```
+impl<T: Send, K: SlabKey + Send> ParallelExtend<(K, T)> for SlabMap<T, K> {
+    fn par_extend<I: IntoParallelIterator<Item = (K, T)>>(&mut self, par_iter: I) {
+        let items: Vec<(K, T)> = par_iter.into_par_iter().collect();
+        self.upsert_from(items, |value, new_value| *value = new_value);
+    }
+}
+
+/// Collects keys and values in parallel, then stores them in the same way as [`FromIterator`].
+impl<T: Send, K: SlabKey + Send> FromParallelIterator<(K, T)> for SlabMap<T, K> {
+    fn from_par_iter<I: IntoParallelIterator<Item = (K, T)>>(par_iter: I) -> Self {
+        let items: Vec<(K, T)> = par_iter.into_par_iter().collect();
+        Self::from_iter_with_capacity(items, 0)
+    }
+}
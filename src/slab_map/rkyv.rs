@@ -0,0 +1,193 @@
+use alloc::vec::Vec;
+use core::{iter::FusedIterator, marker::PhantomData};
+
+use rkyv::{
+    munge::munge,
+    primitive::ArchivedUsize,
+    rancor::Fallible,
+    ser::{Allocator, Writer},
+    vec::{ArchivedVec, VecResolver},
+    Archive, Deserialize, Place, Portable, Serialize,
+};
+
+use super::SlabMap;
+use crate::SlabKey;
+
+/// An archived [`SlabMap`].
+///
+/// Values are stored contiguously, and an index table maps each key to the position of its value,
+/// so [`get`](Self::get) takes O(1) time and iteration never visits free space.
+///
+/// # Examples
+/// ```
+/// use slabmap::SlabMap;
+///
+/// let mut s = SlabMap::new();
+/// let a = s.insert(10u32);
+/// let b = s.insert(20u32);
+/// s.remove(a);
+///
+/// let bytes = rkyv::to_bytes::<rkyv::rancor::Error>(&s).unwrap();
+/// let archived = rkyv::access::<slabmap::slab_map::ArchivedSlabMap<rkyv::Archived<u32>>, rkyv::rancor::Error>(&bytes).unwrap();
+///
+/// assert_eq!(archived.get(a), None);
+/// assert_eq!(archived.get(b).unwrap(), &20);
+/// assert_eq!(archived.len(), 1);
+/// ```
+#[derive(Portable, rkyv::bytecheck::CheckBytes)]
+#[rkyv(crate = rkyv)]
+#[bytecheck(crate = rkyv::bytecheck)]
+#[repr(C)]
+pub struct ArchivedSlabMap<T, K = usize> {
+    /// The position of the value plus one for each key, or zero for a vacant key.
+    slots: ArchivedVec<ArchivedUsize>,
+    values: ArchivedVec<T>,
+    _key: PhantomData<fn(K) -> K>,
+}
+
+impl<T, K: SlabKey> ArchivedSlabMap<T, K> {
+    /// Returns the number of elements in the ArchivedSlabMap.
+    #[inline]
+    pub fn len(&self) -> usize {
+        self.values.len()
+    }
+
+    /// Returns true if the ArchivedSlabMap contains no elements.
+    #[inline]
+    pub fn is_empty(&self) -> bool {
+        self.values.is_empty()
+    }
+
+    /// Returns a reference to the value corresponding to the key.
+    #[inline]
+    pub fn get(&self, key: K) -> Option<&T> {
+        let position = self.slots.get(key.into_index())?.to_native() as usize;
+        self.values.get(position.checked_sub(1)?)
+    }
+
+    /// Returns true if the ArchivedSlabMap contains a value for the specified key.
+    #[inline]
+    pub fn contains_key(&self, key: K) -> bool {
+        self.get(key).is_some()
+    }
+
+    /// Gets an iterator over the entries of the ArchivedSlabMap, sorted by key.
+    #[inline]
+    pub fn iter(&self) -> ArchivedIter<'_, T, K> {
+        ArchivedIter {
+            slots: self.slots.iter().enumerate(),
+            values: &self.values,
+            len: self.values.len(),
+            _key: PhantomData,
+        }
+    }
+
+    /// Gets an iterator over the values of the ArchivedSlabMap, sorted by key.
+    #[inline]
+    pub fn values(&self) -> core::slice::Iter<'_, T> {
+        self.values.iter()
+    }
+}
+
+impl<'a, T, K: SlabKey> IntoIterator for &'a ArchivedSlabMap<T, K> {
+    type Item = (K, &'a T);
+    type IntoIter = ArchivedIter<'a, T, K>;
+
+    #[inline]
+    fn into_iter(self) -> Self::IntoIter {
+        self.iter()
+    }
+}
+
+/// An iterator over the entries of an [`ArchivedSlabMap`].
+///
+/// This struct is created by the [`iter`](ArchivedSlabMap::iter).
+pub struct ArchivedIter<'a, T, K = usize> {
+    slots: core::iter::Enumerate<core::slice::Iter<'a, ArchivedUsize>>,
+    values: &'a [T],
+    len: usize,
+    _key: PhantomData<fn(K) -> K>,
+}
+impl<'a, T, K: SlabKey> Iterator for ArchivedIter<'a, T, K> {
+    type Item = (K, &'a T);
+    #[inline]
+    fn next(&mut self) -> Option<Self::Item> {
+        if self.len == 0 {
+            return None;
+        }
+        for (key, position) in self.slots.by_ref() {
+            let position = position.to_native() as usize;
+            if let Some(value) = position.checked_sub(1).and_then(|p| self.values.get(p)) {
+                self.len -= 1;
+                return Some((K::from_index(key), value));
+            }
+        }
+        self.len = 0;
+        None
+    }
+    #[inline]
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        (self.len, Some(self.len))
+    }
+}
+impl<T, K: SlabKey> FusedIterator for ArchivedIter<'_, T, K> {}
+impl<T, K: SlabKey> ExactSizeIterator for ArchivedIter<'_, T, K> {}
+
+/// The resolver for an archived [`SlabMap`].
+pub struct SlabMapResolver {
+    slots: VecResolver,
+    values: VecResolver,
+}
+
+impl<T: Archive, K: SlabKey> Archive for SlabMap<T, K> {
+    type Archived = ArchivedSlabMap<T::Archived, K>;
+    type Resolver = SlabMapResolver;
+
+    fn resolve(&self, resolver: Self::Resolver, out: Place<Self::Archived>) {
+        let slots_len = self.max_key().map_or(0, |key| key.into_index() + 1);
+        munge!(let ArchivedSlabMap { slots, values, _key: _ } = out);
+        ArchivedVec::resolve_from_len(slots_len, resolver.slots, slots);
+        ArchivedVec::resolve_from_len(self.len, resolver.values, values);
+    }
+}
+
+impl<T, K, S> Serialize<S> for SlabMap<T, K>
+where
+    T: Serialize<S>,
+    K: SlabKey,
+    S: Fallible + Allocator + Writer + ?Sized,
+{
+    fn serialize(&self, serializer: &mut S) -> Result<Self::Resolver, S::Error> {
+        let mut slots = Vec::new();
+        let mut values = Vec::with_capacity(self.len);
+        for (key, value) in self.raw_iter() {
+            slots.resize(key, 0);
+            values.push(value);
+            slots.push(values.len());
+        }
+        Ok(SlabMapResolver {
+            slots: ArchivedVec::<ArchivedUsize>::serialize_from_slice(&slots, serializer)?,
+            values: ArchivedVec::<T::Archived>::serialize_from_iter::<T, _, _>(
+                values.iter().copied(),
+                serializer,
+            )?,
+        })
+    }
+}
+
+impl<T, K, D> Deserialize<SlabMap<T, K>, D> for ArchivedSlabMap<T::Archived, K>
+where
+    T: Archive,
+    T::Archived: Deserialize<T, D>,
+    K: SlabKey,
+    D: Fallible + ?Sized,
+{
+    fn deserialize(&self, deserializer: &mut D) -> Result<SlabMap<T, K>, D::Error> {
+        let mut this = SlabMap::with_capacity_and_key(self.slots.len());
+        for (key, value) in self.iter() {
+            this.set(key.into_index(), value.deserialize(deserializer)?);
+        }
+        this.rebuild_vacants();
+        Ok(this)
+    }
+}
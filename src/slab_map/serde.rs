@@ -0,0 +1,89 @@
+use core::{fmt, marker::PhantomData};
+
+use serde::{
+    de::{DeserializeSeed, MapAccess, Visitor},
+    Deserialize, Deserializer, Serialize, Serializer,
+};
+
+use super::SlabMap;
+use crate::SlabKey;
+
+/// Serializes the SlabMap as a map from keys to values.
+///
+/// Keys are serialized as the indexes given by [`SlabKey::into_index`].
+impl<T: Serialize, K: SlabKey> Serialize for SlabMap<T, K> {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.collect_map(self.raw_iter())
+    }
+}
+
+/// Deserializes the SlabMap from a map from keys to values, keeping the keys.
+///
+/// Entries are stored as they are parsed, without buffering them in an intermediate collection.
+impl<'de, T: Deserialize<'de>, K: SlabKey> Deserialize<'de> for SlabMap<T, K> {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        let mut this = SlabMap::with_key();
+        this.reload_seed().deserialize(deserializer)?;
+        Ok(this)
+    }
+}
+
+impl<T, K: SlabKey> SlabMap<T, K> {
+    /// Returns a [`DeserializeSeed`] that replaces the contents of the SlabMap with the deserialized entries.
+    ///
+    /// Like [`reload`](Self::reload), the allocation is reused, and entries are stored as they are parsed.
+    ///
+    /// # Examples
+    /// ```
+    /// use serde::de::DeserializeSeed;
+    /// use slabmap::SlabMap;
+    ///
+    /// let mut s = SlabMap::<String>::with_capacity(16);
+    /// let mut d = serde_json::Deserializer::from_str(r#"{"1":"a","4":"b"}"#);
+    /// s.reload_seed().deserialize(&mut d).unwrap();
+    ///
+    /// assert_eq!(s.capacity(), 16);
+    /// assert_eq!(s[1], "a");
+    /// assert_eq!(s[4], "b");
+    /// ```
+    pub fn reload_seed(&mut self) -> ReloadSeed<'_, T, K> {
+        ReloadSeed(self)
+    }
+}
+
+/// A [`DeserializeSeed`] that replaces the contents of a [`SlabMap`].
+///
+/// This struct is created by the [`reload_seed`](SlabMap::reload_seed).
+/// If the deserialization fails, the SlabMap contains the entries parsed before the error.
+pub struct ReloadSeed<'a, T, K = usize>(&'a mut SlabMap<T, K>);
+
+impl<'de, T: Deserialize<'de>, K: SlabKey> DeserializeSeed<'de> for ReloadSeed<'_, T, K> {
+    type Value = ();
+
+    fn deserialize<D: Deserializer<'de>>(self, deserializer: D) -> Result<(), D::Error> {
+        self.0.clear();
+        let result = deserializer.deserialize_map(ReloadVisitor(self.0, PhantomData));
+        self.0.rebuild_vacants();
+        result
+    }
+}
+
+struct ReloadVisitor<'a, 'de, T, K>(&'a mut SlabMap<T, K>, PhantomData<fn() -> &'de ()>);
+
+impl<'de, T: Deserialize<'de>, K: SlabKey> Visitor<'de> for ReloadVisitor<'_, 'de, T, K> {
+    type Value = ();
+
+    fn expecting(&self, formatter: &mut fmt::Formatter) -> fmt::Result {
+        formatter.write_str("a map from keys to values")
+    }
+
+    fn visit_map<A: MapAccess<'de>>(self, mut map: A) -> Result<(), A::Error> {
+        if let Some(len) = map.size_hint() {
+            self.0.entries.reserve(len);
+        }
+        while let Some((key, value)) = map.next_entry()? {
+            self.0.set(key, value);
+        }
+        Ok(())
+    }
+}
@@ -0,0 +1,200 @@
+//! Alternative serde representations of [`SlabMap`], for use with `#[serde(with = "...")]`.
+//!
+//! By default, a SlabMap is serialized as a map from keys to values.
+//! The modules in this module serialize it in other shapes that suit particular formats better.
+//! All of them keep the keys, and free space is rebuilt on deserialization.
+//!
+//! - [`dense`] : A sequence of `Option<T>` indexed by key, with `None` for free space. Compact for binary formats when the SlabMap has little free space.
+//! - [`pairs`] : A sequence of `(key, value)` pairs. Suitable for formats that do not support maps with integer keys.
+//! - [`string_keys`] : A map whose keys are strings. Suitable for JSON objects consumed by other languages.
+
+use alloc::string::{String, ToString};
+use core::{fmt, marker::PhantomData};
+
+use serde::{
+    de::{self, MapAccess, SeqAccess, Unexpected, Visitor},
+    Deserialize, Deserializer, Serialize, Serializer,
+};
+
+use super::{Entry, SlabMap};
+use crate::SlabKey;
+
+/// Serializes a SlabMap as a sequence of `Option<T>` indexed by key.
+///
+/// # Examples
+/// ```
+/// use slabmap::{slab_map::serde_repr::dense, SlabMap};
+///
+/// let mut s = SlabMap::new();
+/// s.insert("a");
+/// let key = s.insert("b");
+/// s.insert("c");
+/// s.remove(key);
+///
+/// let mut json = Vec::new();
+/// dense::serialize(&s, &mut serde_json::Serializer::new(&mut json)).unwrap();
+/// assert_eq!(String::from_utf8(json).unwrap(), r#"["a",null,"c"]"#);
+///
+/// let d: SlabMap<String> = dense::deserialize(&mut serde_json::Deserializer::from_str(r#"[null,"x"]"#)).unwrap();
+/// assert_eq!(d[1], "x");
+/// ```
+pub mod dense {
+    use super::*;
+
+    /// Serializes a SlabMap as a sequence of `Option<T>` indexed by key.
+    pub fn serialize<T: Serialize, K: SlabKey, S: Serializer>(
+        this: &SlabMap<T, K>,
+        serializer: S,
+    ) -> Result<S::Ok, S::Error> {
+        let len = this.max_key().map_or(0, |key| key.into_index() + 1);
+        serializer.collect_seq(this.entries.range(0..len).map(|(_, e)| match e {
+            Entry::Occupied(value) => Some(value),
+            _ => None,
+        }))
+    }
+
+    /// Deserializes a SlabMap from a sequence of `Option<T>` indexed by key.
+    pub fn deserialize<'de, T: Deserialize<'de>, K: SlabKey, D: Deserializer<'de>>(
+        deserializer: D,
+    ) -> Result<SlabMap<T, K>, D::Error> {
+        deserializer.deserialize_seq(DenseVisitor(PhantomData))
+    }
+
+    struct DenseVisitor<T, K>(PhantomData<fn() -> SlabMap<T, K>>);
+
+    impl<'de, T: Deserialize<'de>, K: SlabKey> Visitor<'de> for DenseVisitor<T, K> {
+        type Value = SlabMap<T, K>;
+
+        fn expecting(&self, formatter: &mut fmt::Formatter) -> fmt::Result {
+            formatter.write_str("a sequence of optional values")
+        }
+
+        fn visit_seq<A: SeqAccess<'de>>(self, mut seq: A) -> Result<Self::Value, A::Error> {
+            let mut this = SlabMap::with_capacity_and_key(seq.size_hint().unwrap_or(0));
+            let mut key = 0;
+            while let Some(value) = seq.next_element::<Option<T>>()? {
+                if let Some(value) = value {
+                    this.set(key, value);
+                }
+                key += 1;
+            }
+            this.rebuild_vacants();
+            Ok(this)
+        }
+    }
+}
+
+/// Serializes a SlabMap as a sequence of `(key, value)` pairs.
+///
+/// # Examples
+/// ```
+/// use slabmap::{slab_map::serde_repr::pairs, SlabMap};
+///
+/// let mut s = SlabMap::new();
+/// let key = s.insert("a");
+/// s.insert("b");
+/// s.remove(key);
+///
+/// let mut json = Vec::new();
+/// pairs::serialize(&s, &mut serde_json::Serializer::new(&mut json)).unwrap();
+/// assert_eq!(String::from_utf8(json).unwrap(), r#"[[1,"b"]]"#);
+/// ```
+pub mod pairs {
+    use super::*;
+
+    /// Serializes a SlabMap as a sequence of `(key, value)` pairs.
+    pub fn serialize<T: Serialize, K: SlabKey, S: Serializer>(
+        this: &SlabMap<T, K>,
+        serializer: S,
+    ) -> Result<S::Ok, S::Error> {
+        serializer.collect_seq(this.raw_iter())
+    }
+
+    /// Deserializes a SlabMap from a sequence of `(key, value)` pairs.
+    ///
+    /// If a key appears more than once, the last value is kept.
+    pub fn deserialize<'de, T: Deserialize<'de>, K: SlabKey, D: Deserializer<'de>>(
+        deserializer: D,
+    ) -> Result<SlabMap<T, K>, D::Error> {
+        deserializer.deserialize_seq(PairsVisitor(PhantomData))
+    }
+
+    struct PairsVisitor<T, K>(PhantomData<fn() -> SlabMap<T, K>>);
+
+    impl<'de, T: Deserialize<'de>, K: SlabKey> Visitor<'de> for PairsVisitor<T, K> {
+        type Value = SlabMap<T, K>;
+
+        fn expecting(&self, formatter: &mut fmt::Formatter) -> fmt::Result {
+            formatter.write_str("a sequence of pairs of a key and a value")
+        }
+
+        fn visit_seq<A: SeqAccess<'de>>(self, mut seq: A) -> Result<Self::Value, A::Error> {
+            let mut this = SlabMap::with_key();
+            while let Some((key, value)) = seq.next_element()? {
+                this.set(key, value);
+            }
+            this.rebuild_vacants();
+            Ok(this)
+        }
+    }
+}
+
+/// Serializes a SlabMap as a map whose keys are strings.
+///
+/// # Examples
+/// ```
+/// use slabmap::{slab_map::serde_repr::string_keys, SlabMap};
+///
+/// let mut s = SlabMap::new();
+/// s.insert(10);
+/// s.insert(20);
+///
+/// let mut json = Vec::new();
+/// string_keys::serialize(&s, &mut serde_json::Serializer::new(&mut json)).unwrap();
+/// assert_eq!(String::from_utf8(json).unwrap(), r#"{"0":10,"1":20}"#);
+/// ```
+pub mod string_keys {
+    use super::*;
+
+    /// Serializes a SlabMap as a map whose keys are strings.
+    pub fn serialize<T: Serialize, K: SlabKey, S: Serializer>(
+        this: &SlabMap<T, K>,
+        serializer: S,
+    ) -> Result<S::Ok, S::Error> {
+        serializer.collect_map(this.raw_iter().map(|(key, value)| (key.to_string(), value)))
+    }
+
+    /// Deserializes a SlabMap from a map whose keys are strings.
+    ///
+    /// If a key appears more than once, the last value is kept.
+    pub fn deserialize<'de, T: Deserialize<'de>, K: SlabKey, D: Deserializer<'de>>(
+        deserializer: D,
+    ) -> Result<SlabMap<T, K>, D::Error> {
+        deserializer.deserialize_map(StringKeysVisitor(PhantomData))
+    }
+
+    struct StringKeysVisitor<T, K>(PhantomData<fn() -> SlabMap<T, K>>);
+
+    impl<'de, T: Deserialize<'de>, K: SlabKey> Visitor<'de> for StringKeysVisitor<T, K> {
+        type Value = SlabMap<T, K>;
+
+        fn expecting(&self, formatter: &mut fmt::Formatter) -> fmt::Result {
+            formatter.write_str("a map from string keys to values")
+        }
+
+        fn visit_map<A: MapAccess<'de>>(self, mut map: A) -> Result<Self::Value, A::Error> {
+            let mut this = SlabMap::with_key();
+            while let Some((key, value)) = map.next_entry::<String, T>()? {
+                let Ok(key) = key.parse() else {
+                    return Err(de::Error::invalid_value(
+                        Unexpected::Str(&key),
+                        &"a string of an unsigned integer",
+                    ));
+                };
+                this.set(key, value);
+            }
+            this.rebuild_vacants();
+            Ok(this)
+        }
+    }
+}
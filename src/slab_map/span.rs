@@ -0,0 +1,305 @@
+use core::{
+    iter::FusedIterator,
+    ops::{Index, IndexMut, Range},
+};
+
+use super::{run_len, storage::Entries, Entry, SlabMap};
+use crate::SlabKey;
+
+impl<T, K: SlabKey> SlabMap<T, K> {
+    /// Returns a view of the values at keys `start..start + len` if all of them are occupied.
+    ///
+    /// This is useful for groups of related values inserted at consecutive keys.
+    ///
+    /// # Examples
+    /// ```
+    /// use slabmap::SlabMap;
+    ///
+    /// let mut s = SlabMap::new();
+    /// let k0 = s.insert(10);
+    /// s.insert(20);
+    /// s.insert(30);
+    ///
+    /// let span = s.get_span(k0, 3).unwrap();
+    /// assert_eq!(span[1], 20);
+    /// assert_eq!(span.iter().sum::<i32>(), 60);
+    ///
+    /// s.remove(k0 + 1);
+    /// assert!(s.get_span(k0, 3).is_none());
+    /// ```
+    pub fn get_span(&self, start: K, len: usize) -> Option<Span<'_, T>> {
+        let start = start.into_index();
+        let values = self.entries.get_occupied(start..start.checked_add(len)?)?;
+        Some(Span(values))
+    }
+
+    /// Inserts values at consecutive keys and returns the range of the keys.
+    ///
+    /// The values are stored after the largest key in use, reusing the free space at the end, so the free list is not consulted for each value.
+    ///
+    /// # Examples
+    /// ```
+    /// use slabmap::SlabMap;
+    ///
+    /// let mut s = SlabMap::new();
+    /// s.insert(0);
+    /// s.insert(1);
+    /// s.remove(0);
+    ///
+    /// let keys = s.insert_contiguous([10, 20, 30]);
+    /// assert_eq!(keys, 2..5);
+    /// assert_eq!(s.get_span(keys.start, keys.len()).unwrap().iter().sum::<i32>(), 60);
+    /// ```
+    pub fn insert_contiguous(&mut self, iter: impl IntoIterator<Item = T>) -> Range<usize> {
+        self.insert_contiguous_with(iter.into_iter(), |entries, iter| {
+            entries.extend(iter.map(Entry::Occupied))
+        })
+    }
+
+    /// Inserts copies of `values` at consecutive keys and returns the range of the keys.
+    ///
+    /// This is the same as [`insert_contiguous`](Self::insert_contiguous),
+    /// except that the values stored after the end of the entries are copied as a whole instead of one by one.
+    ///
+    /// # Examples
+    /// ```
+    /// use slabmap::SlabMap;
+    ///
+    /// let mut s = SlabMap::new();
+    /// s.insert(0);
+    /// s.insert(1);
+    /// s.remove(1);
+    ///
+    /// let keys = s.extend_copy(&[10, 20, 30]);
+    /// assert_eq!(keys, 1..4);
+    /// assert_eq!(s.values().copied().collect::<Vec<_>>(), [0, 10, 20, 30]);
+    /// ```
+    pub fn extend_copy(&mut self, values: &[T]) -> Range<usize>
+    where
+        T: Copy,
+    {
+        self.insert_contiguous_with(values.iter().copied(), |entries, iter| {
+            entries.extend_occupied_copy(&values[values.len() - iter.len()..])
+        })
+    }
+
+    /// Fills the free space at the end with values from `iter`, and then stores the rest with `append`.
+    fn insert_contiguous_with<I: Iterator<Item = T>>(
+        &mut self,
+        mut iter: I,
+        append: impl FnOnce(&mut Entries<T>, I),
+    ) -> Range<usize> {
+        let entries_len = self.entries.len();
+        let start = match self.entries.last() {
+            Some(Entry::VacantHead { vacant_len: 1 }) => entries_len - 1,
+            Some(Entry::VacantTail { vacant_len }) => entries_len - run_len(vacant_len),
+            _ => entries_len,
+        };
+        let mut idx = start;
+        while idx < entries_len {
+            let Some(value) = iter.next() else {
+                break;
+            };
+            self.entries.set(idx, Entry::Occupied(value));
+            idx += 1;
+        }
+        if let Some(x) = &mut self.extras {
+            x.non_optimized_count = x.non_optimized_count.saturating_sub(idx - start);
+        }
+        let end = if idx < entries_len {
+            if idx != start {
+                self.set_vacant_run(idx, entries_len - idx);
+                self.extras_mut().vacant_heads.push(idx);
+                if self.extras().vacant_heads.len() > self.entries.len() {
+                    self.rebuild_vacant_heads();
+                }
+            }
+            idx
+        } else {
+            append(&mut self.entries, iter);
+            self.entries.len()
+        };
+        let count = end - start;
+        self.len += count;
+        if count != 0 {
+            self.on_occupied(start);
+            self.on_occupied(end - 1);
+        }
+        if let Some(x) = &mut self.extras {
+            let q = &mut x.quarantine;
+            if count != 0 && (q.window != 0 || !q.keys.is_empty()) {
+                q.inserts = q.inserts.wrapping_add(count);
+                let inserts = q.inserts;
+                self.release_quarantine(inserts);
+            }
+        }
+        start..end
+    }
+
+    /// Returns a mutable view of the values at keys `start..start + len` if all of them are occupied.
+    pub fn get_span_mut(&mut self, start: K, len: usize) -> Option<SpanMut<'_, T>> {
+        let start = start.into_index();
+        let values = self
+            .entries
+            .get_occupied_mut(start..start.checked_add(len)?)?;
+        Some(SpanMut(values))
+    }
+}
+
+/// A view of values at consecutive keys of a [`SlabMap`].
+///
+/// This struct is created by the [`get_span`](SlabMap::get_span).
+pub struct Span<'a, T>(&'a [T]);
+
+impl<'a, T> Span<'a, T> {
+    /// Returns the number of values in the span.
+    #[inline]
+    pub fn len(&self) -> usize {
+        self.0.len()
+    }
+
+    /// Returns true if the span contains no values.
+    #[inline]
+    pub fn is_empty(&self) -> bool {
+        self.0.is_empty()
+    }
+
+    /// Returns a reference to the value at `index` in the span.
+    #[inline]
+    pub fn get(&self, index: usize) -> Option<&'a T> {
+        self.0.get(index)
+    }
+
+    /// Gets an iterator over the values in the span.
+    #[inline]
+    pub fn iter(&self) -> SpanIter<'a, T> {
+        SpanIter(self.0.iter())
+    }
+}
+impl<T> Index<usize> for Span<'_, T> {
+    type Output = T;
+
+    #[inline]
+    fn index(&self, index: usize) -> &Self::Output {
+        &self.0[index]
+    }
+}
+impl<'a, T> IntoIterator for Span<'a, T> {
+    type Item = &'a T;
+    type IntoIter = SpanIter<'a, T>;
+
+    #[inline]
+    fn into_iter(self) -> Self::IntoIter {
+        self.iter()
+    }
+}
+
+/// A mutable view of values at consecutive keys of a [`SlabMap`].
+///
+/// This struct is created by the [`get_span_mut`](SlabMap::get_span_mut).
+pub struct SpanMut<'a, T>(&'a mut [T]);
+
+impl<T> SpanMut<'_, T> {
+    /// Returns the number of values in the span.
+    #[inline]
+    pub fn len(&self) -> usize {
+        self.0.len()
+    }
+
+    /// Returns true if the span contains no values.
+    #[inline]
+    pub fn is_empty(&self) -> bool {
+        self.0.is_empty()
+    }
+
+    /// Returns a reference to the value at `index` in the span.
+    #[inline]
+    pub fn get(&self, index: usize) -> Option<&T> {
+        self.0.get(index)
+    }
+
+    /// Returns a mutable reference to the value at `index` in the span.
+    #[inline]
+    pub fn get_mut(&mut self, index: usize) -> Option<&mut T> {
+        self.0.get_mut(index)
+    }
+
+    /// Gets an iterator over the values in the span.
+    #[inline]
+    pub fn iter(&self) -> SpanIter<'_, T> {
+        SpanIter(self.0.iter())
+    }
+
+    /// Gets a mutable iterator over the values in the span.
+    #[inline]
+    pub fn iter_mut(&mut self) -> SpanIterMut<'_, T> {
+        SpanIterMut(self.0.iter_mut())
+    }
+}
+impl<T> Index<usize> for SpanMut<'_, T> {
+    type Output = T;
+
+    #[inline]
+    fn index(&self, index: usize) -> &Self::Output {
+        &self.0[index]
+    }
+}
+impl<T> IndexMut<usize> for SpanMut<'_, T> {
+    #[inline]
+    fn index_mut(&mut self, index: usize) -> &mut Self::Output {
+        &mut self.0[index]
+    }
+}
+impl<'a, T> IntoIterator for SpanMut<'a, T> {
+    type Item = &'a mut T;
+    type IntoIter = SpanIterMut<'a, T>;
+
+    #[inline]
+    fn into_iter(self) -> Self::IntoIter {
+        SpanIterMut(self.0.iter_mut())
+    }
+}
+
+/// An iterator over the values of a [`Span`] or [`SpanMut`].
+pub struct SpanIter<'a, T>(core::slice::Iter<'a, T>);
+impl<'a, T> Iterator for SpanIter<'a, T> {
+    type Item = &'a T;
+    #[inline]
+    fn next(&mut self) -> Option<Self::Item> {
+        self.0.next()
+    }
+    #[inline]
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        self.0.size_hint()
+    }
+}
+impl<T> DoubleEndedIterator for SpanIter<'_, T> {
+    #[inline]
+    fn next_back(&mut self) -> Option<Self::Item> {
+        self.0.next_back()
+    }
+}
+impl<T> FusedIterator for SpanIter<'_, T> {}
+impl<T> ExactSizeIterator for SpanIter<'_, T> {}
+
+/// A mutable iterator over the values of a [`SpanMut`].
+pub struct SpanIterMut<'a, T>(core::slice::IterMut<'a, T>);
+impl<'a, T> Iterator for SpanIterMut<'a, T> {
+    type Item = &'a mut T;
+    #[inline]
+    fn next(&mut self) -> Option<Self::Item> {
+        self.0.next()
+    }
+    #[inline]
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        self.0.size_hint()
+    }
+}
+impl<T> DoubleEndedIterator for SpanIterMut<'_, T> {
+    #[inline]
+    fn next_back(&mut self) -> Option<Self::Item> {
+        self.0.next_back()
+    }
+}
+impl<T> FusedIterator for SpanIterMut<'_, T> {}
+impl<T> ExactSizeIterator for SpanIterMut<'_, T> {}
@@ -0,0 +1,758 @@
+use alloc::{
+    collections::TryReserveError,
+    vec::{self, Vec},
+};
+use core::{
+    iter::FusedIterator,
+    mem::{needs_drop, replace, size_of, take, MaybeUninit},
+    ops, slice,
+};
+
+use super::{Entry, RunLen};
+
+const TAIL: RunLen = 1 << (RunLen::BITS - 1);
+const HEAD: RunLen = 1 << (RunLen::BITS - 2);
+
+/// The maximum length of a run of vacant entries.
+pub(super) const MAX_RUN_LEN: RunLen = HEAD - 1;
+
+/// The state of an entry without its value, packed into a single [`RunLen`].
+///
+/// The head and the tail of a run set the `HEAD` and `TAIL` bits and store the length of the run in the remaining bits.
+/// The other states are small constants.
+#[derive(Clone, Copy, PartialEq, Eq)]
+pub(super) struct Meta(RunLen);
+
+impl Meta {
+    const OCCUPIED: Self = Self(0);
+    const VACANT_BODY: Self = Self(1);
+    const QUARANTINED: Self = Self(2);
+    const CLAIMED: Self = Self(3);
+
+    #[inline]
+    fn new<V>(e: &Entry<V>) -> Self {
+        match *e {
+            Entry::Occupied(_) => Self::OCCUPIED,
+            Entry::VacantHead { vacant_len } => {
+                debug_assert!(vacant_len <= MAX_RUN_LEN);
+                Self(HEAD | vacant_len)
+            }
+            Entry::VacantTail { vacant_len } => {
+                debug_assert!(vacant_len <= MAX_RUN_LEN);
+                Self(TAIL | vacant_len)
+            }
+            Entry::VacantBody => Self::VACANT_BODY,
+            Entry::Quarantined => Self::QUARANTINED,
+            Entry::Claimed => Self::CLAIMED,
+        }
+    }
+
+    #[inline]
+    pub(super) fn is_occupied(self) -> bool {
+        self == Self::OCCUPIED
+    }
+
+    /// Returns true if the entry is neither occupied nor the head of a run,
+    /// so an iterator can only step over it one entry at a time.
+    #[inline]
+    fn is_passive(self) -> bool {
+        self != Self::OCCUPIED && self.0 & HEAD == 0
+    }
+
+    #[inline]
+    fn get(self) -> Entry<()> {
+        if self.0 & TAIL != 0 {
+            Entry::VacantTail {
+                vacant_len: self.0 & !TAIL,
+            }
+        } else if self.0 & HEAD != 0 {
+            Entry::VacantHead {
+                vacant_len: self.0 & !HEAD,
+            }
+        } else {
+            match self {
+                Self::OCCUPIED => Entry::Occupied(()),
+                Self::VACANT_BODY => Entry::VacantBody,
+                Self::QUARANTINED => Entry::Quarantined,
+                _ => Entry::Claimed,
+            }
+        }
+    }
+
+    /// Returns the state of an entry that is known not to be occupied.
+    #[inline]
+    fn get_unoccupied<V>(self) -> Entry<V> {
+        debug_assert!(!self.is_occupied());
+        self.get().map(|()| unreachable!())
+    }
+}
+
+/// Returns the number of passive entries at the beginning of `metas`.
+///
+/// The states are checked a chunk at a time without branching on each entry,
+/// so that the compiler can vectorize the scan over long stretches of quarantined or claimed entries.
+#[inline]
+fn passive_len(metas: &[Meta]) -> usize {
+    const CHUNK_LEN: usize = 16;
+    let mut len = 0;
+    for chunk in metas.chunks_exact(CHUNK_LEN) {
+        if !chunk.iter().fold(true, |all, m| all & m.is_passive()) {
+            break;
+        }
+        len += CHUNK_LEN;
+    }
+    let rest = &metas[len..];
+    len + rest
+        .iter()
+        .position(|m| !m.is_passive())
+        .unwrap_or(rest.len())
+}
+
+/// An iterator over the states of entries that can also be viewed as a slice.
+pub(super) trait Metas: Iterator<Item = Meta> {
+    fn as_slice(&self) -> &[Meta];
+}
+impl Metas for vec::IntoIter<Meta> {
+    #[inline]
+    fn as_slice(&self) -> &[Meta] {
+        self.as_slice()
+    }
+}
+impl Metas for vec::Drain<'_, Meta> {
+    #[inline]
+    fn as_slice(&self) -> &[Meta] {
+        self.as_slice()
+    }
+}
+
+/// An iterator over the states of borrowed entries.
+pub(super) struct MetaIter<'a>(slice::Iter<'a, Meta>);
+
+impl Iterator for MetaIter<'_> {
+    type Item = Meta;
+
+    #[inline]
+    fn next(&mut self) -> Option<Self::Item> {
+        self.0.next().copied()
+    }
+    #[inline]
+    fn nth(&mut self, n: usize) -> Option<Self::Item> {
+        self.0.nth(n).copied()
+    }
+    #[inline]
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        self.0.size_hint()
+    }
+}
+impl ExactSizeIterator for MetaIter<'_> {}
+impl FusedIterator for MetaIter<'_> {}
+impl Metas for MetaIter<'_> {
+    #[inline]
+    fn as_slice(&self) -> &[Meta] {
+        self.0.as_slice()
+    }
+}
+
+impl<T> Entry<T> {
+    #[inline]
+    fn map<U>(self, f: impl FnOnce(T) -> U) -> Entry<U> {
+        match self {
+            Entry::Occupied(value) => Entry::Occupied(f(value)),
+            Entry::VacantHead { vacant_len } => Entry::VacantHead { vacant_len },
+            Entry::VacantTail { vacant_len } => Entry::VacantTail { vacant_len },
+            Entry::VacantBody => Entry::VacantBody,
+            Entry::Quarantined => Entry::Quarantined,
+            Entry::Claimed => Entry::Claimed,
+        }
+    }
+}
+
+/// The entries of a [`SlabMap`](super::SlabMap), stored as an array of values and an array of [`Meta`].
+///
+/// Since the state of each entry is not stored next to its value, small values are not padded to the size of the state,
+/// and scanning the states does not load the values.
+///
+/// `values[idx]` is initialized if and only if `metas[idx]` is occupied, and both arrays always have the same length.
+pub(super) struct Entries<T> {
+    metas: Vec<Meta>,
+    values: Vec<MaybeUninit<T>>,
+}
+
+impl<T> Entries<T> {
+    #[inline]
+    pub const fn new() -> Self {
+        Self {
+            metas: Vec::new(),
+            values: Vec::new(),
+        }
+    }
+
+    #[inline]
+    pub fn with_capacity(capacity: usize) -> Self {
+        Self {
+            metas: Vec::with_capacity(capacity),
+            values: Vec::with_capacity(capacity),
+        }
+    }
+
+    /// Returns the number of bytes allocated for each entry.
+    #[inline]
+    pub const fn entry_size() -> usize {
+        size_of::<T>() + size_of::<Meta>()
+    }
+
+    #[inline]
+    pub fn len(&self) -> usize {
+        self.metas.len()
+    }
+
+    #[inline]
+    pub fn capacity(&self) -> usize {
+        self.metas.capacity().min(self.values.capacity())
+    }
+
+    #[inline]
+    pub fn allocated_bytes(&self) -> usize {
+        self.metas.capacity() * size_of::<Meta>() + self.values.capacity() * size_of::<T>()
+    }
+
+    #[inline]
+    pub fn reserve(&mut self, additional: usize) {
+        self.metas.reserve(additional);
+        self.values.reserve(additional);
+    }
+
+    #[inline]
+    pub fn try_reserve(&mut self, additional: usize) -> Result<(), TryReserveError> {
+        self.metas.try_reserve(additional)?;
+        self.values.try_reserve(additional)
+    }
+
+    #[inline]
+    pub fn try_reserve_exact(&mut self, additional: usize) -> Result<(), TryReserveError> {
+        self.metas.try_reserve_exact(additional)?;
+        self.values.try_reserve_exact(additional)
+    }
+
+    pub fn shrink_to(&mut self, min_capacity: usize) {
+        self.metas.shrink_to(min_capacity);
+        self.values.shrink_to(min_capacity);
+    }
+
+    #[inline]
+    pub fn get(&self, idx: usize) -> Option<Entry<&T>> {
+        let meta = *self.metas.get(idx)?;
+        // SAFETY: The value of an occupied entry is initialized.
+        Some(
+            meta.get()
+                .map(|()| unsafe { self.values[idx].assume_init_ref() }),
+        )
+    }
+
+    #[inline]
+    pub fn get_mut(&mut self, idx: usize) -> Option<Entry<&mut T>> {
+        let meta = *self.metas.get(idx)?;
+        // SAFETY: The value of an occupied entry is initialized.
+        Some(
+            meta.get()
+                .map(|()| unsafe { self.values[idx].assume_init_mut() }),
+        )
+    }
+
+    /// Returns the entry at `idx`.
+    ///
+    /// # Panics
+    /// Panics if `idx` is out of bounds.
+    #[inline]
+    pub fn entry(&self, idx: usize) -> Entry<&T> {
+        self.get(idx).expect("index out of bounds")
+    }
+
+    /// Returns the index of the first entry at or after `idx` that is occupied or the head of a run.
+    #[inline]
+    pub fn skip_passive(&self, idx: usize) -> usize {
+        idx + passive_len(&self.metas[idx..])
+    }
+
+    #[inline]
+    pub fn last(&self) -> Option<Entry<&T>> {
+        self.get(self.len().checked_sub(1)?)
+    }
+
+    /// Returns mutable references to the values at `idxs` if all of them are occupied and distinct.
+    pub fn get_disjoint_mut<const N: usize>(&mut self, idxs: [usize; N]) -> Option<[&mut T; N]> {
+        let is_occupied = |&idx: &usize| self.metas.get(idx).is_some_and(|m| m.is_occupied());
+        if !idxs.iter().all(is_occupied) {
+            return None;
+        }
+        let values = self.values.get_disjoint_mut(idxs).ok()?;
+        // SAFETY: All the entries are occupied.
+        Some(values.map(|value| unsafe { value.assume_init_mut() }))
+    }
+
+    /// Returns the values at `range` as a slice if all of them are occupied.
+    pub fn get_occupied(&self, range: ops::Range<usize>) -> Option<&[T]> {
+        let metas = self.metas.get(range.clone())?;
+        if !metas.iter().all(|m| m.is_occupied()) {
+            return None;
+        }
+        let values: *const [MaybeUninit<T>] = &self.values[range];
+        // SAFETY: All the values are initialized, and `MaybeUninit<T>` has the same layout as `T`.
+        Some(unsafe { &*(values as *const [T]) })
+    }
+
+    /// Returns the values at `range` as a mutable slice if all of them are occupied.
+    pub fn get_occupied_mut(&mut self, range: ops::Range<usize>) -> Option<&mut [T]> {
+        let metas = self.metas.get(range.clone())?;
+        if !metas.iter().all(|m| m.is_occupied()) {
+            return None;
+        }
+        let values: *mut [MaybeUninit<T>] = &mut self.values[range];
+        // SAFETY: All the values are initialized, and `MaybeUninit<T>` has the same layout as `T`.
+        Some(unsafe { &mut *(values as *mut [T]) })
+    }
+
+    /// Replaces the entry at `idx` and returns the old entry.
+    ///
+    /// # Panics
+    /// Panics if `idx` is out of bounds.
+    #[inline]
+    pub fn replace(&mut self, idx: usize, e: Entry<T>) -> Entry<T> {
+        let meta = Meta::new(&e);
+        let value = &mut self.values[idx];
+        let old_meta = replace(&mut self.metas[idx], meta);
+        // SAFETY: The value of an occupied entry is initialized, and the new state no longer refers to it.
+        let old = old_meta.get().map(|()| unsafe { value.assume_init_read() });
+        if let Entry::Occupied(new_value) = e {
+            value.write(new_value);
+        }
+        old
+    }
+
+    /// Sets the entry at `idx`, dropping the old value.
+    ///
+    /// # Panics
+    /// Panics if `idx` is out of bounds.
+    #[inline]
+    pub fn set(&mut self, idx: usize, e: Entry<T>) {
+        drop(self.replace(idx, e));
+    }
+
+    /// Stores a value at `idx` and returns a mutable reference to it, dropping the old value.
+    ///
+    /// # Panics
+    /// Panics if `idx` is out of bounds.
+    #[inline]
+    pub fn set_value(&mut self, idx: usize, value: T) -> &mut T {
+        self.set(idx, Entry::Occupied(value));
+        // SAFETY: The value has just been stored.
+        unsafe { self.values[idx].assume_init_mut() }
+    }
+
+    #[inline]
+    pub fn push(&mut self, e: Entry<T>) {
+        let meta = Meta::new(&e);
+        // Reserve first so that the arrays never differ in length.
+        self.metas.reserve(1);
+        self.values.push(match e {
+            Entry::Occupied(value) => MaybeUninit::new(value),
+            _ => MaybeUninit::uninit(),
+        });
+        self.metas.push(meta);
+    }
+
+    #[inline]
+    pub fn pop(&mut self) -> Option<Entry<T>> {
+        let meta = self.metas.pop()?;
+        let value = self.values.pop().unwrap();
+        // SAFETY: The value of an occupied entry is initialized.
+        Some(meta.get().map(|()| unsafe { value.assume_init() }))
+    }
+
+    pub fn resize_with(&mut self, len: usize, mut f: impl FnMut() -> Entry<T>) {
+        if len <= self.len() {
+            self.truncate(len);
+        } else {
+            self.reserve(len - self.len());
+            while self.len() < len {
+                self.push(f());
+            }
+        }
+    }
+
+    pub fn truncate(&mut self, len: usize) {
+        if needs_drop::<T>() {
+            while self.len() > len {
+                drop(self.pop());
+            }
+        } else {
+            self.metas.truncate(len);
+            self.values.truncate(len);
+        }
+    }
+
+    #[inline]
+    pub fn clear(&mut self) {
+        self.truncate(0);
+    }
+
+    /// Appends copies of the entries of `other`, keeping the states as they are.
+    pub fn extend_from_entries(&mut self, other: &Self)
+    where
+        T: Copy,
+    {
+        self.reserve(other.len());
+        self.values.extend_from_slice(&other.values);
+        self.metas.extend_from_slice(&other.metas);
+    }
+
+    /// Appends `values` as occupied entries by copying the slice as a whole.
+    pub fn extend_occupied_copy(&mut self, values: &[T])
+    where
+        T: Copy,
+    {
+        self.reserve(values.len());
+        let values: *const [T] = values;
+        // SAFETY: `MaybeUninit<T>` has the same layout as `T`.
+        self.values
+            .extend_from_slice(unsafe { &*(values as *const [MaybeUninit<T>]) });
+        self.metas.resize(self.values.len(), Meta::OCCUPIED);
+    }
+
+    /// Appends copies of the occupied values to `out` in index order, copying each run of occupied entries as a whole.
+    pub fn copy_occupied_into(&self, out: &mut Vec<T>)
+    where
+        T: Copy,
+    {
+        let mut idx = 0;
+        while let Some(n) = self.metas[idx..].iter().position(|m| m.is_occupied()) {
+            let start = idx + n;
+            idx = self.metas[start..]
+                .iter()
+                .position(|m| !m.is_occupied())
+                .map_or(self.len(), |n| start + n);
+            let values: *const [MaybeUninit<T>] = &self.values[start..idx];
+            // SAFETY: All the values are initialized, and `MaybeUninit<T>` has the same layout as `T`.
+            out.extend_from_slice(unsafe { &*(values as *const [T]) });
+        }
+    }
+
+    #[inline]
+    pub fn iter(&self) -> Iter<'_, T> {
+        RawIter::new(MetaIter(self.metas.iter()), self.values.iter(), 0)
+    }
+
+    #[inline]
+    pub fn iter_mut(&mut self) -> IterMut<'_, T> {
+        RawIter::new(MetaIter(self.metas.iter()), self.values.iter_mut(), 0)
+    }
+
+    /// Gets an iterator over the entries at `range`.
+    ///
+    /// # Panics
+    /// Panics if `range` is out of bounds.
+    #[inline]
+    pub fn range(&self, range: ops::Range<usize>) -> Iter<'_, T> {
+        let start = range.start;
+        let metas = MetaIter(self.metas[range.clone()].iter());
+        RawIter::new(metas, self.values[range].iter(), start)
+    }
+
+    /// Gets a mutable iterator over the entries at `range`.
+    ///
+    /// # Panics
+    /// Panics if `range` is out of bounds.
+    #[inline]
+    pub fn range_mut(&mut self, range: ops::Range<usize>) -> IterMut<'_, T> {
+        let start = range.start;
+        let metas = MetaIter(self.metas[range.clone()].iter());
+        RawIter::new(metas, self.values[range].iter_mut(), start)
+    }
+
+    /// Removes all entries and returns them as an iterator.
+    ///
+    /// The values that are not yielded are dropped with the iterator.
+    #[inline]
+    pub fn drain(&mut self) -> Drain<'_, T> {
+        RawIter::new(self.metas.drain(..), self.values.drain(..), 0)
+    }
+
+    /// Returns the states and the storage of the values for operations over the whole array.
+    ///
+    /// The values of occupied entries are initialized. The others are uninitialized and may be overwritten.
+    #[cfg(any(feature = "rayon", feature = "zeroize"))]
+    #[inline]
+    pub fn split_mut(&mut self) -> (&[Meta], &mut [MaybeUninit<T>]) {
+        (&self.metas, &mut self.values)
+    }
+
+    /// Reserves capacity like [`reserve`](Self::reserve), but zeroizes the old storage of the values when it is reallocated.
+    #[cfg(feature = "zeroize")]
+    pub fn reserve_zeroizing(&mut self, additional: usize) {
+        use zeroize::Zeroize;
+
+        self.metas.reserve(additional);
+        if self.values.capacity() - self.values.len() < additional {
+            let capacity = (self.values.len() + additional).max(self.values.capacity() * 2);
+            let mut values = Vec::with_capacity(capacity);
+            values.append(&mut self.values);
+            self.values.spare_capacity_mut().zeroize();
+            self.values = values;
+        }
+    }
+
+    /// Returns the storage of the values beyond the length.
+    #[cfg(feature = "zeroize")]
+    #[inline]
+    pub fn spare_capacity_mut(&mut self) -> &mut [MaybeUninit<MaybeUninit<T>>] {
+        self.values.spare_capacity_mut()
+    }
+}
+
+impl<T> Drop for Entries<T> {
+    fn drop(&mut self) {
+        self.clear();
+    }
+}
+impl<T> Default for Entries<T> {
+    #[inline]
+    fn default() -> Self {
+        Self::new()
+    }
+}
+impl<T: Clone> Clone for Entries<T> {
+    fn clone(&self) -> Self {
+        let mut entries = Self::with_capacity(self.len());
+        for (_, e) in self.iter() {
+            entries.push(e.map(T::clone));
+        }
+        entries
+    }
+    fn clone_from(&mut self, source: &Self) {
+        self.clear();
+        self.reserve(source.len());
+        for (_, e) in source.iter() {
+            self.push(e.map(T::clone));
+        }
+    }
+}
+impl<T> Extend<Entry<T>> for Entries<T> {
+    fn extend<I: IntoIterator<Item = Entry<T>>>(&mut self, iter: I) {
+        let iter = iter.into_iter();
+        self.reserve(iter.size_hint().0);
+        for e in iter {
+            self.push(e);
+        }
+    }
+}
+impl<T> FromIterator<Entry<T>> for Entries<T> {
+    fn from_iter<I: IntoIterator<Item = Entry<T>>>(iter: I) -> Self {
+        let mut entries = Self::new();
+        entries.extend(iter);
+        entries
+    }
+}
+impl<T> IntoIterator for Entries<T> {
+    type Item = (usize, Entry<T>);
+    type IntoIter = IntoIter<T>;
+
+    #[inline]
+    fn into_iter(mut self) -> Self::IntoIter {
+        let metas = take(&mut self.metas);
+        let values = take(&mut self.values);
+        RawIter::new(metas.into_iter(), values.into_iter(), 0)
+    }
+}
+
+/// The storage of a value, which becomes the value of an occupied entry.
+pub(super) trait Slot {
+    type Value;
+
+    /// True if the values must be dropped when the iterator is dropped.
+    const NEEDS_DROP: bool;
+
+    /// # Safety
+    /// The value must be initialized.
+    unsafe fn assume_init(self) -> Self::Value;
+}
+impl<'a, T> Slot for &'a MaybeUninit<T> {
+    type Value = &'a T;
+    const NEEDS_DROP: bool = false;
+
+    #[inline]
+    unsafe fn assume_init(self) -> Self::Value {
+        self.assume_init_ref()
+    }
+}
+impl<'a, T> Slot for &'a mut MaybeUninit<T> {
+    type Value = &'a mut T;
+    const NEEDS_DROP: bool = false;
+
+    #[inline]
+    unsafe fn assume_init(self) -> Self::Value {
+        self.assume_init_mut()
+    }
+}
+impl<T> Slot for MaybeUninit<T> {
+    type Value = T;
+    const NEEDS_DROP: bool = needs_drop::<T>();
+
+    #[inline]
+    unsafe fn assume_init(self) -> Self::Value {
+        self.assume_init()
+    }
+}
+
+pub(super) type Iter<'a, T> = RawIter<MetaIter<'a>, slice::Iter<'a, MaybeUninit<T>>>;
+pub(super) type IterMut<'a, T> = RawIter<MetaIter<'a>, slice::IterMut<'a, MaybeUninit<T>>>;
+pub(super) type IntoIter<T> = RawIter<vec::IntoIter<Meta>, vec::IntoIter<MaybeUninit<T>>>;
+pub(super) type Drain<'a, T> = RawIter<vec::Drain<'a, Meta>, vec::Drain<'a, MaybeUninit<T>>>;
+
+/// An iterator over the states and the values of entries in lockstep, yielding each entry with its index.
+///
+/// `metas` and `values` always have the same number of remaining items, so only `metas` is checked for the end.
+/// Owned values that are not yielded are dropped with the iterator.
+pub(super) struct RawIter<M, V>
+where
+    M: Metas,
+    V: Iterator,
+    V::Item: Slot,
+{
+    metas: M,
+    values: V,
+    idx: usize,
+}
+impl<M, V> RawIter<M, V>
+where
+    M: Metas,
+    V: Iterator,
+    V::Item: Slot,
+{
+    #[inline]
+    fn new(metas: M, values: V, idx: usize) -> Self {
+        Self { metas, values, idx }
+    }
+
+    /// Skips `n` vacant entries in O(1).
+    ///
+    /// Unlike [`nth`](Iterator::nth), the values of the skipped entries are not dropped, so they must not be occupied.
+    #[inline]
+    pub fn skip_vacant(&mut self, n: usize) {
+        if let Some(n) = n.checked_sub(1) {
+            self.metas.nth(n);
+            self.values.nth(n);
+            self.idx += n + 1;
+        }
+    }
+
+    /// Skips the following entries that are neither occupied nor the head of a run.
+    #[inline]
+    pub fn skip_passive(&mut self) {
+        self.skip_vacant(passive_len(self.metas.as_slice()));
+    }
+
+    /// Returns the next occupied entry, skipping runs of vacant entries by their length.
+    #[inline]
+    pub fn next_occupied(&mut self) -> Option<(usize, <V::Item as Slot>::Value)> {
+        loop {
+            match self.next()? {
+                (idx, Entry::Occupied(value)) => return Some((idx, value)),
+                (_, Entry::VacantHead { vacant_len }) => {
+                    self.skip_vacant(super::run_len(vacant_len) - 1)
+                }
+                _ => self.skip_passive(),
+            }
+        }
+    }
+
+    /// Returns the `n`th next occupied entry, where `occupied` is the number of the remaining occupied entries.
+    ///
+    /// If all the remaining entries are occupied, the entry is found without checking the states of the skipped entries.
+    #[inline]
+    pub fn nth_occupied(
+        &mut self,
+        n: usize,
+        occupied: usize,
+    ) -> Option<(usize, <V::Item as Slot>::Value)> {
+        if occupied == self.metas.as_slice().len() {
+            let (idx, e) = self.nth(n)?;
+            let Entry::Occupied(value) = e else {
+                unreachable!()
+            };
+            return Some((idx, value));
+        }
+        for _ in 0..n {
+            self.next_occupied()?;
+        }
+        self.next_occupied()
+    }
+}
+impl<M, V> Iterator for RawIter<M, V>
+where
+    M: Metas,
+    V: Iterator,
+    V::Item: Slot,
+{
+    type Item = (usize, Entry<<V::Item as Slot>::Value>);
+
+    #[inline]
+    fn next(&mut self) -> Option<Self::Item> {
+        let meta = self.metas.next()?;
+        // SAFETY: `values` has as many remaining items as `metas`.
+        let value = unsafe { self.values.next().unwrap_unchecked() };
+        let idx = self.idx;
+        self.idx += 1;
+        let e = if meta.is_occupied() {
+            // SAFETY: The value of an occupied entry is initialized.
+            Entry::Occupied(unsafe { value.assume_init() })
+        } else {
+            meta.get_unoccupied()
+        };
+        Some((idx, e))
+    }
+    #[inline]
+    fn nth(&mut self, n: usize) -> Option<Self::Item> {
+        if <V::Item as Slot>::NEEDS_DROP {
+            for _ in 0..n {
+                self.next()?;
+            }
+        } else {
+            self.skip_vacant(n);
+        }
+        self.next()
+    }
+    #[inline]
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        self.metas.size_hint()
+    }
+}
+impl<M, V> ExactSizeIterator for RawIter<M, V>
+where
+    M: Metas + ExactSizeIterator,
+    V: Iterator,
+    V::Item: Slot,
+{
+}
+impl<M, V> FusedIterator for RawIter<M, V>
+where
+    M: Metas + FusedIterator,
+    V: Iterator,
+    V::Item: Slot,
+{
+}
+impl<M, V> Drop for RawIter<M, V>
+where
+    M: Metas,
+    V: Iterator,
+    V::Item: Slot,
+{
+    fn drop(&mut self) {
+        if <V::Item as Slot>::NEEDS_DROP {
+            while let Some((_, e)) = self.next() {
+                if let Entry::VacantHead { vacant_len } = e {
+                    self.skip_vacant(super::run_len(vacant_len) - 1);
+                }
+            }
+        }
+    }
+}
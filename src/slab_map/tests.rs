@@ -1,6 +1,16 @@
-use std::time::Instant;
+use std::{
+    collections::{BTreeMap, BTreeSet},
+    time::Instant,
+};
 
-use crate::SlabMap;
+use rand::{rngs::StdRng, Rng, SeedableRng};
+
+use crate::{
+    slab_map::{
+        run_len, Entry, IntegrityError, KeyConflict, OccupiedError, RunKind, ShrinkPolicy, Visit,
+    },
+    SlabKey, SlabMap,
+};
 
 #[test]
 fn test_new() {
@@ -16,6 +26,22 @@ fn test_with_capacity() {
     }
 }
 
+#[test]
+fn size_of_slab_map() {
+    // The values, the states, the length and the boxed state for free space and optional features.
+    assert_eq!(size_of::<SlabMap<u32>>(), size_of::<[usize; 8]>());
+    assert_eq!(size_of::<SlabMap<u32>>(), size_of::<SlabMap<[u8; 100]>>());
+
+    let mut s = SlabMap::new();
+    for i in 0..10 {
+        s.insert(i);
+    }
+    s.remove(9);
+    assert!(s.extras.is_none());
+    s.remove(0);
+    assert!(s.extras.is_some());
+}
+
 #[test]
 fn test_retain() {
     let mut s = SlabMap::new();
@@ -358,3 +384,2088 @@ fn reserve_exact() {
     s.reserve_exact(10);
     assert!(s.capacity() == 10);
 }
+
+#[test]
+fn leak() {
+    let mut s = SlabMap::new();
+    let key = s.insert(10);
+    let s: &'static mut SlabMap<u32> = s.leak();
+    s.insert(20);
+    assert_eq!(s[key], 10);
+    assert_eq!(s.len(), 2);
+}
+
+#[test]
+fn clone_copy() {
+    let mut s = SlabMap::new();
+    for i in 0..10 {
+        s.insert(i);
+    }
+    s.remove(3);
+    s.remove(4);
+    s.remove(7);
+
+    let mut c = s.clone_copy();
+    let e: Vec<_> = s.iter().map(|(k, v)| (k, *v)).collect();
+    let a: Vec<_> = c.iter().map(|(k, v)| (k, *v)).collect();
+    assert_eq!(a, e);
+    assert_eq!(c.len(), s.len());
+
+    assert_eq!(c.insert(100), s.insert(100));
+}
+
+#[test]
+fn clone_from_copy() {
+    let mut s0 = SlabMap::new();
+    let mut s1 = SlabMap::new();
+    for i in 0..10 {
+        s0.insert(i);
+    }
+    s0.remove(5);
+    for _ in 0..1000 {
+        s1.insert(0);
+    }
+    let cap_old = s1.capacity();
+    s1.clone_from_copy(&s0);
+    assert_eq!(s1.capacity(), cap_old);
+    let e: Vec<_> = s0.iter().collect();
+    let a: Vec<_> = s1.iter().collect();
+    assert_eq!(a, e);
+}
+
+#[test]
+fn drain_copy_into() {
+    let mut s = SlabMap::new();
+    s.set_quarantine(2);
+    for i in 0..10 {
+        s.insert(i);
+    }
+    s.remove(0);
+    s.remove(4);
+    s.remove(5);
+    s.claim_key();
+    s.insert(10);
+    let e: Vec<_> = s.values().copied().collect();
+    let mut a = Vec::new();
+    s.drain_copy_into(&mut a);
+    assert_eq!(a, e);
+    assert!(s.is_empty());
+    check_vacants(&s);
+    assert_eq!(s.insert(0), 0);
+}
+
+fn check_vacants<T>(s: &SlabMap<T>) {
+    assert_eq!(s.check_integrity(), Ok(()));
+    let mut idx = 0;
+    let mut prev_vacant = false;
+    while idx < s.entries.len() {
+        match s.entries.entry(idx) {
+            Entry::Occupied(_) => {
+                prev_vacant = false;
+                idx += 1;
+            }
+            Entry::VacantHead { vacant_len } => {
+                assert!(!prev_vacant, "adjacent vacant runs at {idx}");
+                if vacant_len > 1 {
+                    assert!(
+                        matches!(s.entries.entry(idx + run_len(vacant_len) - 1), Entry::VacantTail { vacant_len: l } if l == vacant_len),
+                        "tail of run at {idx}"
+                    );
+                }
+                assert!(
+                    s.extras().vacant_heads.contains(&idx),
+                    "run at {idx} is not reusable"
+                );
+                prev_vacant = true;
+                idx += run_len(vacant_len);
+            }
+            Entry::Claimed => {
+                prev_vacant = false;
+                idx += 1;
+            }
+            Entry::Quarantined => {
+                assert!(
+                    s.extras()
+                        .quarantine
+                        .keys
+                        .iter()
+                        .any(|&(key, _)| key == idx),
+                    "quarantined entry at {idx} is not in quarantine"
+                );
+                prev_vacant = false;
+                idx += 1;
+            }
+            _ => panic!("run without head at {idx}"),
+        }
+    }
+}
+
+#[test]
+fn merge_vacant_on_remove() {
+    let mut s = SlabMap::new();
+    for i in 0..10 {
+        s.insert(i);
+    }
+    s.remove(3);
+    s.remove(5);
+    s.remove(4);
+    check_vacants(&s);
+    assert!(matches!(
+        s.entries.entry(3),
+        Entry::VacantHead { vacant_len: 3 }
+    ));
+
+    s.remove(2);
+    s.remove(6);
+    check_vacants(&s);
+    assert!(matches!(
+        s.entries.entry(2),
+        Entry::VacantHead { vacant_len: 5 }
+    ));
+
+    let a: Vec<_> = s.keys().collect();
+    assert_eq!(a, vec![0, 1, 7, 8, 9]);
+}
+
+#[test]
+fn remove_last_truncates_vacant_tail() {
+    let mut s = SlabMap::new();
+    for i in 0..10 {
+        s.insert(i);
+    }
+    for key in 3..9 {
+        s.remove(key);
+    }
+    s.remove(9);
+    assert_eq!(s.entries.len(), 3);
+    assert!(s.is_optimized());
+    assert_eq!(s.check_integrity(), Ok(()));
+    assert_eq!(s.insert(10), 3);
+
+    let key = s.claim_key();
+    s.remove(3);
+    s.abandon(key);
+    assert_eq!(s.entries.len(), 3);
+    assert_eq!(s.check_integrity(), Ok(()));
+}
+
+#[test]
+fn merge_vacant_random() {
+    let mut rng = StdRng::seed_from_u64(0);
+    let mut s = SlabMap::new();
+    let mut m = BTreeMap::new();
+    for _ in 0..10000 {
+        if rng.gen_bool(0.5) {
+            let key = s.insert(0);
+            m.insert(key, 0);
+        } else {
+            let key = rng.gen_range(0..100);
+            assert_eq!(s.remove(key), m.remove(&key));
+        }
+        check_vacants(&s);
+    }
+    let a: Vec<_> = s.keys().collect();
+    let e: Vec<_> = m.keys().copied().collect();
+    assert_eq!(a, e);
+}
+
+#[cfg(feature = "zeroize")]
+#[test]
+fn zeroize() {
+    use zeroize::Zeroize;
+
+    let mut s = SlabMap::new();
+    let k0 = s.insert(vec![1u8, 2, 3]);
+    let k1 = s.insert(vec![4u8, 5, 6]);
+    s.insert(vec![7u8]);
+    assert!(s.remove_zeroize(k1));
+    assert!(!s.remove_zeroize(k1));
+    assert_eq!(s.len(), 2);
+    check_vacants(&s);
+    s.remove(k0);
+    s.zeroize_vacants();
+    check_vacants(&s);
+
+    s.zeroize();
+    assert!(s.is_empty());
+}
+
+#[cfg(feature = "zeroize")]
+#[test]
+fn remove_zeroize_with_shrink_policy() {
+    let mut s = SlabMap::new();
+    s.set_shrink_policy(Some(ShrinkPolicy {
+        max_capacity_ratio: 3,
+        patience: 1,
+        min_capacity: 0,
+    }));
+    for i in 0..100 {
+        s.insert(vec![i as u8]);
+    }
+    for key in 1..99 {
+        assert!(s.remove_zeroize(key));
+    }
+    let capacity = s.capacity();
+    assert!(s.remove_zeroize(99));
+    assert!(s.capacity() < capacity);
+    assert_eq!(s.values().cloned().collect::<Vec<_>>(), [vec![0u8]]);
+    check_vacants(&s);
+}
+
+#[cfg(feature = "zeroize")]
+#[test]
+fn zeroizing_slab_map() {
+    use super::ZeroizingSlabMap;
+
+    fn item(s: &mut ZeroizingSlabMap<u64>, idx: usize) -> u64 {
+        // SAFETY: The item was written by an insertion and then zeroized.
+        unsafe { s.0.entries.split_mut().1[idx].assume_init() }
+    }
+    fn spare_item(s: &mut ZeroizingSlabMap<u64>, idx: usize) -> u64 {
+        // SAFETY: The item was written by an insertion and then zeroized.
+        unsafe {
+            s.0.entries.spare_capacity_mut()[idx]
+                .assume_init()
+                .assume_init()
+        }
+    }
+
+    let mut s = ZeroizingSlabMap::new();
+    for _ in 0..3 {
+        s.insert(u64::MAX);
+    }
+    assert!(s.remove(1));
+    assert!(!s.remove(1));
+    assert_eq!(item(&mut s, 1), 0);
+    assert!(s.remove(2));
+    assert_eq!(spare_item(&mut s, 0), 0);
+    check_vacants(&s);
+
+    *s.get_mut(0).unwrap() -= 1;
+    assert_eq!(s[0], u64::MAX - 1);
+    s.clear();
+    assert!(s.is_empty());
+    assert_eq!(spare_item(&mut s, 0), 0);
+
+    for i in 0..100 {
+        assert_eq!(s.insert(i), i as usize);
+    }
+    assert!(s.values().copied().eq(0..100));
+}
+
+#[test]
+fn shrink_policy() {
+    let mut s = SlabMap::new();
+    s.set_shrink_policy(Some(ShrinkPolicy {
+        max_capacity_ratio: 4,
+        patience: 10,
+        min_capacity: 16,
+    }));
+    let keys: Vec<_> = (0..1000).map(|i| s.insert(i)).collect();
+    let capacity = s.capacity();
+    for &key in keys[100..].iter().rev() {
+        s.remove(key);
+    }
+    let shrunk_capacity = s.capacity();
+    assert!(shrunk_capacity < capacity);
+    assert!(shrunk_capacity >= 100);
+    check_vacants(&s);
+
+    for &key in keys[80..100].iter().rev() {
+        s.remove(key);
+    }
+    assert_eq!(s.capacity(), shrunk_capacity, "hysteresis");
+
+    let a: Vec<_> = s.values().copied().collect();
+    let e: Vec<_> = (0..80).collect();
+    assert_eq!(a, e);
+}
+
+#[test]
+fn shrink_policy_counts_insertions() {
+    let mut s = SlabMap::new();
+    s.set_shrink_policy(Some(ShrinkPolicy {
+        max_capacity_ratio: 4,
+        patience: 200,
+        min_capacity: 16,
+    }));
+    let keys: Vec<_> = (0..1000).map(|i| s.insert(i)).collect();
+    let capacity = s.capacity();
+    // Only the removals after `len` drops below a quarter of the capacity are counted, which is fewer than `patience`.
+    for &key in keys[100..].iter().rev() {
+        s.remove(key);
+    }
+    assert_eq!(s.capacity(), capacity);
+    for i in 0..100 {
+        s.insert(i);
+    }
+    assert!(s.capacity() < capacity);
+    check_vacants(&s);
+}
+
+#[test]
+fn shrink_policy_does_not_rebuild_if_it_cannot_shrink() {
+    let mut s = SlabMap::new();
+    s.set_shrink_policy(Some(ShrinkPolicy {
+        max_capacity_ratio: 4,
+        patience: 10,
+        min_capacity: 16,
+    }));
+    let keys: Vec<_> = (0..1000).map(|i| s.insert(i)).collect();
+    let capacity = s.capacity();
+    for &key in &keys[..990] {
+        s.remove(key);
+    }
+    assert_eq!(s.capacity(), capacity);
+    assert_eq!(
+        s.extras().non_optimized_count,
+        990,
+        "free space was rebuilt"
+    );
+    check_vacants(&s);
+}
+
+#[test]
+#[should_panic]
+fn shrink_policy_ratio_2() {
+    let mut s = SlabMap::<u32>::new();
+    s.set_shrink_policy(Some(ShrinkPolicy {
+        max_capacity_ratio: 2,
+        ..ShrinkPolicy::default()
+    }));
+}
+
+#[test]
+#[should_panic]
+fn shrink_policy_patience_0() {
+    let mut s = SlabMap::<u32>::new();
+    s.set_shrink_policy(Some(ShrinkPolicy {
+        patience: 0,
+        ..ShrinkPolicy::default()
+    }));
+}
+
+#[test]
+fn shrink_policy_none() {
+    let mut s = SlabMap::new();
+    let keys: Vec<_> = (0..1000).map(|i| s.insert(i)).collect();
+    let capacity = s.capacity();
+    for &key in &keys[10..] {
+        s.remove(key);
+    }
+    assert_eq!(s.capacity(), capacity);
+}
+
+#[test]
+fn quarantine() {
+    let mut s = SlabMap::new();
+    s.set_quarantine(3);
+    let keys: Vec<_> = (0..10).map(|i| s.insert(i)).collect();
+    s.remove(keys[2]);
+    s.remove(keys[3]);
+    s.remove(keys[9]);
+    assert_eq!(s.quarantined_len(), 3);
+    check_vacants(&s);
+    let new_keys: Vec<_> = (0..3).map(|i| s.insert(i)).collect();
+    assert_eq!(new_keys, [10, 11, 12]);
+    let mut reused: Vec<_> = (3..6).map(|i| s.insert(i)).collect();
+    reused.sort();
+    assert_eq!(reused, [2, 3, 9]);
+    assert_eq!(s.quarantined_len(), 0);
+    check_vacants(&s);
+}
+
+#[test]
+fn iter_skips_quarantined_and_claimed() {
+    let mut s = SlabMap::new();
+    s.set_quarantine(1000);
+    for i in 0..100 {
+        s.insert(i);
+    }
+    for key in 0..100 {
+        if key % 37 != 1 {
+            s.remove(key);
+        }
+    }
+    for _ in 0..40 {
+        s.claim_key();
+    }
+    s.insert(140);
+    let e = [1, 38, 75, 140];
+    check_vacants(&s);
+
+    assert_eq!(s.keys().collect::<Vec<_>>(), e);
+    assert_eq!(s.iter_mut().map(|(k, _)| k).collect::<Vec<_>>(), e);
+    assert_eq!(s.range(2..).map(|(k, _)| k).collect::<Vec<_>>(), e[1..]);
+    assert_eq!(
+        s.iter_from_token(2).map(|(k, _)| k).collect::<Vec<_>>(),
+        e[1..]
+    );
+    assert_eq!(s.next_occupied_key(75), Some(140));
+    assert_eq!(s.clone().into_iter().map(|(k, _)| k).collect::<Vec<_>>(), e);
+    assert_eq!(s.clone().drain().map(|(k, _)| k).collect::<Vec<_>>(), e);
+    assert_eq!(
+        s.drain_range(39..).map(|(k, _)| k).collect::<Vec<_>>(),
+        [75, 140]
+    );
+    assert_eq!(s.min_key(), Some(1));
+}
+
+#[test]
+fn reserve_with_quarantine() {
+    let mut s = SlabMap::new();
+    s.set_quarantine(10);
+    let keys: Vec<_> = (0..16).map(|i| s.insert(i)).collect();
+    for key in keys {
+        s.remove(key);
+    }
+    s.reserve_exact(5);
+    assert!(s.capacity() >= 21);
+}
+
+#[test]
+fn reserve_with_claimed() {
+    let mut s = SlabMap::new();
+    let keys: Vec<_> = (0..16).map(|i| s.insert(i)).collect();
+    for &key in &keys[..8] {
+        s.remove(key);
+    }
+    for _ in 0..4 {
+        s.claim_key();
+    }
+    s.reserve(8);
+    let capacity = s.capacity();
+    for i in 0..8 {
+        s.insert(i);
+    }
+    assert_eq!(s.capacity(), capacity);
+}
+
+#[test]
+fn flush_quarantine() {
+    let mut s = SlabMap::new();
+    s.set_quarantine(100);
+    let k0 = s.insert(0);
+    let k1 = s.insert(1);
+    s.remove(k0);
+    s.remove(k1);
+    assert!(s.is_empty());
+    assert_eq!(s.quarantined_len(), 2);
+    s.flush_quarantine();
+    assert_eq!(s.quarantined_len(), 0);
+    assert_eq!(s.insert(2), k0);
+}
+
+#[test]
+fn quarantine_random() {
+    let mut rng = StdRng::seed_from_u64(0);
+    let mut s = SlabMap::new();
+    s.set_quarantine(5);
+    let mut removed_at = BTreeMap::new();
+    let mut inserts = 0;
+    for i in 0..5000 {
+        match rng.gen_range(0..20) {
+            0..=9 => {
+                let key = s.insert(i);
+                if let Some(at) = removed_at.remove(&key) {
+                    assert!(inserts - at >= 5);
+                }
+                inserts += 1;
+            }
+            10..=17 => {
+                let keys: Vec<_> = s.keys().collect();
+                if !keys.is_empty() {
+                    let key = keys[rng.gen_range(0..keys.len())];
+                    s.remove(key);
+                    removed_at.insert(key, inserts);
+                }
+            }
+            18 => s.optimize(),
+            _ => s.retain(|_, v| *v % 3 != 0),
+        }
+        check_vacants(&s);
+    }
+}
+
+#[test]
+fn compact_sorted_by() {
+    let mut rng = StdRng::seed_from_u64(0);
+    let mut s = SlabMap::new();
+    let keys: Vec<_> = (0..200).map(|_| s.insert(rng.gen_range(0..50))).collect();
+    for &key in keys.iter().step_by(3) {
+        s.remove(key);
+    }
+    let old: BTreeMap<_, _> = s.iter().map(|(k, &v)| (k, v)).collect();
+    let mut remap = BTreeMap::new();
+    s.compact_sorted_by(
+        |a, b| b.cmp(a),
+        |old, new| {
+            remap.insert(old, new);
+        },
+    );
+    check_vacants(&s);
+    assert_eq!(s.len(), old.len());
+    for (old_key, value) in &old {
+        assert_eq!(s[remap[old_key]], *value);
+    }
+    let keys: Vec<_> = s.keys().collect();
+    let e: Vec<_> = (0..old.len()).collect();
+    assert_eq!(keys, e);
+    let values: Vec<_> = s.values().copied().collect();
+    assert!(values.windows(2).all(|w| w[0] >= w[1]));
+    assert_eq!(s.insert(0), old.len());
+}
+
+#[test]
+fn clone_subset() {
+    let mut s = SlabMap::new();
+    let keys: Vec<_> = (0..100).map(|i| s.insert(i)).collect();
+    let subset = [keys[90], keys[3], keys[50], keys[3], 1000];
+    let t = s.clone_subset(subset);
+    check_vacants(&t);
+    let a: Vec<_> = t.iter().map(|(k, &v)| (k, v)).collect();
+    assert_eq!(a, [(3, 3), (50, 50), (90, 90)]);
+    assert_eq!(s.len(), 100);
+}
+
+#[test]
+fn take_subset() {
+    let mut s = SlabMap::new();
+    let keys: Vec<_> = (0..100).map(|i| s.insert(i)).collect();
+    let t = s.take_subset([keys[99], keys[3], keys[3], 1000]);
+    check_vacants(&s);
+    check_vacants(&t);
+    let a: Vec<_> = t.iter().map(|(k, &v)| (k, v)).collect();
+    assert_eq!(a, [(3, 3), (99, 99)]);
+    assert_eq!(s.len(), 98);
+    assert!(!s.contains_key(keys[3]));
+}
+
+#[test]
+fn reload() {
+    let mut s = SlabMap::new();
+    for i in 0..100 {
+        s.insert(i);
+    }
+    let capacity = s.capacity();
+    s.reload([(5, 50), (2, 20), (40, 400), (5, 51)]);
+    check_vacants(&s);
+    assert_eq!(s.capacity(), capacity);
+    let a: Vec<_> = s.iter().map(|(k, &v)| (k, v)).collect();
+    assert_eq!(a, [(2, 20), (5, 51), (40, 400)]);
+    assert_eq!(s.len(), 3);
+    assert_eq!(s.insert(0), 0);
+}
+
+#[test]
+fn get_span() {
+    let mut s = SlabMap::new();
+    for i in 0..10 {
+        s.insert(i);
+    }
+    assert_eq!(
+        s.get_span(2, 3)
+            .unwrap()
+            .iter()
+            .copied()
+            .collect::<Vec<_>>(),
+        [2, 3, 4]
+    );
+    assert!(s.get_span(8, 3).is_none());
+    assert!(s.get_span(usize::MAX, 2).is_none());
+    assert_eq!(s.get_span(10, 0).map(|s| s.len()), Some(0));
+
+    let mut span = s.get_span_mut(5, 2).unwrap();
+    span[0] += 10;
+    for v in span.iter_mut() {
+        *v *= 2;
+    }
+    assert_eq!(s[5], 30);
+    assert_eq!(s[6], 12);
+
+    s.remove(6);
+    assert!(s.get_span(5, 2).is_none());
+    assert!(s.get_span_mut(5, 2).is_none());
+}
+
+#[cfg(feature = "serde")]
+#[test]
+fn serde_roundtrip() {
+    let mut s = SlabMap::new();
+    for i in 0..10 {
+        s.insert(i);
+    }
+    s.retain(|key, _| key % 3 != 0);
+    let json = serde_json::to_string(&s).unwrap();
+    let d: SlabMap<i32> = serde_json::from_str(&json).unwrap();
+    assert_eq!(d.iter().collect::<Vec<_>>(), s.iter().collect::<Vec<_>>());
+
+    let mut d = d;
+    for key in [0, 3, 6, 9] {
+        d.insert(key);
+    }
+    assert_eq!(d.len(), 10);
+}
+
+#[cfg(feature = "serde")]
+#[test]
+fn serde_reload_seed() {
+    use serde::de::DeserializeSeed;
+
+    let mut s = SlabMap::new();
+    s.insert(1);
+    s.insert(2);
+    let mut d = serde_json::Deserializer::from_str(r#"{"2":10,"5":20,"2":30}"#);
+    s.reload_seed().deserialize(&mut d).unwrap();
+    assert_eq!(s.iter().collect::<Vec<_>>(), [(2, &30), (5, &20)]);
+
+    let mut d = serde_json::Deserializer::from_str(r#"{"1":10,"x":20}"#);
+    assert!(s.reload_seed().deserialize(&mut d).is_err());
+    assert_eq!(s.iter().collect::<Vec<_>>(), [(1, &10)]);
+    assert_eq!(s.insert(0), 0);
+}
+
+#[test]
+fn extend() {
+    let mut s = SlabMap::new();
+    s.extend(0..10);
+    assert_eq!(s.len(), 10);
+    for key in [2, 3, 7] {
+        s.remove(key);
+    }
+    s.optimize();
+    s.extend(10..15);
+    assert_eq!(s.len(), 12);
+    assert_eq!(s.keys().collect::<Vec<_>>(), (0..12).collect::<Vec<_>>());
+    assert_eq!(s[10], 13);
+    assert_eq!(s[11], 14);
+    check_vacants(&s);
+
+    s.set_quarantine(2);
+    s.remove(0);
+    s.extend([20, 21]);
+    assert!(!s.contains_key(0));
+    assert_eq!(s.len(), 13);
+}
+
+#[test]
+fn extend_panic() {
+    let mut s = SlabMap::new();
+    s.extend(0..4);
+    s.remove(1);
+    let r = std::panic::catch_unwind(std::panic::AssertUnwindSafe(|| {
+        s.extend((10..20).map(|i| if i < 13 { i } else { panic!() }))
+    }));
+    assert!(r.is_err());
+    s.check_integrity().unwrap();
+    check_vacants(&s);
+    assert_eq!(s.len(), 6);
+    assert_eq!(
+        s.values().copied().collect::<Vec<_>>(),
+        [0, 10, 2, 3, 11, 12]
+    );
+    s.remove(5);
+    assert_eq!(s.keys().collect::<Vec<_>>(), [0, 1, 2, 3, 4]);
+}
+
+#[test]
+fn get_or_try_insert_with() {
+    let mut rng = StdRng::seed_from_u64(0);
+    let mut s = SlabMap::new();
+    let mut e = BTreeMap::new();
+    for i in 0..2000 {
+        let key = rng.gen_range(0..64);
+        match rng.gen_range(0..4) {
+            0 => {
+                let r = s.get_or_try_insert_with(key, || Err::<usize, _>(()));
+                assert_eq!(r.ok().copied(), e.get(&key).copied());
+            }
+            1 | 2 => {
+                let v = *s.get_or_try_insert_with(key, || Ok::<_, ()>(i)).unwrap();
+                assert_eq!(v, *e.entry(key).or_insert(i));
+            }
+            _ => {
+                if rng.gen_bool(0.5) {
+                    assert_eq!(s.remove(key), e.remove(&key));
+                } else {
+                    let key = s.insert(i);
+                    assert!(e.insert(key, i).is_none());
+                }
+            }
+        }
+        check_vacants(&s);
+        assert_eq!(s.len(), e.len());
+        assert!(s.iter().eq(e.iter().map(|(&k, v)| (k, v))));
+    }
+}
+
+#[test]
+fn get_or_try_insert_with_quarantined() {
+    let mut s = SlabMap::new();
+    s.set_quarantine(4);
+    let a = s.insert(1);
+    s.insert(2);
+    s.remove(a);
+    assert_eq!(s.get_or_try_insert_with(a, || Ok::<_, ()>(3)), Ok(&mut 3));
+    assert_eq!(s.quarantined_len(), 0);
+    for _ in 0..8 {
+        s.insert(0);
+    }
+    assert_eq!(s[a], 3);
+}
+
+#[test]
+fn upsert_from() {
+    let mut s = SlabMap::new();
+    for i in 0..10 {
+        s.insert(i);
+    }
+    s.retain(|key, _| key % 2 == 0);
+    s.upsert_from((0..12).map(|key| (key, 100)), |v, n| *v += n);
+    check_vacants(&s);
+    assert_eq!(s.len(), 12);
+    for key in 0..12 {
+        let expected = if key % 2 == 0 && key < 10 {
+            key + 100
+        } else {
+            100
+        };
+        assert_eq!(s[key], expected);
+    }
+}
+
+#[test]
+fn values_unordered() {
+    let mut rng = StdRng::seed_from_u64(0);
+    let mut s = SlabMap::new();
+    for i in 0..1000 {
+        if rng.gen_bool(0.6) || s.is_empty() {
+            s.insert(i);
+        } else {
+            s.remove(rng.gen_range(0..i));
+        }
+        if i % 100 == 0 {
+            s.set_quarantine(i % 300);
+        }
+        let mut e: Vec<_> = s.values().collect();
+        let mut a: Vec<_> = s.values_unordered().collect();
+        e.sort();
+        a.sort();
+        assert_eq!(a, e);
+    }
+    let mut sum = 0;
+    s.for_each_unordered(|v| sum += v);
+    assert_eq!(sum, s.values().sum::<usize>());
+}
+
+#[test]
+fn clear_deferred() {
+    use std::rc::Rc;
+
+    let rc = Rc::new(());
+    let mut s = SlabMap::new();
+    for _ in 0..10 {
+        s.insert(rc.clone());
+    }
+    s.remove(3);
+    s.set_quarantine(1);
+    s.remove(5);
+    let d = s.clear_deferred();
+    assert!(s.is_empty());
+    assert_eq!(s.quarantined_len(), 0);
+    assert_eq!(s.capacity(), 0);
+    assert_eq!(d.len(), 8);
+    assert_eq!(Rc::strong_count(&rc), 9);
+    drop(d);
+    assert_eq!(Rc::strong_count(&rc), 1);
+    assert_eq!(s.insert(rc.clone()), 0);
+}
+
+#[test]
+fn values_are_dropped_once() {
+    use std::rc::Rc;
+
+    let rc = Rc::new(());
+    let new = || {
+        let mut s = SlabMap::new();
+        for _ in 0..20 {
+            s.insert(rc.clone());
+        }
+        for key in [3, 4, 5, 10, 19] {
+            s.remove(key);
+        }
+        s
+    };
+    drop(new());
+    assert_eq!(Rc::strong_count(&rc), 1);
+
+    let mut iter = new().into_iter();
+    iter.next();
+    drop(iter);
+    assert_eq!(Rc::strong_count(&rc), 1);
+
+    new().drain().take(2).for_each(drop);
+    assert_eq!(Rc::strong_count(&rc), 1);
+
+    let s = new();
+    let c = s.clone();
+    assert_eq!(Rc::strong_count(&rc), 31);
+    drop((s, c));
+    assert_eq!(Rc::strong_count(&rc), 1);
+
+    let mut s = new();
+    s.retain(|key, _| key % 2 == 0);
+    assert_eq!(Rc::strong_count(&rc), 1 + s.len());
+    s.compact(|_, _, _| true);
+    assert_eq!(Rc::strong_count(&rc), 1 + s.len());
+    drop(s);
+    assert_eq!(Rc::strong_count(&rc), 1);
+}
+
+#[test]
+fn iter_nth_last_fold() {
+    use std::rc::Rc;
+
+    let rc = Rc::new(());
+    for removed in [&[][..], &[0, 3, 4, 5, 10, 19][..]] {
+        let mut s = SlabMap::new();
+        for i in 0..20 {
+            s.insert(i);
+        }
+        for &key in removed {
+            s.remove(key);
+        }
+        let e: Vec<_> = s.iter().map(|(k, &v)| (k, v)).collect();
+        for n in 0..=e.len() {
+            let mut iter = s.iter();
+            assert_eq!(iter.nth(n).map(|(k, &v)| (k, v)), e.get(n).copied());
+            assert_eq!(iter.len(), e.len().saturating_sub(n + 1));
+            let rest: Vec<_> = iter.map(|(k, &v)| (k, v)).collect();
+            assert_eq!(rest, e.get(n + 1..).unwrap_or(&[]));
+            assert_eq!(s.iter_mut().nth(n).map(|(k, v)| (k, *v)), e.get(n).copied());
+            assert_eq!(s.clone().into_iter().nth(n), e.get(n).copied());
+        }
+        assert_eq!(s.iter().last().map(|(k, &v)| (k, v)), e.last().copied());
+        assert_eq!(s.iter_mut().last().map(|(k, v)| (k, *v)), e.last().copied());
+        assert_eq!(s.clone().into_iter().last(), e.last().copied());
+        let sum = e.iter().map(|x| x.1).sum::<i32>();
+        assert_eq!(s.iter().fold(0, |acc, (_, v)| acc + v), sum);
+        assert_eq!(s.iter_mut().fold(0, |acc, (_, v)| acc + *v), sum);
+        assert_eq!(s.clone().into_iter().fold(0, |acc, (_, v)| acc + v), sum);
+
+        let mut s = SlabMap::new();
+        for _ in 0..20 {
+            s.insert(rc.clone());
+        }
+        for &key in removed {
+            s.remove(key);
+        }
+        let mut iter = s.into_iter();
+        iter.nth(3);
+        assert_eq!(Rc::strong_count(&rc), 1 + iter.len());
+        drop(iter);
+        assert_eq!(Rc::strong_count(&rc), 1);
+    }
+}
+
+#[cfg(feature = "background-drop")]
+#[test]
+fn drop_in_background() {
+    use std::sync::mpsc;
+
+    struct Notify(mpsc::Sender<std::thread::ThreadId>);
+    impl Drop for Notify {
+        fn drop(&mut self) {
+            self.0.send(std::thread::current().id()).unwrap();
+        }
+    }
+    let (tx, rx) = mpsc::channel();
+    let mut s = SlabMap::new();
+    s.insert(Notify(tx.clone()));
+    s.insert(Notify(tx));
+    s.drop_in_background();
+    for _ in 0..2 {
+        assert_ne!(rx.recv().unwrap(), std::thread::current().id());
+    }
+}
+
+#[test]
+fn iter_from_token() {
+    let mut rng = StdRng::seed_from_u64(0);
+    let mut s = SlabMap::new();
+    for i in 0..500 {
+        s.insert(i);
+    }
+    let mut token = 0;
+    let mut seen = Vec::new();
+    loop {
+        let mut cursor = s.iter_from_token(token);
+        let page: Vec<_> = cursor.by_ref().take(7).map(|(k, _)| k).collect();
+        token = cursor.token();
+        if page.is_empty() {
+            break;
+        }
+        assert!(page.iter().all(|&k| seen.last().is_none_or(|&l| l < k)));
+        seen.extend(page);
+        for _ in 0..3 {
+            s.remove(rng.gen_range(0..500));
+        }
+    }
+    let e: Vec<_> = s.keys().collect();
+    assert!(e.iter().all(|k| seen.contains(k)));
+    assert_eq!(s.iter_from_token(usize::MAX).next(), None);
+}
+
+#[cfg(feature = "std")]
+#[test]
+fn eq_values_ignoring_keys() {
+    let mut rng = StdRng::seed_from_u64(0);
+    for _ in 0..100 {
+        let mut a = SlabMap::new();
+        let mut b = SlabMap::new();
+        let values: Vec<_> = (0..20).map(|_| rng.gen_range(0..5)).collect();
+        for &v in &values {
+            a.insert(v);
+            b.insert(100);
+        }
+        b.retain(|key, _| key % 2 == 0);
+        let mut shuffled = values.clone();
+        shuffled.sort();
+        for v in shuffled {
+            b.insert(v);
+        }
+        b.retain(|_, v| *v != 100);
+        assert!(a.eq_values_ignoring_keys(&b));
+
+        let key = rng.gen_range(0..20);
+        let old = a[key];
+        a[key] = (old + 1) % 5;
+        assert!(!a.eq_values_ignoring_keys(&b));
+        a.remove(key);
+        assert!(!a.eq_values_ignoring_keys(&b));
+    }
+}
+
+#[test]
+fn min_key_max_key() {
+    let mut rng = StdRng::seed_from_u64(0);
+    for quarantine in [0, 3] {
+        let mut s = SlabMap::new();
+        s.set_quarantine(quarantine);
+        let mut keys = BTreeSet::new();
+        for _ in 0..2000 {
+            if rng.gen_bool(0.55) {
+                keys.insert(s.insert(()));
+            } else if let Some(&key) = keys.iter().nth(rng.gen_range(0..keys.len().max(1))) {
+                s.remove(key);
+                keys.remove(&key);
+            }
+            assert_eq!(s.min_key(), keys.first().copied());
+            assert_eq!(s.max_key(), keys.last().copied());
+        }
+        while let Some((key, _)) = s.pop_last() {
+            assert_eq!(keys.pop_last(), Some(key));
+            if let Some((key, _)) = s.pop_first() {
+                assert_eq!(keys.pop_first(), Some(key));
+            }
+        }
+        assert!(keys.is_empty());
+    }
+}
+
+#[test]
+fn min_key_max_key_bulk() {
+    let mut rng = StdRng::seed_from_u64(1);
+    let mut s = SlabMap::new();
+    let mut keys = BTreeSet::new();
+    for _ in 0..1000 {
+        match rng.gen_range(0..8) {
+            0..=2 => {
+                keys.insert(s.insert(()));
+            }
+            3 => {
+                keys.extend(s.insert_contiguous([(); 3]));
+            }
+            4 => {
+                let key = s.claim_key();
+                if rng.gen_bool(0.5) {
+                    s.fill(key, ());
+                    keys.insert(key);
+                } else {
+                    s.abandon(key);
+                }
+            }
+            5 => {
+                let key = rng.gen_range(0..s.entries.len() + 3);
+                if s.insert_at(key, ()).is_none() {
+                    keys.insert(key);
+                }
+            }
+            6 => {
+                let start = rng.gen_range(0..s.entries.len() + 1);
+                let end = rng.gen_range(start..s.entries.len() + 2);
+                s.drain_range(start..end);
+                keys.retain(|&key| !(start..end).contains(&key));
+            }
+            _ => {
+                let key = rng.gen_range(0..s.entries.len() + 1);
+                s.remove(key);
+                keys.remove(&key);
+            }
+        }
+        s.check_integrity().unwrap();
+        assert_eq!(s.min_key(), keys.first().copied());
+        assert_eq!(s.max_key(), keys.last().copied());
+    }
+    s.retain(|key, _| key % 2 == 0);
+    keys.retain(|key| key % 2 == 0);
+    assert_eq!(s.min_key(), keys.first().copied());
+    assert_eq!(s.max_key(), keys.last().copied());
+    s.defragment();
+    assert_eq!(s.min_key(), Some(0));
+    assert_eq!(s.max_key(), Some(keys.len() - 1));
+}
+
+#[test]
+fn first_last() {
+    let mut s = SlabMap::new();
+    assert_eq!(s.first_mut(), None);
+    assert_eq!(s.last_mut(), None);
+    for i in 0..5 {
+        s.insert(i);
+    }
+    s.remove(0);
+    s.remove(4);
+    *s.first_mut().unwrap().1 += 10;
+    *s.last_mut().unwrap().1 += 20;
+    assert_eq!(s.first(), Some((1, &11)));
+    assert_eq!(s.last(), Some((3, &23)));
+}
+
+#[test]
+fn vacant_entry() {
+    let mut s = SlabMap::new();
+    s.set_quarantine(2);
+    for i in 0..20 {
+        let e = s.vacant_entry();
+        let key = e.key();
+        *e.insert(key) += 1;
+        assert_eq!(s[key], key + 1);
+        if i % 3 == 0 {
+            s.remove(key / 2);
+        }
+    }
+}
+
+#[test]
+fn typed_key() {
+    #[derive(Clone, Copy, Debug, PartialEq, Eq)]
+    struct Id(usize);
+    impl SlabKey for Id {
+        fn from_index(index: usize) -> Self {
+            Id(index)
+        }
+        fn into_index(self) -> usize {
+            self.0
+        }
+    }
+
+    let mut s = SlabMap::<&str, Id>::with_key();
+    let a = s.insert("a");
+    let b = s.insert_with_key(|key| if key == Id(1) { "b" } else { "x" });
+    let c = s.insert("c");
+    assert_eq!((a, b, c), (Id(0), Id(1), Id(2)));
+    assert_eq!(s.remove(b), Some("b"));
+    assert_eq!(s.keys().collect::<Vec<_>>(), [Id(0), Id(2)]);
+    s.retain(|key, _| key != a);
+    assert_eq!(s.iter().collect::<Vec<_>>(), [(Id(2), &"c")]);
+    assert_eq!(s.max_key(), Some(c));
+    assert_eq!(format!("{s:?}"), r#"{Id(2): "c"}"#);
+
+    let t: SlabMap<_, Id> = s.into_iter().collect();
+    assert_eq!(t[c], "c");
+}
+
+#[cfg(feature = "serde")]
+#[test]
+fn serde_repr_roundtrip() {
+    use crate::slab_map::serde_repr::{dense, pairs, string_keys};
+
+    let mut s = SlabMap::new();
+    for i in 0..10 {
+        s.insert(i);
+    }
+    s.retain(|key, _| key % 3 == 1);
+    let e = s.iter().collect::<Vec<_>>();
+
+    let mut json = Vec::new();
+    dense::serialize(&s, &mut serde_json::Serializer::new(&mut json)).unwrap();
+    let mut d: SlabMap<i32> =
+        dense::deserialize(&mut serde_json::Deserializer::from_slice(&json)).unwrap();
+    assert_eq!(d.iter().collect::<Vec<_>>(), e);
+    assert_eq!(d.insert(0), 0);
+
+    let mut json = Vec::new();
+    pairs::serialize(&s, &mut serde_json::Serializer::new(&mut json)).unwrap();
+    let mut d: SlabMap<i32> =
+        pairs::deserialize(&mut serde_json::Deserializer::from_slice(&json)).unwrap();
+    assert_eq!(d.iter().collect::<Vec<_>>(), e);
+    assert_eq!(d.insert(0), 0);
+
+    let mut json = Vec::new();
+    string_keys::serialize(&s, &mut serde_json::Serializer::new(&mut json)).unwrap();
+    let mut d: SlabMap<i32> =
+        string_keys::deserialize(&mut serde_json::Deserializer::from_slice(&json)).unwrap();
+    assert_eq!(d.iter().collect::<Vec<_>>(), e);
+    assert_eq!(d.insert(0), 0);
+
+    let r: Result<SlabMap<i32>, _> =
+        string_keys::deserialize(&mut serde_json::Deserializer::from_str(r#"{"x":1}"#));
+    assert!(r.is_err());
+}
+
+#[cfg(feature = "rkyv")]
+#[test]
+fn rkyv_roundtrip() {
+    use crate::slab_map::ArchivedSlabMap;
+    use rkyv::rancor::Error;
+
+    let mut s = SlabMap::new();
+    for i in 0..10u32 {
+        s.insert(i * 10);
+    }
+    s.retain(|key, _| key % 3 != 0);
+    let bytes = rkyv::to_bytes::<Error>(&s).unwrap();
+    let a = rkyv::access::<ArchivedSlabMap<rkyv::Archived<u32>>, Error>(&bytes).unwrap();
+    assert_eq!(a.len(), s.len());
+    for key in 0..12 {
+        assert_eq!(a.get(key).map(|v| v.to_native()), s.get(key).copied());
+    }
+    let entries: Vec<_> = a.iter().map(|(k, v)| (k, v.to_native())).collect();
+    let expected: Vec<_> = s.iter().map(|(k, v)| (k, *v)).collect();
+    assert_eq!(entries, expected);
+    assert_eq!(a.iter().len(), s.len());
+
+    let mut d = rkyv::deserialize::<SlabMap<u32>, Error>(a).unwrap();
+    assert_eq!(d.iter().collect::<Vec<_>>(), s.iter().collect::<Vec<_>>());
+    check_vacants(&d);
+    for key in [0, 3, 6, 9] {
+        assert_eq!(d.insert(key), key as usize);
+    }
+    assert_eq!(d.len(), 10);
+}
+
+#[cfg(feature = "rkyv")]
+#[test]
+fn rkyv_empty() {
+    use crate::slab_map::ArchivedSlabMap;
+    use rkyv::rancor::Error;
+
+    let s = SlabMap::<u32>::new();
+    let bytes = rkyv::to_bytes::<Error>(&s).unwrap();
+    let a = rkyv::access::<ArchivedSlabMap<rkyv::Archived<u32>>, Error>(&bytes).unwrap();
+    assert!(a.is_empty());
+    assert_eq!(a.iter().next(), None);
+    assert_eq!(a.get(0), None);
+}
+
+#[cfg(feature = "rayon")]
+#[test]
+fn par_retain() {
+    let mut rng = StdRng::seed_from_u64(0);
+    let mut a = SlabMap::new();
+    let mut e = SlabMap::new();
+    for _ in 0..1000 {
+        let value = rng.gen_range(0..100);
+        a.insert(value);
+        e.insert(value);
+    }
+    a.retain(|key, _| key % 7 != 0);
+    e.retain(|key, _| key % 7 != 0);
+
+    a.par_retain(|key, value| {
+        *value += 1;
+        key % 3 != 0 && *value % 2 == 0
+    });
+    e.retain(|key, value| {
+        *value += 1;
+        key % 3 != 0 && *value % 2 == 0
+    });
+    assert_eq!(a.iter().collect::<Vec<_>>(), e.iter().collect::<Vec<_>>());
+    check_vacants(&a);
+}
+
+#[cfg(feature = "rayon")]
+#[test]
+fn par_extend_and_collect() {
+    use rayon::prelude::*;
+
+    let mut s = SlabMap::new();
+    for i in 0..10 {
+        s.insert(i);
+    }
+    s.retain(|key, _| key % 2 == 0);
+    s.par_extend(
+        (0..20)
+            .into_par_iter()
+            .filter(|i| i % 3 == 0)
+            .map(|i| (i, i * 100)),
+    );
+    let e: Vec<_> = (0..20)
+        .filter_map(|i| match (i % 3 == 0, i % 2 == 0 && i < 10) {
+            (true, _) => Some((i, i * 100)),
+            (false, true) => Some((i, i)),
+            _ => None,
+        })
+        .collect();
+    assert_eq!(s.iter().map(|(k, v)| (k, *v)).collect::<Vec<_>>(), e);
+    check_vacants(&s);
+
+    let c: SlabMap<_> = e.par_iter().map(|&(k, v)| (k, v)).collect();
+    assert_eq!(c.iter().map(|(k, v)| (k, *v)).collect::<Vec<_>>(), e);
+    check_vacants(&c);
+}
+
+#[test]
+fn get_disjoint_mut() {
+    let mut s = SlabMap::new();
+    let keys: Vec<_> = (0..5).map(|i| s.insert(i)).collect();
+    s.remove(keys[2]);
+
+    let [a, b, c] = s.get_disjoint_mut([keys[4], keys[0], keys[3]]).unwrap();
+    std::mem::swap(a, b);
+    *c += 10;
+    assert_eq!(s.values().copied().collect::<Vec<_>>(), [4, 1, 13, 0]);
+
+    assert!(s.get_disjoint_mut([keys[0], keys[2]]).is_none());
+    assert!(s.get_disjoint_mut([keys[1], keys[1]]).is_none());
+    assert!(s.get_disjoint_mut([keys[1], 100]).is_none());
+    assert_eq!(s.get_disjoint_mut::<0>([]), Some([]));
+}
+
+#[test]
+fn get2_mut_compat_with_slab() {
+    let mut a = SlabMap::new();
+    let mut e = slab::Slab::new();
+    for i in 0..4 {
+        a.insert(i);
+        e.insert(i);
+    }
+    a.remove(2);
+    e.remove(2);
+    for k0 in 0..5 {
+        for k1 in (0..5).filter(|&k1| k1 != k0) {
+            assert_eq!(a.get2_mut(k0, k1), e.get2_mut(k0, k1), "{k0}, {k1}");
+        }
+        assert_eq!(a.get2_mut(k0, k0), None);
+    }
+}
+
+#[test]
+fn iter_keys_mut() {
+    let mut s = SlabMap::new();
+    for i in 0..20 {
+        s.insert(i);
+    }
+    s.retain(|key, _| key % 3 != 0);
+    let a: Vec<_> = s
+        .iter_keys_mut([19, 4, 3, 4, 1, 30, usize::MAX, 0])
+        .map(|(k, v)| {
+            *v += 100;
+            k
+        })
+        .collect();
+    assert_eq!(a, [1, 4, 19]);
+    assert_eq!(s[4], 104);
+    assert_eq!(s[5], 5);
+    assert_eq!(s.iter_keys_mut([]).count(), 0);
+}
+
+#[test]
+fn drain_range() {
+    let mut rng = StdRng::seed_from_u64(0);
+    for _ in 0..200 {
+        let mut a = SlabMap::new();
+        let mut e = BTreeMap::new();
+        let n = rng.gen_range(0..40);
+        for i in 0..n {
+            a.insert(i);
+            e.insert(i, i);
+        }
+        for _ in 0..rng.gen_range(0..n + 1) {
+            let key = rng.gen_range(0..n.max(1));
+            a.remove(key);
+            e.remove(&key);
+        }
+        let start = rng.gen_range(0..n + 5);
+        let end = rng.gen_range(start..n + 10);
+        let take = rng.gen_range(0..5);
+        let d: Vec<_> = a.drain_range(start..end).take(take).collect();
+        let expected: Vec<_> = e.range(start..end).map(|(k, v)| (*k, *v)).collect();
+        assert_eq!(d, expected[..take.min(expected.len())]);
+        e.retain(|k, _| !(start..end).contains(k));
+        assert_eq!(
+            a.iter().map(|(k, v)| (k, *v)).collect::<Vec<_>>(),
+            e.into_iter().collect::<Vec<_>>()
+        );
+        check_vacants(&a);
+        for (_, e) in a.entries.iter() {
+            if let Entry::VacantTail { vacant_len } = e {
+                assert!(vacant_len > 1);
+            }
+        }
+    }
+}
+
+#[test]
+fn drain_range_bounds() {
+    let mut s = SlabMap::new();
+    for i in 0..10 {
+        s.insert(i);
+    }
+    assert_eq!(s.drain_range(8..).count(), 2);
+    assert_eq!(s.drain_range(..=1).count(), 2);
+    assert_eq!(s.drain_range(5..5).count(), 0);
+    assert_eq!(s.keys().collect::<Vec<_>>(), [2, 3, 4, 5, 6, 7]);
+    check_vacants(&s);
+    assert_eq!(s.insert(100), 0);
+    assert_eq!(s.drain_range(..).count(), 7);
+    assert!(s.is_empty());
+}
+
+#[test]
+fn range() {
+    let mut rng = StdRng::seed_from_u64(0);
+    for _ in 0..200 {
+        let mut a = SlabMap::new();
+        let mut e = BTreeMap::new();
+        let n = rng.gen_range(0..40);
+        for i in 0..n {
+            a.insert(i);
+            e.insert(i, i);
+        }
+        for _ in 0..rng.gen_range(0..n + 1) {
+            let key = rng.gen_range(0..n.max(1));
+            a.remove(key);
+            e.remove(&key);
+        }
+        let start = rng.gen_range(0..n + 5);
+        let end = rng.gen_range(start..n + 10);
+        let r: Vec<_> = a.range(start..end).map(|(k, v)| (k, *v)).collect();
+        let expected: Vec<_> = e.range(start..end).map(|(k, v)| (*k, *v)).collect();
+        assert_eq!(r, expected);
+        for (_, v) in a.range_mut(start..=end) {
+            *v += 100;
+        }
+        for (_, v) in e.range_mut(start..=end) {
+            *v += 100;
+        }
+        assert_eq!(
+            a.iter().map(|(k, v)| (k, *v)).collect::<Vec<_>>(),
+            e.into_iter().collect::<Vec<_>>()
+        );
+    }
+}
+
+#[test]
+fn next_prev_occupied_key() {
+    let mut rng = StdRng::seed_from_u64(0);
+    for _ in 0..200 {
+        let mut a = SlabMap::new();
+        let mut e = BTreeMap::new();
+        let n = rng.gen_range(0..40);
+        for i in 0..n {
+            a.insert(i);
+            e.insert(i, i);
+        }
+        for _ in 0..rng.gen_range(0..n + 1) {
+            let key = rng.gen_range(0..n.max(1));
+            a.remove(key);
+            e.remove(&key);
+        }
+        for key in 0..n + 2 {
+            assert_eq!(
+                a.next_occupied_key(key),
+                e.range(key + 1..).next().map(|(k, _)| *k)
+            );
+            assert_eq!(
+                a.prev_occupied_key(key),
+                e.range(..key).next_back().map(|(k, _)| *k)
+            );
+        }
+    }
+    assert_eq!(SlabMap::<u8>::new().next_occupied_key(usize::MAX), None);
+}
+
+#[test]
+fn next_key() {
+    let mut rng = StdRng::seed_from_u64(0);
+    for quarantine in [0, 1, 3] {
+        let mut s = SlabMap::new();
+        s.set_quarantine(quarantine);
+        let mut keys = Vec::new();
+        for _ in 0..2000 {
+            if rng.gen_bool(0.5) {
+                let key = s.next_key();
+                assert_eq!(s.insert(()), key);
+                keys.push(key);
+            } else if !keys.is_empty() {
+                let key = keys.swap_remove(rng.gen_range(0..keys.len()));
+                s.remove(key);
+            }
+            assert_eq!(s.vacant_entry().key(), s.next_key());
+        }
+    }
+}
+
+#[test]
+fn find_key() {
+    let mut rng = StdRng::seed_from_u64(0);
+    for _ in 0..200 {
+        let mut s = SlabMap::new();
+        let n = rng.gen_range(0..40);
+        for _ in 0..n {
+            s.insert(rng.gen_range(0..10));
+        }
+        for _ in 0..rng.gen_range(0..n + 1) {
+            s.remove(rng.gen_range(0..n.max(1)));
+        }
+        let target = rng.gen_range(0..10);
+        let expected = s.iter().find(|(_, v)| **v == target);
+        assert_eq!(s.find_entry(|v| *v == target), expected);
+        assert_eq!(s.find_key(|v| *v == target), expected.map(|(k, _)| k));
+    }
+}
+
+#[test]
+fn insert_at() {
+    let mut rng = StdRng::seed_from_u64(0);
+    for quarantine in [0, 2] {
+        let mut s = SlabMap::new();
+        s.set_quarantine(quarantine);
+        let mut e = BTreeMap::new();
+        for _ in 0..2000 {
+            let key = rng.gen_range(0..60);
+            match rng.gen_range(0..3) {
+                0 => assert_eq!(s.insert_at(key, key), e.insert(key, key)),
+                1 => assert_eq!(s.remove(key), e.remove(&key)),
+                _ => {
+                    let key = s.insert(0);
+                    *s.get_mut(key).unwrap() = key;
+                    assert_eq!(e.insert(key, key), None);
+                }
+            }
+            check_vacants(&s);
+        }
+        assert_eq!(
+            s.iter().map(|(k, v)| (k, *v)).collect::<Vec<_>>(),
+            e.into_iter().collect::<Vec<_>>()
+        );
+    }
+}
+
+#[test]
+fn try_insert_at() {
+    let mut s = SlabMap::new();
+    s.set_quarantine(2);
+    assert_eq!(s.try_insert_at(2, 20), Ok(()));
+    assert_eq!(
+        s.try_insert_at(2, 21),
+        Err(OccupiedError { key: 2, value: 21 })
+    );
+    s.remove(2);
+    assert_eq!(s.try_insert_at(2, 22), Ok(()));
+    assert_eq!(s.quarantined_len(), 0);
+    assert_eq!(s.try_insert_at(0, 0), Ok(()));
+    assert_eq!(s.iter().collect::<Vec<_>>(), [(0, &0), (2, &22)]);
+    check_vacants(&s);
+
+    let key = s.claim_key();
+    assert_eq!(
+        s.try_insert_at(key, 1),
+        Err(OccupiedError { key, value: 1 })
+    );
+    assert_eq!(s.len(), 2);
+    s.fill(key, 1);
+    check_vacants(&s);
+}
+
+#[test]
+fn try_insert_with() {
+    let mut s = SlabMap::new();
+    s.set_quarantine(1);
+    for i in 0..10 {
+        s.insert(i);
+    }
+    s.remove(3);
+    s.remove(4);
+    s.insert(100);
+    let next = s.next_key();
+    assert_eq!(s.try_insert_with(|_| Err::<usize, _>(())), Err(()));
+    assert_eq!(s.next_key(), next);
+    check_vacants(&s);
+    assert_eq!(s.try_insert_with(Ok::<_, ()>), Ok(next));
+    assert_eq!(s[next], next);
+}
+
+#[test]
+fn insert_mut() {
+    let mut s = SlabMap::new();
+    s.insert(0);
+    s.insert(1);
+    s.remove(0);
+    let (key, value) = s.insert_mut(10);
+    *value += 1;
+    assert_eq!((key, s[key]), (0, 11));
+    let (key, value) = s.insert_mut_with_key(|key| key * 10);
+    *value += 1;
+    assert_eq!((key, s[key]), (2, 21));
+}
+
+#[test]
+fn insert_contiguous() {
+    let mut rng = StdRng::seed_from_u64(0);
+    for quarantine in [0, 2] {
+        let mut s = SlabMap::new();
+        s.set_quarantine(quarantine);
+        let mut e = BTreeMap::new();
+        for _ in 0..500 {
+            match rng.gen_range(0..3) {
+                0 => {
+                    let n = rng.gen_range(0..5);
+                    let values: Vec<_> = (0..n).collect();
+                    let keys = if rng.gen_bool(0.5) {
+                        s.insert_contiguous(values.iter().copied())
+                    } else {
+                        s.extend_copy(&values)
+                    };
+                    assert_eq!(keys.len(), n);
+                    assert!(s.range(keys.end..).next().is_none());
+                    for (i, key) in keys.enumerate() {
+                        assert_eq!(e.insert(key, i), None);
+                    }
+                }
+                1 => {
+                    let key = s.insert(100);
+                    assert_eq!(e.insert(key, 100), None);
+                }
+                _ => {
+                    let key = rng.gen_range(0..s.capacity().max(1));
+                    assert_eq!(s.remove(key), e.remove(&key));
+                }
+            }
+            check_vacants(&s);
+            assert_eq!(s.len(), e.len());
+        }
+        assert_eq!(
+            s.iter().map(|(k, v)| (k, *v)).collect::<Vec<_>>(),
+            e.into_iter().collect::<Vec<_>>()
+        );
+    }
+}
+
+#[test]
+fn claim_key() {
+    let mut rng = StdRng::seed_from_u64(0);
+    for quarantine in [0, 2] {
+        let mut s = SlabMap::new();
+        s.set_quarantine(quarantine);
+        let mut claimed = Vec::new();
+        let mut e = BTreeMap::new();
+        for _ in 0..2000 {
+            match rng.gen_range(0..5) {
+                0 => {
+                    let key = s.claim_key();
+                    assert!(!e.contains_key(&key) && !claimed.contains(&key));
+                    claimed.push(key);
+                }
+                1 if !claimed.is_empty() => {
+                    let key = claimed.swap_remove(rng.gen_range(0..claimed.len()));
+                    *s.fill(key, 0) = key;
+                    e.insert(key, key);
+                }
+                2 if !claimed.is_empty() => {
+                    let key = claimed.swap_remove(rng.gen_range(0..claimed.len()));
+                    s.abandon(key);
+                }
+                3 => {
+                    let key = s.insert(0);
+                    assert!(!e.contains_key(&key) && !claimed.contains(&key));
+                    s[key] = key;
+                    e.insert(key, key);
+                }
+                _ => {
+                    let key = rng.gen_range(0..s.capacity().max(1));
+                    assert_eq!(s.remove(key), e.remove(&key));
+                }
+            }
+            check_vacants(&s);
+            assert_eq!(s.len(), e.len());
+        }
+        assert_eq!(
+            s.iter().map(|(k, v)| (k, *v)).collect::<Vec<_>>(),
+            e.into_iter().collect::<Vec<_>>()
+        );
+    }
+}
+
+#[test]
+#[should_panic(expected = "key is claimed.")]
+fn insert_at_claimed() {
+    let mut s = SlabMap::new();
+    let key = s.claim_key();
+    s.insert_at(key, 1);
+}
+
+#[test]
+fn insert_at_claimed_keeps_claim() {
+    let mut s = SlabMap::new();
+    s.insert(0);
+    let key = s.claim_key();
+    s.insert(2);
+    let r = std::panic::catch_unwind(std::panic::AssertUnwindSafe(|| s.insert_at(key, 1)));
+    assert!(r.is_err());
+    s.check_integrity().unwrap();
+    assert_eq!(s.len(), 2);
+    assert_eq!(*s.fill(key, 1), 1);
+    assert_eq!(s.iter().collect::<Vec<_>>(), [(0, &0), (1, &1), (2, &2)]);
+}
+
+#[test]
+#[should_panic]
+fn fill_unclaimed() {
+    let mut s = SlabMap::new();
+    let key = s.insert(0);
+    s.fill(key, 1);
+}
+
+#[test]
+fn from_vec() {
+    let mut s = SlabMap::from(vec![10, 20, 30]);
+    assert_eq!(s.len(), 3);
+    assert_eq!(s.iter().collect::<Vec<_>>(), [(0, &10), (1, &20), (2, &30)]);
+    s.remove(1);
+    check_vacants(&s);
+    assert_eq!(s.insert(40), 1);
+    assert_eq!(s.insert(50), 3);
+    assert!(SlabMap::<u8>::from_vec(Vec::new()).is_empty());
+}
+
+#[test]
+fn into_vec_from_slots() {
+    let mut rng = StdRng::seed_from_u64(0);
+    for _ in 0..100 {
+        let slots: Vec<_> = (0..rng.gen_range(0..30))
+            .map(|i| rng.gen_bool(0.5).then_some(i))
+            .collect();
+        let s = SlabMap::from(slots.clone());
+        check_vacants(&s);
+        assert_eq!(s.len(), slots.iter().flatten().count());
+        let mut v = s.into_vec();
+        while slots.len() > v.len() {
+            v.push(None);
+        }
+        assert_eq!(v, slots);
+    }
+}
+
+#[test]
+fn into_keys_values() {
+    let mut s = SlabMap::new();
+    for i in 0..10 {
+        s.insert(i * 10);
+    }
+    s.remove(3);
+    s.remove(4);
+    let keys = s.clone().into_keys();
+    assert_eq!(keys.len(), 8);
+    assert_eq!(keys.collect::<Vec<_>>(), s.keys().collect::<Vec<_>>());
+    let values = s.clone().into_values();
+    assert_eq!(values.len(), 8);
+    assert_eq!(
+        values.collect::<Vec<_>>(),
+        s.values().copied().collect::<Vec<_>>()
+    );
+}
+
+#[test]
+fn compact() {
+    let mut rng = StdRng::seed_from_u64(0);
+    for quarantine in [0, 3] {
+        for _ in 0..100 {
+            let mut s = SlabMap::new();
+            s.set_quarantine(quarantine);
+            let n = rng.gen_range(0..40);
+            for i in 0..n {
+                s.insert(i);
+            }
+            for _ in 0..rng.gen_range(0..n + 1) {
+                s.remove(rng.gen_range(0..n.max(1)));
+            }
+            let mut e: BTreeMap<_, _> = s.iter().map(|(k, v)| (*v, k)).collect();
+            let mut vetoed = Vec::new();
+            s.compact(|value, old_key, new_key| {
+                assert!(new_key < old_key);
+                assert_eq!(e[value], old_key);
+                if rng.gen_bool(0.2) {
+                    vetoed.push(old_key);
+                    return false;
+                }
+                e.insert(*value, new_key);
+                true
+            });
+            check_vacants(&s);
+            assert_eq!(s.len(), e.len());
+            for (value, key) in e {
+                assert_eq!(s[key], value);
+            }
+            if quarantine == 0 && vetoed.is_empty() {
+                assert!(s.keys().eq(0..s.len()));
+            }
+            for key in vetoed {
+                assert!(s.contains_key(key));
+            }
+        }
+    }
+}
+
+#[test]
+fn defragment() {
+    let mut rng = StdRng::seed_from_u64(0);
+    for _ in 0..100 {
+        let mut s = SlabMap::new();
+        s.set_quarantine(rng.gen_range(0..3));
+        let n = rng.gen_range(0..40);
+        for i in 0..n {
+            s.insert(i);
+        }
+        for _ in 0..rng.gen_range(0..n + 1) {
+            s.remove(rng.gen_range(0..n.max(1)));
+        }
+        let old: Vec<_> = s.iter().map(|(k, v)| (k, *v)).collect();
+        let remap = s.defragment();
+        assert_eq!(remap.len(), old.len());
+        for (&(old_key, value), &(remap_old, remap_new)) in old.iter().zip(&remap) {
+            assert_eq!(old_key, remap_old);
+            assert_eq!(s[remap_new], value);
+        }
+        assert!(s.keys().eq(0..s.len()));
+        check_vacants(&s);
+    }
+}
+
+#[test]
+fn compact_sorted_by_cmp_panic() {
+    let mut s = SlabMap::new();
+    for i in 0..5 {
+        s.insert(4 - i);
+    }
+    s.remove(1);
+    let r = std::panic::catch_unwind(std::panic::AssertUnwindSafe(|| {
+        s.compact_sorted_by(|_, _| panic!(), |_, _| {})
+    }));
+    assert!(r.is_err());
+    check_vacants(&s);
+    assert_eq!(s.len(), 4);
+    assert_eq!(
+        s.iter().collect::<Vec<_>>(),
+        [(0, &4), (2, &2), (3, &1), (4, &0)]
+    );
+}
+
+#[test]
+fn compact_sorted_by_remap_panic() {
+    let mut s = SlabMap::new();
+    for i in 0..5 {
+        s.insert(4 - i);
+    }
+    s.remove(1);
+    let r = std::panic::catch_unwind(std::panic::AssertUnwindSafe(|| {
+        s.compact_sorted_by(|x, y| x.cmp(y), |_, _| panic!())
+    }));
+    assert!(r.is_err());
+    check_vacants(&s);
+    assert_eq!(s.len(), 4);
+    assert_eq!(s.values().copied().collect::<Vec<_>>(), [0, 1, 2, 4]);
+}
+
+#[test]
+fn append() {
+    let mut rng = StdRng::seed_from_u64(0);
+    for _ in 0..200 {
+        let mut a = SlabMap::new();
+        let mut b = SlabMap::new();
+        a.set_quarantine(rng.gen_range(0..2));
+        for i in 0..rng.gen_range(0..30) {
+            let s = if rng.gen_bool(0.5) { &mut a } else { &mut b };
+            s.insert_at(i, i);
+        }
+        for _ in 0..rng.gen_range(0..10) {
+            let key = rng.gen_range(0..30);
+            a.remove(key);
+            b.remove(key);
+        }
+        if rng.gen_bool(0.3) {
+            let key = rng.gen_range(0..30);
+            a.insert_at(key, 100);
+            b.insert_at(key, 100);
+        }
+        let conflict = b.keys().find(|&key| a.contains_key(key));
+        let mut e: BTreeMap<_, _> = a.iter().chain(b.iter()).map(|(k, v)| (k, *v)).collect();
+        let (a_len, b_len) = (a.len(), b.len());
+        match a.append(&mut b) {
+            Ok(()) => {
+                assert_eq!(conflict, None);
+                assert!(b.is_empty());
+                assert_eq!(a.len(), a_len + b_len);
+            }
+            Err(KeyConflict { key }) => {
+                assert_eq!(Some(key), conflict);
+                assert_eq!((a.len(), b.len()), (a_len, b_len));
+                e = a.iter().map(|(k, v)| (k, *v)).collect();
+            }
+        }
+        check_vacants(&a);
+        assert_eq!(
+            a.iter().map(|(k, v)| (k, *v)).collect::<Vec<_>>(),
+            e.into_iter().collect::<Vec<_>>()
+        );
+    }
+}
+
+#[test]
+fn merge_remap() {
+    let mut a = SlabMap::new();
+    for i in 0..5 {
+        a.insert(i);
+    }
+    a.remove(1);
+    a.remove(3);
+    let mut b = SlabMap::new();
+    for i in 10..15 {
+        b.insert(i);
+    }
+    b.remove(0);
+    let expected: Vec<_> = b.iter().map(|(k, v)| (k, *v)).collect();
+    let remap = a.merge_remap(b);
+    assert_eq!(remap.len(), expected.len());
+    for ((old, value), &(remap_old, new)) in expected.into_iter().zip(&remap) {
+        assert_eq!(old, remap_old);
+        assert_eq!(a[new], value);
+    }
+    assert_eq!(a.len(), 7);
+    check_vacants(&a);
+}
+
+#[test]
+fn visit_mut() {
+    let mut rng = StdRng::seed_from_u64(0);
+    for _ in 0..100 {
+        let mut s = SlabMap::new();
+        let mut e = BTreeMap::new();
+        let n = rng.gen_range(0..40);
+        for i in 0..n {
+            s.insert(i);
+            e.insert(i, i);
+        }
+        for _ in 0..rng.gen_range(0..n + 1) {
+            let key = rng.gen_range(0..n.max(1));
+            s.remove(key);
+            e.remove(&key);
+        }
+        s.visit_mut(|key, value| {
+            assert_eq!(key, *value);
+            match key % 3 {
+                0 => Visit::Keep,
+                1 => Visit::Remove,
+                _ => Visit::Replace(key + 100),
+            }
+        });
+        e.retain(|k, _| k % 3 != 1);
+        for (k, v) in &mut e {
+            if *k % 3 == 2 {
+                *v += 100;
+            }
+        }
+        check_vacants(&s);
+        assert_eq!(
+            s.iter().map(|(k, v)| (k, *v)).collect::<Vec<_>>(),
+            e.into_iter().collect::<Vec<_>>()
+        );
+    }
+}
+
+#[test]
+fn is_dense() {
+    let mut s = SlabMap::new();
+    assert!(s.is_dense());
+    for i in 0..5 {
+        s.insert(i);
+    }
+    assert!(s.is_dense());
+    s.remove(4);
+    assert!(s.is_dense());
+    s.set_quarantine(1);
+    s.remove(3);
+    assert!(!s.is_dense());
+    s.claim_key();
+    assert!(!s.is_dense());
+}
+
+#[cfg(feature = "u32-index")]
+#[test]
+fn u32_index_entry_size() {
+    assert_eq!(SlabMap::<u32>::entry_size(), 8);
+}
+
+#[test]
+fn debug_layout() {
+    let mut s = SlabMap::new();
+    for i in 0..8 {
+        s.insert(i);
+    }
+    s.remove(5);
+    s.remove(6);
+    s.claim_key();
+    s.remove(1);
+    s.set_quarantine(1);
+    s.remove(2);
+    let runs: Vec<_> = s
+        .debug_layout()
+        .runs
+        .iter()
+        .map(|r| (r.start, r.len, r.kind))
+        .collect();
+    assert_eq!(
+        runs,
+        [
+            (0, 1, RunKind::Occupied),
+            (1, 1, RunKind::Vacant),
+            (2, 1, RunKind::Quarantined),
+            (3, 2, RunKind::Occupied),
+            (5, 1, RunKind::Claimed),
+            (6, 1, RunKind::Vacant),
+            (7, 1, RunKind::Occupied),
+        ]
+    );
+}
+
+#[test]
+fn check_integrity_detects_corruption() {
+    let new = || {
+        let mut s = SlabMap::new();
+        for i in 0..6 {
+            s.insert(i);
+        }
+        s.remove(2);
+        s.remove(3);
+        s
+    };
+    assert_eq!(new().check_integrity(), Ok(()));
+
+    let mut s = new();
+    s.len += 1;
+    assert_eq!(
+        s.check_integrity(),
+        Err(IntegrityError::Len {
+            expected: 5,
+            actual: 4
+        })
+    );
+
+    let mut s = new();
+    s.entries.set(3, Entry::VacantBody);
+    assert_eq!(
+        s.check_integrity(),
+        Err(IntegrityError::BrokenRun { index: 2 })
+    );
+
+    let mut s = new();
+    s.extras_mut().vacant_heads.clear();
+    assert_eq!(
+        s.check_integrity(),
+        Err(IntegrityError::UnreachableRun { index: 2 })
+    );
+
+    let mut s = new();
+    s.entries.set(4, Entry::VacantHead { vacant_len: 1 });
+    s.extras_mut().vacant_heads.push(4);
+    s.len -= 1;
+    assert_eq!(
+        s.check_integrity(),
+        Err(IntegrityError::UnmergedRuns { index: 4 })
+    );
+
+    let mut s = new();
+    s.extras_mut().non_optimized_count = 3;
+    assert_eq!(
+        s.check_integrity(),
+        Err(IntegrityError::NonOptimizedCount {
+            count: 3,
+            vacant: 2
+        })
+    );
+}
+
+#[test]
+fn optimize_step() {
+    let mut rng = StdRng::seed_from_u64(0);
+    for _ in 0..50 {
+        let mut s = SlabMap::new();
+        for i in 0..200 {
+            s.insert(i);
+        }
+        for _ in 0..150 {
+            s.remove(rng.gen_range(0..200));
+        }
+        loop {
+            match rng.gen_range(0..4) {
+                0 => {
+                    s.insert(0);
+                }
+                1 => {
+                    s.remove(rng.gen_range(0..200));
+                }
+                _ => {
+                    if s.optimize_step(rng.gen_range(0..20)) {
+                        break;
+                    }
+                }
+            }
+            check_vacants(&s);
+        }
+        check_vacants(&s);
+        assert!(s.optimize_step(0));
+        assert!(!matches!(
+            s.entries.last(),
+            Some(Entry::VacantHead { .. } | Entry::VacantTail { .. })
+        ));
+        let vacants: Vec<_> = (0..s.entries.len())
+            .filter(|&key| !s.contains_key(key))
+            .collect();
+        for key in vacants {
+            assert_eq!(s.insert(0), key);
+        }
+    }
+}
+
+#[test]
+fn optimize_and_shrink() {
+    let mut s = SlabMap::with_capacity(64);
+    for i in 0..10 {
+        s.insert(i);
+    }
+    s.drain_range(8..);
+    s.extras_mut().non_optimized_count = 0;
+    s.optimize();
+    assert_eq!(s.entries.len(), 10);
+
+    s.optimize_and_shrink(None);
+    check_vacants(&s);
+    assert_eq!(s.entries.len(), 8);
+    assert!(s.capacity() >= 64);
+
+    s.optimize_and_shrink(Some(0));
+    assert!(s.capacity() < 64);
+    assert_eq!(s.insert(10), 8);
+}
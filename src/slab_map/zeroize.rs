@@ -0,0 +1,169 @@
+use core::ops::Deref;
+
+use zeroize::{Zeroize, ZeroizeOnDrop};
+
+use super::SlabMap;
+use crate::SlabKey;
+
+impl<T, K: SlabKey> SlabMap<T, K> {
+    /// Overwrites the memory of the free spaces with zeros.
+    ///
+    /// Values moved out by [`remove`](SlabMap::remove) may leave their bytes in the free spaces.
+    /// This method wipes those bytes, including the unused capacity.
+    pub fn zeroize_vacants(&mut self) {
+        let (metas, values) = self.entries.split_mut();
+        for (meta, value) in metas.iter().zip(values) {
+            if !meta.is_occupied() {
+                value.zeroize();
+            }
+        }
+        self.entries.spare_capacity_mut().zeroize();
+    }
+}
+
+impl<T: Zeroize, K: SlabKey> SlabMap<T, K> {
+    /// Removes a key from the SlabMap, zeroizing the value and the memory it occupied.
+    ///
+    /// Returns true if the key was previously in the SlabMap.
+    ///
+    /// # Examples
+    /// ```
+    /// use slabmap::SlabMap;
+    ///
+    /// let mut s = SlabMap::new();
+    /// let key = s.insert(vec![1u8, 2, 3]);
+    ///
+    /// assert!(s.remove_zeroize(key));
+    /// assert!(!s.remove_zeroize(key));
+    /// ```
+    pub fn remove_zeroize(&mut self, key: K) -> bool {
+        let Some(value) = self.get_mut(key) else {
+            return false;
+        };
+        value.zeroize();
+        // The shrink policy is suspended during `remove` so that the storage is not reallocated before the slot is wiped.
+        let auto_shrink = self.extras.as_mut().and_then(|x| x.auto_shrink.take());
+        let entries_len = self.entries.len();
+        drop(self.remove(key));
+        if let Some(value) = self.entries.split_mut().1.get_mut(key.into_index()) {
+            value.zeroize();
+        } else {
+            let removed_len = entries_len - self.entries.len();
+            self.entries.spare_capacity_mut()[..removed_len].zeroize();
+        }
+        if auto_shrink.is_some() {
+            self.extras_mut().auto_shrink = auto_shrink;
+            self.count_operation();
+        }
+        true
+    }
+
+    /// Reserves capacity like [`reserve`](SlabMap::reserve), but zeroizes the old storage when it is reallocated.
+    pub(crate) fn reserve_zeroizing(&mut self, additional: usize) {
+        self.entries.reserve_zeroizing(additional);
+    }
+}
+
+/// Zeroizes all values and clears the SlabMap, including the unused capacity.
+///
+/// Use [`ZeroizingSlabMap`] to also zeroize removed values and the SlabMap on drop.
+impl<T: Zeroize, K: SlabKey> Zeroize for SlabMap<T, K> {
+    fn zeroize(&mut self) {
+        for value in self.values_mut() {
+            value.zeroize();
+        }
+        self.clear();
+        self.entries.spare_capacity_mut().zeroize();
+    }
+}
+
+/// A [`SlabMap`] that zeroizes the memory of values when they are removed, cleared or dropped.
+///
+/// [`SlabMap::remove_zeroize`] and [`Zeroize`] only work if they are called for every removal.
+/// This wrapper only exposes operations that wipe the memory they release, and zeroizes the whole SlabMap on drop.
+/// When the storage grows, the old storage is zeroized before it is freed.
+///
+/// The SlabMap can be read through [`Deref`].
+/// As with [`zeroize::Zeroizing`], copies made by moving the wrapper itself are not wiped.
+///
+/// # Examples
+/// ```
+/// use slabmap::slab_map::ZeroizingSlabMap;
+///
+/// let mut s = ZeroizingSlabMap::new();
+/// let key = s.insert(*b"secret");
+/// assert_eq!(s[key], *b"secret");
+///
+/// assert!(s.remove(key));
+/// assert!(s.is_empty());
+/// ```
+pub struct ZeroizingSlabMap<T: Zeroize, K: SlabKey = usize>(pub(super) SlabMap<T, K>);
+
+impl<T: Zeroize> ZeroizingSlabMap<T> {
+    /// Constructs a new, empty `ZeroizingSlabMap<T>`.
+    #[inline]
+    pub const fn new() -> Self {
+        Self(SlabMap::new())
+    }
+}
+
+impl<T: Zeroize, K: SlabKey> ZeroizingSlabMap<T, K> {
+    /// Inserts a value into the SlabMap and returns the key associated with it.
+    ///
+    /// If the storage is full, it is moved to a larger allocation and the old allocation is zeroized.
+    pub fn insert(&mut self, value: T) -> K {
+        if self.0.next_vacant_idx().is_none() {
+            self.0.reserve_zeroizing(1);
+        }
+        self.0.insert(value)
+    }
+
+    /// Returns a mutable reference to the value corresponding to the key.
+    #[inline]
+    pub fn get_mut(&mut self, key: K) -> Option<&mut T> {
+        self.0.get_mut(key)
+    }
+
+    /// Removes a key from the SlabMap, zeroizing the value and the memory it occupied.
+    ///
+    /// Returns true if the key was previously in the SlabMap.
+    pub fn remove(&mut self, key: K) -> bool {
+        self.0.remove_zeroize(key)
+    }
+
+    /// Zeroizes all values and clears the SlabMap, including the unused capacity.
+    pub fn clear(&mut self) {
+        self.0.zeroize();
+    }
+}
+
+/// Automatic shrinking is disabled, because it would free storage without zeroizing it.
+impl<T: Zeroize, K: SlabKey> From<SlabMap<T, K>> for ZeroizingSlabMap<T, K> {
+    fn from(mut value: SlabMap<T, K>) -> Self {
+        value.set_shrink_policy(None);
+        Self(value)
+    }
+}
+
+impl<T: Zeroize, K: SlabKey> Default for ZeroizingSlabMap<T, K> {
+    fn default() -> Self {
+        Self(SlabMap::default())
+    }
+}
+
+impl<T: Zeroize, K: SlabKey> Deref for ZeroizingSlabMap<T, K> {
+    type Target = SlabMap<T, K>;
+
+    #[inline]
+    fn deref(&self) -> &Self::Target {
+        &self.0
+    }
+}
+
+impl<T: Zeroize, K: SlabKey> Drop for ZeroizingSlabMap<T, K> {
+    fn drop(&mut self) {
+        self.0.zeroize();
+    }
+}
+
+impl<T: Zeroize, K: SlabKey> ZeroizeOnDrop for ZeroizingSlabMap<T, K> {}
@@ -0,0 +1,453 @@
+//! A slab over a caller-provided buffer that never allocates.
+
+use core::{fmt::Debug, iter::FusedIterator};
+
+#[cfg(test)]
+mod tests;
+
+const NONE: usize = usize::MAX;
+
+/// A slot of the storage used by [`SlabMapRef`].
+pub struct Slot<T>(SlotState<T>);
+
+/// Free slots form runs as in [`SlabMap`](crate::SlabMap), with the length of a run stored at both ends.
+///
+/// [`SlabMap`](crate::SlabMap) keeps the heads of the runs in a separate stack, which needs a `Vec`.
+/// Here the runs are linked through their heads instead, so that nothing is allocated.
+/// The list is doubly linked because merged runs must be unlinked in O(1).
+enum SlotState<T> {
+    Occupied(T),
+    VacantHead {
+        len: usize,
+        prev: usize,
+        next: usize,
+    },
+    VacantTail {
+        len: usize,
+    },
+    VacantBody,
+}
+
+impl<T> Slot<T> {
+    /// Constructs a new vacant slot.
+    #[inline]
+    pub const fn new() -> Self {
+        Self(SlotState::VacantBody)
+    }
+}
+impl<T> Default for Slot<T> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+impl<T: Debug> Debug for Slot<T> {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        match &self.0 {
+            SlotState::Occupied(value) => f.debug_tuple("Occupied").field(value).finish(),
+            SlotState::VacantHead { .. } | SlotState::VacantTail { .. } | SlotState::VacantBody => {
+                f.write_str("Vacant")
+            }
+        }
+    }
+}
+
+/// A slab that stores values in a caller-provided slice of [`Slot`] and never allocates.
+///
+/// The number of elements is limited by the length of the slice.
+/// Values left in the slice when the SlabMapRef is dropped are dropped with the slice.
+///
+/// As with [`SlabMap`](crate::SlabMap), adjacent free slots are merged into runs,
+/// so iteration skips each run at once and free space at the end is released.
+///
+/// # Examples
+/// ```
+/// use slabmap::slab_map_ref::{SlabMapRef, Slot};
+///
+/// let mut slots: [Slot<&str>; 2] = std::array::from_fn(|_| Slot::new());
+/// let mut s = SlabMapRef::new(&mut slots);
+/// let a = s.try_insert("a").unwrap();
+/// let b = s.try_insert("b").unwrap();
+/// assert_eq!(s.try_insert("c"), Err("c"));
+///
+/// assert_eq!(s.remove(a), Some("a"));
+/// assert_eq!(s.try_insert("c"), Ok(a));
+/// assert_eq!(s[b], "b");
+/// ```
+pub struct SlabMapRef<'a, T> {
+    slots: &'a mut [Slot<T>],
+    len: usize,
+    used: usize,
+    free_head: usize,
+}
+
+impl<'a, T> SlabMapRef<'a, T> {
+    /// Constructs a new, empty `SlabMapRef<T>` over `slots`.
+    ///
+    /// Values already stored in `slots` are dropped.
+    pub fn new(slots: &'a mut [Slot<T>]) -> Self {
+        for slot in slots.iter_mut() {
+            *slot = Slot::new();
+        }
+        Self {
+            slots,
+            len: 0,
+            used: 0,
+            free_head: NONE,
+        }
+    }
+
+    /// Returns the maximum number of elements the SlabMapRef can hold.
+    #[inline]
+    pub fn capacity(&self) -> usize {
+        self.slots.len()
+    }
+
+    /// Returns the number of elements in the SlabMapRef.
+    #[inline]
+    pub fn len(&self) -> usize {
+        self.len
+    }
+
+    /// Returns true if the SlabMapRef contains no elements.
+    #[inline]
+    pub fn is_empty(&self) -> bool {
+        self.len == 0
+    }
+
+    /// Returns true if no more elements can be inserted.
+    #[inline]
+    pub fn is_full(&self) -> bool {
+        self.len == self.slots.len()
+    }
+
+    /// Returns a reference to the value corresponding to the key.
+    #[inline]
+    pub fn get(&self, key: usize) -> Option<&T> {
+        if let SlotState::Occupied(value) = &self.slots.get(key)?.0 {
+            Some(value)
+        } else {
+            None
+        }
+    }
+
+    /// Returns a mutable reference to the value corresponding to the key.
+    #[inline]
+    pub fn get_mut(&mut self, key: usize) -> Option<&mut T> {
+        if let SlotState::Occupied(value) = &mut self.slots.get_mut(key)?.0 {
+            Some(value)
+        } else {
+            None
+        }
+    }
+
+    /// Returns true if the SlabMapRef contains a value for the specified key.
+    #[inline]
+    pub fn contains_key(&self, key: usize) -> bool {
+        self.get(key).is_some()
+    }
+
+    /// Inserts a value into the SlabMapRef.
+    ///
+    /// Returns the key associated with the value, or the value itself if the SlabMapRef is full.
+    pub fn try_insert(&mut self, value: T) -> Result<usize, T> {
+        let key = if self.free_head != NONE {
+            let key = self.free_head;
+            let SlotState::VacantHead { len, .. } = self.slots[key].0 else {
+                unreachable!()
+            };
+            self.unlink(key);
+            if len > 1 {
+                self.push_run(key + 1, len - 1);
+            }
+            key
+        } else if self.used < self.slots.len() {
+            self.used += 1;
+            self.used - 1
+        } else {
+            return Err(value);
+        };
+        self.slots[key].0 = SlotState::Occupied(value);
+        self.len += 1;
+        Ok(key)
+    }
+
+    /// Removes a key from the SlabMapRef, returning the value at the key if the key was previously in the SlabMapRef.
+    pub fn remove(&mut self, key: usize) -> Option<T> {
+        let slot = self.slots.get_mut(key)?;
+        if !matches!(slot.0, SlotState::Occupied(_)) {
+            return None;
+        }
+        let SlotState::Occupied(value) = core::mem::replace(&mut slot.0, SlotState::VacantBody)
+        else {
+            unreachable!()
+        };
+        self.len -= 1;
+        let start = match key.checked_sub(1).map(|idx| &self.slots[idx].0) {
+            Some(SlotState::VacantTail { len }) => key - len,
+            Some(SlotState::VacantHead { .. }) => key - 1,
+            _ => key,
+        };
+        if start < key {
+            self.unlink(start);
+            self.slots[key - 1].0 = SlotState::VacantBody;
+        }
+        if key + 1 == self.used {
+            for slot in &mut self.slots[start..key] {
+                *slot = Slot::new();
+            }
+            self.used = start;
+        } else {
+            let mut end = key + 1;
+            if let SlotState::VacantHead { len, .. } = self.slots[end].0 {
+                self.unlink(end);
+                self.slots[end].0 = SlotState::VacantBody;
+                end += len;
+            }
+            self.push_run(start, end - start);
+        }
+        if self.len == 0 {
+            self.clear();
+        }
+        Some(value)
+    }
+
+    /// Writes a run of free slots at `start..start + len` and makes it the first run to be reused.
+    fn push_run(&mut self, start: usize, len: usize) {
+        if self.free_head != NONE {
+            if let SlotState::VacantHead { prev, .. } = &mut self.slots[self.free_head].0 {
+                *prev = start;
+            }
+        }
+        self.slots[start].0 = SlotState::VacantHead {
+            len,
+            prev: NONE,
+            next: self.free_head,
+        };
+        if len > 1 {
+            self.slots[start + len - 1].0 = SlotState::VacantTail { len };
+        }
+        self.free_head = start;
+    }
+
+    /// Removes the run starting at `start` from the list of runs to be reused.
+    fn unlink(&mut self, start: usize) {
+        let SlotState::VacantHead { prev, next, .. } = self.slots[start].0 else {
+            unreachable!()
+        };
+        if prev == NONE {
+            self.free_head = next;
+        } else if let SlotState::VacantHead { next: n, .. } = &mut self.slots[prev].0 {
+            *n = next;
+        }
+        if next != NONE {
+            if let SlotState::VacantHead { prev: p, .. } = &mut self.slots[next].0 {
+                *p = prev;
+            }
+        }
+    }
+
+    /// Clears the SlabMapRef, removing all values.
+    pub fn clear(&mut self) {
+        for slot in &mut self.slots[..self.used] {
+            *slot = Slot::new();
+        }
+        self.len = 0;
+        self.used = 0;
+        self.free_head = NONE;
+    }
+
+    /// Gets an iterator over the entries of the SlabMapRef, sorted by key.
+    #[inline]
+    pub fn iter(&self) -> Iter<'_, T> {
+        Iter {
+            iter: self.slots[..self.used].iter().enumerate(),
+            len: self.len,
+        }
+    }
+
+    /// Gets a mutable iterator over the entries of the SlabMapRef, sorted by key.
+    #[inline]
+    pub fn iter_mut(&mut self) -> IterMut<'_, T> {
+        IterMut {
+            iter: self.slots[..self.used].iter_mut().enumerate(),
+            len: self.len,
+        }
+    }
+
+    /// Gets an iterator over the keys of the SlabMapRef, in sorted order.
+    #[inline]
+    pub fn keys(&self) -> Keys<'_, T> {
+        Keys(self.iter())
+    }
+
+    /// Gets an iterator over the values of the SlabMapRef.
+    #[inline]
+    pub fn values(&self) -> Values<'_, T> {
+        Values(self.iter())
+    }
+
+    /// Gets a mutable iterator over the values of the SlabMapRef.
+    #[inline]
+    pub fn values_mut(&mut self) -> ValuesMut<'_, T> {
+        ValuesMut(self.iter_mut())
+    }
+}
+impl<T: Debug> Debug for SlabMapRef<'_, T> {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        f.debug_map().entries(self.iter()).finish()
+    }
+}
+
+impl<T> core::ops::Index<usize> for SlabMapRef<'_, T> {
+    type Output = T;
+
+    #[inline]
+    fn index(&self, index: usize) -> &Self::Output {
+        self.get(index).expect("out of index.")
+    }
+}
+impl<T> core::ops::IndexMut<usize> for SlabMapRef<'_, T> {
+    #[inline]
+    fn index_mut(&mut self, index: usize) -> &mut Self::Output {
+        self.get_mut(index).expect("out of index.")
+    }
+}
+
+impl<'a, T> IntoIterator for &'a SlabMapRef<'_, T> {
+    type Item = (usize, &'a T);
+    type IntoIter = Iter<'a, T>;
+
+    #[inline]
+    fn into_iter(self) -> Self::IntoIter {
+        self.iter()
+    }
+}
+impl<'a, T> IntoIterator for &'a mut SlabMapRef<'_, T> {
+    type Item = (usize, &'a mut T);
+    type IntoIter = IterMut<'a, T>;
+
+    #[inline]
+    fn into_iter(self) -> Self::IntoIter {
+        self.iter_mut()
+    }
+}
+
+/// An iterator over the entries of a [`SlabMapRef`].
+///
+/// This struct is created by the [`iter`](SlabMapRef::iter).
+pub struct Iter<'a, T> {
+    iter: core::iter::Enumerate<core::slice::Iter<'a, Slot<T>>>,
+    len: usize,
+}
+impl<'a, T> Iterator for Iter<'a, T> {
+    type Item = (usize, &'a T);
+    #[inline]
+    fn next(&mut self) -> Option<Self::Item> {
+        while let Some((key, slot)) = self.iter.next() {
+            match &slot.0 {
+                SlotState::Occupied(value) => {
+                    self.len -= 1;
+                    return Some((key, value));
+                }
+                SlotState::VacantHead { len, .. } if *len > 1 => {
+                    self.iter.nth(*len - 2);
+                }
+                _ => {}
+            }
+        }
+        None
+    }
+    #[inline]
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        (self.len, Some(self.len))
+    }
+}
+impl<T> FusedIterator for Iter<'_, T> {}
+impl<T> ExactSizeIterator for Iter<'_, T> {}
+
+/// A mutable iterator over the entries of a [`SlabMapRef`].
+///
+/// This struct is created by the [`iter_mut`](SlabMapRef::iter_mut).
+pub struct IterMut<'a, T> {
+    iter: core::iter::Enumerate<core::slice::IterMut<'a, Slot<T>>>,
+    len: usize,
+}
+impl<'a, T> Iterator for IterMut<'a, T> {
+    type Item = (usize, &'a mut T);
+    #[inline]
+    fn next(&mut self) -> Option<Self::Item> {
+        while let Some((key, slot)) = self.iter.next() {
+            match &mut slot.0 {
+                SlotState::Occupied(value) => {
+                    self.len -= 1;
+                    return Some((key, value));
+                }
+                SlotState::VacantHead { len, .. } if *len > 1 => {
+                    self.iter.nth(*len - 2);
+                }
+                _ => {}
+            }
+        }
+        None
+    }
+    #[inline]
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        (self.len, Some(self.len))
+    }
+}
+impl<T> FusedIterator for IterMut<'_, T> {}
+impl<T> ExactSizeIterator for IterMut<'_, T> {}
+
+/// An iterator over the keys of a [`SlabMapRef`].
+///
+/// This struct is created by the [`keys`](SlabMapRef::keys).
+pub struct Keys<'a, T>(Iter<'a, T>);
+impl<T> Iterator for Keys<'_, T> {
+    type Item = usize;
+    #[inline]
+    fn next(&mut self) -> Option<Self::Item> {
+        self.0.next().map(|(k, _)| k)
+    }
+    #[inline]
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        self.0.size_hint()
+    }
+}
+impl<T> FusedIterator for Keys<'_, T> {}
+impl<T> ExactSizeIterator for Keys<'_, T> {}
+
+/// An iterator over the values of a [`SlabMapRef`].
+///
+/// This struct is created by the [`values`](SlabMapRef::values).
+pub struct Values<'a, T>(Iter<'a, T>);
+impl<'a, T> Iterator for Values<'a, T> {
+    type Item = &'a T;
+    #[inline]
+    fn next(&mut self) -> Option<Self::Item> {
+        self.0.next().map(|(_, v)| v)
+    }
+    #[inline]
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        self.0.size_hint()
+    }
+}
+impl<T> FusedIterator for Values<'_, T> {}
+impl<T> ExactSizeIterator for Values<'_, T> {}
+
+/// A mutable iterator over the values of a [`SlabMapRef`].
+///
+/// This struct is created by the [`values_mut`](SlabMapRef::values_mut).
+pub struct ValuesMut<'a, T>(IterMut<'a, T>);
+impl<'a, T> Iterator for ValuesMut<'a, T> {
+    type Item = &'a mut T;
+    #[inline]
+    fn next(&mut self) -> Option<Self::Item> {
+        self.0.next().map(|(_, v)| v)
+    }
+    #[inline]
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        self.0.size_hint()
+    }
+}
+impl<T> FusedIterator for ValuesMut<'_, T> {}
+impl<T> ExactSizeIterator for ValuesMut<'_, T> {}
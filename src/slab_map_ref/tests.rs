@@ -0,0 +1,123 @@
+use std::{collections::BTreeMap, rc::Rc};
+
+use rand::{rngs::StdRng, Rng, SeedableRng};
+
+use crate::slab_map_ref::{SlabMapRef, Slot, SlotState, NONE};
+
+#[test]
+fn random() {
+    let mut rng = StdRng::seed_from_u64(0);
+    let mut slots: Vec<Slot<usize>> = (0..64).map(|_| Slot::new()).collect();
+    let mut s = SlabMapRef::new(&mut slots);
+    let mut e = BTreeMap::new();
+    for i in 0..5000 {
+        if rng.gen_bool(0.5) {
+            match s.try_insert(i) {
+                Ok(key) => assert_eq!(e.insert(key, i), None),
+                Err(value) => {
+                    assert_eq!(value, i);
+                    assert_eq!(e.len(), 64);
+                }
+            }
+        } else if let Some(&key) = e.keys().nth(rng.gen_range(0..e.len().max(1))) {
+            assert_eq!(s.remove(key), e.remove(&key));
+        }
+        assert_eq!(s.len(), e.len());
+        check_runs(&s);
+    }
+    let a: Vec<_> = s.iter().map(|(k, &v)| (k, v)).collect();
+    let e: Vec<_> = e.into_iter().collect();
+    assert_eq!(a, e);
+    assert!(s.keys().all(|key| key < 64));
+}
+
+#[test]
+fn drop_values() {
+    let value = Rc::new(());
+    let mut slots: [Slot<Rc<()>>; 4] = std::array::from_fn(|_| Slot::new());
+    {
+        let mut s = SlabMapRef::new(&mut slots);
+        s.try_insert(value.clone()).unwrap();
+        s.try_insert(value.clone()).unwrap();
+    }
+    assert_eq!(Rc::strong_count(&value), 3);
+    SlabMapRef::new(&mut slots);
+    assert_eq!(Rc::strong_count(&value), 1);
+}
+
+#[test]
+fn zero_capacity() {
+    let mut s = SlabMapRef::<u8>::new(&mut []);
+    assert!(s.is_full());
+    assert_eq!(s.try_insert(1), Err(1));
+}
+
+#[test]
+fn merge_runs() {
+    let mut slots: [Slot<usize>; 8] = std::array::from_fn(|_| Slot::new());
+    let mut s = SlabMapRef::new(&mut slots);
+    for i in 0..8 {
+        s.try_insert(i).unwrap();
+    }
+    for key in [1, 3, 2, 5, 7] {
+        s.remove(key);
+        check_runs(&s);
+    }
+    assert_eq!(s.used, 7);
+    assert_eq!(s.keys().collect::<Vec<_>>(), [0, 4, 6]);
+    assert_eq!(s.try_insert(10), Ok(5));
+    assert_eq!(s.try_insert(11), Ok(1));
+    assert_eq!(s.try_insert(12), Ok(2));
+    check_runs(&s);
+    s.remove(6);
+    assert_eq!(s.used, 6);
+    check_runs(&s);
+}
+
+fn check_runs<T>(s: &SlabMapRef<T>) {
+    let mut heads = Vec::new();
+    let mut len = 0;
+    let mut idx = 0;
+    let mut prev_vacant = false;
+    while idx < s.used {
+        match &s.slots[idx].0 {
+            SlotState::Occupied(_) => {
+                len += 1;
+                prev_vacant = false;
+                idx += 1;
+            }
+            SlotState::VacantHead { len: run_len, .. } => {
+                assert!(!prev_vacant, "unmerged runs at {idx}");
+                let end = idx + run_len;
+                assert!(end < s.used, "free space at the end");
+                if *run_len > 1 {
+                    assert!(
+                        matches!(s.slots[end - 1].0, SlotState::VacantTail { len } if len == *run_len)
+                    );
+                    for i in idx + 1..end - 1 {
+                        assert!(matches!(s.slots[i].0, SlotState::VacantBody));
+                    }
+                }
+                heads.push(idx);
+                prev_vacant = true;
+                idx = end;
+            }
+            _ => panic!("broken run at {idx}"),
+        }
+    }
+    assert_eq!(len, s.len);
+    let mut linked = Vec::new();
+    let mut prev = NONE;
+    let mut head = s.free_head;
+    while head != NONE {
+        let SlotState::VacantHead { prev: p, next, .. } = s.slots[head].0 else {
+            panic!("not a head at {head}");
+        };
+        assert_eq!(p, prev);
+        linked.push(head);
+        prev = head;
+        head = next;
+    }
+    linked.sort();
+    assert_eq!(linked, heads);
+}
@@ -0,0 +1,212 @@
+//! A [`SlabMap`] that keeps removed values for reuse.
+
+use alloc::vec::Vec;
+use core::fmt::Debug;
+
+use derive_ex::derive_ex;
+
+use crate::{
+    slab_map::{Iter, IterMut, Keys, Values, ValuesMut},
+    SlabMap,
+};
+
+#[cfg(test)]
+mod tests;
+
+/// A [`SlabMap`] that keeps removed values for reuse instead of dropping them.
+///
+/// This is useful for values that own large buffers, such as `Vec` or `String`,
+/// because the allocations of removed values are reused by [`insert_reuse`](SlabPool::insert_reuse).
+///
+/// # Examples
+/// ```
+/// use slabmap::slab_pool::SlabPool;
+///
+/// let mut s = SlabPool::<Vec<u8>>::new();
+/// let key = s.insert_reuse(|v| v.extend_from_slice(b"abc"));
+/// let capacity = s[key].capacity();
+///
+/// assert!(s.remove(key));
+/// assert_eq!(s.pooled_len(), 1);
+///
+/// let key = s.insert_reuse(|v| v.clear());
+/// assert!(s[key].is_empty());
+/// assert_eq!(s[key].capacity(), capacity);
+/// ```
+#[derive_ex(Clone(bound(T)), Default(bound()))]
+pub struct SlabPool<T> {
+    map: SlabMap<T>,
+    pool: Vec<T>,
+}
+
+impl<T> SlabPool<T> {
+    /// Constructs a new, empty `SlabPool<T>`.
+    #[inline]
+    pub const fn new() -> Self {
+        Self {
+            map: SlabMap::new(),
+            pool: Vec::new(),
+        }
+    }
+
+    /// Returns the number of elements in the SlabPool.
+    #[inline]
+    pub fn len(&self) -> usize {
+        self.map.len()
+    }
+
+    /// Returns true if the SlabPool contains no elements.
+    #[inline]
+    pub fn is_empty(&self) -> bool {
+        self.map.is_empty()
+    }
+
+    /// Returns the number of removed values kept for reuse.
+    #[inline]
+    pub fn pooled_len(&self) -> usize {
+        self.pool.len()
+    }
+
+    /// Returns a reference to the value corresponding to the key.
+    #[inline]
+    pub fn get(&self, key: usize) -> Option<&T> {
+        self.map.get(key)
+    }
+
+    /// Returns a mutable reference to the value corresponding to the key.
+    #[inline]
+    pub fn get_mut(&mut self, key: usize) -> Option<&mut T> {
+        self.map.get_mut(key)
+    }
+
+    /// Returns true if the SlabPool contains a value for the specified key.
+    #[inline]
+    pub fn contains_key(&self, key: usize) -> bool {
+        self.map.contains_key(key)
+    }
+
+    /// Inserts a value into the SlabPool.
+    ///
+    /// Returns the key associated with the value.
+    #[inline]
+    pub fn insert(&mut self, value: T) -> usize {
+        self.map.insert(value)
+    }
+
+    /// Inserts a value reinitialized by `reset`, reusing a removed value if there is one.
+    ///
+    /// If there is no removed value, `reset` is applied to `T::default()`.
+    ///
+    /// Returns the key associated with the value.
+    pub fn insert_reuse(&mut self, reset: impl FnOnce(&mut T)) -> usize
+    where
+        T: Default,
+    {
+        let mut value = self.pool.pop().unwrap_or_default();
+        reset(&mut value);
+        self.map.insert(value)
+    }
+
+    /// Removes a key from the SlabPool, keeping the value for reuse.
+    ///
+    /// Returns true if the key was previously in the SlabPool.
+    pub fn remove(&mut self, key: usize) -> bool {
+        if let Some(value) = self.map.remove(key) {
+            self.pool.push(value);
+            true
+        } else {
+            false
+        }
+    }
+
+    /// Removes a key from the SlabPool, returning the value instead of keeping it for reuse.
+    #[inline]
+    pub fn take(&mut self, key: usize) -> Option<T> {
+        self.map.remove(key)
+    }
+
+    /// Removes all keys from the SlabPool, keeping the values for reuse.
+    pub fn clear(&mut self) {
+        self.pool.extend(self.map.drain().map(|(_, value)| value));
+    }
+
+    /// Drops removed values so that at most `max_len` values are kept for reuse.
+    pub fn shrink_pool(&mut self, max_len: usize) {
+        self.pool.truncate(max_len);
+    }
+
+    /// Returns a reference to the underlying [`SlabMap`].
+    #[inline]
+    pub fn as_slab_map(&self) -> &SlabMap<T> {
+        &self.map
+    }
+
+    /// Gets an iterator over the entries of the SlabPool, sorted by key.
+    #[inline]
+    pub fn iter(&self) -> Iter<'_, T> {
+        self.map.iter()
+    }
+
+    /// Gets a mutable iterator over the entries of the SlabPool, sorted by key.
+    #[inline]
+    pub fn iter_mut(&mut self) -> IterMut<'_, T> {
+        self.map.iter_mut()
+    }
+
+    /// Gets an iterator over the keys of the SlabPool, in sorted order.
+    #[inline]
+    pub fn keys(&self) -> Keys<'_, T> {
+        self.map.keys()
+    }
+
+    /// Gets an iterator over the values of the SlabPool.
+    #[inline]
+    pub fn values(&self) -> Values<'_, T> {
+        self.map.values()
+    }
+
+    /// Gets a mutable iterator over the values of the SlabPool.
+    #[inline]
+    pub fn values_mut(&mut self) -> ValuesMut<'_, T> {
+        self.map.values_mut()
+    }
+}
+impl<T: Debug> Debug for SlabPool<T> {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        self.map.fmt(f)
+    }
+}
+
+impl<T> core::ops::Index<usize> for SlabPool<T> {
+    type Output = T;
+
+    #[inline]
+    fn index(&self, index: usize) -> &Self::Output {
+        self.get(index).expect("out of index.")
+    }
+}
+impl<T> core::ops::IndexMut<usize> for SlabPool<T> {
+    #[inline]
+    fn index_mut(&mut self, index: usize) -> &mut Self::Output {
+        self.get_mut(index).expect("out of index.")
+    }
+}
+
+impl<'a, T> IntoIterator for &'a SlabPool<T> {
+    type Item = (usize, &'a T);
+    type IntoIter = Iter<'a, T>;
+
+    #[inline]
+    fn into_iter(self) -> Self::IntoIter {
+        self.iter()
+    }
+}
+impl<'a, T> IntoIterator for &'a mut SlabPool<T> {
+    type Item = (usize, &'a mut T);
+    type IntoIter = IterMut<'a, T>;
+
+    #[inline]
+    fn into_iter(self) -> Self::IntoIter {
+        self.iter_mut()
+    }
+}
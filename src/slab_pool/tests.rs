@@ -0,0 +1,53 @@
+use std::rc::Rc;
+
+use crate::slab_pool::SlabPool;
+
+#[test]
+fn remove_keeps_value() {
+    let value = Rc::new(());
+    let mut s = SlabPool::new();
+    let key = s.insert(value.clone());
+    assert!(s.remove(key));
+    assert!(!s.remove(key));
+    assert_eq!(s.len(), 0);
+    assert_eq!(s.pooled_len(), 1);
+    assert_eq!(Rc::strong_count(&value), 2);
+
+    s.shrink_pool(0);
+    assert_eq!(Rc::strong_count(&value), 1);
+}
+
+#[test]
+fn insert_reuse() {
+    let mut s = SlabPool::<String>::new();
+    let key = s.insert_reuse(|v| v.push_str("hello world"));
+    assert_eq!(s[key], "hello world");
+    let ptr = s[key].as_ptr();
+    s.remove(key);
+
+    let key = s.insert_reuse(|v| {
+        v.clear();
+        v.push_str("abc");
+    });
+    assert_eq!(s[key], "abc");
+    assert_eq!(s[key].as_ptr(), ptr);
+    assert_eq!(s.pooled_len(), 0);
+}
+
+#[test]
+fn take() {
+    let mut s = SlabPool::new();
+    let key = s.insert(10);
+    assert_eq!(s.take(key), Some(10));
+    assert_eq!(s.pooled_len(), 0);
+}
+
+#[test]
+fn clear() {
+    let mut s = SlabPool::new();
+    s.insert(1);
+    s.insert(2);
+    s.clear();
+    assert!(s.is_empty());
+    assert_eq!(s.pooled_len(), 2);
+}
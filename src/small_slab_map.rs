@@ -1,25 +1,38 @@
 //! A variant of [`SlabMap`] that avoids heap allocation when the number of elements is small.
 
-use std::{
-    array::{self, from_fn},
-    collections::TryReserveError,
-    fmt::Debug,
-    iter::{self, FusedIterator},
-    mem,
-    result::Result,
-    slice,
-};
+use alloc::collections::TryReserveError;
+use core::{fmt::Debug, iter::FusedIterator, mem, result::Result, slice};
 
 use derive_ex::derive_ex;
 
 use crate::SlabMap;
 
+use self::{inline::Inline, sparse::Sparse};
+
+#[cfg(feature = "zeroize")]
+pub use self::zeroize::ZeroizingSmallSlabMap;
+
+mod inline;
+#[cfg(feature = "serde")]
+mod serde;
+mod sparse;
 #[cfg(test)]
 mod tests;
+#[cfg(feature = "zeroize")]
+mod zeroize;
 
+/// `Inline` stores the value for key `i` at `items[i]`, with a bitmask of the occupied keys.
+/// Since the values are not wrapped in `Option`, types without a niche do not need extra space for each item.
+///
+/// `Sparse` stores pairs of a key and a value in `items[..len]`, sorted by key.
+/// It is used when a key is too large for `Inline` but the number of elements still fits.
+///
+/// The size of the inline variants grows with `N`, so `Heap` is not boxed even though it is larger for small `N`.
+#[allow(clippy::large_enum_variant)]
 #[derive(Clone)]
 enum Data<T, const N: usize> {
-    Inline { len: u8, items: [Option<T>; N] },
+    Inline(Inline<T, N>),
+    Sparse(Sparse<T, N>),
     Heap(SlabMap<T>),
 }
 
@@ -27,6 +40,8 @@ enum Data<T, const N: usize> {
 ///
 /// If the number of elements is less than or equal to the generic parameter `N`,
 /// heap allocation is not performed and data is stored in an inline array.
+/// This also applies when keys are `N` or greater, for example when built by [`from_iter`](FromIterator::from_iter).
+/// In that case, or when keys are 64 or greater, keys are stored alongside the values.
 ///
 /// It is recommended that `N` be equal to or less than 16.
 /// Larger values may result in inefficient operation.
@@ -52,23 +67,15 @@ enum Data<T, const N: usize> {
 /// ```
 #[derive_ex(Default(bound()))]
 #[default(Self::new())]
-pub struct SmallSlabMap<T, const N: usize>(Option<Data<T, N>>);
+pub struct SmallSlabMap<T, const N: usize>(Data<T, N>);
 
 impl<T, const N: usize> SmallSlabMap<T, N> {
-    const INLINE_CAPACITY: usize = {
-        let value = N;
-        let value_max = u8::MAX as usize;
-        if value <= value_max {
-            value
-        } else {
-            value_max
-        }
-    };
+    const INLINE_CAPACITY: usize = N;
     /// Constructs a new, empty `SmallSlabMap<T, N>`.
     /// The SmallSlabMap will not allocate until elements are pushed onto it.
     #[inline]
     pub const fn new() -> Self {
-        Self(None)
+        Self(Data::Inline(Inline::new()))
     }
 
     /// Constructs a new, empty `SmallSlabMap<T, N>` with the specified capacity.
@@ -77,7 +84,7 @@ impl<T, const N: usize> SmallSlabMap<T, N> {
         if capacity <= Self::INLINE_CAPACITY {
             Self::new()
         } else {
-            Self(Some(Data::Heap(SlabMap::with_capacity(capacity))))
+            Self(Data::Heap(SlabMap::with_capacity(capacity)))
         }
     }
 
@@ -94,24 +101,24 @@ impl<T, const N: usize> SmallSlabMap<T, N> {
         this
     }
     fn set(&mut self, key: usize, value: T) {
-        if key >= Self::INLINE_CAPACITY {
-            self.as_heap();
-        }
-        match self.as_data() {
-            Data::Inline { len, items } => {
-                if items[key].is_none() {
-                    *len += 1;
-                }
-                items[key] = Some(value);
+        if key >= Inline::<T, N>::CAPACITY && matches!(self.0, Data::Inline(_)) {
+            self.as_sparse();
+        }
+        match &mut self.0 {
+            Data::Inline(m) => {
+                m.insert(key, value);
             }
+            Data::Sparse(m) => match m.position(key) {
+                Ok(index) => m.entries_mut()[index].1 = value,
+                Err(index) if m.len() < Self::INLINE_CAPACITY => m.insert(index, key, value),
+                Err(_) => self.as_heap().set(key, value),
+            },
             Data::Heap(m) => m.set(key, value),
         }
     }
     fn rebuild_vacants(&mut self) {
-        match self.as_data() {
-            Data::Inline { len, items } => {
-                *len = items.iter().filter(|x| x.is_some()).count() as u8
-            }
+        match &mut self.0 {
+            Data::Inline(_) | Data::Sparse(_) => {}
             Data::Heap(m) => m.rebuild_vacants(),
         }
     }
@@ -120,11 +127,44 @@ impl<T, const N: usize> SmallSlabMap<T, N> {
     #[inline]
     pub fn capacity(&self) -> usize {
         match &self.0 {
-            None | Some(Data::Inline { .. }) => Self::INLINE_CAPACITY,
-            Some(Data::Heap(m)) => m.capacity(),
+            Data::Inline(_) | Data::Sparse(_) => Self::INLINE_CAPACITY,
+            Data::Heap(m) => m.capacity(),
         }
     }
 
+    /// Returns the number of elements the SmallSlabMap can hold without heap allocation, which is `N`.
+    #[inline]
+    pub const fn inline_capacity() -> usize {
+        Self::INLINE_CAPACITY
+    }
+
+    /// Returns true if the elements are stored in the inline array.
+    ///
+    /// # Examples
+    /// ```
+    /// use slabmap::SmallSlabMap;
+    ///
+    /// let mut s = SmallSlabMap::<_, 1>::new();
+    /// s.insert(10);
+    /// assert!(s.is_inline());
+    ///
+    /// s.insert(20);
+    /// assert!(!s.is_inline());
+    /// ```
+    #[inline]
+    pub fn is_inline(&self) -> bool {
+        !self.is_spilled()
+    }
+
+    /// Returns true if the elements have been moved to the heap.
+    ///
+    /// Once spilled, the elements stay on the heap even if they are removed,
+    /// until [`drain_and_shrink`](Self::drain_and_shrink) is called.
+    #[inline]
+    pub fn is_spilled(&self) -> bool {
+        matches!(self.0, Data::Heap(_))
+    }
+
     /// Reserves capacity for at least additional more elements to be inserted in the given `SmallSlabMap<T, N>`.
     ///
     /// # Panics
@@ -137,7 +177,7 @@ impl<T, const N: usize> SmallSlabMap<T, N> {
     /// Try to reserve capacity for at least additional more elements to be inserted in the given `SmallSlabMap<T, N>`.
     #[inline]
     pub fn try_reserve(&mut self, additional: usize) -> Result<(), TryReserveError> {
-        if !self.is_heap() && self.len() + additional <= Self::INLINE_CAPACITY {
+        if !self.is_spilled() && self.len() + additional <= Self::INLINE_CAPACITY {
             Ok(())
         } else {
             self.as_heap().try_reserve(additional)
@@ -156,7 +196,7 @@ impl<T, const N: usize> SmallSlabMap<T, N> {
     /// Try to reserve the minimum capacity for exactly additional more elements to be inserted in the given `SmallSlabMap<T, N>`.
     #[inline]
     pub fn try_reserve_exact(&mut self, additional: usize) -> Result<(), TryReserveError> {
-        if !self.is_heap() && self.len() + additional <= Self::INLINE_CAPACITY {
+        if !self.is_spilled() && self.len() + additional <= Self::INLINE_CAPACITY {
             Ok(())
         } else {
             self.as_heap().try_reserve_exact(additional)
@@ -186,9 +226,9 @@ impl<T, const N: usize> SmallSlabMap<T, N> {
     #[inline]
     pub fn len(&self) -> usize {
         match &self.0 {
-            None => 0,
-            Some(Data::Inline { len, .. }) => *len as usize,
-            Some(Data::Heap(m)) => m.len(),
+            Data::Inline(m) => m.len(),
+            Data::Sparse(m) => m.len(),
+            Data::Heap(m) => m.len(),
         }
     }
 
@@ -226,8 +266,9 @@ impl<T, const N: usize> SmallSlabMap<T, N> {
     /// ```
     #[inline]
     pub fn get(&self, key: usize) -> Option<&T> {
-        match self.0.as_ref()? {
-            Data::Inline { items, .. } => items.get(key)?.as_ref(),
+        match &self.0 {
+            Data::Inline(m) => m.get(key),
+            Data::Sparse(m) => m.get(key),
             Data::Heap(m) => m.get(key),
         }
     }
@@ -235,8 +276,9 @@ impl<T, const N: usize> SmallSlabMap<T, N> {
     /// Returns a mutable reference to the value corresponding to the key.
     #[inline]
     pub fn get_mut(&mut self, key: usize) -> Option<&mut T> {
-        match self.as_data() {
-            Data::Inline { items, .. } => items.get_mut(key)?.as_mut(),
+        match &mut self.0 {
+            Data::Inline(m) => m.get_mut(key),
+            Data::Sparse(m) => m.get_mut(key),
             Data::Heap(m) => m.get_mut(key),
         }
     }
@@ -293,12 +335,21 @@ impl<T, const N: usize> SmallSlabMap<T, N> {
     #[inline]
     pub fn insert_with_key(&mut self, f: impl FnOnce(usize) -> T) -> usize {
         self.reserve(1);
-        match self.as_data() {
-            Data::Inline { len, items } => {
-                let index = items.iter().position(|x| x.is_none()).unwrap();
-                items[index] = Some(f(index));
-                *len += 1;
-                index
+        if let Data::Inline(m) = &mut self.0 {
+            if let Some(key) = m.vacant_key() {
+                m.insert(key, f(key));
+                return key;
+            }
+            self.as_sparse();
+        }
+        match &mut self.0 {
+            Data::Inline(_) => unreachable!(),
+            Data::Sparse(m) => {
+                let key = (m.entries().iter().zip(0..))
+                    .position(|((key, _), index)| *key != index)
+                    .unwrap_or(m.len());
+                m.insert(key, key, f(key));
+                key
             }
             Data::Heap(m) => m.insert_with_key(f),
         }
@@ -316,13 +367,11 @@ impl<T, const N: usize> SmallSlabMap<T, N> {
     /// assert_eq!(s.remove(key), None);
     /// ```
     pub fn remove(&mut self, key: usize) -> Option<T> {
-        match self.as_data() {
-            Data::Inline { items, len } => {
-                let ret = items.get_mut(key)?.take();
-                if ret.is_some() {
-                    *len -= 1;
-                }
-                ret
+        match &mut self.0 {
+            Data::Inline(m) => m.remove(key),
+            Data::Sparse(m) => {
+                let index = m.position(key).ok()?;
+                Some(m.remove(index).1)
             }
             Data::Heap(m) => m.remove(key),
         }
@@ -343,17 +392,18 @@ impl<T, const N: usize> SmallSlabMap<T, N> {
     /// assert_eq!(s.is_empty(), true);
     /// ```
     pub fn clear(&mut self) {
-        match &mut self.as_data() {
-            Data::Inline { len, items } => {
-                *len = 0;
-                *items = from_fn(|_| None);
-            }
+        match &mut self.0 {
+            Data::Inline(m) => m.clear(),
+            Data::Sparse(_) => *self = Self::new(),
             Data::Heap(m) => m.clear(),
         }
     }
 
     /// Clears the SmallSlabMap, returning all values as an iterator and optimize free spaces.
     ///
+    /// If the values are stored on the heap, the heap allocation is kept for subsequent insertions.
+    /// Use [`drain_and_shrink`](Self::drain_and_shrink) to return to inline storage.
+    ///
     /// # Examples
     /// ```
     /// use slabmap::SmallSlabMap;
@@ -370,17 +420,39 @@ impl<T, const N: usize> SmallSlabMap<T, N> {
     /// assert_eq!(d, e);
     /// ```
     pub fn drain(&mut self) -> Drain<T, N> {
-        match self.as_data() {
-            Data::Inline { len, items } => {
-                let len = mem::take(len);
-                let items = mem::replace(items, from_fn(|_| None));
-                return Drain(RawDrain::Inline {
-                    iter: items.into_iter().enumerate(),
-                    len: len as usize,
-                });
-            }
-            Data::Heap(m) => Drain(RawDrain::Heap(m.drain())),
-        }
+        if !self.is_spilled() {
+            return match mem::take(self).0 {
+                Data::Inline(m) => Drain(RawDrain::Inline(m.into_iter())),
+                Data::Sparse(m) => Drain(RawDrain::Sparse(m.into_iter())),
+                Data::Heap(_) => unreachable!(),
+            };
+        }
+        let Data::Heap(m) = &mut self.0 else {
+            unreachable!()
+        };
+        Drain(RawDrain::Heap(m.drain()))
+    }
+
+    /// Clears the SmallSlabMap, returning all values as an iterator and releasing the heap allocation.
+    ///
+    /// After this call, values are stored inline again.
+    ///
+    /// # Examples
+    /// ```
+    /// use slabmap::SmallSlabMap;
+    ///
+    /// let mut s = SmallSlabMap::<_, 1>::new();
+    /// s.insert(10);
+    /// s.insert(20);
+    /// let inline_capacity = SmallSlabMap::<i32, 1>::new().capacity();
+    /// assert!(s.capacity() > inline_capacity);
+    ///
+    /// let d: Vec<_> = s.drain_and_shrink().collect();
+    /// assert_eq!(d.len(), 2);
+    /// assert_eq!(s.capacity(), inline_capacity);
+    /// ```
+    pub fn drain_and_shrink(&mut self) -> IntoIter<T, N> {
+        mem::take(self).into_iter()
     }
 
     /// Retains only the elements specified by the predicate and optimize free spaces.
@@ -400,94 +472,67 @@ impl<T, const N: usize> SmallSlabMap<T, N> {
     /// let value: Vec<_> = s.values().cloned().collect();
     /// assert_eq!(value, vec![10, 20]);
     /// ```
-    pub fn retain(&mut self, mut f: impl FnMut(usize, &mut T) -> bool) {
-        match self.as_data() {
-            Data::Inline { items, len } => {
-                let mut len_new = 0;
-                for item in items {
-                    if let Some(value) = item {
-                        if f(len_new, value) {
-                            len_new += 1;
-                        } else {
-                            *item = None;
-                        }
-                    }
-                }
-                *len = len_new as u8;
-            }
+    pub fn retain(&mut self, f: impl FnMut(usize, &mut T) -> bool) {
+        match &mut self.0 {
+            Data::Inline(m) => m.retain(f),
+            Data::Sparse(m) => m.retain(f),
             Data::Heap(m) => m.retain(f),
         }
     }
 
-    /// Optimizing the free space for speeding up iterations.
+    /// Optimizing the free space.
+    ///
+    /// Free space at the end is released, and subsequent insertions reuse free space in ascending order of key.
     ///
     /// If the free space has already been optimized, this method does nothing and completes with O(1).
     pub fn optimize(&mut self) {
         match &mut self.0 {
-            None | Some(Data::Inline { .. }) => {}
-            Some(Data::Heap(m)) => m.optimize(),
+            Data::Inline(_) | Data::Sparse(_) => {}
+            Data::Heap(m) => m.optimize(),
         }
     }
 
     /// Gets an iterator over the entries of the SmallSlabMap, sorted by key.
-    ///
-    /// If you make a large number of [`remove`](SmallSlabMap::remove) calls, [`optimize`](SmallSlabMap::optimize) should be called before calling this function.
     #[inline]
     pub fn iter(&self) -> Iter<T, N> {
         self.into_iter()
     }
 
     /// Gets a mutable iterator over the entries of the SmallSlabMap, sorted by key.
-    ///
-    /// If you make a large number of [`remove`](SmallSlabMap::remove) calls, [`optimize`](SmallSlabMap::optimize) should be called before calling this function.
     #[inline]
     pub fn iter_mut(&mut self) -> IterMut<T, N> {
         self.into_iter()
     }
 
     /// Gets an iterator over the keys of the SmallSlabMap, in sorted order.
-    ///
-    /// If you make a large number of [`remove`](SmallSlabMap::remove) calls, [`optimize`](SmallSlabMap::optimize) should be called before calling this function.
     #[inline]
     pub fn keys(&self) -> Keys<T, N> {
         Keys(self.iter())
     }
 
     /// Gets an iterator over the values of the SmallSlabMap.
-    ///
-    /// If you make a large number of [`remove`](SmallSlabMap::remove) calls, [`optimize`](SmallSlabMap::optimize) should be called before calling this function.
     #[inline]
     pub fn values(&self) -> Values<T, N> {
         Values(self.iter())
     }
 
     /// Gets a mutable iterator over the values of the SmallSlabMap.
-    ///
-    /// If you make a large number of [`remove`](SmallSlabMap::remove) calls, [`optimize`](SmallSlabMap::optimize) should be called before calling this function.
     #[inline]
     pub fn values_mut(&mut self) -> ValuesMut<T, N> {
         ValuesMut(self.iter_mut())
     }
 
-    fn is_heap(&self) -> bool {
-        matches!(self.0, Some(Data::Heap(_)))
-    }
-    fn as_data(&mut self) -> &mut Data<T, N> {
-        if self.0.is_none() {
-            self.0 = Some(Data::Inline {
-                len: 0,
-                items: from_fn(|_| None),
-            });
+    fn as_sparse(&mut self) {
+        if let Data::Inline(m) = &mut self.0 {
+            let m = m.take();
+            self.0 = Data::Sparse(m.into());
         }
-        self.0.as_mut().unwrap()
     }
     fn as_heap(&mut self) -> &mut SlabMap<T> {
-        if !self.is_heap() {
-            self.0 = Some(Data::Heap(
-                mem::take(self).into_iter().collect::<SlabMap<T>>(),
-            ));
+        if !self.is_spilled() {
+            self.0 = Data::Heap(mem::take(self).into_iter().collect::<SlabMap<T>>());
         }
-        if let Some(Data::Heap(m)) = &mut self.0 {
+        if let Data::Heap(m) = &mut self.0 {
             m
         } else {
             unreachable!()
@@ -496,7 +541,7 @@ impl<T, const N: usize> SmallSlabMap<T, N> {
 }
 
 impl<T: Debug, const N: usize> Debug for SmallSlabMap<T, N> {
-    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
         f.debug_map().entries(self.iter()).finish()
     }
 }
@@ -510,7 +555,9 @@ where
     }
     fn clone_from(&mut self, source: &Self) {
         self.clear();
-        self.reserve(source.keys().map(|x| x + 1).max().unwrap_or(0));
+        if source.is_spilled() {
+            self.reserve(source.keys().map(|x| x + 1).max().unwrap_or(0));
+        }
         for (key, value) in source {
             self.set(key, value.clone());
         }
@@ -518,7 +565,7 @@ where
     }
 }
 
-impl<T, const N: usize> std::ops::Index<usize> for SmallSlabMap<T, N> {
+impl<T, const N: usize> core::ops::Index<usize> for SmallSlabMap<T, N> {
     type Output = T;
 
     #[inline]
@@ -526,7 +573,7 @@ impl<T, const N: usize> std::ops::Index<usize> for SmallSlabMap<T, N> {
         self.get(index).expect("out of index.")
     }
 }
-impl<T, const N: usize> std::ops::IndexMut<usize> for SmallSlabMap<T, N> {
+impl<T, const N: usize> core::ops::IndexMut<usize> for SmallSlabMap<T, N> {
     #[inline]
     fn index_mut(&mut self, index: usize) -> &mut Self::Output {
         self.get_mut(index).expect("out of index.")
@@ -540,10 +587,8 @@ impl<T, const N: usize> FromIterator<(usize, T)> for SmallSlabMap<T, N> {
 }
 
 enum RawIntoIter<T, const N: usize> {
-    Inline {
-        iter: iter::Enumerate<array::IntoIter<Option<T>, N>>,
-        len: usize,
-    },
+    Inline(inline::IntoIter<T, N>),
+    Sparse(sparse::IntoIter<T, N>),
     Heap(crate::slab_map::IntoIter<T>),
 }
 
@@ -557,17 +602,8 @@ impl<T, const N: usize> Iterator for IntoIter<T, N> {
 
     fn next(&mut self) -> Option<Self::Item> {
         match &mut self.0 {
-            RawIntoIter::Inline { iter, len } => {
-                if *len == 0 {
-                    return None;
-                }
-                *len -= 1;
-                loop {
-                    if let (key, Some(value)) = iter.next().unwrap() {
-                        return Some((key, value));
-                    }
-                }
-            }
+            RawIntoIter::Inline(iter) => iter.next(),
+            RawIntoIter::Sparse(iter) => iter.next(),
             RawIntoIter::Heap(iter) => iter.next(),
         }
     }
@@ -582,7 +618,8 @@ impl<T, const N: usize> Iterator for IntoIter<T, N> {
 impl<T, const N: usize> ExactSizeIterator for IntoIter<T, N> {
     fn len(&self) -> usize {
         match &self.0 {
-            RawIntoIter::Inline { len, .. } => *len,
+            RawIntoIter::Inline(iter) => iter.len(),
+            RawIntoIter::Sparse(iter) => iter.len(),
             RawIntoIter::Heap(iter) => iter.len(),
         }
     }
@@ -595,24 +632,16 @@ impl<T, const N: usize> IntoIterator for SmallSlabMap<T, N> {
     type IntoIter = IntoIter<T, N>;
     fn into_iter(self) -> Self::IntoIter {
         match self.0 {
-            None => IntoIter(RawIntoIter::Inline {
-                iter: from_fn(|_| None).into_iter().enumerate(),
-                len: 0,
-            }),
-            Some(Data::Inline { len, items }) => IntoIter(RawIntoIter::Inline {
-                iter: items.into_iter().enumerate(),
-                len: len as usize,
-            }),
-            Some(Data::Heap(m)) => IntoIter(RawIntoIter::Heap(m.into_iter())),
+            Data::Inline(m) => IntoIter(RawIntoIter::Inline(m.into_iter())),
+            Data::Sparse(m) => IntoIter(RawIntoIter::Sparse(m.into_iter())),
+            Data::Heap(m) => IntoIter(RawIntoIter::Heap(m.into_iter())),
         }
     }
 }
 
 enum RawDrain<'a, T, const N: usize> {
-    Inline {
-        iter: iter::Enumerate<array::IntoIter<Option<T>, N>>,
-        len: usize,
-    },
+    Inline(inline::IntoIter<T, N>),
+    Sparse(sparse::IntoIter<T, N>),
     Heap(crate::slab_map::Drain<'a, T>),
 }
 
@@ -626,18 +655,8 @@ impl<'a, T, const N: usize> Iterator for Drain<'a, T, N> {
 
     fn next(&mut self) -> Option<Self::Item> {
         match &mut self.0 {
-            RawDrain::Inline { iter, len } => {
-                if *len == 0 {
-                    return None;
-                }
-                *len -= 1;
-                loop {
-                    let (key, value) = iter.next().unwrap();
-                    if let Some(value) = value {
-                        return Some((key, value));
-                    }
-                }
-            }
+            RawDrain::Inline(iter) => iter.next(),
+            RawDrain::Sparse(iter) => iter.next(),
             RawDrain::Heap(iter) => iter.next(),
         }
     }
@@ -652,41 +671,34 @@ impl<'a, T, const N: usize> Iterator for Drain<'a, T, N> {
 impl<'a, T, const N: usize> ExactSizeIterator for Drain<'a, T, N> {
     fn len(&self) -> usize {
         match &self.0 {
-            RawDrain::Inline { len, .. } => *len,
+            RawDrain::Inline(iter) => iter.len(),
+            RawDrain::Sparse(iter) => iter.len(),
             RawDrain::Heap(iter) => iter.len(),
         }
     }
 }
 impl<'a, T, const N: usize> FusedIterator for Drain<'a, T, N> {}
 
-enum RawIter<'a, T, const N: usize> {
-    Inline {
-        iter: iter::Enumerate<slice::Iter<'a, Option<T>>>,
-        len: usize,
-    },
+enum RawIter<'a, T> {
+    Inline(inline::Iter<'a, T>),
+    Sparse(slice::Iter<'a, (usize, T)>),
     Heap(crate::slab_map::Iter<'a, T>),
 }
 
 /// An iterator over the entries of a [`SmallSlabMap`].
 ///
 /// This struct is created by the [`iter`](SmallSlabMap::iter).
-pub struct Iter<'a, T, const N: usize>(RawIter<'a, T, N>);
+pub struct Iter<'a, T, const N: usize>(RawIter<'a, T>);
 
 impl<'a, T, const N: usize> Iterator for Iter<'a, T, N> {
     type Item = (usize, &'a T);
 
     fn next(&mut self) -> Option<Self::Item> {
         match &mut self.0 {
-            RawIter::Inline { iter, len } => {
-                if *len == 0 {
-                    return None;
-                }
-                *len -= 1;
-                loop {
-                    if let (key, Some(value)) = iter.next().unwrap() {
-                        return Some((key, value));
-                    }
-                }
+            RawIter::Inline(iter) => iter.next(),
+            RawIter::Sparse(iter) => {
+                let (key, value) = iter.next()?;
+                Some((*key, value))
             }
             RawIter::Heap(iter) => iter.next(),
         }
@@ -703,7 +715,8 @@ impl<'a, T, const N: usize> Iterator for Iter<'a, T, N> {
 impl<'a, T, const N: usize> ExactSizeIterator for Iter<'a, T, N> {
     fn len(&self) -> usize {
         match &self.0 {
-            RawIter::Inline { len, .. } => *len,
+            RawIter::Inline(iter) => iter.len(),
+            RawIter::Sparse(iter) => iter.len(),
             RawIter::Heap(iter) => iter.len(),
         }
     }
@@ -715,47 +728,33 @@ impl<'a, T, const N: usize> IntoIterator for &'a SmallSlabMap<T, N> {
     type IntoIter = Iter<'a, T, N>;
     fn into_iter(self) -> Self::IntoIter {
         match &self.0 {
-            None => Iter(RawIter::Inline {
-                iter: [].iter().enumerate(),
-                len: 0,
-            }),
-            Some(Data::Inline { len, items }) => Iter(RawIter::Inline {
-                iter: items.iter().enumerate(),
-                len: *len as usize,
-            }),
-            Some(Data::Heap(m)) => Iter(RawIter::Heap(m.iter())),
+            Data::Inline(m) => Iter(RawIter::Inline(m.iter())),
+            Data::Sparse(m) => Iter(RawIter::Sparse(m.entries().iter())),
+            Data::Heap(m) => Iter(RawIter::Heap(m.iter())),
         }
     }
 }
 
-enum RawIterMut<'a, T, const N: usize> {
-    Inline {
-        iter: iter::Enumerate<slice::IterMut<'a, Option<T>>>,
-        len: usize,
-    },
+enum RawIterMut<'a, T> {
+    Inline(inline::IterMut<'a, T>),
+    Sparse(slice::IterMut<'a, (usize, T)>),
     Heap(crate::slab_map::IterMut<'a, T>),
 }
 
 /// A mutable iterator over the entries of a [`SmallSlabMap`].
 ///
 /// This struct is created by the [`iter_mut`](SmallSlabMap::iter_mut).
-pub struct IterMut<'a, T, const N: usize>(RawIterMut<'a, T, N>);
+pub struct IterMut<'a, T, const N: usize>(RawIterMut<'a, T>);
 
 impl<'a, T, const N: usize> Iterator for IterMut<'a, T, N> {
     type Item = (usize, &'a mut T);
 
     fn next(&mut self) -> Option<Self::Item> {
         match &mut self.0 {
-            RawIterMut::Inline { iter, len } => {
-                if *len == 0 {
-                    return None;
-                }
-                *len -= 1;
-                loop {
-                    if let (key, Some(value)) = iter.next().unwrap() {
-                        return Some((key, value));
-                    }
-                }
+            RawIterMut::Inline(iter) => iter.next(),
+            RawIterMut::Sparse(iter) => {
+                let (key, value) = iter.next()?;
+                Some((*key, value))
             }
             RawIterMut::Heap(iter) => iter.next(),
         }
@@ -772,7 +771,8 @@ impl<'a, T, const N: usize> Iterator for IterMut<'a, T, N> {
 impl<'a, T, const N: usize> ExactSizeIterator for IterMut<'a, T, N> {
     fn len(&self) -> usize {
         match &self.0 {
-            RawIterMut::Inline { len, .. } => *len,
+            RawIterMut::Inline(iter) => iter.len(),
+            RawIterMut::Sparse(iter) => iter.len(),
             RawIterMut::Heap(iter) => iter.len(),
         }
     }
@@ -784,15 +784,9 @@ impl<'a, T, const N: usize> IntoIterator for &'a mut SmallSlabMap<T, N> {
     type IntoIter = IterMut<'a, T, N>;
     fn into_iter(self) -> Self::IntoIter {
         match &mut self.0 {
-            None => IterMut(RawIterMut::Inline {
-                iter: [].iter_mut().enumerate(),
-                len: 0,
-            }),
-            Some(Data::Inline { len, items }) => IterMut(RawIterMut::Inline {
-                iter: items.iter_mut().enumerate(),
-                len: *len as usize,
-            }),
-            Some(Data::Heap(m)) => IterMut(RawIterMut::Heap(m.iter_mut())),
+            Data::Inline(m) => IterMut(RawIterMut::Inline(m.iter_mut())),
+            Data::Sparse(m) => IterMut(RawIterMut::Sparse(m.entries_mut().iter_mut())),
+            Data::Heap(m) => IterMut(RawIterMut::Heap(m.iter_mut())),
         }
     }
 }
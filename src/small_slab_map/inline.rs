@@ -0,0 +1,307 @@
+use core::{
+    iter::FusedIterator,
+    mem::{replace, take, MaybeUninit},
+    slice,
+};
+
+/// The occupancy bitmask of [`Inline`].
+type Mask = u64;
+
+/// Values stored at the index of their keys, with a bitmask of the occupied keys.
+///
+/// Since the bitmask has a fixed width, only the first [`CAPACITY`](Self::CAPACITY) items are used.
+pub(super) struct Inline<T, const N: usize> {
+    mask: Mask,
+    items: [MaybeUninit<T>; N],
+}
+
+impl<T, const N: usize> Inline<T, N> {
+    pub const CAPACITY: usize = if N < Mask::BITS as usize {
+        N
+    } else {
+        Mask::BITS as usize
+    };
+
+    #[inline]
+    pub const fn new() -> Self {
+        Self {
+            mask: 0,
+            items: [const { MaybeUninit::uninit() }; N],
+        }
+    }
+
+    #[inline]
+    pub fn len(&self) -> usize {
+        self.mask.count_ones() as usize
+    }
+
+    #[inline]
+    fn is_occupied(&self, key: usize) -> bool {
+        key < Self::CAPACITY && self.mask & (1 << key) != 0
+    }
+
+    #[inline]
+    pub fn get(&self, key: usize) -> Option<&T> {
+        if !self.is_occupied(key) {
+            return None;
+        }
+        // SAFETY: The item of an occupied key is initialized.
+        Some(unsafe { self.items[key].assume_init_ref() })
+    }
+
+    #[inline]
+    pub fn get_mut(&mut self, key: usize) -> Option<&mut T> {
+        if !self.is_occupied(key) {
+            return None;
+        }
+        // SAFETY: The item of an occupied key is initialized.
+        Some(unsafe { self.items[key].assume_init_mut() })
+    }
+
+    /// Returns the smallest key that is not occupied.
+    #[inline]
+    pub fn vacant_key(&self) -> Option<usize> {
+        let key = (!self.mask).trailing_zeros() as usize;
+        (key < Self::CAPACITY).then_some(key)
+    }
+
+    /// Stores `value` at `key`, returning the previous value.
+    ///
+    /// # Panics
+    /// Panics if `key` is not less than [`CAPACITY`](Self::CAPACITY).
+    #[inline]
+    pub fn insert(&mut self, key: usize, value: T) -> Option<T> {
+        assert!(key < Self::CAPACITY);
+        let old = self.remove(key);
+        self.items[key].write(value);
+        self.mask |= 1 << key;
+        old
+    }
+
+    #[inline]
+    pub fn remove(&mut self, key: usize) -> Option<T> {
+        if !self.is_occupied(key) {
+            return None;
+        }
+        self.mask &= !(1 << key);
+        // SAFETY: The item was occupied, and it is no longer marked as occupied, so it is read only once.
+        Some(unsafe { self.items[key].assume_init_read() })
+    }
+
+    pub fn retain(&mut self, mut f: impl FnMut(usize, &mut T) -> bool) {
+        for key in Bits(self.mask) {
+            // SAFETY: The item of an occupied key is initialized.
+            if !f(key, unsafe { self.items[key].assume_init_mut() }) {
+                self.remove(key);
+            }
+        }
+    }
+
+    pub fn clear(&mut self) {
+        for key in Bits(take(&mut self.mask)) {
+            // SAFETY: The item was occupied, and it is no longer marked as occupied, so it is dropped only once.
+            unsafe { self.items[key].assume_init_drop() }
+        }
+    }
+
+    #[inline]
+    pub fn take(&mut self) -> Self {
+        replace(self, Self::new())
+    }
+
+    #[inline]
+    pub fn iter(&self) -> Iter<'_, T> {
+        Iter {
+            mask: self.mask,
+            items: &self.items,
+        }
+    }
+
+    #[inline]
+    pub fn iter_mut(&mut self) -> IterMut<'_, T> {
+        IterMut {
+            mask: self.mask,
+            items: self.items.iter_mut(),
+            pos: 0,
+        }
+    }
+
+    #[cfg(all(test, feature = "zeroize"))]
+    pub fn items(&self) -> &[MaybeUninit<T>; N] {
+        &self.items
+    }
+
+    #[cfg(feature = "zeroize")]
+    pub fn zeroize_vacants(&mut self) {
+        use zeroize::Zeroize;
+
+        for (key, item) in self.items.iter_mut().enumerate() {
+            if key >= Self::CAPACITY || self.mask & (1 << key) == 0 {
+                item.zeroize();
+            }
+        }
+    }
+
+    #[cfg(feature = "zeroize")]
+    pub fn remove_zeroize(&mut self, key: usize) -> bool
+    where
+        T: zeroize::Zeroize,
+    {
+        use zeroize::Zeroize;
+
+        if !self.is_occupied(key) {
+            return false;
+        }
+        let item = &mut self.items[key];
+        // SAFETY: The item of an occupied key is initialized.
+        unsafe { item.assume_init_mut() }.zeroize();
+        self.mask &= !(1 << key);
+        // SAFETY: The item was occupied, and it is no longer marked as occupied, so it is dropped only once.
+        unsafe { item.assume_init_drop() };
+        item.zeroize();
+        true
+    }
+}
+
+#[cfg(feature = "zeroize")]
+impl<T: zeroize::Zeroize, const N: usize> zeroize::Zeroize for Inline<T, N> {
+    fn zeroize(&mut self) {
+        for (_, value) in self.iter_mut() {
+            value.zeroize();
+        }
+        self.clear();
+        self.items.zeroize();
+    }
+}
+
+impl<T, const N: usize> Drop for Inline<T, N> {
+    fn drop(&mut self) {
+        self.clear();
+    }
+}
+
+impl<T: Clone, const N: usize> Clone for Inline<T, N> {
+    fn clone(&self) -> Self {
+        let mut c = Self::new();
+        for (key, value) in self.iter() {
+            c.items[key].write(value.clone());
+            c.mask |= 1 << key;
+        }
+        c
+    }
+}
+
+impl<T, const N: usize> IntoIterator for Inline<T, N> {
+    type Item = (usize, T);
+    type IntoIter = IntoIter<T, N>;
+
+    #[inline]
+    fn into_iter(mut self) -> Self::IntoIter {
+        IntoIter {
+            mask: take(&mut self.mask),
+            items: replace(&mut self.items, [const { MaybeUninit::uninit() }; N]),
+        }
+    }
+}
+
+/// An iterator over the positions of the set bits of a [`Mask`], in ascending order.
+struct Bits(Mask);
+
+impl Iterator for Bits {
+    type Item = usize;
+
+    #[inline]
+    fn next(&mut self) -> Option<Self::Item> {
+        if self.0 == 0 {
+            return None;
+        }
+        let key = self.0.trailing_zeros() as usize;
+        self.0 &= self.0 - 1;
+        Some(key)
+    }
+    #[inline]
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        let len = self.0.count_ones() as usize;
+        (len, Some(len))
+    }
+}
+
+pub(super) struct Iter<'a, T> {
+    mask: Mask,
+    items: &'a [MaybeUninit<T>],
+}
+
+impl<'a, T> Iterator for Iter<'a, T> {
+    type Item = (usize, &'a T);
+
+    #[inline]
+    fn next(&mut self) -> Option<Self::Item> {
+        let key = Bits(self.mask).next()?;
+        self.mask &= self.mask - 1;
+        // SAFETY: The item of an occupied key is initialized.
+        Some((key, unsafe { self.items[key].assume_init_ref() }))
+    }
+    #[inline]
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        Bits(self.mask).size_hint()
+    }
+}
+impl<T> ExactSizeIterator for Iter<'_, T> {}
+impl<T> FusedIterator for Iter<'_, T> {}
+
+pub(super) struct IterMut<'a, T> {
+    mask: Mask,
+    items: slice::IterMut<'a, MaybeUninit<T>>,
+    pos: usize,
+}
+
+impl<'a, T> Iterator for IterMut<'a, T> {
+    type Item = (usize, &'a mut T);
+
+    #[inline]
+    fn next(&mut self) -> Option<Self::Item> {
+        let key = Bits(self.mask).next()?;
+        self.mask &= self.mask - 1;
+        let item = self.items.nth(key - self.pos)?;
+        self.pos = key + 1;
+        // SAFETY: The item of an occupied key is initialized.
+        Some((key, unsafe { item.assume_init_mut() }))
+    }
+    #[inline]
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        Bits(self.mask).size_hint()
+    }
+}
+impl<T> ExactSizeIterator for IterMut<'_, T> {}
+impl<T> FusedIterator for IterMut<'_, T> {}
+
+pub(super) struct IntoIter<T, const N: usize> {
+    mask: Mask,
+    items: [MaybeUninit<T>; N],
+}
+
+impl<T, const N: usize> Iterator for IntoIter<T, N> {
+    type Item = (usize, T);
+
+    #[inline]
+    fn next(&mut self) -> Option<Self::Item> {
+        let key = Bits(self.mask).next()?;
+        self.mask &= self.mask - 1;
+        // SAFETY: The item was occupied, and it is no longer marked as occupied, so it is read only once.
+        Some((key, unsafe { self.items[key].assume_init_read() }))
+    }
+    #[inline]
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        Bits(self.mask).size_hint()
+    }
+}
+impl<T, const N: usize> ExactSizeIterator for IntoIter<T, N> {}
+impl<T, const N: usize> FusedIterator for IntoIter<T, N> {}
+impl<T, const N: usize> Drop for IntoIter<T, N> {
+    fn drop(&mut self) {
+        for key in Bits(take(&mut self.mask)) {
+            // SAFETY: The item was occupied, and it is no longer marked as occupied, so it is dropped only once.
+            unsafe { self.items[key].assume_init_drop() }
+        }
+    }
+}
@@ -0,0 +1,43 @@
+use core::{fmt, marker::PhantomData};
+
+use serde::{
+    de::{MapAccess, Visitor},
+    Deserialize, Deserializer, Serialize, Serializer,
+};
+
+use super::SmallSlabMap;
+
+/// Serializes the SmallSlabMap as a map from keys to values, in the same format as [`SlabMap`](crate::SlabMap).
+impl<T: Serialize, const N: usize> Serialize for SmallSlabMap<T, N> {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.collect_map(self.iter())
+    }
+}
+
+/// Deserializes the SmallSlabMap from a map from keys to values, keeping the keys.
+///
+/// Free space is rebuilt after all entries are stored, so subsequent insertions reuse the missing keys.
+impl<'de, T: Deserialize<'de>, const N: usize> Deserialize<'de> for SmallSlabMap<T, N> {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        deserializer.deserialize_map(SmallSlabMapVisitor(PhantomData))
+    }
+}
+
+struct SmallSlabMapVisitor<T, const N: usize>(PhantomData<fn() -> SmallSlabMap<T, N>>);
+
+impl<'de, T: Deserialize<'de>, const N: usize> Visitor<'de> for SmallSlabMapVisitor<T, N> {
+    type Value = SmallSlabMap<T, N>;
+
+    fn expecting(&self, formatter: &mut fmt::Formatter) -> fmt::Result {
+        formatter.write_str("a map from keys to values")
+    }
+
+    fn visit_map<A: MapAccess<'de>>(self, mut map: A) -> Result<Self::Value, A::Error> {
+        let mut this = SmallSlabMap::new();
+        while let Some((key, value)) = map.next_entry()? {
+            this.set(key, value);
+        }
+        this.rebuild_vacants();
+        Ok(this)
+    }
+}
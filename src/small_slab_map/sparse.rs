@@ -0,0 +1,224 @@
+use core::{
+    iter::FusedIterator,
+    mem::{replace, MaybeUninit},
+    ptr, slice,
+};
+
+use super::inline::Inline;
+
+/// Pairs of a key and a value sorted by key, stored in the first `len` items.
+///
+/// Since the items are aligned to `usize`, `len` is a `usize` without increasing the size.
+pub(super) struct Sparse<T, const N: usize> {
+    len: usize,
+    items: [MaybeUninit<(usize, T)>; N],
+}
+
+impl<T, const N: usize> Sparse<T, N> {
+    #[inline]
+    pub const fn new() -> Self {
+        Self {
+            len: 0,
+            items: [const { MaybeUninit::uninit() }; N],
+        }
+    }
+
+    #[inline]
+    pub fn len(&self) -> usize {
+        self.len
+    }
+
+    #[inline]
+    pub fn entries(&self) -> &[(usize, T)] {
+        // SAFETY: The first `len` items are initialized, and `MaybeUninit<T>` has the same layout as `T`.
+        unsafe { slice::from_raw_parts(self.items.as_ptr().cast(), self.len()) }
+    }
+
+    #[inline]
+    pub fn entries_mut(&mut self) -> &mut [(usize, T)] {
+        // SAFETY: The first `len` items are initialized, and `MaybeUninit<T>` has the same layout as `T`.
+        unsafe { slice::from_raw_parts_mut(self.items.as_mut_ptr().cast(), self.len()) }
+    }
+
+    /// Searches for the index of `key` in [`entries`](Self::entries).
+    #[inline]
+    pub fn position(&self, key: usize) -> Result<usize, usize> {
+        self.entries().binary_search_by_key(&key, |e| e.0)
+    }
+
+    #[inline]
+    pub fn get(&self, key: usize) -> Option<&T> {
+        let index = self.position(key).ok()?;
+        Some(&self.entries()[index].1)
+    }
+
+    #[inline]
+    pub fn get_mut(&mut self, key: usize) -> Option<&mut T> {
+        let index = self.position(key).ok()?;
+        Some(&mut self.entries_mut()[index].1)
+    }
+
+    /// Inserts a pair of a key and a value at `index` of [`entries`](Self::entries).
+    ///
+    /// # Panics
+    /// Panics if the Sparse is full or `index` is greater than the length.
+    pub fn insert(&mut self, index: usize, key: usize, value: T) {
+        let len = self.len();
+        assert!(len < N && index <= len);
+        let p = self.items.as_mut_ptr();
+        // SAFETY: `index..len` and `index + 1..len + 1` are within the array,
+        // and the item at `index` is overwritten without dropping after it is moved.
+        unsafe { ptr::copy(p.add(index), p.add(index + 1), len - index) };
+        self.items[index].write((key, value));
+        self.len += 1;
+    }
+
+    /// Removes the pair at `index` of [`entries`](Self::entries).
+    ///
+    /// # Panics
+    /// Panics if `index` is out of bounds.
+    pub fn remove(&mut self, index: usize) -> (usize, T) {
+        let len = self.len();
+        assert!(index < len);
+        let p = self.items.as_mut_ptr();
+        // SAFETY: The item at `index` is initialized, and the items after it are moved to fill its place.
+        let e = unsafe {
+            let e = p.add(index).read().assume_init();
+            ptr::copy(p.add(index + 1), p.add(index), len - index - 1);
+            e
+        };
+        self.len -= 1;
+        e
+    }
+
+    pub fn retain(&mut self, mut f: impl FnMut(usize, &mut T) -> bool) {
+        let mut index = 0;
+        while index < self.len() {
+            let (key, value) = &mut self.entries_mut()[index];
+            if f(*key, value) {
+                index += 1;
+            } else {
+                self.remove(index);
+            }
+        }
+    }
+
+    pub fn clear(&mut self) {
+        let entries: *mut [(usize, T)] = self.entries_mut();
+        self.len = 0;
+        // SAFETY: The entries are no longer counted in `len`, so they are dropped only once.
+        unsafe { ptr::drop_in_place(entries) }
+    }
+
+    #[cfg(feature = "zeroize")]
+    pub fn zeroize_vacants(&mut self) {
+        use zeroize::Zeroize;
+
+        let len = self.len();
+        self.items[len..].zeroize();
+    }
+
+    #[cfg(feature = "zeroize")]
+    pub fn remove_zeroize(&mut self, key: usize) -> bool
+    where
+        T: zeroize::Zeroize,
+    {
+        use zeroize::Zeroize;
+
+        let Ok(index) = self.position(key) else {
+            return false;
+        };
+        self.entries_mut()[index].1.zeroize();
+        self.remove(index);
+        let len = self.len();
+        self.items[len].zeroize();
+        true
+    }
+}
+
+#[cfg(feature = "zeroize")]
+impl<T: zeroize::Zeroize, const N: usize> zeroize::Zeroize for Sparse<T, N> {
+    fn zeroize(&mut self) {
+        for (_, value) in self.entries_mut() {
+            value.zeroize();
+        }
+        self.clear();
+        self.items.zeroize();
+    }
+}
+
+impl<T, const N: usize> From<Inline<T, N>> for Sparse<T, N> {
+    fn from(inline: Inline<T, N>) -> Self {
+        let mut s = Self::new();
+        for (key, value) in inline {
+            s.insert(s.len(), key, value);
+        }
+        s
+    }
+}
+
+impl<T, const N: usize> Drop for Sparse<T, N> {
+    fn drop(&mut self) {
+        self.clear();
+    }
+}
+
+impl<T: Clone, const N: usize> Clone for Sparse<T, N> {
+    fn clone(&self) -> Self {
+        let mut c = Self::new();
+        for (key, value) in self.entries() {
+            c.insert(c.len(), *key, value.clone());
+        }
+        c
+    }
+}
+
+impl<T, const N: usize> IntoIterator for Sparse<T, N> {
+    type Item = (usize, T);
+    type IntoIter = IntoIter<T, N>;
+
+    #[inline]
+    fn into_iter(mut self) -> Self::IntoIter {
+        IntoIter {
+            index: 0,
+            len: replace(&mut self.len, 0),
+            items: replace(&mut self.items, [const { MaybeUninit::uninit() }; N]),
+        }
+    }
+}
+
+pub(super) struct IntoIter<T, const N: usize> {
+    index: usize,
+    len: usize,
+    items: [MaybeUninit<(usize, T)>; N],
+}
+
+impl<T, const N: usize> Iterator for IntoIter<T, N> {
+    type Item = (usize, T);
+
+    #[inline]
+    fn next(&mut self) -> Option<Self::Item> {
+        if self.index == self.len {
+            return None;
+        }
+        let index = self.index;
+        self.index += 1;
+        // SAFETY: The items in `index..len` are initialized, and each of them is read only once.
+        Some(unsafe { self.items[index].assume_init_read() })
+    }
+    #[inline]
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        let len = self.len - self.index;
+        (len, Some(len))
+    }
+}
+impl<T, const N: usize> ExactSizeIterator for IntoIter<T, N> {}
+impl<T, const N: usize> FusedIterator for IntoIter<T, N> {}
+impl<T, const N: usize> Drop for IntoIter<T, N> {
+    fn drop(&mut self) {
+        let rest = &mut self.items[self.index..self.len];
+        self.index = self.len;
+        // SAFETY: The items that are not yielded are initialized, and `index` is moved past them, so they are dropped only once.
+        unsafe { ptr::drop_in_place(rest as *mut [MaybeUninit<(usize, T)>] as *mut [(usize, T)]) }
+    }
+}
@@ -31,6 +31,113 @@ fn test_retain() {
     assert_eq!(s.len(), 2);
 }
 
+#[test]
+fn retain_passes_keys() {
+    let mut s = SmallSlabMap::<_, 4>::new();
+    for i in 0..4 {
+        s.insert(i * 10);
+    }
+    s.remove(1);
+    let mut keys = Vec::new();
+    s.retain(|key, _| {
+        keys.push(key);
+        key != 2
+    });
+    assert_eq!(keys, [0, 2, 3]);
+    assert_eq!(s.iter().collect::<Vec<_>>(), [(0, &0), (3, &30)]);
+}
+
+#[test]
+fn inline_without_option() {
+    use std::mem::size_of;
+
+    let items = size_of::<[(usize, u64); 32]>();
+    assert!(size_of::<SmallSlabMap<u64, 32>>() <= items + 2 * size_of::<usize>());
+
+    // The heap variant must not be larger than the inline storage even for small N.
+    let items = size_of::<[(usize, u32); 4]>();
+    assert!(size_of::<SmallSlabMap<u32, 4>>() <= items + 2 * size_of::<usize>());
+}
+
+#[test]
+fn inline_more_than_mask_width() {
+    let mut s = SmallSlabMap::<_, 100>::new();
+    for i in 0..100 {
+        assert_eq!(s.insert(i), i);
+    }
+    assert_eq!(s.capacity(), 100);
+    assert_eq!(s.get(99), Some(&99));
+    s.remove(70);
+    s.remove(3);
+    assert_eq!(s.insert(200), 3);
+    assert_eq!(s.len(), 99);
+    let e: Vec<_> = (0..100).filter(|&k| k != 70).collect();
+    assert_eq!(s.keys().collect::<Vec<_>>(), e);
+    assert_eq!(s.capacity(), 100);
+}
+
+#[test]
+fn inline_capacity_above_u8() {
+    let mut s = SmallSlabMap::<_, 1024>::new();
+    for i in 0..1024 {
+        assert_eq!(s.insert(i), i);
+    }
+    assert!(s.is_inline());
+    assert_eq!(s.len(), 1024);
+    assert_eq!(s.get(1000), Some(&1000));
+    s.insert(1024);
+    assert!(s.is_spilled());
+    assert_eq!(s.keys().collect::<Vec<_>>(), (0..1025).collect::<Vec<_>>());
+}
+
+#[test]
+fn values_are_dropped_once() {
+    use std::rc::Rc;
+
+    let rc = Rc::new(());
+    for keys in [&[0, 1, 2][..], &[0, 5, 9][..]] {
+        let new = || {
+            let s: SmallSlabMap<_, 4> = keys.iter().map(|&k| (k, rc.clone())).collect();
+            s
+        };
+        drop(new());
+        assert_eq!(Rc::strong_count(&rc), 1);
+
+        let mut s = new();
+        s.remove(keys[1]);
+        assert_eq!(Rc::strong_count(&rc), 3);
+        let c = s.clone();
+        assert_eq!(Rc::strong_count(&rc), 5);
+        drop(c);
+
+        let mut iter = new().into_iter();
+        iter.next();
+        drop(iter);
+        assert_eq!(Rc::strong_count(&rc), 3);
+
+        s.drain().take(1).for_each(drop);
+        assert_eq!(Rc::strong_count(&rc), 1);
+    }
+}
+
+#[test]
+fn is_inline() {
+    assert_eq!(SmallSlabMap::<u32, 4>::inline_capacity(), 4);
+    assert_eq!(SmallSlabMap::<u32, 1000>::inline_capacity(), 1000);
+
+    let mut s = SmallSlabMap::<_, 2>::new();
+    assert!(s.is_inline());
+    let keys: Vec<_> = (0..3).map(|i| s.insert(i)).collect();
+    assert!(s.is_spilled());
+    for key in keys {
+        s.remove(key);
+    }
+    assert!(s.is_spilled());
+    s.drain_and_shrink();
+    assert!(s.is_inline());
+    assert!(!s.is_spilled());
+}
+
 #[test]
 fn test_len() {
     let mut s = SmallSlabMap::<_, 1>::new();
@@ -129,6 +236,37 @@ fn test_drain() {
     assert_eq!(d, e);
 }
 
+#[test]
+fn drain_keeps_heap() {
+    let mut s = SmallSlabMap::<_, 1>::new();
+    for i in 0..100 {
+        s.insert(i);
+    }
+    let capacity = s.capacity();
+    for _ in 0..10 {
+        assert_eq!(s.drain().count(), 100);
+        assert_eq!(s.capacity(), capacity);
+        for i in 0..100 {
+            s.insert(i);
+        }
+        assert_eq!(s.capacity(), capacity);
+    }
+}
+
+#[test]
+fn drain_and_shrink() {
+    let mut s = SmallSlabMap::<_, 1>::new();
+    let inline_capacity = s.capacity();
+    let k0 = s.insert(10);
+    let k1 = s.insert(20);
+    let mut d: Vec<_> = s.drain_and_shrink().collect();
+    d.sort();
+    assert_eq!(d, [(k0, 10), (k1, 20)]);
+    assert!(s.is_empty());
+    assert_eq!(s.capacity(), inline_capacity);
+    assert_eq!(s.insert(30), 0);
+}
+
 #[test]
 fn test_optimize() {
     let mut s = SmallSlabMap::<_, 1>::new();
@@ -229,6 +367,49 @@ fn from_iter() {
     assert_eq!(s[0], 3);
 }
 
+#[test]
+fn from_iter_sparse() {
+    let s: SmallSlabMap<usize, 4> = [(100, 1), (7, 2), (5000, 3)].into_iter().collect();
+    assert_eq!(s.capacity(), 4);
+    assert_eq!(s.len(), 3);
+    assert_eq!(s[100], 1);
+    assert_eq!(s[7], 2);
+    assert_eq!(s[5000], 3);
+    assert_eq!(s.get(8), None);
+    let e: Vec<_> = s.iter().map(|(k, v)| (k, *v)).collect();
+    assert_eq!(e, vec![(7, 2), (100, 1), (5000, 3)]);
+}
+
+#[test]
+fn sparse_insert_remove() {
+    let mut s: SmallSlabMap<usize, 4> = [(1, 10), (100, 20)].into_iter().collect();
+    assert_eq!(s.insert(30), 0);
+    assert_eq!(s.insert(40), 2);
+    assert_eq!(s.capacity(), 4);
+    assert_eq!(s.remove(1), Some(10));
+    assert_eq!(s.remove(1), None);
+    assert_eq!(s.insert(50), 1);
+    let e: Vec<_> = s.iter().map(|(k, v)| (k, *v)).collect();
+    assert_eq!(e, vec![(0, 30), (1, 50), (2, 40), (100, 20)]);
+
+    s.insert(60);
+    assert!(s.capacity() > 4);
+    let e: Vec<_> = s.into_iter().collect();
+    assert_eq!(e, vec![(0, 30), (1, 50), (2, 40), (3, 60), (100, 20)]);
+}
+
+#[test]
+fn sparse_retain_drain() {
+    let mut s: SmallSlabMap<usize, 4> = [(10, 1), (20, 2), (30, 3)].into_iter().collect();
+    s.retain(|k, _| k != 20);
+    let e: Vec<_> = s.iter().map(|(k, v)| (k, *v)).collect();
+    assert_eq!(e, vec![(10, 1), (30, 3)]);
+    let e: Vec<_> = s.drain().collect();
+    assert_eq!(e, vec![(10, 1), (30, 3)]);
+    assert!(s.is_empty());
+    assert_eq!(s.insert(5), 0);
+}
+
 #[test]
 fn merge_vacant() {
     let mut s: SmallSlabMap<_, 1> = [(0, 10), (1, 11), (2, 12), (3, 13)].into_iter().collect();
@@ -358,3 +539,108 @@ fn reserve_exact() {
     s.reserve_exact(10);
     assert!(s.capacity() == 10);
 }
+
+#[cfg(feature = "zeroize")]
+#[test]
+fn zeroize() {
+    use zeroize::Zeroize;
+
+    let mut s = SmallSlabMap::<_, 4>::new();
+    let k0 = s.insert(vec![1u8, 2, 3]);
+    let k1 = s.insert(vec![4u8, 5, 6]);
+    assert!(s.remove_zeroize(k1));
+    assert!(!s.remove_zeroize(k1));
+    assert_eq!(s.len(), 1);
+    assert_eq!(s[k0], vec![1, 2, 3]);
+    s.zeroize_vacants();
+    assert_eq!(s[k0], vec![1, 2, 3]);
+
+    s.zeroize();
+    assert!(s.is_empty());
+    assert_eq!(s.insert(vec![]), 0);
+}
+
+#[cfg(feature = "zeroize")]
+#[test]
+fn zeroize_sparse() {
+    use zeroize::Zeroize;
+
+    let mut s: SmallSlabMap<_, 4> = [(10, vec![1u8]), (20, vec![2u8])].into_iter().collect();
+    assert!(s.remove_zeroize(10));
+    assert!(!s.remove_zeroize(10));
+    s.zeroize_vacants();
+    assert_eq!(s[20], vec![2]);
+    s.zeroize();
+    assert!(s.is_empty());
+}
+
+#[cfg(feature = "zeroize")]
+#[test]
+fn zeroizing_small_slab_map() {
+    use super::{zeroize::ZeroizingSmallSlabMap, Data};
+
+    fn inline_item(s: &ZeroizingSmallSlabMap<u64, 4>, key: usize) -> u64 {
+        let Data::Inline(m) = &s.0 .0 else {
+            panic!("not inline")
+        };
+        // SAFETY: The item was written by an insertion and then zeroized.
+        unsafe { m.items()[key].assume_init() }
+    }
+
+    let mut s = ZeroizingSmallSlabMap::<u64, 4>::new();
+    let k0 = s.insert(u64::MAX);
+    let k1 = s.insert(u64::MAX);
+    assert!(s.remove(k1));
+    assert!(!s.remove(k1));
+    assert_eq!(inline_item(&s, k1), 0);
+    s.clear();
+    assert_eq!(inline_item(&s, k0), 0);
+
+    for i in 1..=10 {
+        s.insert(i);
+    }
+    assert!(s.is_spilled());
+    assert_eq!(
+        s.values().copied().collect::<Vec<_>>(),
+        (1..=10).collect::<Vec<_>>()
+    );
+    *s.get_mut(3).unwrap() += 10;
+    assert_eq!(s[3], 14);
+    assert!(s.remove(3));
+    assert_eq!(s.len(), 9);
+
+    let mut s: ZeroizingSmallSlabMap<u64, 4> = [(10, 1), (20, 2)]
+        .into_iter()
+        .collect::<SmallSlabMap<_, 4>>()
+        .into();
+    for i in 0..3 {
+        s.insert(i);
+    }
+    assert!(s.is_spilled());
+    assert_eq!(
+        s.iter().map(|(k, v)| (k, *v)).collect::<Vec<_>>(),
+        [(0, 0), (1, 1), (2, 2), (10, 1), (20, 2)]
+    );
+}
+
+#[cfg(feature = "serde")]
+#[test]
+fn serde_roundtrip() {
+    for n in [2, 10] {
+        let mut s = SmallSlabMap::<i32, 4>::new();
+        for i in 0..n {
+            s.insert(i);
+        }
+        s.retain(|key, _| key % 3 != 0);
+        let json = serde_json::to_string(&s).unwrap();
+        let mut d: SmallSlabMap<i32, 4> = serde_json::from_str(&json).unwrap();
+        assert_eq!(d.iter().collect::<Vec<_>>(), s.iter().collect::<Vec<_>>());
+
+        let s2: crate::SlabMap<i32> = serde_json::from_str(&json).unwrap();
+        assert_eq!(s2.iter().collect::<Vec<_>>(), s.iter().collect::<Vec<_>>());
+
+        let len = d.len();
+        assert_eq!(d.insert(100), 0);
+        assert_eq!(d.len(), len + 1);
+    }
+}
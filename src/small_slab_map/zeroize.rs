@@ -0,0 +1,151 @@
+use alloc::vec::Vec;
+use core::ops::Deref;
+
+use zeroize::{Zeroize, ZeroizeOnDrop};
+
+use super::{Data, Inline, SmallSlabMap};
+use crate::SlabMap;
+
+impl<T, const N: usize> SmallSlabMap<T, N> {
+    /// Overwrites the memory of the free spaces with zeros.
+    ///
+    /// Values moved out by [`remove`](SmallSlabMap::remove) may leave their bytes in the free spaces.
+    /// This method wipes those bytes, including the unused capacity.
+    pub fn zeroize_vacants(&mut self) {
+        match &mut self.0 {
+            Data::Inline(m) => m.zeroize_vacants(),
+            Data::Sparse(m) => m.zeroize_vacants(),
+            Data::Heap(m) => m.zeroize_vacants(),
+        }
+    }
+}
+
+impl<T: Zeroize, const N: usize> SmallSlabMap<T, N> {
+    /// Removes a key from the SmallSlabMap, zeroizing the value and the memory it occupied.
+    ///
+    /// Returns true if the key was previously in the SmallSlabMap.
+    pub fn remove_zeroize(&mut self, key: usize) -> bool {
+        match &mut self.0 {
+            Data::Inline(m) => m.remove_zeroize(key),
+            Data::Sparse(m) => m.remove_zeroize(key),
+            Data::Heap(m) => m.remove_zeroize(key),
+        }
+    }
+
+    /// Moves the values to the heap if one more value does not fit inline, zeroizing the inline storage left behind.
+    fn reserve_zeroizing(&mut self) {
+        let capacity = match &mut self.0 {
+            Data::Inline(m) if m.len() >= Inline::<T, N>::CAPACITY => N,
+            Data::Sparse(m) if m.len() >= N => N,
+            Data::Inline(_) | Data::Sparse(_) => return,
+            Data::Heap(m) => {
+                m.reserve_zeroizing(1);
+                return;
+            }
+        };
+        let mut heap = SlabMap::with_capacity(capacity * 2);
+        for key in self.keys().collect::<Vec<_>>() {
+            let value = self.remove(key).unwrap();
+            heap.set(key, value);
+        }
+        heap.rebuild_vacants();
+        self.zeroize_vacants();
+        self.0 = Data::Heap(heap);
+    }
+}
+
+/// Zeroizes all values and clears the SmallSlabMap, including the inline array and the unused capacity.
+///
+/// Use [`ZeroizingSmallSlabMap`] to also zeroize removed values and the SmallSlabMap on drop.
+impl<T: Zeroize, const N: usize> Zeroize for SmallSlabMap<T, N> {
+    fn zeroize(&mut self) {
+        match &mut self.0 {
+            Data::Inline(m) => m.zeroize(),
+            Data::Sparse(m) => m.zeroize(),
+            Data::Heap(m) => m.zeroize(),
+        }
+    }
+}
+
+/// A [`SmallSlabMap`] that zeroizes the memory of values when they are removed, cleared or dropped.
+///
+/// This is the [`SmallSlabMap`] counterpart of [`ZeroizingSlabMap`](crate::slab_map::ZeroizingSlabMap).
+/// When the values no longer fit inline, the inline storage is zeroized after they are moved to the heap.
+///
+/// # Examples
+/// ```
+/// use slabmap::small_slab_map::ZeroizingSmallSlabMap;
+///
+/// let mut s = ZeroizingSmallSlabMap::<_, 4>::new();
+/// let key = s.insert(*b"secret");
+/// assert_eq!(s[key], *b"secret");
+///
+/// assert!(s.remove(key));
+/// assert!(s.is_empty());
+/// ```
+pub struct ZeroizingSmallSlabMap<T: Zeroize, const N: usize>(pub(super) SmallSlabMap<T, N>);
+
+impl<T: Zeroize, const N: usize> ZeroizingSmallSlabMap<T, N> {
+    /// Constructs a new, empty `ZeroizingSmallSlabMap<T, N>`.
+    #[inline]
+    pub const fn new() -> Self {
+        Self(SmallSlabMap::new())
+    }
+
+    /// Inserts a value into the SmallSlabMap and returns the key associated with it.
+    pub fn insert(&mut self, value: T) -> usize {
+        self.0.reserve_zeroizing();
+        self.0.insert(value)
+    }
+
+    /// Returns a mutable reference to the value corresponding to the key.
+    #[inline]
+    pub fn get_mut(&mut self, key: usize) -> Option<&mut T> {
+        self.0.get_mut(key)
+    }
+
+    /// Removes a key from the SmallSlabMap, zeroizing the value and the memory it occupied.
+    ///
+    /// Returns true if the key was previously in the SmallSlabMap.
+    pub fn remove(&mut self, key: usize) -> bool {
+        self.0.remove_zeroize(key)
+    }
+
+    /// Zeroizes all values and clears the SmallSlabMap, including the inline array and the unused capacity.
+    pub fn clear(&mut self) {
+        self.0.zeroize();
+    }
+}
+
+/// Automatic shrinking of a spilled SmallSlabMap is disabled, because it would free storage without zeroizing it.
+impl<T: Zeroize, const N: usize> From<SmallSlabMap<T, N>> for ZeroizingSmallSlabMap<T, N> {
+    fn from(mut value: SmallSlabMap<T, N>) -> Self {
+        if let Data::Heap(m) = &mut value.0 {
+            m.set_shrink_policy(None);
+        }
+        Self(value)
+    }
+}
+
+impl<T: Zeroize, const N: usize> Default for ZeroizingSmallSlabMap<T, N> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<T: Zeroize, const N: usize> Deref for ZeroizingSmallSlabMap<T, N> {
+    type Target = SmallSlabMap<T, N>;
+
+    #[inline]
+    fn deref(&self) -> &Self::Target {
+        &self.0
+    }
+}
+
+impl<T: Zeroize, const N: usize> Drop for ZeroizingSmallSlabMap<T, N> {
+    fn drop(&mut self) {
+        self.0.zeroize();
+    }
+}
+
+impl<T: Zeroize, const N: usize> ZeroizeOnDrop for ZeroizingSmallSlabMap<T, N> {}
@@ -0,0 +1,249 @@
+//! A [`SlabMap`] with a secondary index sorted by a key extracted from the values.
+
+use alloc::collections::{btree_set, BTreeSet};
+use core::{
+    fmt::Debug,
+    iter::FusedIterator,
+    ops::{Bound, RangeBounds},
+};
+
+use derive_ex::derive_ex;
+
+use crate::{
+    slab_map::{Iter, Keys, Values},
+    SlabMap,
+};
+
+#[cfg(test)]
+mod tests;
+
+/// A [`SlabMap`] that keeps its values sorted by a key extracted by a closure.
+///
+/// Keyed access is O(1) as with [`SlabMap`], and iteration in sort order and range queries over the extracted keys are also available.
+///
+/// Values cannot be borrowed mutably because the extracted key could change.
+/// Use [`update`](Self::update) instead.
+///
+/// # Examples
+/// ```
+/// use slabmap::sorted_index::SortedIndex;
+///
+/// let mut s = SortedIndex::new(|v: &(&str, u32)| v.1);
+/// let a = s.insert(("a", 30));
+/// let b = s.insert(("b", 10));
+/// let c = s.insert(("c", 20));
+///
+/// let sorted: Vec<_> = s.iter_sorted().map(|(key, _)| key).collect();
+/// assert_eq!(sorted, [b, c, a]);
+///
+/// let range: Vec<_> = s.range(15..).map(|(_, v)| v.0).collect();
+/// assert_eq!(range, ["c", "a"]);
+/// ```
+#[derive_ex(Clone(bound(T, K, F)))]
+pub struct SortedIndex<T, K, F> {
+    map: SlabMap<T>,
+    index: BTreeSet<(K, usize)>,
+    f: F,
+}
+
+impl<T, K: Ord, F: Fn(&T) -> K> SortedIndex<T, K, F> {
+    /// Constructs a new, empty `SortedIndex` that sorts values by the key returned by `f`.
+    ///
+    /// `f` must return the same key for the same value.
+    pub fn new(f: F) -> Self {
+        Self {
+            map: SlabMap::new(),
+            index: BTreeSet::new(),
+            f,
+        }
+    }
+
+    /// Returns a reference to the underlying [`SlabMap`].
+    #[inline]
+    pub fn as_slab_map(&self) -> &SlabMap<T> {
+        &self.map
+    }
+
+    /// Returns the number of elements in the SortedIndex.
+    #[inline]
+    pub fn len(&self) -> usize {
+        self.map.len()
+    }
+
+    /// Returns true if the SortedIndex contains no elements.
+    #[inline]
+    pub fn is_empty(&self) -> bool {
+        self.map.is_empty()
+    }
+
+    /// Returns a reference to the value corresponding to the key.
+    #[inline]
+    pub fn get(&self, key: usize) -> Option<&T> {
+        self.map.get(key)
+    }
+
+    /// Returns true if the SortedIndex contains a value for the specified key.
+    #[inline]
+    pub fn contains_key(&self, key: usize) -> bool {
+        self.map.contains_key(key)
+    }
+
+    /// Inserts a value into the SortedIndex.
+    ///
+    /// Returns the key associated with the value.
+    pub fn insert(&mut self, value: T) -> usize {
+        let sort_key = (self.f)(&value);
+        let key = self.map.insert(value);
+        self.index.insert((sort_key, key));
+        key
+    }
+
+    /// Removes a key from the SortedIndex, returning the value at the key if the key was previously in the SortedIndex.
+    pub fn remove(&mut self, key: usize) -> Option<T> {
+        let value = self.map.remove(key)?;
+        self.index.remove(&((self.f)(&value), key));
+        Some(value)
+    }
+
+    /// Modifies the value at the key with `f` and updates the index.
+    ///
+    /// Returns false if the key does not exist.
+    /// If `f` panics, the index is updated to the value as `f` left it.
+    pub fn update(&mut self, key: usize, f: impl FnOnce(&mut T)) -> bool {
+        let Some(value) = self.map.get_mut(key) else {
+            return false;
+        };
+        let guard = Reindex {
+            sort_key: Some((self.f)(value)),
+            index: &mut self.index,
+            f: &self.f,
+            value,
+            key,
+        };
+        f(guard.value);
+        true
+    }
+
+    /// Clears the SortedIndex, removing all values.
+    pub fn clear(&mut self) {
+        self.map.clear();
+        self.index.clear();
+    }
+
+    /// Optimizing the free space.
+    pub fn optimize(&mut self) {
+        self.map.optimize();
+    }
+
+    /// Gets an iterator over the entries of the SortedIndex, sorted by the extracted key.
+    ///
+    /// Entries with the same extracted key are sorted by key.
+    #[inline]
+    pub fn iter_sorted(&self) -> SortedIter<'_, T, K> {
+        SortedIter {
+            iter: self.index.range::<(K, usize), _>(..),
+            map: &self.map,
+        }
+    }
+
+    /// Gets an iterator over the entries whose extracted key is within `range`, sorted by the extracted key.
+    pub fn range(&self, range: impl RangeBounds<K>) -> SortedIter<'_, T, K>
+    where
+        K: Clone,
+    {
+        let start = match range.start_bound() {
+            Bound::Included(k) => Bound::Included((k.clone(), usize::MIN)),
+            Bound::Excluded(k) => Bound::Excluded((k.clone(), usize::MAX)),
+            Bound::Unbounded => Bound::Unbounded,
+        };
+        let end = match range.end_bound() {
+            Bound::Included(k) => Bound::Included((k.clone(), usize::MAX)),
+            Bound::Excluded(k) => Bound::Excluded((k.clone(), usize::MIN)),
+            Bound::Unbounded => Bound::Unbounded,
+        };
+        SortedIter {
+            iter: self.index.range((start, end)),
+            map: &self.map,
+        }
+    }
+
+    /// Gets an iterator over the entries of the SortedIndex, sorted by key.
+    #[inline]
+    pub fn iter(&self) -> Iter<'_, T> {
+        self.map.iter()
+    }
+
+    /// Gets an iterator over the keys of the SortedIndex, in sorted order.
+    #[inline]
+    pub fn keys(&self) -> Keys<'_, T> {
+        self.map.keys()
+    }
+
+    /// Gets an iterator over the values of the SortedIndex.
+    #[inline]
+    pub fn values(&self) -> Values<'_, T> {
+        self.map.values()
+    }
+}
+impl<T: Debug, K, F> Debug for SortedIndex<T, K, F> {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        self.map.fmt(f)
+    }
+}
+
+impl<T, K, F> core::ops::Index<usize> for SortedIndex<T, K, F> {
+    type Output = T;
+
+    #[inline]
+    fn index(&self, index: usize) -> &Self::Output {
+        self.map.get(index).expect("out of index.")
+    }
+}
+
+/// Moves the entry of a value in the index to the current sort key of the value when dropped,
+/// so that the index stays consistent even if the update of the value panics.
+struct Reindex<'a, T, K: Ord, F: Fn(&T) -> K> {
+    sort_key: Option<K>,
+    index: &'a mut BTreeSet<(K, usize)>,
+    f: &'a F,
+    value: &'a mut T,
+    key: usize,
+}
+impl<T, K: Ord, F: Fn(&T) -> K> Drop for Reindex<'_, T, K, F> {
+    fn drop(&mut self) {
+        let old = self.sort_key.take().unwrap();
+        let new = (self.f)(self.value);
+        if old != new {
+            self.index.remove(&(old, self.key));
+            self.index.insert((new, self.key));
+        }
+    }
+}
+
+/// An iterator over the entries of a [`SortedIndex`] in sort order.
+///
+/// This struct is created by the [`iter_sorted`](SortedIndex::iter_sorted) and [`range`](SortedIndex::range).
+pub struct SortedIter<'a, T, K> {
+    iter: btree_set::Range<'a, (K, usize)>,
+    map: &'a SlabMap<T>,
+}
+impl<'a, T, K> Iterator for SortedIter<'a, T, K> {
+    type Item = (usize, &'a T);
+    #[inline]
+    fn next(&mut self) -> Option<Self::Item> {
+        let &(_, key) = self.iter.next()?;
+        Some((key, &self.map[key]))
+    }
+    #[inline]
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        self.iter.size_hint()
+    }
+}
+impl<T, K> DoubleEndedIterator for SortedIter<'_, T, K> {
+    #[inline]
+    fn next_back(&mut self) -> Option<Self::Item> {
+        let &(_, key) = self.iter.next_back()?;
+        Some((key, &self.map[key]))
+    }
+}
+impl<T, K> FusedIterator for SortedIter<'_, T, K> {}
@@ -0,0 +1,80 @@
+use std::collections::BTreeMap;
+
+use rand::{rngs::StdRng, Rng, SeedableRng};
+
+use crate::sorted_index::SortedIndex;
+
+#[test]
+fn random() {
+    let mut rng = StdRng::seed_from_u64(0);
+    let mut s = SortedIndex::new(|v: &u32| *v % 100);
+    let mut e = BTreeMap::new();
+    for _ in 0..2000 {
+        match rng.gen_range(0..10) {
+            0..=4 => {
+                let value = rng.gen_range(0..1000);
+                let key = s.insert(value);
+                e.insert(key, value);
+            }
+            5..=7 => {
+                if let Some(&key) = e.keys().nth(rng.gen_range(0..e.len().max(1))) {
+                    assert_eq!(s.remove(key), e.remove(&key));
+                }
+            }
+            _ => {
+                if let Some(&key) = e.keys().nth(rng.gen_range(0..e.len().max(1))) {
+                    let value = rng.gen_range(0..1000);
+                    assert!(s.update(key, |v| *v = value));
+                    e.insert(key, value);
+                }
+            }
+        }
+        let mut sorted: Vec<_> = e.iter().map(|(&k, &v)| (v % 100, k)).collect();
+        sorted.sort();
+        let a: Vec<_> = s.iter_sorted().map(|(k, v)| (v % 100, k)).collect();
+        assert_eq!(a, sorted);
+    }
+}
+
+#[test]
+fn range() {
+    let mut s = SortedIndex::new(|v: &u32| *v);
+    for v in [5, 1, 3, 3, 4, 2] {
+        s.insert(v);
+    }
+    let a: Vec<_> = s.range(2..4).map(|(_, &v)| v).collect();
+    assert_eq!(a, [2, 3, 3]);
+    let a: Vec<_> = s.range(3..=3).map(|(_, &v)| v).collect();
+    assert_eq!(a, [3, 3]);
+    let a: Vec<_> = s
+        .range((std::ops::Bound::Excluded(3), std::ops::Bound::Unbounded))
+        .map(|(_, &v)| v)
+        .collect();
+    assert_eq!(a, [4, 5]);
+    let a: Vec<_> = s.iter_sorted().rev().map(|(_, &v)| v).collect();
+    assert_eq!(a, [5, 4, 3, 3, 2, 1]);
+}
+
+#[test]
+fn update_panic() {
+    let mut s = SortedIndex::new(|v: &u32| *v);
+    let a = s.insert(1);
+    let b = s.insert(2);
+    let r = std::panic::catch_unwind(std::panic::AssertUnwindSafe(|| {
+        s.update(a, |v| {
+            *v = 3;
+            panic!()
+        })
+    }));
+    assert!(r.is_err());
+    let sorted: Vec<_> = s.iter_sorted().map(|(k, &v)| (k, v)).collect();
+    assert_eq!(sorted, [(b, 2), (a, 3)]);
+    assert_eq!(s.remove(a), Some(3));
+    assert_eq!(s.iter_sorted().count(), 1);
+}
+
+#[test]
+fn update_missing_key() {
+    let mut s = SortedIndex::new(|v: &u32| *v);
+    assert!(!s.update(0, |v| *v = 1));
+}
@@ -0,0 +1,150 @@
+//! A [`SlabMap`] that locks each value separately.
+
+use std::{
+    fmt::{self, Debug},
+    sync::{PoisonError, RwLock, RwLockReadGuard, RwLockWriteGuard},
+};
+
+use crate::SlabMap;
+
+#[cfg(test)]
+mod tests;
+
+/// A [`SlabMap`] that can be shared between threads, with a lock for each value.
+///
+/// Unlike [`SharedSlabMap`](crate::shared_slab_map::SharedSlabMap), [`get_mut`](Self::get_mut) locks only the value,
+/// so threads accessing different keys do not wait for each other.
+/// Only [`insert`](Self::insert) and [`remove`](Self::remove) lock the whole map.
+///
+/// A SyncSlabMap poisoned by a panic is still usable.
+///
+/// # Examples
+/// ```
+/// use slabmap::sync_slab_map::SyncSlabMap;
+///
+/// let s = SyncSlabMap::new();
+/// let a = s.insert(1);
+/// let b = s.insert(2);
+///
+/// std::thread::scope(|scope| {
+///     scope.spawn(|| s.get_mut(a, |v| *v += 10));
+///     scope.spawn(|| s.get_mut(b, |v| *v += 20));
+/// });
+///
+/// assert_eq!(s.get(a, |v| *v), Some(11));
+/// assert_eq!(s.remove(b), Some(22));
+/// ```
+pub struct SyncSlabMap<T>(RwLock<SlabMap<RwLock<T>>>);
+
+impl<T> SyncSlabMap<T> {
+    /// Constructs a new, empty `SyncSlabMap<T>`.
+    pub const fn new() -> Self {
+        Self(RwLock::new(SlabMap::new()))
+    }
+
+    /// Returns the underlying [`SlabMap`].
+    pub fn into_inner(self) -> SlabMap<T> {
+        self.0
+            .into_inner()
+            .unwrap_or_else(PoisonError::into_inner)
+            .into_iter()
+            .map(|(key, value)| (key, into_inner(value)))
+            .collect()
+    }
+
+    fn read_lock(&self) -> RwLockReadGuard<'_, SlabMap<RwLock<T>>> {
+        self.0.read().unwrap_or_else(PoisonError::into_inner)
+    }
+    fn write_lock(&self) -> RwLockWriteGuard<'_, SlabMap<RwLock<T>>> {
+        self.0.write().unwrap_or_else(PoisonError::into_inner)
+    }
+
+    /// Returns the number of elements in the SyncSlabMap.
+    pub fn len(&self) -> usize {
+        self.read_lock().len()
+    }
+
+    /// Returns true if the SyncSlabMap contains no elements.
+    pub fn is_empty(&self) -> bool {
+        self.read_lock().is_empty()
+    }
+
+    /// Returns true if the SyncSlabMap contains a value for the specified key.
+    pub fn contains_key(&self, key: usize) -> bool {
+        self.read_lock().contains_key(key)
+    }
+
+    /// Inserts a value into the SyncSlabMap.
+    ///
+    /// Returns the key associated with the value.
+    pub fn insert(&self, value: T) -> usize {
+        self.write_lock().insert(RwLock::new(value))
+    }
+
+    /// Removes a key from the SyncSlabMap, returning the value at the key if the key was previously in the SyncSlabMap.
+    ///
+    /// Waits until other threads finish accessing the SyncSlabMap.
+    pub fn remove(&self, key: usize) -> Option<T> {
+        self.write_lock().remove(key).map(into_inner)
+    }
+
+    /// Calls `f` with a reference to the value corresponding to the key, under a read lock of the value.
+    ///
+    /// Returns `None` if the key does not exist.
+    pub fn get<R>(&self, key: usize, f: impl FnOnce(&T) -> R) -> Option<R> {
+        let s = self.read_lock();
+        let value = s.get(key)?.read().unwrap_or_else(PoisonError::into_inner);
+        Some(f(&value))
+    }
+
+    /// Calls `f` with a mutable reference to the value corresponding to the key, under a write lock of the value.
+    ///
+    /// Other values can be accessed by other threads while `f` is running.
+    ///
+    /// Returns `None` if the key does not exist.
+    pub fn get_mut<R>(&self, key: usize, f: impl FnOnce(&mut T) -> R) -> Option<R> {
+        let s = self.read_lock();
+        let mut value = s.get(key)?.write().unwrap_or_else(PoisonError::into_inner);
+        Some(f(&mut value))
+    }
+}
+fn into_inner<T>(value: RwLock<T>) -> T {
+    value.into_inner().unwrap_or_else(PoisonError::into_inner)
+}
+impl<T> Default for SyncSlabMap<T> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+impl<T> From<SlabMap<T>> for SyncSlabMap<T> {
+    fn from(value: SlabMap<T>) -> Self {
+        Self(RwLock::new(
+            value
+                .into_iter()
+                .map(|(key, value)| (key, RwLock::new(value)))
+                .collect(),
+        ))
+    }
+}
+impl<T: Debug> Debug for SyncSlabMap<T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self.0.try_read() {
+            Ok(m) => f
+                .debug_map()
+                .entries(m.iter().map(|(key, value)| (key, ValueDebug(value))))
+                .finish(),
+            Err(_) => f.write_str("SyncSlabMap { <locked> }"),
+        }
+    }
+}
+
+struct ValueDebug<'a, T>(&'a RwLock<T>);
+
+impl<T: Debug> Debug for ValueDebug<'_, T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self.0.try_read() {
+            Ok(value) => value.fmt(f),
+            Err(_) => f.write_str("<locked>"),
+        }
+    }
+}
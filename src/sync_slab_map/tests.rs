@@ -0,0 +1,57 @@
+use std::{
+    sync::{Barrier, Mutex},
+    thread,
+};
+
+use crate::{sync_slab_map::SyncSlabMap, SlabMap};
+
+#[test]
+fn writers_of_different_keys() {
+    let s = SyncSlabMap::new();
+    let keys: Vec<_> = (0..4).map(|_| s.insert(0)).collect();
+    let barrier = Barrier::new(keys.len());
+    thread::scope(|scope| {
+        for &key in &keys {
+            let s = &s;
+            let barrier = &barrier;
+            scope.spawn(move || {
+                // All threads hold the lock of their own value at the same time.
+                s.get_mut(key, |v| {
+                    barrier.wait();
+                    *v += key;
+                });
+            });
+        }
+    });
+    for &key in &keys {
+        assert_eq!(s.get(key, |v| *v), Some(key));
+    }
+}
+
+#[test]
+fn insert_remove() {
+    let s = SyncSlabMap::new();
+    let a = s.insert(Mutex::new(1));
+    let b = s.insert(Mutex::new(2));
+    assert_eq!(s.len(), 2);
+    assert!(s.remove(a).is_some());
+    assert!(!s.contains_key(a));
+    assert_eq!(s.get(a, |_| ()), None);
+    assert_eq!(s.get_mut(a, |_| ()), None);
+    assert_eq!(s.get(b, |v| *v.lock().unwrap()), Some(2));
+    s.remove(b);
+    assert!(s.is_empty());
+}
+
+#[test]
+fn from_slab_map() {
+    let mut m = SlabMap::new();
+    m.insert(1);
+    let key = m.insert(2);
+    m.remove(0);
+    let s = SyncSlabMap::from(m);
+    assert_eq!(format!("{s:?}"), "{1: 2}");
+    s.get_mut(key, |v| *v += 1);
+    let m = s.into_inner();
+    assert_eq!(m.iter().collect::<Vec<_>>(), [(key, &3)]);
+}
@@ -0,0 +1,237 @@
+//! A [`SlabMap`] that remembers recently removed entries for debugging.
+
+use std::{
+    backtrace::Backtrace,
+    collections::{vec_deque, VecDeque},
+    fmt::{self, Debug},
+    panic::Location,
+};
+
+use crate::{
+    slab_map::{Iter, IterMut, Keys, Values, ValuesMut},
+    SlabMap,
+};
+
+#[cfg(test)]
+mod tests;
+
+/// An entry removed from a [`TracedSlabMap`].
+pub struct RemovedEntry {
+    /// The key of the removed value.
+    pub key: usize,
+    /// The removed value formatted with `{:?}`.
+    pub value: String,
+    /// The location of the call that removed the value.
+    pub location: &'static Location<'static>,
+    /// The backtrace of the call that removed the value.
+    ///
+    /// Captured according to the `RUST_BACKTRACE` and `RUST_LIB_BACKTRACE` environment variables, see [`Backtrace::capture`].
+    pub backtrace: Backtrace,
+}
+impl Debug for RemovedEntry {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("RemovedEntry")
+            .field("key", &self.key)
+            .field("value", &self.value)
+            .field("location", &self.location)
+            .finish_non_exhaustive()
+    }
+}
+impl fmt::Display for RemovedEntry {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "key {} ({}) removed at {}",
+            self.key, self.value, self.location
+        )
+    }
+}
+
+/// A [`SlabMap`] that remembers the last removed entries, the location of the removal and its backtrace.
+///
+/// This answers "who removed this key?" without adding logs at every call site.
+///
+/// # Examples
+/// ```
+/// use slabmap::traced_slab_map::TracedSlabMap;
+///
+/// let mut s = TracedSlabMap::new(16);
+/// let key = s.insert("handle");
+/// s.remove(key);
+///
+/// let removed = s.find_removed(key).unwrap();
+/// assert_eq!(removed.value, "\"handle\"");
+/// assert_eq!(removed.location.file(), file!());
+/// ```
+pub struct TracedSlabMap<T: Debug> {
+    map: SlabMap<T>,
+    removed: VecDeque<RemovedEntry>,
+    history_len: usize,
+}
+
+impl<T: Debug> TracedSlabMap<T> {
+    /// Constructs a new, empty `TracedSlabMap<T>` that remembers the last `history_len` removed entries.
+    pub fn new(history_len: usize) -> Self {
+        Self {
+            map: SlabMap::new(),
+            removed: VecDeque::with_capacity(history_len),
+            history_len,
+        }
+    }
+
+    /// Returns a reference to the underlying [`SlabMap`].
+    #[inline]
+    pub fn as_slab_map(&self) -> &SlabMap<T> {
+        &self.map
+    }
+
+    /// Gets an iterator over the recently removed entries, oldest first.
+    #[inline]
+    pub fn recently_removed(&self) -> vec_deque::Iter<'_, RemovedEntry> {
+        self.removed.iter()
+    }
+
+    /// Returns the most recent removal of the key, if it is still remembered.
+    pub fn find_removed(&self, key: usize) -> Option<&RemovedEntry> {
+        self.removed.iter().rev().find(|e| e.key == key)
+    }
+
+    fn record(&mut self, key: usize, value: &T, location: &'static Location<'static>) {
+        if self.history_len == 0 {
+            return;
+        }
+        if self.removed.len() == self.history_len {
+            self.removed.pop_front();
+        }
+        self.removed.push_back(RemovedEntry {
+            key,
+            value: format!("{value:?}"),
+            location,
+            backtrace: Backtrace::capture(),
+        });
+    }
+
+    /// Returns the number of elements in the TracedSlabMap.
+    #[inline]
+    pub fn len(&self) -> usize {
+        self.map.len()
+    }
+
+    /// Returns true if the TracedSlabMap contains no elements.
+    #[inline]
+    pub fn is_empty(&self) -> bool {
+        self.map.is_empty()
+    }
+
+    /// Returns a reference to the value corresponding to the key.
+    #[inline]
+    pub fn get(&self, key: usize) -> Option<&T> {
+        self.map.get(key)
+    }
+
+    /// Returns a mutable reference to the value corresponding to the key.
+    #[inline]
+    pub fn get_mut(&mut self, key: usize) -> Option<&mut T> {
+        self.map.get_mut(key)
+    }
+
+    /// Returns true if the TracedSlabMap contains a value for the specified key.
+    #[inline]
+    pub fn contains_key(&self, key: usize) -> bool {
+        self.map.contains_key(key)
+    }
+
+    /// Inserts a value into the TracedSlabMap.
+    ///
+    /// Returns the key associated with the value.
+    #[inline]
+    pub fn insert(&mut self, value: T) -> usize {
+        self.map.insert(value)
+    }
+
+    /// Removes a key from the TracedSlabMap, returning the value at the key if the key was previously in the TracedSlabMap.
+    ///
+    /// The removal is remembered with the location of the caller.
+    #[track_caller]
+    pub fn remove(&mut self, key: usize) -> Option<T> {
+        let value = self.map.remove(key)?;
+        self.record(key, &value, Location::caller());
+        Some(value)
+    }
+
+    /// Clears the TracedSlabMap, removing all values.
+    ///
+    /// Each removal is remembered with the location of the caller.
+    #[track_caller]
+    pub fn clear(&mut self) {
+        self.retain(|_, _| false);
+    }
+
+    /// Retains only the elements specified by the predicate and optimize free spaces.
+    ///
+    /// Each removal is remembered with the location of the caller.
+    #[track_caller]
+    pub fn retain(&mut self, mut f: impl FnMut(usize, &mut T) -> bool) {
+        let location = Location::caller();
+        let keys: Vec<_> = self
+            .map
+            .iter_mut()
+            .filter_map(|(key, value)| (!f(key, value)).then_some(key))
+            .collect();
+        for key in keys {
+            let value = self.map.remove(key).unwrap();
+            self.record(key, &value, location);
+        }
+        self.map.optimize();
+    }
+
+    /// Gets an iterator over the entries of the TracedSlabMap, sorted by key.
+    #[inline]
+    pub fn iter(&self) -> Iter<'_, T> {
+        self.map.iter()
+    }
+
+    /// Gets a mutable iterator over the entries of the TracedSlabMap, sorted by key.
+    #[inline]
+    pub fn iter_mut(&mut self) -> IterMut<'_, T> {
+        self.map.iter_mut()
+    }
+
+    /// Gets an iterator over the keys of the TracedSlabMap, in sorted order.
+    #[inline]
+    pub fn keys(&self) -> Keys<'_, T> {
+        self.map.keys()
+    }
+
+    /// Gets an iterator over the values of the TracedSlabMap.
+    #[inline]
+    pub fn values(&self) -> Values<'_, T> {
+        self.map.values()
+    }
+
+    /// Gets a mutable iterator over the values of the TracedSlabMap.
+    #[inline]
+    pub fn values_mut(&mut self) -> ValuesMut<'_, T> {
+        self.map.values_mut()
+    }
+}
+impl<T: Debug> Debug for TracedSlabMap<T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        self.map.fmt(f)
+    }
+}
+
+impl<T: Debug> std::ops::Index<usize> for TracedSlabMap<T> {
+    type Output = T;
+
+    #[inline]
+    fn index(&self, index: usize) -> &Self::Output {
+        self.get(index).expect("out of index.")
+    }
+}
+impl<T: Debug> std::ops::IndexMut<usize> for TracedSlabMap<T> {
+    #[inline]
+    fn index_mut(&mut self, index: usize) -> &mut Self::Output {
+        self.get_mut(index).expect("out of index.")
+    }
+}
@@ -0,0 +1,39 @@
+use crate::traced_slab_map::TracedSlabMap;
+
+#[test]
+fn history_len() {
+    let mut s = TracedSlabMap::new(3);
+    let keys: Vec<_> = (0..10).map(|i| s.insert(i)).collect();
+    for &key in &keys[..5] {
+        s.remove(key);
+    }
+    let removed: Vec<_> = s.recently_removed().map(|e| e.key).collect();
+    assert_eq!(removed, [keys[2], keys[3], keys[4]]);
+    assert!(s.find_removed(keys[0]).is_none());
+    assert_eq!(s.find_removed(keys[4]).unwrap().value, "4");
+}
+
+#[test]
+fn location() {
+    let mut s = TracedSlabMap::new(8);
+    let a = s.insert("a");
+    let b = s.insert("b");
+    let line = line!() + 1;
+    s.remove(a);
+    s.retain(|_, _| false);
+    let e = s.find_removed(a).unwrap();
+    assert_eq!(e.location.file(), file!());
+    assert_eq!(e.location.line(), line);
+    let e = s.find_removed(b).unwrap();
+    assert_eq!(e.location.line(), line + 1);
+    assert!(s.is_empty());
+    assert!(e.to_string().contains("removed at"));
+}
+
+#[test]
+fn zero_history() {
+    let mut s = TracedSlabMap::new(0);
+    let key = s.insert(1);
+    s.remove(key);
+    assert_eq!(s.recently_removed().count(), 0);
+}
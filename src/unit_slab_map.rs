@@ -0,0 +1,245 @@
+//! A slab of `()` values stored as bits.
+
+use alloc::vec::Vec;
+use core::{fmt::Debug, iter::FusedIterator};
+
+#[cfg(test)]
+mod tests;
+
+const BITS: usize = u64::BITS as usize;
+
+/// A [`SlabMap`](crate::SlabMap) specialized for `()` values.
+///
+/// Only the occupancy is stored as a bitmap, so each entry uses 1 bit instead of the 16 bytes used by an entry of `SlabMap<()>` on 64-bit targets.
+/// This is suited for using a slab only as a key allocator.
+///
+/// Unlike `SlabMap`, the lowest free key is reused first.
+///
+/// # Examples
+/// ```
+/// use slabmap::unit_slab_map::UnitSlabMap;
+///
+/// let mut s = UnitSlabMap::new();
+/// let a = s.insert(());
+/// let b = s.insert(());
+///
+/// assert!(s.contains_key(a));
+/// assert_eq!(s.remove(a), Some(()));
+/// assert_eq!(s.get(a), None);
+/// assert_eq!(s.insert(()), a);
+/// assert_eq!(s.keys().collect::<Vec<_>>(), [a, b]);
+/// ```
+#[derive(Clone, Default)]
+pub struct UnitSlabMap {
+    occupied: Vec<u64>,
+    len: usize,
+    /// All words before this index are fully occupied.
+    vacant_hint: usize,
+}
+
+fn split(key: usize) -> (usize, u64) {
+    (key / BITS, 1 << (key % BITS))
+}
+
+impl UnitSlabMap {
+    /// Constructs a new, empty `UnitSlabMap`.
+    #[inline]
+    pub const fn new() -> Self {
+        Self {
+            occupied: Vec::new(),
+            len: 0,
+            vacant_hint: 0,
+        }
+    }
+
+    /// Constructs a new, empty `UnitSlabMap` with at least the specified capacity.
+    pub fn with_capacity(capacity: usize) -> Self {
+        Self {
+            occupied: Vec::with_capacity(capacity.div_ceil(BITS)),
+            len: 0,
+            vacant_hint: 0,
+        }
+    }
+
+    /// Returns the number of elements the UnitSlabMap can hold without reallocating.
+    #[inline]
+    pub fn capacity(&self) -> usize {
+        self.occupied.capacity() * BITS
+    }
+
+    /// Returns the number of elements in the UnitSlabMap.
+    #[inline]
+    pub fn len(&self) -> usize {
+        self.len
+    }
+
+    /// Returns true if the UnitSlabMap contains no elements.
+    #[inline]
+    pub fn is_empty(&self) -> bool {
+        self.len == 0
+    }
+
+    /// Returns a reference to the value corresponding to the key.
+    #[inline]
+    pub fn get(&self, key: usize) -> Option<&()> {
+        self.contains_key(key).then_some(&())
+    }
+
+    /// Returns true if the UnitSlabMap contains a value for the specified key.
+    #[inline]
+    pub fn contains_key(&self, key: usize) -> bool {
+        let (word, mask) = split(key);
+        self.occupied.get(word).is_some_and(|w| w & mask != 0)
+    }
+
+    /// Inserts a value into the UnitSlabMap.
+    ///
+    /// Returns the key associated with the value.
+    /// The lowest free key is reused first.
+    pub fn insert(&mut self, _value: ()) -> usize {
+        while self.vacant_hint < self.occupied.len() && self.occupied[self.vacant_hint] == !0 {
+            self.vacant_hint += 1;
+        }
+        if self.vacant_hint == self.occupied.len() {
+            self.occupied.push(0);
+        }
+        let word = self.vacant_hint;
+        let key = word * BITS + self.occupied[word].trailing_ones() as usize;
+        self.occupied[word] |= split(key).1;
+        self.len += 1;
+        key
+    }
+
+    /// Removes a key from the UnitSlabMap, returning the value at the key if the key was previously in the UnitSlabMap.
+    pub fn remove(&mut self, key: usize) -> Option<()> {
+        if !self.contains_key(key) {
+            return None;
+        }
+        let (word, mask) = split(key);
+        self.occupied[word] &= !mask;
+        self.vacant_hint = self.vacant_hint.min(word);
+        self.len -= 1;
+        if self.len == 0 {
+            self.clear();
+        }
+        Some(())
+    }
+
+    /// Clears the UnitSlabMap, removing all values.
+    pub fn clear(&mut self) {
+        self.occupied.clear();
+        self.len = 0;
+        self.vacant_hint = 0;
+    }
+
+    /// Retains only the elements specified by the predicate.
+    pub fn retain(&mut self, mut f: impl FnMut(usize, &mut ()) -> bool) {
+        for (word, bits) in self.occupied.iter_mut().enumerate() {
+            let mut rest = *bits;
+            while rest != 0 {
+                let bit = rest.trailing_zeros() as usize;
+                rest &= rest - 1;
+                if !f(word * BITS + bit, &mut ()) {
+                    *bits &= !(1 << bit);
+                    self.len -= 1;
+                    self.vacant_hint = self.vacant_hint.min(word);
+                }
+            }
+        }
+        if self.len == 0 {
+            self.clear();
+        }
+    }
+
+    /// Gets an iterator over the entries of the UnitSlabMap, sorted by key.
+    #[inline]
+    pub fn iter(&self) -> Iter<'_> {
+        Iter(self.keys())
+    }
+
+    /// Gets an iterator over the keys of the UnitSlabMap, in sorted order.
+    #[inline]
+    pub fn keys(&self) -> Keys<'_> {
+        Keys {
+            occupied: &self.occupied,
+            word: 0,
+            bits: self.occupied.first().copied().unwrap_or(0),
+            len: self.len,
+        }
+    }
+}
+impl Debug for UnitSlabMap {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        f.debug_map().entries(self.iter()).finish()
+    }
+}
+
+impl core::ops::Index<usize> for UnitSlabMap {
+    type Output = ();
+
+    #[inline]
+    fn index(&self, index: usize) -> &Self::Output {
+        self.get(index).expect("out of index.")
+    }
+}
+
+impl<'a> IntoIterator for &'a UnitSlabMap {
+    type Item = (usize, &'a ());
+    type IntoIter = Iter<'a>;
+
+    #[inline]
+    fn into_iter(self) -> Self::IntoIter {
+        self.iter()
+    }
+}
+
+/// An iterator over the entries of a [`UnitSlabMap`].
+///
+/// This struct is created by the [`iter`](UnitSlabMap::iter).
+pub struct Iter<'a>(Keys<'a>);
+impl<'a> Iterator for Iter<'a> {
+    type Item = (usize, &'a ());
+    #[inline]
+    fn next(&mut self) -> Option<Self::Item> {
+        self.0.next().map(|k| (k, &()))
+    }
+    #[inline]
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        self.0.size_hint()
+    }
+}
+impl FusedIterator for Iter<'_> {}
+impl ExactSizeIterator for Iter<'_> {}
+
+/// An iterator over the keys of a [`UnitSlabMap`].
+///
+/// This struct is created by the [`keys`](UnitSlabMap::keys).
+pub struct Keys<'a> {
+    occupied: &'a [u64],
+    word: usize,
+    bits: u64,
+    len: usize,
+}
+impl Iterator for Keys<'_> {
+    type Item = usize;
+    #[inline]
+    fn next(&mut self) -> Option<Self::Item> {
+        if self.len == 0 {
+            return None;
+        }
+        while self.bits == 0 {
+            self.word += 1;
+            self.bits = self.occupied[self.word];
+        }
+        let bit = self.bits.trailing_zeros() as usize;
+        self.bits &= self.bits - 1;
+        self.len -= 1;
+        Some(self.word * BITS + bit)
+    }
+    #[inline]
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        (self.len, Some(self.len))
+    }
+}
+impl FusedIterator for Keys<'_> {}
+impl ExactSizeIterator for Keys<'_> {}
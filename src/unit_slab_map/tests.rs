@@ -0,0 +1,31 @@
+use std::collections::BTreeSet;
+
+use rand::{rngs::StdRng, Rng, SeedableRng};
+
+use crate::unit_slab_map::UnitSlabMap;
+
+#[test]
+fn random() {
+    let mut rng = StdRng::seed_from_u64(0);
+    let mut s = UnitSlabMap::new();
+    let mut e = BTreeSet::new();
+    for _ in 0..10000 {
+        match rng.gen_range(0..10) {
+            0..=5 => {
+                let key = s.insert(());
+                assert_eq!(key, (0..).find(|k| !e.contains(k)).unwrap());
+                e.insert(key);
+            }
+            6..=8 => {
+                let key = rng.gen_range(0..e.len() + 10);
+                assert_eq!(s.remove(key), e.remove(&key).then_some(()));
+            }
+            _ => {
+                s.retain(|key, _| key % 5 != 0);
+                e.retain(|key| key % 5 != 0);
+            }
+        }
+        assert_eq!(s.len(), e.len());
+    }
+    assert!(s.keys().eq(e.iter().copied()));
+}
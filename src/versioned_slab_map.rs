@@ -0,0 +1,392 @@
+//! A generational slab that rejects stale keys.
+
+use alloc::vec::Vec;
+use core::{fmt::Debug, iter::FusedIterator, mem::replace};
+
+use derive_ex::derive_ex;
+
+use crate::dense_versioned_slab_map::VersionedKey;
+
+#[cfg(test)]
+mod tests;
+
+const NONE: usize = usize::MAX;
+
+#[derive(Clone)]
+struct Slot<T> {
+    /// Odd while the slot is occupied.
+    version: u32,
+    state: SlotState<T>,
+}
+
+#[derive(Clone)]
+enum SlotState<T> {
+    Occupied(T),
+    Vacant { next: usize },
+}
+
+/// A generational slab whose keys carry the version of the slot.
+///
+/// The version of a slot is updated whenever its value is removed,
+/// so a key whose value was removed does not resolve to a value inserted later at the same index.
+///
+/// # Examples
+/// ```
+/// use slabmap::versioned_slab_map::VersionedSlabMap;
+///
+/// let mut s = VersionedSlabMap::new();
+/// let a = s.insert("a");
+/// s.remove(a);
+///
+/// let b = s.insert("b");
+/// assert_eq!(a.index, b.index);
+/// assert_eq!(s.get(a), None);
+/// assert_eq!(s[b], "b");
+/// ```
+#[derive_ex(Clone(bound(T)), Default(bound()))]
+pub struct VersionedSlabMap<T> {
+    slots: Vec<Slot<T>>,
+    len: usize,
+    #[default(NONE)]
+    free_head: usize,
+}
+
+impl<T> VersionedSlabMap<T> {
+    /// Constructs a new, empty `VersionedSlabMap<T>`.
+    #[inline]
+    pub const fn new() -> Self {
+        Self {
+            slots: Vec::new(),
+            len: 0,
+            free_head: NONE,
+        }
+    }
+
+    /// Constructs a new, empty `VersionedSlabMap<T>` with the specified capacity.
+    #[inline]
+    pub fn with_capacity(capacity: usize) -> Self {
+        Self {
+            slots: Vec::with_capacity(capacity),
+            len: 0,
+            free_head: NONE,
+        }
+    }
+
+    /// Returns the number of elements the VersionedSlabMap can hold without reallocating.
+    #[inline]
+    pub fn capacity(&self) -> usize {
+        self.slots.capacity()
+    }
+
+    /// Returns the number of elements in the VersionedSlabMap.
+    #[inline]
+    pub fn len(&self) -> usize {
+        self.len
+    }
+
+    /// Returns true if the VersionedSlabMap contains no elements.
+    #[inline]
+    pub fn is_empty(&self) -> bool {
+        self.len == 0
+    }
+
+    /// Returns a reference to the value corresponding to the key.
+    #[inline]
+    pub fn get(&self, key: VersionedKey) -> Option<&T> {
+        let slot = self.slots.get(key.index)?;
+        match &slot.state {
+            SlotState::Occupied(value) if slot.version == key.version => Some(value),
+            _ => None,
+        }
+    }
+
+    /// Returns a mutable reference to the value corresponding to the key.
+    #[inline]
+    pub fn get_mut(&mut self, key: VersionedKey) -> Option<&mut T> {
+        let slot = self.slots.get_mut(key.index)?;
+        match &mut slot.state {
+            SlotState::Occupied(value) if slot.version == key.version => Some(value),
+            _ => None,
+        }
+    }
+
+    /// Returns true if the VersionedSlabMap contains a value for the specified key.
+    #[inline]
+    pub fn contains_key(&self, key: VersionedKey) -> bool {
+        self.get(key).is_some()
+    }
+
+    /// Inserts a value into the VersionedSlabMap.
+    ///
+    /// Returns the key associated with the value.
+    pub fn insert(&mut self, value: T) -> VersionedKey {
+        self.insert_with_key(|_| value)
+    }
+
+    /// Inserts a value given by `f` into the VersionedSlabMap. The key to be associated with the value is passed to `f`.
+    ///
+    /// Returns the key associated with the value.
+    pub fn insert_with_key(&mut self, f: impl FnOnce(VersionedKey) -> T) -> VersionedKey {
+        if self.free_head != NONE {
+            let index = self.free_head;
+            let slot = &mut self.slots[index];
+            let SlotState::Vacant { next } = slot.state else {
+                unreachable!()
+            };
+            let key = VersionedKey {
+                index,
+                version: slot.version.wrapping_add(1),
+            };
+            slot.state = SlotState::Occupied(f(key));
+            slot.version = key.version;
+            self.free_head = next;
+            self.len += 1;
+            key
+        } else {
+            let key = VersionedKey {
+                index: self.slots.len(),
+                version: 1,
+            };
+            self.slots.push(Slot {
+                version: key.version,
+                state: SlotState::Occupied(f(key)),
+            });
+            self.len += 1;
+            key
+        }
+    }
+
+    /// Removes a key from the VersionedSlabMap, returning the value at the key if the key was previously in the VersionedSlabMap.
+    pub fn remove(&mut self, key: VersionedKey) -> Option<T> {
+        if !self.contains_key(key) {
+            return None;
+        }
+        let value = self.free_slot(key.index);
+        self.len -= 1;
+        Some(value)
+    }
+    fn free_slot(&mut self, index: usize) -> T {
+        let slot = &mut self.slots[index];
+        slot.version = slot.version.wrapping_add(1);
+        let state = replace(
+            &mut slot.state,
+            SlotState::Vacant {
+                next: self.free_head,
+            },
+        );
+        self.free_head = index;
+        match state {
+            SlotState::Occupied(value) => value,
+            SlotState::Vacant { .. } => unreachable!(),
+        }
+    }
+
+    /// Clears the VersionedSlabMap, removing all values.
+    ///
+    /// All keys are invalidated, and the slots are kept for reuse.
+    pub fn clear(&mut self) {
+        self.retain(|_, _| false);
+    }
+
+    /// Retains only the elements specified by the predicate.
+    pub fn retain(&mut self, mut f: impl FnMut(VersionedKey, &mut T) -> bool) {
+        for index in 0..self.slots.len() {
+            let slot = &mut self.slots[index];
+            let key = VersionedKey {
+                index,
+                version: slot.version,
+            };
+            if let SlotState::Occupied(value) = &mut slot.state {
+                if !f(key, value) {
+                    self.free_slot(index);
+                    self.len -= 1;
+                }
+            }
+        }
+    }
+
+    /// Gets an iterator over the entries of the VersionedSlabMap, sorted by index.
+    #[inline]
+    pub fn iter(&self) -> Iter<'_, T> {
+        Iter {
+            iter: self.slots.iter().enumerate(),
+            len: self.len,
+        }
+    }
+
+    /// Gets a mutable iterator over the entries of the VersionedSlabMap, sorted by index.
+    #[inline]
+    pub fn iter_mut(&mut self) -> IterMut<'_, T> {
+        IterMut {
+            iter: self.slots.iter_mut().enumerate(),
+            len: self.len,
+        }
+    }
+
+    /// Gets an iterator over the keys of the VersionedSlabMap, sorted by index.
+    #[inline]
+    pub fn keys(&self) -> Keys<'_, T> {
+        Keys(self.iter())
+    }
+
+    /// Gets an iterator over the values of the VersionedSlabMap.
+    #[inline]
+    pub fn values(&self) -> Values<'_, T> {
+        Values(self.iter())
+    }
+
+    /// Gets a mutable iterator over the values of the VersionedSlabMap.
+    #[inline]
+    pub fn values_mut(&mut self) -> ValuesMut<'_, T> {
+        ValuesMut(self.iter_mut())
+    }
+}
+impl<T: Debug> Debug for VersionedSlabMap<T> {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        f.debug_map().entries(self.iter()).finish()
+    }
+}
+
+impl<T> core::ops::Index<VersionedKey> for VersionedSlabMap<T> {
+    type Output = T;
+
+    #[inline]
+    fn index(&self, index: VersionedKey) -> &Self::Output {
+        self.get(index).expect("out of index.")
+    }
+}
+impl<T> core::ops::IndexMut<VersionedKey> for VersionedSlabMap<T> {
+    #[inline]
+    fn index_mut(&mut self, index: VersionedKey) -> &mut Self::Output {
+        self.get_mut(index).expect("out of index.")
+    }
+}
+
+impl<'a, T> IntoIterator for &'a VersionedSlabMap<T> {
+    type Item = (VersionedKey, &'a T);
+    type IntoIter = Iter<'a, T>;
+
+    #[inline]
+    fn into_iter(self) -> Self::IntoIter {
+        self.iter()
+    }
+}
+impl<'a, T> IntoIterator for &'a mut VersionedSlabMap<T> {
+    type Item = (VersionedKey, &'a mut T);
+    type IntoIter = IterMut<'a, T>;
+
+    #[inline]
+    fn into_iter(self) -> Self::IntoIter {
+        self.iter_mut()
+    }
+}
+
+/// An iterator over the entries of a [`VersionedSlabMap`].
+///
+/// This struct is created by the [`iter`](VersionedSlabMap::iter).
+pub struct Iter<'a, T> {
+    iter: core::iter::Enumerate<core::slice::Iter<'a, Slot<T>>>,
+    len: usize,
+}
+impl<'a, T> Iterator for Iter<'a, T> {
+    type Item = (VersionedKey, &'a T);
+    #[inline]
+    fn next(&mut self) -> Option<Self::Item> {
+        for (index, slot) in self.iter.by_ref() {
+            if let SlotState::Occupied(value) = &slot.state {
+                self.len -= 1;
+                let version = slot.version;
+                return Some((VersionedKey { index, version }, value));
+            }
+        }
+        None
+    }
+    #[inline]
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        (self.len, Some(self.len))
+    }
+}
+impl<T> FusedIterator for Iter<'_, T> {}
+impl<T> ExactSizeIterator for Iter<'_, T> {}
+
+/// A mutable iterator over the entries of a [`VersionedSlabMap`].
+///
+/// This struct is created by the [`iter_mut`](VersionedSlabMap::iter_mut).
+pub struct IterMut<'a, T> {
+    iter: core::iter::Enumerate<core::slice::IterMut<'a, Slot<T>>>,
+    len: usize,
+}
+impl<'a, T> Iterator for IterMut<'a, T> {
+    type Item = (VersionedKey, &'a mut T);
+    #[inline]
+    fn next(&mut self) -> Option<Self::Item> {
+        for (index, slot) in self.iter.by_ref() {
+            if let SlotState::Occupied(value) = &mut slot.state {
+                self.len -= 1;
+                let version = slot.version;
+                return Some((VersionedKey { index, version }, value));
+            }
+        }
+        None
+    }
+    #[inline]
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        (self.len, Some(self.len))
+    }
+}
+impl<T> FusedIterator for IterMut<'_, T> {}
+impl<T> ExactSizeIterator for IterMut<'_, T> {}
+
+/// An iterator over the keys of a [`VersionedSlabMap`].
+///
+/// This struct is created by the [`keys`](VersionedSlabMap::keys).
+pub struct Keys<'a, T>(Iter<'a, T>);
+impl<T> Iterator for Keys<'_, T> {
+    type Item = VersionedKey;
+    #[inline]
+    fn next(&mut self) -> Option<Self::Item> {
+        self.0.next().map(|(k, _)| k)
+    }
+    #[inline]
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        self.0.size_hint()
+    }
+}
+impl<T> FusedIterator for Keys<'_, T> {}
+impl<T> ExactSizeIterator for Keys<'_, T> {}
+
+/// An iterator over the values of a [`VersionedSlabMap`].
+///
+/// This struct is created by the [`values`](VersionedSlabMap::values).
+pub struct Values<'a, T>(Iter<'a, T>);
+impl<'a, T> Iterator for Values<'a, T> {
+    type Item = &'a T;
+    #[inline]
+    fn next(&mut self) -> Option<Self::Item> {
+        self.0.next().map(|(_, v)| v)
+    }
+    #[inline]
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        self.0.size_hint()
+    }
+}
+impl<T> FusedIterator for Values<'_, T> {}
+impl<T> ExactSizeIterator for Values<'_, T> {}
+
+/// A mutable iterator over the values of a [`VersionedSlabMap`].
+///
+/// This struct is created by the [`values_mut`](VersionedSlabMap::values_mut).
+pub struct ValuesMut<'a, T>(IterMut<'a, T>);
+impl<'a, T> Iterator for ValuesMut<'a, T> {
+    type Item = &'a mut T;
+    #[inline]
+    fn next(&mut self) -> Option<Self::Item> {
+        self.0.next().map(|(_, v)| v)
+    }
+    #[inline]
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        self.0.size_hint()
+    }
+}
+impl<T> FusedIterator for ValuesMut<'_, T> {}
+impl<T> ExactSizeIterator for ValuesMut<'_, T> {}
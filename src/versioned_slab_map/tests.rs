@@ -0,0 +1,68 @@
+use std::collections::HashMap;
+
+use rand::{rngs::StdRng, Rng, SeedableRng};
+
+use crate::versioned_slab_map::VersionedSlabMap;
+
+#[test]
+fn stale_key() {
+    let mut s = VersionedSlabMap::new();
+    let a = s.insert(1);
+    s.remove(a);
+    let b = s.insert(2);
+    assert_eq!(a.index, b.index);
+    assert_ne!(a.version, b.version);
+    assert_eq!(s.get(a), None);
+    assert_eq!(s.remove(a), None);
+    assert_eq!(s.get(b), Some(&2));
+}
+
+#[test]
+fn clear_invalidates_keys() {
+    let mut s = VersionedSlabMap::new();
+    let a = s.insert(1);
+    let b = s.insert(2);
+    s.clear();
+    assert!(s.is_empty());
+    let c = s.insert(3);
+    let d = s.insert(4);
+    assert!(!s.contains_key(a));
+    assert!(!s.contains_key(b));
+    assert_eq!(s[c] + s[d], 7);
+    assert_eq!(s.len(), 2);
+}
+
+#[test]
+fn insert_with_key() {
+    let mut s = VersionedSlabMap::new();
+    let a = s.insert(None);
+    s.remove(a);
+    let b = s.insert_with_key(Some);
+    assert_eq!(s[b], Some(b));
+}
+
+#[test]
+fn random() {
+    let mut rng = StdRng::seed_from_u64(0);
+    let mut s = VersionedSlabMap::new();
+    let mut m = HashMap::new();
+    let mut removed = Vec::new();
+    for i in 0..10000 {
+        if rng.gen_bool(0.5) || m.is_empty() {
+            let key = s.insert(i);
+            m.insert(key, i);
+        } else {
+            let key = *m.keys().nth(rng.gen_range(0..m.len())).unwrap();
+            assert_eq!(s.remove(key), m.remove(&key));
+            removed.push(key);
+        }
+        assert_eq!(s.len(), m.len());
+    }
+    for key in removed {
+        assert_eq!(s.get(key), None);
+    }
+    let mut e: Vec<_> = m.into_iter().collect();
+    e.sort();
+    let a: Vec<_> = s.iter().map(|(k, v)| (k, *v)).collect();
+    assert_eq!(a, e);
+}
@@ -0,0 +1,37 @@
+#![cfg(feature = "derive")]
+
+use slabmap::SlabStored;
+
+#[derive(SlabStored, Debug, PartialEq)]
+struct Item(u32);
+
+#[derive(SlabStored)]
+#[slab_stored(key = "NodeId", registry = "Nodes")]
+pub struct Node {
+    parent: Option<NodeId>,
+}
+
+#[test]
+fn default_names() {
+    let mut items = ItemRegistry::new();
+    let a: ItemKey = items.insert(Item(1));
+    let b = items.insert(Item(2));
+    items[b].0 += 10;
+    assert_eq!(items.len(), 2);
+    assert_eq!(
+        items.iter().collect::<Vec<_>>(),
+        [(a, &Item(1)), (b, &Item(12))]
+    );
+    assert_eq!(items.remove(a), Some(Item(1)));
+    assert_eq!(items.get(a), None);
+    assert_eq!(usize::from(b), b.0);
+}
+
+#[test]
+fn custom_names() {
+    let mut nodes = Nodes::default();
+    let root = nodes.insert(Node { parent: None });
+    let child = nodes.insert(Node { parent: Some(root) });
+    assert_eq!(nodes[child].parent, Some(root));
+    assert_eq!(nodes.as_slab_map().len(), 2);
+}